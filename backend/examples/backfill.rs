@@ -0,0 +1,90 @@
+/// 历史K线回补
+///
+/// 从交易所分页拉取K线, 补齐存储中的缺口, 可选地重采样为更大周期。
+/// 设置 `DATABASE_URL` 时写入 Postgres, 否则写入本地 CSV 缓存 (`--csv` 指定目录)。
+///
+/// 运行方式:
+/// ```
+/// cargo run --example backfill -- binance BTCUSDT 1m 2025-01-01 2025-01-08 --resample 5m,1h,1d
+/// cargo run --example backfill -- okex BTC-USDT-SWAP 1h 2025-01-01 2025-02-01 --csv data/market
+/// ```
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::NaiveDate;
+use nof0_backend::backtest::interval_secs;
+use nof0_backend::brokers::binance::{BinanceConfig, BinanceRestClient};
+use nof0_backend::brokers::okex::{OkexConfig, OkexRestClient};
+use nof0_backend::history::{
+    resample, Backfiller, CsvMarketDataStore, KlineSource, MarketDataStore, PgMarketDataStore,
+};
+use std::sync::Arc;
+
+fn parse_date(s: &str) -> Result<i64> {
+    let date =
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").with_context(|| format!("Invalid date: {}", s))?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 5 {
+        bail!("usage: backfill <binance|okex> <symbol> <interval> <start> <end> [--csv dir] [--resample 5m,1h]");
+    }
+    let (venue, symbol, interval) = (&args[0], &args[1], &args[2]);
+    let (start, end) = (parse_date(&args[3])?, parse_date(&args[4])?);
+
+    let option = |name: &str| {
+        args.iter()
+            .position(|a| a == name)
+            .and_then(|i| args.get(i + 1))
+            .cloned()
+    };
+
+    let source: Arc<dyn KlineSource> = match venue.as_str() {
+        "binance" => Arc::new(BinanceRestClient::new(&BinanceConfig::default())),
+        "okex" => Arc::new(OkexRestClient::new(&OkexConfig::default())),
+        other => bail!("Unsupported venue: {}", other),
+    };
+
+    let store: Arc<dyn MarketDataStore> = match std::env::var("DATABASE_URL") {
+        Ok(url) if option("--csv").is_none() => {
+            let pool = sqlx::PgPool::connect(&url).await?;
+            Arc::new(PgMarketDataStore::new(Arc::new(pool)))
+        }
+        _ => Arc::new(CsvMarketDataStore::new(
+            option("--csv").unwrap_or_else(|| "data/market".to_string()),
+        )),
+    };
+
+    let report = Backfiller::new(source.clone(), store.clone())
+        .backfill(symbol, interval, start, end)
+        .await?;
+    println!(
+        "{} {} {}: {} gaps found, {} klines fetched",
+        venue, symbol, interval, report.gaps_found, report.fetched
+    );
+    for (gap_start, gap_end) in &report.remaining_gaps {
+        println!("  still missing [{}, {})", gap_start, gap_end);
+    }
+
+    if let Some(targets) = option("--resample") {
+        let from_secs =
+            interval_secs(interval).ok_or_else(|| anyhow!("Invalid interval: {}", interval))?;
+        let klines = store
+            .load_klines(source.venue(), symbol, interval, start, end)
+            .await?;
+        for target in targets.split(',') {
+            let to_secs =
+                interval_secs(target).ok_or_else(|| anyhow!("Invalid interval: {}", target))?;
+            let bars = resample(&klines, from_secs, to_secs)?;
+            let stored = store
+                .upsert_klines(source.venue(), symbol, target, &bars)
+                .await?;
+            println!("  resampled {} {} klines", stored, target);
+        }
+    }
+
+    Ok(())
+}
@@ -1,14 +1,12 @@
 /// 经纪商使用示例
 ///
-/// 展示如何使用 BrokerRegistry 和 BrokerInstance enum
+/// 展示如何使用 BrokerRegistry 和 DynBroker trait object
 ///
 /// 运行方式:
 /// ```
 /// cargo run --example broker_usage
 /// ```
-use nof0_backend::brokers::{
-    Analytics, BrokerInstance, BrokerRegistry, MarketData, MockBroker, Trading,
-};
+use nof0_backend::brokers::{BrokerRegistry, MockBroker};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -18,21 +16,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 2. 创建 Mock Broker
     let mock_broker = MockBroker::new();
 
-    // 3. 将 Mock Broker 包装为 BrokerInstance 并注册
-    registry.register(BrokerInstance::Mock(mock_broker));
+    // 3. 注册 Mock Broker (任何实现了 Broker 的类型都可以直接注册)
+    registry.register(mock_broker);
 
     // 4. 从注册表获取 broker
     let broker = registry
         .get("mock")
         .expect("Mock broker should be registered");
 
-    // 5. 使用 MarketData trait
+    // 5. 获取行情
     println!("=== 获取价格 ===");
     let prices = broker.get_prices().await?;
-    println!("BTC 价格: ${}", prices.btc.price);
-    println!("ETH 价格: ${}", prices.eth.price);
+    for (symbol, price) in prices.prices.iter().take(3) {
+        println!("{} 价格: ${}", symbol, price);
+    }
 
-    // 6. 使用 Trading trait - 下单
+    // 6. 下单
     println!("\n=== 下单 ===");
     use nof0_backend::brokers::{OrderRequest, OrderSide, OrderType};
     let order_request = OrderRequest {
@@ -42,20 +41,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         quantity: 0.1,
         price: Some(50000.0),
         time_in_force: Some("GTC".to_string()),
-        client_order_id: Some("test-order-001".to_string()),
+        reduce_only: false,
     };
 
     let order_response = broker.place_order(order_request).await?;
     println!("订单ID: {}", order_response.order_id);
     println!("订单状态: {:?}", order_response.status);
 
-    // 7. 使用 Analytics trait - 获取排行榜
+    // 7. 获取排行榜
     println!("\n=== 排行榜 ===");
     let leaderboard = broker.get_leaderboard().await?;
-    for entry in leaderboard.entries.iter().take(3) {
+    for entry in leaderboard.leaderboard.iter().take(3) {
         println!(
             "排名 {}: {} - 总收益率: {:.2}%",
-            entry.rank, entry.model_name, entry.total_return_pct
+            entry.rank, entry.model_name, entry.return_pct
         );
     }
 
@@ -6,11 +6,8 @@
 /// ```
 /// cargo run --example three_brokers_demo
 /// ```
-
-use nof0_backend::brokers::{
-    AccountManagement, Analytics, BinanceBroker, Broker, BrokerInstance, BrokerRegistry, CtpBroker,
-    MarketData, OkexBroker, Trading,
-};
+use nof0_backend::brokers::{BinanceBroker, BrokerRegistry, CtpBroker, DynBroker, OkexBroker};
+use nof0_backend::config::AgentsConfig;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -18,6 +15,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // 1. 创建 BrokerRegistry
     let mut registry = BrokerRegistry::new();
+    let agents = AgentsConfig::load_default()?;
 
     // 2. 创建并注册 Binance (币安)
     let binance_config = nof0_backend::brokers::binance::BinanceConfig::default();
@@ -25,20 +23,23 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         "binance".to_string(),
         "Binance (币安)".to_string(),
         binance_config,
-    );
-    registry.register(BrokerInstance::Binance(binance));
+    )
+    .with_models(agents.models_for_broker("binance"));
+    registry.register(binance);
     println!("✅ Binance (币安) 已注册");
 
     // 3. 创建并注册 OKEX
     let okex_config = nof0_backend::brokers::okex::OkexConfig::default();
-    let okex = OkexBroker::new("okex".to_string(), "OKEX".to_string(), okex_config);
-    registry.register(BrokerInstance::Okex(okex));
+    let okex = OkexBroker::new("okex".to_string(), "OKEX".to_string(), okex_config)
+        .with_models(agents.models_for_broker("okex"));
+    registry.register(okex);
     println!("✅ OKEX 已注册");
 
     // 4. 创建并注册 CTP (中国期货)
     let ctp_config = nof0_backend::brokers::ctp::CtpConfig::default();
-    let ctp = CtpBroker::new("ctp".to_string(), "CTP (中国期货)".to_string(), ctp_config);
-    registry.register(BrokerInstance::Ctp(ctp));
+    let ctp = CtpBroker::new("ctp".to_string(), "CTP (中国期货)".to_string(), ctp_config)
+        .with_models(agents.models_for_broker("ctp"));
+    registry.register(ctp);
     println!("✅ CTP (中国期货) 已注册");
 
     println!("\n已注册的交易所: {:?}\n", registry.list_ids());
@@ -46,17 +47,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 5. 演示 Binance
     println!("=== Binance (币安) 行情 ===");
     let binance_broker = registry.get("binance").expect("Binance broker not found");
-    demonstrate_broker(binance_broker, "BTCUSDT").await?;
+    demonstrate_broker(binance_broker.as_ref(), "BTCUSDT").await?;
 
     // 6. 演示 OKEX
     println!("\n=== OKEX 行情 ===");
     let okex_broker = registry.get("okex").expect("OKEX broker not found");
-    demonstrate_broker(okex_broker, "BTC-USDT").await?;
+    demonstrate_broker(okex_broker.as_ref(), "BTC-USDT").await?;
 
     // 7. 演示 CTP
     println!("\n=== CTP (中国期货) 行情 ===");
     let ctp_broker = registry.get("ctp").expect("CTP broker not found");
-    demonstrate_broker(ctp_broker, "IF2501").await?;
+    demonstrate_broker(ctp_broker.as_ref(), "IF2501").await?;
 
     // 8. 比较三个交易所的数据
     println!("\n=== 三大交易所对比 ===");
@@ -68,7 +69,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
 /// 演示单个交易所的功能
 async fn demonstrate_broker(
-    broker: &BrokerInstance,
+    broker: &dyn DynBroker,
     symbol: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // 获取价格
@@ -95,11 +96,21 @@ async fn demonstrate_broker(
     println!("\n📋 {} 订单簿 (前3档):", symbol);
     println!("  买盘:");
     for (i, bid) in orderbook.bids.iter().take(3).enumerate() {
-        println!("    [{}] 价格: ${:.2}, 数量: {:.2}", i + 1, bid.price, bid.quantity);
+        println!(
+            "    [{}] 价格: ${:.2}, 数量: {:.2}",
+            i + 1,
+            bid.price,
+            bid.quantity
+        );
     }
     println!("  卖盘:");
     for (i, ask) in orderbook.asks.iter().take(3).enumerate() {
-        println!("    [{}] 价格: ${:.2}, 数量: {:.2}", i + 1, ask.price, ask.quantity);
+        println!(
+            "    [{}] 价格: ${:.2}, 数量: {:.2}",
+            i + 1,
+            ask.price,
+            ask.quantity
+        );
     }
 
     Ok(())
@@ -107,25 +118,27 @@ async fn demonstrate_broker(
 
 /// 比较三个交易所
 async fn compare_brokers(registry: &BrokerRegistry) -> Result<(), Box<dyn std::error::Error>> {
-    println!("交易所          | AI模型数 | 总成交量24h      | 平均交易次数");
-    println!("---------------|--------|-----------------|------------");
+    println!("交易所          | AI模型数 | 总交易次数 | 净盈亏");
+    println!("---------------|--------|-----------|----------------");
 
     for broker_id in registry.list_ids() {
         if let Some(broker) = registry.get(&broker_id) {
-            let analytics = broker.get_analytics().await?;
-            let models = analytics.metrics.get("total_models").unwrap_or(&0.0);
-            let volume = analytics.metrics.get("total_volume_24h").unwrap_or(&0.0);
-            let avg_trades = analytics
-                .metrics
-                .get("avg_trades_per_model")
-                .unwrap_or(&0.0);
+            let analytics = broker.get_analytics().await?.analytics;
+            let trades: usize = analytics
+                .iter()
+                .map(|a| a.overall_trades_overview_table.total_trades)
+                .sum();
+            let net_pnl: f64 = analytics
+                .iter()
+                .map(|a| a.fee_pnl_moves_breakdown_table.overall_pnl_with_fees)
+                .sum();
 
             println!(
-                "{:<15} | {:>6.0} | ${:>14.0} | {:>10.0}",
+                "{:<15} | {:>6} | {:>9} | ${:>14.2}",
                 broker.broker_name(),
-                models,
-                volume,
-                avg_trades
+                analytics.len(),
+                trades,
+                net_pnl
             );
         }
     }
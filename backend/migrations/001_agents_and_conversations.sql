@@ -0,0 +1,42 @@
+-- 模型/Agent 表
+CREATE TABLE IF NOT EXISTS agents (
+    id VARCHAR(100) PRIMARY KEY,
+    name VARCHAR(200) NOT NULL,
+    provider VARCHAR(50) NOT NULL,
+    model VARCHAR(100) NOT NULL,
+    broker_id VARCHAR(50),
+    strategy VARCHAR(100),
+    risk_level VARCHAR(20),
+    initial_capital DOUBLE PRECISION NOT NULL DEFAULT 0,
+    config JSONB,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 对话会话表 (model_id 不加外键, 允许未登记的模型写入)
+CREATE TABLE IF NOT EXISTS conversations (
+    id BIGSERIAL PRIMARY KEY,
+    model_id VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 对话消息表 (ts_ms 为毫秒时间戳)
+CREATE TABLE IF NOT EXISTS conversation_messages (
+    id BIGSERIAL PRIMARY KEY,
+    conversation_id BIGINT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    role VARCHAR(20) NOT NULL,
+    content TEXT NOT NULL,
+    ts_ms BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_conversations_model_id ON conversations(model_id);
+CREATE INDEX IF NOT EXISTS idx_conversation_messages_conversation
+    ON conversation_messages(conversation_id, ts_ms);
+
+-- 注释
+COMMENT ON TABLE agents IS '参赛模型/Agent 表';
+COMMENT ON TABLE conversations IS 'Agent 对话会话表';
+COMMENT ON TABLE conversation_messages IS 'Agent 对话消息表';
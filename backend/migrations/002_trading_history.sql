@@ -0,0 +1,96 @@
+-- 订单表 (created_at/updated_at 为秒级时间戳)
+CREATE TABLE IF NOT EXISTS orders (
+    broker_id VARCHAR(50) NOT NULL,
+    order_id VARCHAR(100) NOT NULL,
+    model_id VARCHAR(100),
+    symbol VARCHAR(50) NOT NULL,
+    side VARCHAR(10) NOT NULL,
+    order_type VARCHAR(20) NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL,
+    filled_quantity DOUBLE PRECISION NOT NULL DEFAULT 0,
+    price DOUBLE PRECISION,
+    avg_price DOUBLE PRECISION,
+    status VARCHAR(20) NOT NULL,
+    created_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL,
+    PRIMARY KEY (broker_id, order_id)
+);
+
+-- 成交表
+CREATE TABLE IF NOT EXISTS fills (
+    broker_id VARCHAR(50) NOT NULL,
+    trade_id VARCHAR(100) NOT NULL,
+    order_id VARCHAR(100) NOT NULL,
+    model_id VARCHAR(100),
+    symbol VARCHAR(50) NOT NULL,
+    side VARCHAR(10) NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL,
+    fee DOUBLE PRECISION NOT NULL DEFAULT 0,
+    ts BIGINT NOT NULL,
+    PRIMARY KEY (broker_id, trade_id)
+);
+
+-- 持仓快照表 (同一 snapshot_ts 的行构成一次快照)
+CREATE TABLE IF NOT EXISTS position_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    broker_id VARCHAR(50) NOT NULL,
+    model_id VARCHAR(100),
+    snapshot_ts BIGINT NOT NULL,
+    symbol VARCHAR(50) NOT NULL,
+    entry_price DOUBLE PRECISION NOT NULL,
+    current_price DOUBLE PRECISION NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL,
+    unrealized_pnl DOUBLE PRECISION NOT NULL,
+    direction VARCHAR(10),
+    leverage INTEGER,
+    margin DOUBLE PRECISION
+);
+
+-- 账户权益快照表
+CREATE TABLE IF NOT EXISTS account_equity_snapshots (
+    id BIGSERIAL PRIMARY KEY,
+    broker_id VARCHAR(50) NOT NULL,
+    model_id VARCHAR(100) NOT NULL,
+    ts BIGINT NOT NULL,
+    equity DOUBLE PRECISION NOT NULL,
+    cash DOUBLE PRECISION,
+    realized_pnl DOUBLE PRECISION NOT NULL DEFAULT 0,
+    unrealized_pnl DOUBLE PRECISION NOT NULL DEFAULT 0,
+    return_pct DOUBLE PRECISION NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- LLM 调用记录表
+CREATE TABLE IF NOT EXISTS llm_calls (
+    id BIGSERIAL PRIMARY KEY,
+    model_id VARCHAR(100),
+    conversation_id BIGINT REFERENCES conversations(id) ON DELETE SET NULL,
+    provider VARCHAR(50) NOT NULL,
+    model VARCHAR(100) NOT NULL,
+    prompt_tokens INTEGER,
+    completion_tokens INTEGER,
+    latency_ms BIGINT,
+    request JSONB,
+    response JSONB,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 索引
+CREATE INDEX IF NOT EXISTS idx_orders_symbol ON orders(broker_id, symbol, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_orders_model_id ON orders(model_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_fills_order ON fills(broker_id, order_id);
+CREATE INDEX IF NOT EXISTS idx_fills_ts ON fills(broker_id, ts DESC);
+CREATE INDEX IF NOT EXISTS idx_position_snapshots_ts ON position_snapshots(broker_id, snapshot_ts DESC);
+CREATE INDEX IF NOT EXISTS idx_account_equity_model_ts ON account_equity_snapshots(model_id, ts);
+CREATE INDEX IF NOT EXISTS idx_llm_calls_model_id ON llm_calls(model_id, created_at DESC);
+
+-- 注释
+COMMENT ON TABLE orders IS '订单表';
+COMMENT ON TABLE fills IS '成交表';
+COMMENT ON TABLE position_snapshots IS '持仓快照表';
+COMMENT ON TABLE account_equity_snapshots IS '账户权益快照表';
+COMMENT ON TABLE llm_calls IS 'LLM 调用记录表';
+
+COMMENT ON COLUMN orders.status IS '订单状态: pending, accepted, partially_filled, filled, cancelled, rejected';
@@ -0,0 +1,30 @@
+-- 历史K线表 (open_time 为开盘时间, 秒级时间戳)
+CREATE TABLE IF NOT EXISTS market_klines (
+    venue VARCHAR(50) NOT NULL,
+    symbol VARCHAR(50) NOT NULL,
+    bar VARCHAR(10) NOT NULL,
+    open_time BIGINT NOT NULL,
+    open DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    close DOUBLE PRECISION NOT NULL,
+    volume DOUBLE PRECISION NOT NULL,
+    open_interest BIGINT,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (venue, symbol, bar, open_time)
+);
+
+-- 逐笔成交表
+CREATE TABLE IF NOT EXISTS market_trades (
+    venue VARCHAR(50) NOT NULL,
+    symbol VARCHAR(50) NOT NULL,
+    trade_id VARCHAR(100) NOT NULL,
+    ts BIGINT NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL,
+    side VARCHAR(10) NOT NULL,
+    PRIMARY KEY (venue, symbol, trade_id)
+);
+
+-- 索引优化查询性能
+CREATE INDEX IF NOT EXISTS idx_market_trades_time ON market_trades(venue, symbol, ts);
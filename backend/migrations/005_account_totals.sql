@@ -0,0 +1,13 @@
+-- 权益快照补充 account-totals 所需字段
+ALTER TABLE account_equity_snapshots
+    ADD COLUMN IF NOT EXISTS model_name VARCHAR(255) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS strategy VARCHAR(100) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS risk_level VARCHAR(50) NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS total_unrealized_pnl DOUBLE PRECISION NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS cum_pnl_pct DOUBLE PRECISION NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS sharpe_ratio DOUBLE PRECISION NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS minute_marker INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS hourly_marker INTEGER NOT NULL DEFAULT 0;
+
+-- 前端按 lastHourlyMarker 增量拉取
+CREATE INDEX IF NOT EXISTS idx_account_equity_hourly_marker ON account_equity_snapshots(hourly_marker, ts);
@@ -0,0 +1,15 @@
+-- 每日排行榜快照 (同一天重复快照覆盖)
+CREATE TABLE IF NOT EXISTS leaderboard_snapshots (
+    snapshot_date DATE NOT NULL,
+    model_id VARCHAR(100) NOT NULL,
+    rank INTEGER NOT NULL,
+    taken_at BIGINT NOT NULL,
+    currency VARCHAR(10) NOT NULL,
+    entry JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (snapshot_date, model_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_leaderboard_snapshots_model ON leaderboard_snapshots(model_id, snapshot_date);
+
+COMMENT ON TABLE leaderboard_snapshots IS '每日排行榜快照表';
@@ -0,0 +1,11 @@
+-- LLM 调用用量与费用 (按 Agent 与决策周期归集)
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS cycle_id VARCHAR(150);
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS cost_usd DOUBLE PRECISION NOT NULL DEFAULT 0;
+ALTER TABLE llm_calls ADD COLUMN IF NOT EXISTS ts BIGINT NOT NULL DEFAULT EXTRACT(EPOCH FROM NOW())::BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_llm_calls_model_ts ON llm_calls(model_id, ts);
+CREATE INDEX IF NOT EXISTS idx_llm_calls_cycle ON llm_calls(cycle_id);
+
+COMMENT ON COLUMN llm_calls.cycle_id IS '决策周期 ID';
+COMMENT ON COLUMN llm_calls.cost_usd IS '按价格表计算的费用 (美元)';
+COMMENT ON COLUMN llm_calls.ts IS '调用时间 (秒), 回测时为模拟时间';
@@ -0,0 +1,6 @@
+-- 会话使用的 Prompt 模板版本 (`id@version`), 用于对比不同 Prompt 的表现
+ALTER TABLE conversations ADD COLUMN IF NOT EXISTS prompt_version VARCHAR(100);
+
+CREATE INDEX IF NOT EXISTS idx_conversations_prompt_version ON conversations(model_id, prompt_version);
+
+COMMENT ON COLUMN conversations.prompt_version IS 'Prompt 模板版本 (id@version)';
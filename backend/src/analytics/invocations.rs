@@ -0,0 +1,54 @@
+use crate::brokers::InvocationBreakdown;
+
+/// 决策调用次数与间隔的累计统计
+#[derive(Debug, Clone, Default)]
+pub struct InvocationStats {
+    pub count: u64,
+    pub last_at: Option<i64>,
+    breaks: u64,
+    sum_break: f64,
+    sum_sq_break: f64,
+    min_break: f64,
+    max_break: f64,
+}
+
+impl InvocationStats {
+    /// 记录一次调用 (秒级时间戳)
+    pub fn record(&mut self, timestamp: i64) {
+        if let Some(last) = self.last_at {
+            let mins = (timestamp - last).max(0) as f64 / 60.0;
+            if self.breaks == 0 {
+                self.min_break = mins;
+                self.max_break = mins;
+            } else {
+                self.min_break = self.min_break.min(mins);
+                self.max_break = self.max_break.max(mins);
+            }
+            self.breaks += 1;
+            self.sum_break += mins;
+            self.sum_sq_break += mins * mins;
+        }
+        self.count += 1;
+        self.last_at = Some(timestamp);
+    }
+
+    pub fn breakdown(&self) -> InvocationBreakdown {
+        let n = self.breaks as f64;
+        let (avg, std) = if self.breaks == 0 {
+            (0.0, 0.0)
+        } else if self.breaks == 1 {
+            (self.sum_break, 0.0)
+        } else {
+            let avg = self.sum_break / n;
+            let variance = (self.sum_sq_break - n * avg * avg) / (n - 1.0);
+            (avg, variance.max(0.0).sqrt())
+        };
+        InvocationBreakdown {
+            num_invocations: self.count,
+            avg_invocation_break_mins: avg,
+            std_invocation_break_mins: std,
+            min_invocation_break_mins: self.min_break,
+            max_invocation_break_mins: self.max_break,
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::brokers::PerformanceMetrics;
+
+const SECS_PER_YEAR: f64 = 365.0 * 86400.0;
+
+/// 算术平均
+pub fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// 样本标准差 (n - 1)
+pub fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let avg = mean(values);
+    let variance =
+        values.iter().map(|v| (v - avg).powi(2)).sum::<f64>() / (values.len() as f64 - 1.0);
+    variance.sqrt()
+}
+
+/// 中位数
+pub fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+/// 逐期收益率
+fn period_returns(equity: &[f64]) -> Vec<f64> {
+    equity
+        .windows(2)
+        .filter(|w| w[0] > 0.0)
+        .map(|w| w[1] / w[0] - 1.0)
+        .collect()
+}
+
+/// 按采样周期年化的夏普比率（无风险利率取 0）
+pub fn sharpe_ratio(equity: &[f64], bar_secs: i64) -> f64 {
+    let returns = period_returns(equity);
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let std = std_dev(&returns);
+    if std <= f64::EPSILON {
+        return 0.0;
+    }
+
+    let periods_per_year = SECS_PER_YEAR / bar_secs.max(1) as f64;
+    mean(&returns) / std * periods_per_year.sqrt()
+}
+
+/// 按采样周期年化的索提诺比率, 只以下行波动作为风险
+pub fn sortino_ratio(equity: &[f64], bar_secs: i64) -> f64 {
+    let returns = period_returns(equity);
+    if returns.len() < 2 {
+        return 0.0;
+    }
+
+    let downside =
+        (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len() as f64).sqrt();
+    if downside <= f64::EPSILON {
+        return 0.0;
+    }
+
+    let periods_per_year = SECS_PER_YEAR / bar_secs.max(1) as f64;
+    mean(&returns) / downside * periods_per_year.sqrt()
+}
+
+/// 最大回撤（百分比）
+pub fn max_drawdown_pct(equity: &[f64]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut max_drawdown: f64 = 0.0;
+    for &value in equity {
+        peak = peak.max(value);
+        if peak > 0.0 {
+            max_drawdown = max_drawdown.max((peak - value) / peak * 100.0);
+        }
+    }
+    max_drawdown
+}
+
+/// 最长回撤持续时间（秒）, 从创新高到重新回到该高点, 未恢复的回撤计算到最后一个点
+pub fn max_drawdown_duration(curve: &[(i64, f64)]) -> i64 {
+    let Some(&(first_ts, first_value)) = curve.first() else {
+        return 0;
+    };
+    let (mut peak_ts, mut peak) = (first_ts, first_value);
+    let mut longest = 0;
+    for &(ts, value) in curve {
+        if value >= peak {
+            peak = value;
+            peak_ts = ts;
+        } else {
+            longest = longest.max(ts - peak_ts);
+        }
+    }
+    longest
+}
+
+/// 由权益曲线 `(时间戳, 权益)` 计算绩效指标
+///
+/// 采样周期取相邻点间隔的中位数, 按 7x24 小时年化。
+pub fn performance_metrics(curve: &[(i64, f64)]) -> PerformanceMetrics {
+    let (Some(&(start, first)), Some(&(end, last))) = (curve.first(), curve.last()) else {
+        return PerformanceMetrics::default();
+    };
+    if curve.len() < 2 || first <= 0.0 {
+        return PerformanceMetrics::default();
+    }
+
+    let equity: Vec<f64> = curve.iter().map(|(_, v)| *v).collect();
+    let intervals: Vec<f64> = curve.windows(2).map(|w| (w[1].0 - w[0].0) as f64).collect();
+    let bar_secs = median(&intervals).round().max(1.0) as i64;
+    let periods_per_year = SECS_PER_YEAR / bar_secs as f64;
+
+    let total_return = last / first - 1.0;
+    let years = (end - start).max(1) as f64 / SECS_PER_YEAR;
+    let annualized_return_pct = if last > 0.0 {
+        ((last / first).powf(1.0 / years) - 1.0) * 100.0
+    } else {
+        -100.0
+    };
+    let max_drawdown = max_drawdown_pct(&equity);
+
+    PerformanceMetrics {
+        total_return_pct: total_return * 100.0,
+        annualized_return_pct,
+        annualized_volatility_pct: std_dev(&period_returns(&equity))
+            * periods_per_year.sqrt()
+            * 100.0,
+        sharpe_ratio: sharpe_ratio(&equity, bar_secs),
+        sortino_ratio: sortino_ratio(&equity, bar_secs),
+        calmar_ratio: if max_drawdown > 0.0 {
+            annualized_return_pct / max_drawdown
+        } else {
+            0.0
+        },
+        max_drawdown_pct: max_drawdown,
+        max_drawdown_duration_secs: max_drawdown_duration(curve),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drawdown_and_ratios() {
+        let curve = vec![
+            (0, 100.0),
+            (3600, 110.0),
+            (7200, 99.0),
+            (10800, 104.5),
+            (14400, 112.0),
+            (18000, 108.0),
+        ];
+        let metrics = performance_metrics(&curve);
+        assert!((metrics.total_return_pct - 8.0).abs() < 1e-9);
+        assert!((metrics.max_drawdown_pct - 10.0).abs() < 1e-9);
+        // 3600 创新高, 14400 才恢复
+        assert_eq!(metrics.max_drawdown_duration_secs, 7200);
+        assert!(metrics.sharpe_ratio > 0.0);
+        assert!(metrics.sortino_ratio > metrics.sharpe_ratio);
+        assert!(metrics.calmar_ratio > 0.0);
+
+        assert_eq!(median(&[3.0, 1.0, 2.0, 10.0]), 2.5);
+        assert_eq!(performance_metrics(&curve[..1]).sharpe_ratio, 0.0);
+    }
+}
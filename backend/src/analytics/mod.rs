@@ -0,0 +1,11 @@
+// Performance analytics: round trips, equity-curve metrics and per-model reports
+
+pub mod invocations;
+pub mod metrics;
+pub mod report;
+pub mod round_trips;
+
+pub use invocations::InvocationStats;
+pub use metrics::*;
+pub use report::*;
+pub use round_trips::*;
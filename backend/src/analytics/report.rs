@@ -0,0 +1,350 @@
+use super::invocations::InvocationStats;
+use super::metrics::{mean, median, performance_metrics, std_dev};
+use super::round_trips::{build_round_trips, RoundTrip, TradeDirection};
+use crate::brokers::*;
+use std::collections::BTreeMap;
+
+/// 持仓时长分布的区间上限 (分钟)
+const HOLDING_BUCKETS: [(f64, &str); 6] = [
+    (15.0, "<15m"),
+    (60.0, "15m-1h"),
+    (240.0, "1h-4h"),
+    (1440.0, "4h-1d"),
+    (10080.0, "1d-1w"),
+    (f64::INFINITY, ">1w"),
+];
+
+/// 资金费流水 (正数为支付, 负数为收取)
+#[derive(Debug, Clone)]
+pub struct FundingPayment {
+    pub symbol: String,
+    pub amount: f64,
+    pub timestamp: i64,
+}
+
+/// 单模型绩效分析
+///
+/// 以成交记录还原的完整交易计算胜负、多空、手续费与品种归因, 以权益快照计算年化收益、
+/// 波动、夏普、索提诺、卡玛与回撤。输出字段与 nof1 `analytics` 接口一致。
+pub struct PerformanceAnalyzer {
+    model_id: String,
+    equity: Vec<(i64, f64)>,
+    funding: Vec<FundingPayment>,
+    invocations: InvocationStats,
+}
+
+impl PerformanceAnalyzer {
+    pub fn new(model_id: impl Into<String>) -> Self {
+        Self {
+            model_id: model_id.into(),
+            equity: Vec::new(),
+            funding: Vec::new(),
+            invocations: InvocationStats::default(),
+        }
+    }
+
+    /// 权益曲线 `(时间戳, 权益)`
+    pub fn with_equity(mut self, equity: Vec<(i64, f64)>) -> Self {
+        self.equity = equity;
+        self
+    }
+
+    /// 由权益快照设置权益曲线
+    pub fn with_account_totals(self, totals: &[AccountTotal]) -> Self {
+        self.with_equity(totals.iter().map(|t| (t.timestamp, t.equity)).collect())
+    }
+
+    pub fn with_funding(mut self, funding: Vec<FundingPayment>) -> Self {
+        self.funding = funding;
+        self
+    }
+
+    pub fn with_invocations(mut self, invocations: InvocationStats) -> Self {
+        self.invocations = invocations;
+        self
+    }
+
+    /// 计算分析报告, `now` 为报告时间 (秒)
+    pub fn analyze(&self, trades: &[Trade], now: i64) -> ModelAnalytics {
+        let trips = build_round_trips(trades);
+        let funding_paid: f64 = self.funding.iter().map(|f| f.amount).sum();
+
+        ModelAnalytics {
+            id: self.model_id.clone(),
+            model_id: self.model_id.clone(),
+            updated_at: now as f64,
+            fee_pnl_moves_breakdown_table: fee_pnl_breakdown(trades, &trips, funding_paid),
+            winners_losers_breakdown_table: winners_losers_breakdown(&trips),
+            longs_shorts_breakdown_table: longs_shorts_breakdown(&trips),
+            overall_trades_overview_table: trades_overview(&trips),
+            invocation_breakdown_table: self.invocations.breakdown(),
+            performance_table: performance_metrics(&self.equity),
+            holding_period_distribution: holding_period_distribution(&trips),
+            symbol_breakdown_table: symbol_attribution(&trips, &self.funding),
+            last_trade_exit_time: trips.iter().map(|t| t.exit_time).max().map(|t| t as f64),
+        }
+    }
+}
+
+fn collect(trips: &[&RoundTrip], f: impl Fn(&RoundTrip) -> f64) -> Vec<f64> {
+    trips.iter().map(|t| f(t)).collect()
+}
+
+fn fee_pnl_breakdown(trades: &[Trade], trips: &[RoundTrip], funding_paid: f64) -> FeePnlBreakdown {
+    let all: Vec<&RoundTrip> = trips.iter().collect();
+    let gross = collect(&all, |t| t.gross_pnl);
+    let net = collect(&all, RoundTrip::net_pnl);
+    let fees: Vec<f64> = trades.iter().map(|t| t.fee).collect();
+
+    let total_fees: f64 = fees.iter().sum();
+    let pnl_without_fees: f64 = gross.iter().sum();
+    FeePnlBreakdown {
+        overall_pnl_with_fees: pnl_without_fees - total_fees - funding_paid,
+        overall_pnl_without_fees: pnl_without_fees,
+        total_fees_paid: total_fees,
+        total_fees_as_pct_of_pnl: if pnl_without_fees > 0.0 {
+            total_fees / pnl_without_fees * 100.0
+        } else {
+            0.0
+        },
+        total_funding_paid: funding_paid,
+        avg_taker_fee: mean(&fees),
+        std_taker_fee: std_dev(&fees),
+        avg_gross_pnl: mean(&gross),
+        std_gross_pnl: std_dev(&gross),
+        avg_net_pnl: mean(&net),
+        std_net_pnl: std_dev(&net),
+        biggest_net_gain: net.iter().copied().fold(0.0, f64::max),
+        biggest_net_loss: net.iter().copied().fold(0.0, f64::min),
+    }
+}
+
+fn winners_losers_breakdown(trips: &[RoundTrip]) -> WinnersLosersBreakdown {
+    let all: Vec<&RoundTrip> = trips.iter().collect();
+    let (winners, losers): (Vec<&RoundTrip>, Vec<&RoundTrip>) =
+        trips.iter().partition(|t| t.is_winner());
+    let win_pnl = collect(&winners, RoundTrip::net_pnl);
+    let loss_pnl = collect(&losers, RoundTrip::net_pnl);
+    let gross_win: f64 = win_pnl.iter().sum();
+    let gross_loss: f64 = -loss_pnl.iter().sum::<f64>();
+
+    WinnersLosersBreakdown {
+        win_rate: if trips.is_empty() {
+            0.0
+        } else {
+            winners.len() as f64 / trips.len() as f64 * 100.0
+        },
+        profit_factor: if gross_loss > 0.0 {
+            gross_win / gross_loss
+        } else {
+            0.0
+        },
+        expectancy: mean(&collect(&all, RoundTrip::net_pnl)),
+        avg_winners_net_pnl: mean(&win_pnl),
+        std_winners_net_pnl: std_dev(&win_pnl),
+        avg_losers_net_pnl: mean(&loss_pnl),
+        std_losers_net_pnl: std_dev(&loss_pnl),
+        avg_winners_notional: mean(&collect(&winners, |t| t.notional)),
+        std_winners_notional: std_dev(&collect(&winners, |t| t.notional)),
+        avg_losers_notional: mean(&collect(&losers, |t| t.notional)),
+        std_losers_notional: std_dev(&collect(&losers, |t| t.notional)),
+        avg_winners_holding_period: mean(&collect(&winners, RoundTrip::holding_mins)),
+        std_winners_holding_period: std_dev(&collect(&winners, RoundTrip::holding_mins)),
+        avg_losers_holding_period: mean(&collect(&losers, RoundTrip::holding_mins)),
+        std_losers_holding_period: std_dev(&collect(&losers, RoundTrip::holding_mins)),
+    }
+}
+
+fn longs_shorts_breakdown(trips: &[RoundTrip]) -> LongsShortsBreakdown {
+    let (longs, shorts): (Vec<&RoundTrip>, Vec<&RoundTrip>) = trips
+        .iter()
+        .partition(|t| t.direction == TradeDirection::Long);
+
+    LongsShortsBreakdown {
+        num_long_trades: longs.len(),
+        num_short_trades: shorts.len(),
+        long_short_trades_ratio: if trips.is_empty() {
+            0.0
+        } else {
+            longs.len() as f64 / trips.len() as f64
+        },
+        avg_longs_net_pnl: mean(&collect(&longs, RoundTrip::net_pnl)),
+        std_longs_net_pnl: std_dev(&collect(&longs, RoundTrip::net_pnl)),
+        avg_shorts_net_pnl: mean(&collect(&shorts, RoundTrip::net_pnl)),
+        std_shorts_net_pnl: std_dev(&collect(&shorts, RoundTrip::net_pnl)),
+        avg_longs_notional: mean(&collect(&longs, |t| t.notional)),
+        std_longs_notional: std_dev(&collect(&longs, |t| t.notional)),
+        avg_shorts_notional: mean(&collect(&shorts, |t| t.notional)),
+        std_shorts_notional: std_dev(&collect(&shorts, |t| t.notional)),
+        avg_longs_holding_period: mean(&collect(&longs, RoundTrip::holding_mins)),
+        std_longs_holding_period: std_dev(&collect(&longs, RoundTrip::holding_mins)),
+        avg_shorts_holding_period: mean(&collect(&shorts, RoundTrip::holding_mins)),
+        std_shorts_holding_period: std_dev(&collect(&shorts, RoundTrip::holding_mins)),
+    }
+}
+
+fn trades_overview(trips: &[RoundTrip]) -> TradesOverview {
+    let all: Vec<&RoundTrip> = trips.iter().collect();
+    let holding = collect(&all, RoundTrip::holding_mins);
+    let notional = collect(&all, |t| t.notional);
+
+    TradesOverview {
+        total_trades: trips.len(),
+        avg_holding_period_mins: mean(&holding),
+        median_holding_period_mins: median(&holding),
+        std_holding_period_mins: std_dev(&holding),
+        avg_size_of_trade_notional: mean(&notional),
+        median_size_of_trade_notional: median(&notional),
+        std_size_of_trade_notional: std_dev(&notional),
+    }
+}
+
+fn holding_period_distribution(trips: &[RoundTrip]) -> Vec<HoldingPeriodBucket> {
+    let mut buckets: Vec<HoldingPeriodBucket> = HOLDING_BUCKETS
+        .iter()
+        .map(|(max, label)| HoldingPeriodBucket {
+            label: label.to_string(),
+            max_mins: max.is_finite().then_some(*max),
+            num_trades: 0,
+            net_pnl: 0.0,
+        })
+        .collect();
+    for trip in trips {
+        let mins = trip.holding_mins();
+        let index = HOLDING_BUCKETS
+            .iter()
+            .position(|(max, _)| mins < *max)
+            .unwrap_or(HOLDING_BUCKETS.len() - 1);
+        buckets[index].num_trades += 1;
+        buckets[index].net_pnl += trip.net_pnl();
+    }
+    buckets
+}
+
+fn symbol_attribution(trips: &[RoundTrip], funding: &[FundingPayment]) -> Vec<SymbolAttribution> {
+    let mut by_symbol: BTreeMap<&str, SymbolAttribution> = BTreeMap::new();
+    let mut wins: BTreeMap<&str, usize> = BTreeMap::new();
+    for trip in trips {
+        let entry = by_symbol
+            .entry(&trip.symbol)
+            .or_insert_with(|| SymbolAttribution {
+                symbol: trip.symbol.clone(),
+                ..Default::default()
+            });
+        entry.num_trades += 1;
+        entry.gross_pnl += trip.gross_pnl;
+        entry.fees_paid += trip.fees;
+        if trip.is_winner() {
+            *wins.entry(&trip.symbol).or_default() += 1;
+        }
+    }
+    for payment in funding {
+        by_symbol
+            .entry(&payment.symbol)
+            .or_insert_with(|| SymbolAttribution {
+                symbol: payment.symbol.clone(),
+                ..Default::default()
+            })
+            .funding_paid += payment.amount;
+    }
+
+    let mut rows: Vec<SymbolAttribution> = by_symbol
+        .into_iter()
+        .map(|(symbol, mut row)| {
+            row.net_pnl = row.gross_pnl - row.fees_paid - row.funding_paid;
+            if row.num_trades > 0 {
+                row.win_rate =
+                    wins.get(symbol).copied().unwrap_or(0) as f64 / row.num_trades as f64 * 100.0;
+            }
+            row
+        })
+        .collect();
+    let total_abs: f64 = rows.iter().map(|r| r.net_pnl.abs()).sum();
+    for row in &mut rows {
+        if total_abs > 0.0 {
+            row.pnl_contribution_pct = row.net_pnl / total_abs * 100.0;
+        }
+    }
+    rows.sort_by(|a, b| b.net_pnl.total_cmp(&a.net_pnl));
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(symbol: &str, side: OrderSide, price: f64, ts: i64) -> Trade {
+        Trade {
+            trade_id: format!("{}-{}", symbol, ts),
+            order_id: format!("{}-{}", symbol, ts),
+            symbol: symbol.to_string(),
+            side,
+            price,
+            quantity: 1.0,
+            fee: 0.5,
+            timestamp: ts,
+        }
+    }
+
+    #[test]
+    fn test_model_report_matches_round_trips() {
+        let trades = vec![
+            // 多单盈利 10, 持有 30 分钟
+            fill("BTCUSDT", OrderSide::Buy, 100.0, 0),
+            fill("BTCUSDT", OrderSide::Sell, 110.0, 1800),
+            // 空单亏损 5, 持有 2 小时
+            fill("ETHUSDT", OrderSide::Sell, 50.0, 0),
+            fill("ETHUSDT", OrderSide::Buy, 55.0, 7200),
+        ];
+        let funding = vec![FundingPayment {
+            symbol: "ETHUSDT".to_string(),
+            amount: 1.0,
+            timestamp: 3600,
+        }];
+        let mut invocations = InvocationStats::default();
+        for ts in [0, 180, 300] {
+            invocations.record(ts);
+        }
+
+        let report = PerformanceAnalyzer::new("model-a")
+            .with_equity(vec![(0, 1000.0), (3600, 1010.0), (7200, 1003.0)])
+            .with_funding(funding)
+            .with_invocations(invocations)
+            .analyze(&trades, 7200);
+
+        let fees = &report.fee_pnl_moves_breakdown_table;
+        assert_eq!(fees.total_fees_paid, 2.0);
+        assert_eq!(fees.overall_pnl_without_fees, 5.0);
+        assert_eq!(fees.overall_pnl_with_fees, 2.0);
+        assert_eq!(fees.biggest_net_gain, 9.0);
+        assert_eq!(fees.biggest_net_loss, -6.0);
+
+        let wl = &report.winners_losers_breakdown_table;
+        assert_eq!(wl.win_rate, 50.0);
+        assert_eq!(wl.profit_factor, 1.5);
+        assert_eq!(wl.avg_winners_holding_period, 30.0);
+
+        let ls = &report.longs_shorts_breakdown_table;
+        assert_eq!((ls.num_long_trades, ls.num_short_trades), (1, 1));
+        assert_eq!(report.overall_trades_overview_table.total_trades, 2);
+        assert_eq!(report.last_trade_exit_time, Some(7200.0));
+
+        let buckets: Vec<usize> = report
+            .holding_period_distribution
+            .iter()
+            .map(|b| b.num_trades)
+            .collect();
+        assert_eq!(buckets, vec![0, 1, 1, 0, 0, 0]);
+
+        let symbols = &report.symbol_breakdown_table;
+        assert_eq!(symbols[0].symbol, "BTCUSDT");
+        assert_eq!(symbols[1].net_pnl, -7.0);
+        assert_eq!(symbols[1].funding_paid, 1.0);
+
+        let inv = &report.invocation_breakdown_table;
+        assert_eq!(inv.num_invocations, 3);
+        assert_eq!(inv.min_invocation_break_mins, 2.0);
+        assert_eq!(inv.max_invocation_break_mins, 3.0);
+        assert!(report.performance_table.max_drawdown_pct > 0.0);
+    }
+}
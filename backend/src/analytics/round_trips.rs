@@ -0,0 +1,221 @@
+use crate::brokers::{OrderSide, Trade};
+use std::collections::HashMap;
+
+const QTY_EPSILON: f64 = 1e-9;
+
+/// 交易方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeDirection {
+    Long,
+    Short,
+}
+
+/// 一笔完整交易: 从空仓开仓到再次空仓
+#[derive(Debug, Clone)]
+pub struct RoundTrip {
+    pub symbol: String,
+    pub direction: TradeDirection,
+    pub entry_time: i64,
+    pub exit_time: i64,
+    /// 开仓均价
+    pub entry_price: f64,
+    /// 平仓均价
+    pub exit_price: f64,
+    /// 累计开仓数量
+    pub quantity: f64,
+    /// 开仓名义金额
+    pub notional: f64,
+    /// 不含手续费的盈亏
+    pub gross_pnl: f64,
+    pub fees: f64,
+}
+
+impl RoundTrip {
+    pub fn net_pnl(&self) -> f64 {
+        self.gross_pnl - self.fees
+    }
+
+    pub fn holding_mins(&self) -> f64 {
+        (self.exit_time - self.entry_time).max(0) as f64 / 60.0
+    }
+
+    pub fn is_winner(&self) -> bool {
+        self.net_pnl() > 0.0
+    }
+}
+
+/// 某品种当前未平的持仓
+struct OpenTrip {
+    direction: TradeDirection,
+    entry_time: i64,
+    /// 剩余持仓
+    quantity: f64,
+    avg_price: f64,
+    opened_quantity: f64,
+    opened_value: f64,
+    closed_quantity: f64,
+    closed_value: f64,
+    gross_pnl: f64,
+    fees: f64,
+}
+
+impl OpenTrip {
+    fn open(direction: TradeDirection, price: f64, quantity: f64, fee: f64, ts: i64) -> Self {
+        Self {
+            direction,
+            entry_time: ts,
+            quantity,
+            avg_price: price,
+            opened_quantity: quantity,
+            opened_value: price * quantity,
+            closed_quantity: 0.0,
+            closed_value: 0.0,
+            gross_pnl: 0.0,
+            fees: fee,
+        }
+    }
+
+    fn into_round_trip(self, symbol: &str, exit_time: i64) -> RoundTrip {
+        RoundTrip {
+            symbol: symbol.to_string(),
+            direction: self.direction,
+            entry_time: self.entry_time,
+            exit_time,
+            entry_price: self.opened_value / self.opened_quantity,
+            exit_price: self.closed_value / self.closed_quantity,
+            quantity: self.opened_quantity,
+            notional: self.opened_value,
+            gross_pnl: self.gross_pnl,
+            fees: self.fees,
+        }
+    }
+}
+
+/// 由成交记录还原已平仓的完整交易
+///
+/// 按品种以均价法累计持仓, 回到空仓时形成一笔交易; 反手成交按数量拆分为平仓与新开仓,
+/// 手续费按比例分摊。尚未平仓的持仓不计入。
+pub fn build_round_trips(trades: &[Trade]) -> Vec<RoundTrip> {
+    let mut sorted: Vec<&Trade> = trades.iter().collect();
+    sorted.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then_with(|| a.trade_id.cmp(&b.trade_id))
+    });
+
+    let mut open: HashMap<&str, OpenTrip> = HashMap::new();
+    let mut trips = Vec::new();
+    for trade in sorted {
+        if trade.quantity <= QTY_EPSILON {
+            continue;
+        }
+        let direction = match trade.side {
+            OrderSide::Buy => TradeDirection::Long,
+            OrderSide::Sell => TradeDirection::Short,
+        };
+
+        let Some(mut trip) = open.remove(trade.symbol.as_str()) else {
+            open.insert(
+                &trade.symbol,
+                OpenTrip::open(
+                    direction,
+                    trade.price,
+                    trade.quantity,
+                    trade.fee,
+                    trade.timestamp,
+                ),
+            );
+            continue;
+        };
+
+        if trip.direction == direction {
+            let value = trip.avg_price * trip.quantity + trade.price * trade.quantity;
+            trip.quantity += trade.quantity;
+            trip.avg_price = value / trip.quantity;
+            trip.opened_quantity += trade.quantity;
+            trip.opened_value += trade.price * trade.quantity;
+            trip.fees += trade.fee;
+            open.insert(&trade.symbol, trip);
+            continue;
+        }
+
+        let closing = trade.quantity.min(trip.quantity);
+        let sign = match trip.direction {
+            TradeDirection::Long => 1.0,
+            TradeDirection::Short => -1.0,
+        };
+        trip.gross_pnl += (trade.price - trip.avg_price) * closing * sign;
+        trip.fees += trade.fee * closing / trade.quantity;
+        trip.quantity -= closing;
+        trip.closed_quantity += closing;
+        trip.closed_value += trade.price * closing;
+
+        if trip.quantity > QTY_EPSILON {
+            open.insert(&trade.symbol, trip);
+            continue;
+        }
+        trips.push(trip.into_round_trip(&trade.symbol, trade.timestamp));
+
+        // 反手: 剩余数量开新仓
+        let remaining = trade.quantity - closing;
+        if remaining > QTY_EPSILON {
+            open.insert(
+                &trade.symbol,
+                OpenTrip::open(
+                    direction,
+                    trade.price,
+                    remaining,
+                    trade.fee * remaining / trade.quantity,
+                    trade.timestamp,
+                ),
+            );
+        }
+    }
+    trips
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(id: &str, side: OrderSide, price: f64, quantity: f64, ts: i64) -> Trade {
+        Trade {
+            trade_id: id.to_string(),
+            order_id: id.to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side,
+            price,
+            quantity,
+            fee: 1.0,
+            timestamp: ts,
+        }
+    }
+
+    #[test]
+    fn test_scale_in_and_flip() {
+        let trades = vec![
+            fill("1", OrderSide::Buy, 100.0, 1.0, 0),
+            fill("2", OrderSide::Buy, 110.0, 1.0, 60),
+            // 平多 2 并反手开空 1
+            fill("3", OrderSide::Sell, 120.0, 3.0, 600),
+            fill("4", OrderSide::Buy, 100.0, 1.0, 1200),
+            // 未平仓的持仓不计入
+            fill("5", OrderSide::Buy, 100.0, 1.0, 1800),
+        ];
+        let trips = build_round_trips(&trades);
+        assert_eq!(trips.len(), 2);
+
+        let long = &trips[0];
+        assert_eq!(long.direction, TradeDirection::Long);
+        assert!((long.entry_price - 105.0).abs() < 1e-9);
+        assert!((long.gross_pnl - 30.0).abs() < 1e-9);
+        assert!((long.fees - (2.0 + 2.0 / 3.0)).abs() < 1e-9);
+        assert!((long.holding_mins() - 10.0).abs() < 1e-9);
+
+        let short = &trips[1];
+        assert_eq!(short.direction, TradeDirection::Short);
+        assert_eq!(short.entry_time, 600);
+        assert!((short.gross_pnl - 20.0).abs() < 1e-9);
+        assert!((short.fees - (1.0 / 3.0 + 1.0)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,371 @@
+use crate::brokers::*;
+use crate::engine::Clock;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+use std::sync::Arc;
+
+/// 回测使用的历史K线
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalData {
+    /// K线周期, 例如 "1m" "1h" "1d"
+    pub interval: String,
+    /// 按品种的K线, 时间戳为开盘时间（秒）
+    pub klines: HashMap<String, Vec<Kline>>,
+}
+
+impl HistoricalData {
+    pub fn new(interval: impl Into<String>) -> Self {
+        Self {
+            interval: interval.into(),
+            klines: HashMap::new(),
+        }
+    }
+
+    /// 添加某品种的K线（按时间排序）
+    pub fn with_klines(mut self, symbol: impl Into<String>, mut klines: Vec<Kline>) -> Self {
+        klines.sort_by_key(|k| k.timestamp);
+        self.klines.insert(symbol.into(), klines);
+        self
+    }
+
+    /// 从 JSON 文件加载
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read historical data {}", path.display()))?;
+        let mut data: Self = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid historical data {}", path.display()))?;
+        for klines in data.klines.values_mut() {
+            klines.sort_by_key(|k| k.timestamp);
+        }
+        data.bar_secs()?;
+        Ok(data)
+    }
+
+    /// K线周期（秒）
+    pub fn bar_secs(&self) -> Result<i64> {
+        interval_secs(&self.interval).ok_or_else(|| anyhow!("Invalid interval: {}", self.interval))
+    }
+
+    /// 品种列表（排序）
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.klines.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// 所有K线的收盘时间（去重升序）
+    pub fn timeline(&self) -> Result<Vec<i64>> {
+        let bar_secs = self.bar_secs()?;
+        let times: BTreeSet<i64> = self
+            .klines
+            .values()
+            .flatten()
+            .map(|k| k.timestamp + bar_secs)
+            .collect();
+        Ok(times.into_iter().collect())
+    }
+
+    /// 在 `close_time` 收盘的K线
+    pub fn bar_closing_at(&self, symbol: &str, close_time: i64) -> Option<&Kline> {
+        let bar_secs = self.bar_secs().ok()?;
+        let klines = self.klines.get(symbol)?;
+        klines
+            .binary_search_by_key(&(close_time - bar_secs), |k| k.timestamp)
+            .ok()
+            .map(|idx| &klines[idx])
+    }
+
+    /// `now` 时已经收盘的K线
+    fn visible(&self, symbol: &str, now: i64, bar_secs: i64) -> &[Kline] {
+        let Some(klines) = self.klines.get(symbol) else {
+            return &[];
+        };
+        let end = klines.partition_point(|k| k.timestamp + bar_secs <= now);
+        &klines[..end]
+    }
+}
+
+/// 解析K线周期, 例如 "30s" "5m" "4h" "1d" "1w"
+pub fn interval_secs(interval: &str) -> Option<i64> {
+    let unit = interval.chars().last()?;
+    let value: i64 = interval[..interval.len() - unit.len_utf8()].parse().ok()?;
+    let secs = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+        'w' => 7 * 86400,
+        _ => return None,
+    };
+    (value > 0).then_some(value * secs)
+}
+
+/// 历史行情源
+///
+/// 按时钟回放历史K线, 只返回当前时间已收盘的数据, 避免未来函数。
+/// 没有订单簿, 交易与账户接口返回 `NotSupported`, 需配合 `PaperBroker` 使用。
+pub struct HistoricalFeed {
+    id: String,
+    data: Arc<HistoricalData>,
+    bar_secs: i64,
+    clock: Clock,
+}
+
+impl HistoricalFeed {
+    pub fn new(data: Arc<HistoricalData>, clock: Clock) -> Result<Self> {
+        Ok(Self {
+            id: "historical".to_string(),
+            bar_secs: data.bar_secs()?,
+            data,
+            clock,
+        })
+    }
+
+    fn visible(&self, symbol: &str) -> &[Kline] {
+        self.data.visible(symbol, self.clock.now(), self.bar_secs)
+    }
+
+    fn ticker(&self, symbol: &str) -> BrokerResult<Ticker24h> {
+        let now = self.clock.now();
+        let bars = self.visible(symbol);
+        let last = bars
+            .last()
+            .ok_or_else(|| BrokerError::InvalidSymbol(symbol.to_string()))?;
+
+        let start = bars.partition_point(|k| k.timestamp + self.bar_secs <= now - 86400);
+        let window = &bars[start..];
+        let open = window.first().map(|k| k.open).unwrap_or(last.open);
+        Ok(Ticker24h {
+            symbol: symbol.to_string(),
+            last_price: last.close,
+            change_24h: if open > 0.0 {
+                (last.close - open) / open * 100.0
+            } else {
+                0.0
+            },
+            high_24h: window.iter().map(|k| k.high).fold(f64::MIN, f64::max),
+            low_24h: window.iter().map(|k| k.low).fold(f64::MAX, f64::min),
+            volume_24h: window.iter().map(|k| k.volume).sum(),
+            open_interest: last.open_interest,
+            timestamp: now,
+        })
+    }
+
+    fn not_supported<T>(&self, what: &str) -> BrokerResult<T> {
+        Err(BrokerError::NotSupported(format!(
+            "{} is not available from historical data",
+            what
+        )))
+    }
+}
+
+impl Broker for HistoricalFeed {
+    fn broker_id(&self) -> &str {
+        &self.id
+    }
+
+    fn broker_name(&self) -> &str {
+        "Historical Feed"
+    }
+}
+
+impl MarketData for HistoricalFeed {
+    fn get_prices(&self) -> impl std::future::Future<Output = Result<Prices, BrokerError>> + Send {
+        async move {
+            let prices = self
+                .data
+                .klines
+                .keys()
+                .filter_map(|s| self.visible(s).last().map(|k| (s.clone(), k.close)))
+                .collect();
+            Ok(Prices { prices })
+        }
+    }
+
+    fn get_orderbook(
+        &self,
+        _symbol: &str,
+    ) -> impl std::future::Future<Output = Result<Orderbook, BrokerError>> + Send {
+        async move { self.not_supported("orderbook") }
+    }
+
+    fn get_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: Option<i32>,
+    ) -> impl std::future::Future<Output = Result<Klines, BrokerError>> + Send {
+        let (symbol, interval) = (symbol.to_string(), interval.to_string());
+        async move {
+            if interval != self.data.interval {
+                return self.not_supported(&format!("{} klines", interval));
+            }
+            let bars = self.visible(&symbol);
+            let limit = limit.unwrap_or(100).max(0) as usize;
+            Ok(Klines {
+                klines: bars[bars.len().saturating_sub(limit)..].to_vec(),
+                symbol,
+                interval,
+            })
+        }
+    }
+
+    fn get_ticker_24h(
+        &self,
+        symbol: &str,
+    ) -> impl std::future::Future<Output = Result<Ticker24h, BrokerError>> + Send {
+        let symbol = symbol.to_string();
+        async move { self.ticker(&symbol) }
+    }
+}
+
+impl Trading for HistoricalFeed {
+    fn place_order(
+        &self,
+        _order: OrderRequest,
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send {
+        async move { self.not_supported("trading") }
+    }
+
+    fn cancel_order(
+        &self,
+        _order_id: &str,
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send {
+        async move { self.not_supported("trading") }
+    }
+
+    fn get_order(
+        &self,
+        _order_id: &str,
+    ) -> impl std::future::Future<Output = Result<Order, BrokerError>> + Send {
+        async move { self.not_supported("trading") }
+    }
+
+    fn get_orders(
+        &self,
+        _symbol: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<Orders, BrokerError>> + Send {
+        async move { self.not_supported("trading") }
+    }
+
+    fn get_trades(&self) -> impl std::future::Future<Output = Result<Trades, BrokerError>> + Send {
+        async move { self.not_supported("trading") }
+    }
+}
+
+impl AccountManagement for HistoricalFeed {
+    fn get_account_totals(
+        &self,
+        _: Option<i32>,
+    ) -> impl std::future::Future<Output = Result<AccountTotals, BrokerError>> + Send {
+        async move { self.not_supported("account") }
+    }
+
+    fn get_model_accounts(
+        &self,
+    ) -> impl std::future::Future<Output = Result<ModelAccounts, BrokerError>> + Send {
+        async move { self.not_supported("account") }
+    }
+
+    fn get_positions(
+        &self,
+        _: Option<i32>,
+    ) -> impl std::future::Future<Output = Result<Positions, BrokerError>> + Send {
+        async move { self.not_supported("account") }
+    }
+
+    fn get_balance(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Balance, BrokerError>> + Send {
+        async move { self.not_supported("account") }
+    }
+
+    fn get_broker_account(
+        &self,
+    ) -> impl std::future::Future<Output = Result<BrokerAccount, BrokerError>> + Send {
+        async move { self.not_supported("account") }
+    }
+}
+
+impl Analytics for HistoricalFeed {
+    fn get_analytics(
+        &self,
+    ) -> impl std::future::Future<Output = Result<AnalyticsData, BrokerError>> + Send {
+        async move { self.not_supported("analytics") }
+    }
+
+    fn get_leaderboard(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Leaderboard, BrokerError>> + Send {
+        async move { self.not_supported("analytics") }
+    }
+
+    fn get_since_inception_values(
+        &self,
+    ) -> impl std::future::Future<Output = Result<SinceInceptionValues, BrokerError>> + Send {
+        async move { self.not_supported("analytics") }
+    }
+
+    fn get_conversations(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Conversations, BrokerError>> + Send {
+        async move { self.not_supported("analytics") }
+    }
+
+    fn get_models_list(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Models, BrokerError>> + Send {
+        async move { self.not_supported("analytics") }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(timestamp: i64, close: f64) -> Kline {
+        Kline {
+            timestamp,
+            open: close - 1.0,
+            high: close + 1.0,
+            low: close - 2.0,
+            close,
+            volume: 10.0,
+            open_interest: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_feed_hides_unclosed_bars() {
+        assert_eq!(interval_secs("5m"), Some(300));
+        assert_eq!(interval_secs("1d"), Some(86400));
+        assert_eq!(interval_secs("x"), None);
+
+        let data = HistoricalData::new("1h").with_klines(
+            "BTCUSDT",
+            vec![bar(7200, 102.0), bar(0, 100.0), bar(3600, 101.0)],
+        );
+        assert_eq!(data.timeline().unwrap(), vec![3600, 7200, 10800]);
+
+        let clock = Clock::simulated(7199);
+        let feed = HistoricalFeed::new(Arc::new(data), clock.clone()).unwrap();
+        let ticker = MarketData::get_ticker_24h(&feed, "BTCUSDT").await.unwrap();
+        assert_eq!(ticker.last_price, 100.0);
+
+        clock.set(7200);
+        let ticker = MarketData::get_ticker_24h(&feed, "BTCUSDT").await.unwrap();
+        assert_eq!(ticker.last_price, 101.0);
+        assert_eq!(ticker.high_24h, 102.0);
+        assert_eq!(ticker.volume_24h, 20.0);
+
+        let klines = MarketData::get_klines(&feed, "BTCUSDT", "1h", Some(1))
+            .await
+            .unwrap();
+        assert_eq!(klines.klines.len(), 1);
+        assert_eq!(klines.klines[0].close, 101.0);
+        assert!(MarketData::get_orderbook(&feed, "BTCUSDT").await.is_err());
+    }
+}
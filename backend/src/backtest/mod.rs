@@ -0,0 +1,7 @@
+// Backtesting engine
+
+pub mod feed;
+pub mod runner;
+
+pub use feed::*;
+pub use runner::*;
@@ -0,0 +1,441 @@
+use super::feed::{HistoricalData, HistoricalFeed};
+use crate::analytics::{max_drawdown_pct, sharpe_ratio};
+use crate::brokers::paper::{PaperAccountSummary, PaperBroker, PaperConfig};
+use crate::brokers::{AccountTotal, AccountTotals, Leaderboard, LeaderboardEntry, Trade};
+use crate::engine::{
+    agent_risk_manager, Agent, AgentCycleReport, Clock, CronSchedule, TradingEngine,
+};
+use crate::leaderboard::{RankingMetric, RankingPolicy};
+use crate::llm::{CachingProvider, LlmProvider};
+use crate::mcp::McpServer;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// 回测配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    /// 开始时间（秒, 含）, 为空时从第一根K线开始
+    #[serde(default)]
+    pub start: Option<i64>,
+    /// 结束时间（秒, 含）, 为空时到最后一根K线
+    #[serde(default)]
+    pub end: Option<i64>,
+    /// 撮合参数, Agent 配置了初始资金时覆盖 `initial_balance`
+    #[serde(default)]
+    pub paper: PaperConfig,
+    /// LLM 回复缓存目录, 相同输入重跑时直接回放
+    #[serde(default)]
+    pub cache_dir: Option<PathBuf>,
+    /// 只回放缓存, 未命中时该轮决策失败
+    #[serde(default)]
+    pub replay_only: bool,
+}
+
+/// 单个 Agent 的回测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReport {
+    pub agent_id: String,
+    pub model_name: String,
+    /// 每根K线收盘后的账户净值
+    pub equity_curve: Vec<AccountTotal>,
+    pub trades: Vec<Trade>,
+    pub cycles: Vec<AgentCycleReport>,
+    /// 失败的决策轮数
+    pub failed_cycles: usize,
+    pub summary: PaperAccountSummary,
+    pub return_pct: f64,
+    pub sharpe_ratio: f64,
+    pub max_drawdown_pct: f64,
+}
+
+/// 回测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestResult {
+    pub start: i64,
+    pub end: i64,
+    pub reports: Vec<BacktestReport>,
+    pub leaderboard: Leaderboard,
+}
+
+impl BacktestResult {
+    /// 所有 Agent 的净值曲线（前端图表格式）
+    pub fn account_totals(&self) -> AccountTotals {
+        AccountTotals {
+            account_totals: self
+                .reports
+                .iter()
+                .flat_map(|r| r.equity_curve.iter().cloned())
+                .collect(),
+        }
+    }
+}
+
+/// 单个 Agent 的回测运行状态
+struct AgentRun {
+    agent: Agent,
+    engine: TradingEngine,
+    broker: PaperBroker,
+    initial: f64,
+    next_decision: i64,
+    cron: Option<CronSchedule>,
+    equity_curve: Vec<AccountTotal>,
+    cycles: Vec<AgentCycleReport>,
+    failed_cycles: usize,
+}
+
+/// 事件驱动回测
+///
+/// 按K线收盘时间推进模拟时钟, 每一步先用收盘K线撮合挂单, 再让到期的 Agent
+/// 通过与实盘相同的 `TradingEngine::execute_agent` 决策（含 `RiskManager` 校验）,
+/// 订单由 `PaperBroker` 模拟成交, 最后记录净值。每个 Agent 拥有独立的账户与风控。
+pub struct Backtester {
+    config: BacktestConfig,
+    data: Arc<HistoricalData>,
+    agents: Vec<(Agent, Arc<dyn LlmProvider>)>,
+}
+
+impl Backtester {
+    pub fn new(data: HistoricalData, config: BacktestConfig) -> Self {
+        Self {
+            config,
+            data: Arc::new(data),
+            agents: Vec::new(),
+        }
+    }
+
+    /// 添加参与回测的 Agent 及其 LLM Provider
+    pub fn add_agent(&mut self, agent: Agent, provider: Arc<dyn LlmProvider>) {
+        self.agents.push((agent, provider));
+    }
+
+    /// 运行回测
+    pub async fn run(&self) -> Result<BacktestResult> {
+        let bar_secs = self.data.bar_secs()?;
+        let timeline: Vec<i64> = self
+            .data
+            .timeline()?
+            .into_iter()
+            .filter(|t| self.config.start.is_none_or(|s| *t >= s))
+            .filter(|t| self.config.end.is_none_or(|e| *t <= e))
+            .collect();
+        let (Some(&start), Some(&end)) = (timeline.first(), timeline.last()) else {
+            bail!("No historical data in the backtest range");
+        };
+
+        let clock = Clock::simulated(start);
+        let mut runs = Vec::with_capacity(self.agents.len());
+        for (agent, provider) in self.agents.iter().filter(|(a, _)| a.enabled) {
+            runs.push(self.prepare(agent, provider.clone(), &clock, start).await?);
+        }
+        info!(
+            "Backtesting {} agents over {} bars ({} - {})",
+            runs.len(),
+            timeline.len(),
+            start,
+            end
+        );
+
+        let symbols = self.data.symbols();
+        for &now in &timeline {
+            clock.set(now);
+
+            for run in runs.iter_mut() {
+                // 1. 收盘K线撮合挂单与止损
+                for symbol in &symbols {
+                    if let Some(bar) = self.data.bar_closing_at(symbol, now) {
+                        run.broker.apply_bar(symbol, bar);
+                    }
+                }
+
+                // 2. 到期的 Agent 执行决策
+                if now >= run.next_decision {
+                    match run.engine.execute_agent(&run.agent).await {
+                        Ok(report) => run.cycles.push(report),
+                        Err(e) => {
+                            warn!("Backtest cycle for {} failed: {:#}", run.agent.id, e);
+                            run.failed_cycles += 1;
+                        }
+                    }
+                    run.next_decision = match &run.cron {
+                        Some(cron) => chrono::DateTime::from_timestamp(now, 0)
+                            .and_then(|t| cron.next_after(t))
+                            .map(|t| t.timestamp())
+                            .unwrap_or(i64::MAX),
+                        None => now + run.engine.interval_for(&run.agent).as_secs() as i64,
+                    };
+                }
+
+                // 3. 记录净值
+                let point = account_total(run, now, start, bar_secs);
+                run.equity_curve.push(point);
+            }
+        }
+
+        let reports: Vec<BacktestReport> =
+            runs.into_iter().map(|run| finish(run, bar_secs)).collect();
+        Ok(BacktestResult {
+            start,
+            end,
+            leaderboard: leaderboard(&reports),
+            reports,
+        })
+    }
+
+    async fn prepare(
+        &self,
+        agent: &Agent,
+        provider: Arc<dyn LlmProvider>,
+        clock: &Clock,
+        start: i64,
+    ) -> Result<AgentRun> {
+        let provider: Arc<dyn LlmProvider> = match &self.config.cache_dir {
+            Some(dir) => {
+                let cache = CachingProvider::new(provider).with_dir(dir);
+                Arc::new(if self.config.replay_only {
+                    cache.replay_only()
+                } else {
+                    cache
+                })
+            }
+            None => provider,
+        };
+
+        let mut paper = self.config.paper.clone();
+        if agent.initial_capital > 0.0 {
+            paper.initial_balance = agent.initial_capital;
+        }
+        let initial = paper.initial_balance;
+
+        let feed = Arc::new(HistoricalFeed::new(self.data.clone(), clock.clone())?);
+        let broker = PaperBroker::new(
+            format!("backtest-{}", agent.id),
+            agent.name.clone(),
+            feed,
+            paper,
+        )
+        .with_clock(clock.clone());
+
+        let risk_manager = agent_risk_manager(agent, agent.risk.clone().unwrap_or_default());
+        let engine = TradingEngine::new(Arc::new(McpServer::new()))
+            .with_risk_manager(Arc::new(risk_manager))
+            .with_clock(clock.clone());
+        engine
+            .register_llm_provider(agent.llm_provider.clone(), provider)
+            .await;
+        engine
+            .register_broker(agent.market.clone(), Arc::new(broker.clone()))
+            .await;
+        engine.register_agent(agent.clone()).await;
+
+        Ok(AgentRun {
+            agent: agent.clone(),
+            engine,
+            broker,
+            initial,
+            next_decision: start,
+            cron: agent
+                .schedule
+                .as_deref()
+                .map(CronSchedule::parse)
+                .transpose()?,
+            equity_curve: Vec::new(),
+            cycles: Vec::new(),
+            failed_cycles: 0,
+        })
+    }
+}
+
+fn return_pct(equity: f64, initial: f64) -> f64 {
+    if initial > 0.0 {
+        (equity - initial) / initial * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn account_total(run: &AgentRun, now: i64, start: i64, bar_secs: i64) -> AccountTotal {
+    let summary = run.broker.summary();
+    let pct = return_pct(summary.equity, run.initial);
+    let mut equity: Vec<f64> = run.equity_curve.iter().map(|p| p.equity).collect();
+    equity.push(summary.equity);
+
+    AccountTotal {
+        model_id: run.agent.id.clone(),
+        model_name: run.agent.name.clone(),
+        strategy: "backtest".to_string(),
+        risk_level: "simulated".to_string(),
+        broker_id: run.agent.market.clone(),
+        timestamp: now,
+        account_value: summary.equity,
+        dollar_equity: summary.equity,
+        equity: summary.equity,
+        realized_pnl: summary.realized_pnl,
+        unrealized_pnl: summary.unrealized_pnl,
+        total_unrealized_pnl: summary.unrealized_pnl,
+        return_pct: pct,
+        cum_pnl_pct: pct,
+        sharpe_ratio: sharpe_ratio(&equity, bar_secs),
+        since_inception_minute_marker: ((now - start) / 60) as i32,
+        since_inception_hourly_marker: ((now - start) / 3600) as i32,
+    }
+}
+
+fn finish(run: AgentRun, bar_secs: i64) -> BacktestReport {
+    let summary = run.broker.summary();
+    let equity: Vec<f64> = run.equity_curve.iter().map(|p| p.equity).collect();
+    let trades = run.broker.trades();
+
+    BacktestReport {
+        agent_id: run.agent.id,
+        model_name: run.agent.name,
+        return_pct: return_pct(summary.equity, run.initial),
+        sharpe_ratio: sharpe_ratio(&equity, bar_secs),
+        max_drawdown_pct: max_drawdown_pct(&equity),
+        equity_curve: run.equity_curve,
+        trades,
+        cycles: run.cycles,
+        failed_cycles: run.failed_cycles,
+        summary,
+    }
+}
+
+/// 按 `return_pct` 降序排名
+fn leaderboard(reports: &[BacktestReport]) -> Leaderboard {
+    let mut entries: Vec<LeaderboardEntry> = reports
+        .iter()
+        .map(|r| LeaderboardEntry {
+            model_id: r.agent_id.clone(),
+            model_name: r.model_name.clone(),
+            equity: r.summary.equity,
+            return_pct: r.return_pct,
+            sharpe_ratio: r.sharpe_ratio,
+            max_drawdown_pct: r.max_drawdown_pct,
+            total_trades: r.trades.len() as i32,
+            num_wins: r.summary.winning_trades,
+            num_losses: r.summary.losing_trades,
+            win_rate: r.summary.win_rate(),
+            ..Default::default()
+        })
+        .collect();
+    RankingPolicy::new(RankingMetric::Return).rank(&mut entries);
+
+    Leaderboard {
+        leaderboard: entries,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::Kline;
+    use crate::llm::{ScriptStep, ScriptedProvider, ScriptedToolCall};
+
+    fn data() -> HistoricalData {
+        let closes = [100.0, 110.0, 105.0, 120.0];
+        let klines = closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| Kline {
+                timestamp: i as i64 * 3600,
+                open: if i == 0 { 100.0 } else { closes[i - 1] },
+                high: close + 1.0,
+                low: close - 1.0,
+                close: *close,
+                volume: 50.0,
+                open_interest: None,
+            })
+            .collect();
+        HistoricalData::new("1h").with_klines("BTCUSDT", klines)
+    }
+
+    fn agent() -> Agent {
+        let mut agent = Agent::new(
+            "momentum".to_string(),
+            "Momentum".to_string(),
+            "scripted".to_string(),
+            "binance".to_string(),
+        );
+        agent.symbols = vec!["BTCUSDT".to_string()];
+        agent.initial_capital = 10_000.0;
+        agent.decision_interval_secs = Some(3600);
+        agent
+    }
+
+    fn script() -> Vec<ScriptStep> {
+        vec![
+            ScriptStep {
+                content: "Buying".to_string(),
+                tool_calls: vec![ScriptedToolCall {
+                    name: "place_order".to_string(),
+                    arguments: serde_json::json!({
+                        "symbol": "BTCUSDT", "side": "buy", "quantity": 10.0
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            ScriptStep {
+                content: "Holding".to_string(),
+                repeat: true,
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_backtest_is_deterministic_with_cache() {
+        let dir = std::env::temp_dir().join(format!("nof0-backtest-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = BacktestConfig {
+            cache_dir: Some(dir.clone()),
+            paper: PaperConfig {
+                taker_fee_rate: 0.0,
+                slippage_bps: 0.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut backtester = Backtester::new(data(), config.clone());
+        let llm = Arc::new(ScriptedProvider::new(script()));
+        backtester.add_agent(agent(), llm.clone());
+        let first = backtester.run().await.unwrap();
+
+        let report = &first.reports[0];
+        assert_eq!((first.start, first.end), (3600, 14400));
+        assert_eq!(report.equity_curve.len(), 4);
+        assert_eq!(report.cycles.len(), 4);
+        assert_eq!(report.failed_cycles, 0);
+        // 第一根K线收盘价 100 买入 10 手, 最后收盘 120
+        assert_eq!(report.trades.len(), 1);
+        assert_eq!(report.trades[0].price, 100.0);
+        assert_eq!(report.trades[0].timestamp, 3600);
+        assert!((report.return_pct - 2.0).abs() < 1e-9);
+        assert!(report.max_drawdown_pct > 0.0);
+        assert_eq!(report.equity_curve[1].since_inception_hourly_marker, 1);
+        assert_eq!(first.leaderboard.leaderboard[0].rank, 1);
+        assert_eq!(first.account_totals().account_totals.len(), 4);
+
+        // 只回放缓存重跑: 不调用 LLM, 结果一致
+        let replay_config = BacktestConfig {
+            replay_only: true,
+            ..config
+        };
+        let mut replay = Backtester::new(data(), replay_config);
+        let offline = Arc::new(ScriptedProvider::new(vec![]));
+        replay.add_agent(agent(), offline.clone());
+        let second = replay.run().await.unwrap();
+
+        assert!(offline.requests().is_empty());
+        assert_eq!(second.reports[0].failed_cycles, 0);
+        assert_eq!(
+            serde_json::to_value(&second.reports[0].equity_curve).unwrap(),
+            serde_json::to_value(&report.equity_curve).unwrap()
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
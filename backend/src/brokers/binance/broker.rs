@@ -2,8 +2,19 @@ use crate::brokers::*;
 use rand::Rng;
 use std::collections::HashMap;
 
+use super::client::BinanceRestClient;
+use super::stream::BinanceMarketStream;
 use super::types::*;
 
+/// Binance 运行模式
+pub enum BinanceMode {
+    /// 模拟模式: 随机游走生成行情与账户数据 (`mock_mode: true`)
+    Simulated,
+
+    /// 真实模式: 通过签名 REST API 访问 Binance (主网或测试网)
+    Rest(BinanceRestClient),
+}
+
 /// Binance (币安) 经纪商实现
 /// 全球领先的加密货币交易所
 #[allow(dead_code)]
@@ -11,12 +22,46 @@ pub struct BinanceBroker {
     id: String,
     name: String,
     config: BinanceConfig,
+    mode: BinanceMode,
+    models: Vec<ModelInfo>,
 }
 
 #[allow(dead_code)]
 impl BinanceBroker {
+    /// 创建经纪商实例, 按 `config.mock_mode` 选择模拟模式或 REST 模式
     pub fn new(id: String, name: String, config: BinanceConfig) -> Self {
-        Self { id, name, config }
+        let mode = if config.mock_mode {
+            BinanceMode::Simulated
+        } else {
+            BinanceMode::Rest(BinanceRestClient::new(&config))
+        };
+
+        Self {
+            id,
+            name,
+            config,
+            mode,
+            models: Vec::new(),
+        }
+    }
+
+    /// 绑定到该经纪商的 AI 模型 (启动时由 agents.yaml 注入)
+    pub fn with_models(mut self, models: Vec<ModelInfo>) -> Self {
+        self.models = models;
+        self
+    }
+
+    /// 是否为模拟模式
+    pub fn is_simulated(&self) -> bool {
+        matches!(self.mode, BinanceMode::Simulated)
+    }
+
+    /// REST 客户端 (模拟模式下为 None)
+    pub fn rest_client(&self) -> Option<&BinanceRestClient> {
+        match &self.mode {
+            BinanceMode::Rest(client) => Some(client),
+            BinanceMode::Simulated => None,
+        }
     }
 
     /// 获取币安支持的加密货币列表
@@ -52,48 +97,12 @@ impl BinanceBroker {
         }
     }
 
-    /// 获取币安的AI模型配置
+    /// 获取绑定到该经纪商的 AI 模型
     fn get_models(&self) -> Vec<ModelInfo> {
-        vec![
-            ModelInfo {
-                model_id: "binance_momentum".to_string(),
-                model_name: "动量突破AI".to_string(),
-                strategy: "Momentum Breakout".to_string(),
-                description: "捕捉加密货币强势突破机会".to_string(),
-                risk_level: "HIGH".to_string(),
-                base_capital: 50000.0,
-            },
-            ModelInfo {
-                model_id: "binance_grid".to_string(),
-                model_name: "网格交易AI".to_string(),
-                strategy: "Grid Trading".to_string(),
-                description: "在波动市场中进行高频网格交易".to_string(),
-                risk_level: "MEDIUM".to_string(),
-                base_capital: 80000.0,
-            },
-            ModelInfo {
-                model_id: "binance_arbitrage".to_string(),
-                model_name: "套利AI".to_string(),
-                strategy: "Cross-Exchange Arbitrage".to_string(),
-                description: "跨交易所和跨币对套利".to_string(),
-                risk_level: "LOW".to_string(),
-                base_capital: 100000.0,
-            },
-        ]
+        self.models.clone()
     }
 }
 
-#[derive(Clone)]
-#[allow(dead_code)]
-struct ModelInfo {
-    model_id: String,
-    model_name: String,
-    strategy: String,
-    description: String,
-    risk_level: String,
-    base_capital: f64,
-}
-
 // ============================================================================
 // Broker Trait Implementation
 // ============================================================================
@@ -113,8 +122,15 @@ impl Broker for BinanceBroker {
 impl MarketData for BinanceBroker {
     fn get_prices(
         &self,
-    ) -> impl std::future::Future<Output = Result<Prices, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Prices, BrokerError>> + Send {
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest
+                    .get_prices(&self.get_symbols())
+                    .await
+                    .map_err(Into::into);
+            }
+
             let symbols = self.get_symbols();
             let mut prices = HashMap::new();
             let mut rng = rand::thread_rng();
@@ -132,10 +148,14 @@ impl MarketData for BinanceBroker {
     fn get_orderbook(
         &self,
         symbol: &str,
-    ) -> impl std::future::Future<Output = Result<Orderbook, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Orderbook, BrokerError>> + Send
     {
         let symbol = symbol.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_orderbook(&symbol, 20).await.map_err(Into::into);
+            }
+
             let mut rng = rand::thread_rng();
             let base_price = self.get_base_price(&symbol);
 
@@ -170,10 +190,17 @@ impl MarketData for BinanceBroker {
         symbol: &str,
         interval: &str,
         limit: Option<i32>,
-    ) -> impl std::future::Future<Output = Result<Klines, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Klines, BrokerError>> + Send {
         let symbol = symbol.to_string();
         let interval = interval.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest
+                    .get_klines(&symbol, &interval, limit)
+                    .await
+                    .map_err(Into::into);
+            }
+
             let mut rng = rand::thread_rng();
             let base_price = self.get_base_price(&symbol);
             let limit = limit.unwrap_or(500);
@@ -224,10 +251,14 @@ impl MarketData for BinanceBroker {
     fn get_ticker_24h(
         &self,
         symbol: &str,
-    ) -> impl std::future::Future<Output = Result<Ticker24h, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Ticker24h, BrokerError>> + Send
     {
         let symbol = symbol.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_ticker_24h(&symbol).await.map_err(Into::into);
+            }
+
             let mut rng = rand::thread_rng();
             let price = self.get_base_price(&symbol);
             let change_pct = rng.gen_range(-8.0..8.0);
@@ -247,16 +278,40 @@ impl MarketData for BinanceBroker {
     }
 }
 
+// ============================================================================
+// MarketDataStream Trait Implementation (行情推送实现)
+// ============================================================================
+impl MarketDataStream for BinanceBroker {
+    fn subscribe_market(
+        &self,
+        subscription: StreamSubscription,
+    ) -> impl std::future::Future<Output = Result<MarketEventStream, BrokerError>> + Send
+    {
+        async move {
+            if self.is_simulated() {
+                return Err(BrokerError::NotSupported(
+                    "market stream is not available in mock mode".to_string(),
+                ));
+            }
+            Ok(BinanceMarketStream::new(&self.config).subscribe(subscription))
+        }
+    }
+}
+
 // ============================================================================
 // Trading Trait Implementation (交易接口实现)
 // ============================================================================
 impl Trading for BinanceBroker {
     fn place_order(
         &self,
-        _order: OrderRequest,
-    ) -> impl std::future::Future<Output = Result<OrderResponse, Box<dyn std::error::Error>>> + Send
+        order: OrderRequest,
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send
     {
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.place_order(&order).await.map_err(Into::into);
+            }
+
             let order_id = format!("BINANCE_{}", chrono::Utc::now().timestamp_millis());
             Ok(OrderResponse {
                 order_id,
@@ -269,10 +324,14 @@ impl Trading for BinanceBroker {
     fn cancel_order(
         &self,
         order_id: &str,
-    ) -> impl std::future::Future<Output = Result<OrderResponse, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send
     {
         let order_id = order_id.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.cancel_order(&order_id).await.map_err(Into::into);
+            }
+
             Ok(OrderResponse {
                 order_id,
                 status: OrderStatus::Cancelled,
@@ -284,9 +343,13 @@ impl Trading for BinanceBroker {
     fn get_order(
         &self,
         order_id: &str,
-    ) -> impl std::future::Future<Output = Result<Order, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Order, BrokerError>> + Send {
         let order_id = order_id.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_order(&order_id).await.map_err(Into::into);
+            }
+
             Ok(Order {
                 order_id,
                 symbol: "BTCUSDT".to_string(),
@@ -305,15 +368,32 @@ impl Trading for BinanceBroker {
 
     fn get_orders(
         &self,
-        _symbol: Option<&str>,
-    ) -> impl std::future::Future<Output = Result<Orders, Box<dyn std::error::Error>>> + Send {
-        async move { Ok(Orders { orders: vec![] }) }
+        symbol: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<Orders, BrokerError>> + Send {
+        let symbol = symbol.map(|s| s.to_string());
+        async move {
+            if let Some(rest) = self.rest_client() {
+                return rest
+                    .get_open_orders(symbol.as_deref())
+                    .await
+                    .map_err(Into::into);
+            }
+            Ok(Orders { orders: vec![] })
+        }
     }
 
     fn get_trades(
         &self,
-    ) -> impl std::future::Future<Output = Result<Trades, Box<dyn std::error::Error>>> + Send {
-        async move { Ok(Trades { trades: vec![] }) }
+    ) -> impl std::future::Future<Output = Result<Trades, BrokerError>> + Send {
+        async move {
+            if let Some(rest) = self.rest_client() {
+                return rest
+                    .get_trades(&self.get_symbols())
+                    .await
+                    .map_err(Into::into);
+            }
+            Ok(Trades { trades: vec![] })
+        }
     }
 }
 
@@ -324,7 +404,7 @@ impl AccountManagement for BinanceBroker {
     fn get_account_totals(
         &self,
         _last_marker: Option<i32>,
-    ) -> impl std::future::Future<Output = Result<AccountTotals, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<AccountTotals, BrokerError>> + Send
     {
         async move {
             let mut rng = rand::thread_rng();
@@ -393,7 +473,7 @@ impl AccountManagement for BinanceBroker {
 
     fn get_model_accounts(
         &self,
-    ) -> impl std::future::Future<Output = Result<ModelAccounts, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<ModelAccounts, BrokerError>> + Send
     {
         async move {
             let mut rng = rand::thread_rng();
@@ -457,9 +537,13 @@ impl AccountManagement for BinanceBroker {
     fn get_positions(
         &self,
         _limit: Option<i32>,
-    ) -> impl std::future::Future<Output = Result<Positions, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Positions, BrokerError>> + Send
     {
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_positions().await.map_err(Into::into);
+            }
+
             let symbols = self.get_symbols();
             let mut rng = rand::thread_rng();
             let now = chrono::Utc::now().timestamp();
@@ -500,8 +584,12 @@ impl AccountManagement for BinanceBroker {
 
     fn get_balance(
         &self,
-    ) -> impl std::future::Future<Output = Result<Balance, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Balance, BrokerError>> + Send {
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_balance().await.map_err(Into::into);
+            }
+
             let mut rng = rand::thread_rng();
             let total = rng.gen_range(50000.0..200000.0);
             let margin_used = total * rng.gen_range(0.3..0.7);
@@ -520,7 +608,7 @@ impl AccountManagement for BinanceBroker {
 
     fn get_broker_account(
         &self,
-    ) -> impl std::future::Future<Output = Result<BrokerAccount, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<BrokerAccount, BrokerError>> + Send
     {
         async move {
             Ok(BrokerAccount {
@@ -540,46 +628,23 @@ impl AccountManagement for BinanceBroker {
 impl Analytics for BinanceBroker {
     fn get_analytics(
         &self,
-    ) -> impl std::future::Future<Output = Result<AnalyticsData, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<AnalyticsData, BrokerError>> + Send
     {
-        async move {
-            let mut metrics = HashMap::new();
-            metrics.insert("total_models".to_string(), 3.0);
-            metrics.insert("total_volume_24h".to_string(), 150000000.0);
-            metrics.insert("avg_trades_per_model".to_string(), 250.0);
-
-            Ok(AnalyticsData { metrics })
-        }
+        // 交易所账户不区分模型, 模型分析由引擎按成交与权益快照计算
+        async move { Ok(AnalyticsData::default()) }
     }
 
     fn get_leaderboard(
         &self,
-    ) -> impl std::future::Future<Output = Result<Leaderboard, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Leaderboard, BrokerError>> + Send
     {
-        async move {
-            let mut rng = rand::thread_rng();
-            let models = self.get_models();
-            let mut entries = vec![];
-
-            for (rank, model) in models.iter().enumerate() {
-                entries.push(LeaderboardEntry {
-                    rank: (rank + 1) as i32,
-                    model_id: model.model_id.clone(),
-                    model_name: model.model_name.clone(),
-                    return_pct: rng.gen_range(-5.0..15.0),
-                    sharpe_ratio: rng.gen_range(0.5..2.5),
-                    total_trades: rng.gen_range(100..500),
-                    win_rate: rng.gen_range(0.45..0.65),
-                });
-            }
-
-            Ok(Leaderboard { leaderboard: entries })
-        }
+        // 排行榜由 leaderboard 模块跨经纪商汇总计算
+        async move { Ok(Leaderboard::default()) }
     }
 
     fn get_since_inception_values(
         &self,
-    ) -> impl std::future::Future<Output = Result<SinceInceptionValues, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<SinceInceptionValues, BrokerError>> + Send
     {
         async move {
             Ok(SinceInceptionValues {
@@ -590,29 +655,18 @@ impl Analytics for BinanceBroker {
 
     fn get_conversations(
         &self,
-    ) -> impl std::future::Future<Output = Result<Conversations, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Conversations, BrokerError>> + Send
     {
         async move { Ok(Conversations { conversations: vec![] }) }
     }
 
     fn get_models_list(
         &self,
-    ) -> impl std::future::Future<Output = Result<Models, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Models, BrokerError>> + Send {
         async move {
-            let models_data = self.get_models();
-            let models = models_data
-                .into_iter()
-                .map(|m| crate::brokers::ModelInfo {
-                    model_id: m.model_id,
-                    model_name: m.model_name,
-                    strategy: m.strategy,
-                    description: m.description,
-                    risk_level: m.risk_level,
-                    base_capital: m.base_capital,
-                })
-                .collect();
-
-            Ok(Models { models })
+            Ok(Models {
+                models: self.get_models(),
+            })
         }
     }
 }
@@ -0,0 +1,1078 @@
+// Binance REST API 客户端
+// Binance Spot REST API Client (HMAC-SHA256 签名)
+
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use super::types::BinanceConfig;
+use crate::brokers::error::BrokerError;
+use crate::brokers::stream::{DepthUpdate, DepthUpdateKind};
+use crate::brokers::types::*;
+
+/// 主网 REST 地址
+pub const BINANCE_MAINNET_URL: &str = "https://api.binance.com";
+
+/// 测试网 REST 地址
+pub const BINANCE_TESTNET_URL: &str = "https://testnet.binance.vision";
+
+/// 时间戳超出 recvWindow 的错误码
+const ERR_TIMESTAMP_OUT_OF_WINDOW: i64 = -1021;
+
+/// 请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Binance 客户端错误
+#[derive(Debug, thiserror::Error)]
+pub enum BinanceApiError {
+    /// 网络/传输错误
+    #[error("Binance HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// 交易所返回的业务错误 ({"code": -1121, "msg": "Invalid symbol."})
+    #[error("Binance API error {code} (HTTP {status}): {msg}")]
+    Api {
+        status: u16,
+        code: i64,
+        msg: String,
+        retry_after: Option<Duration>,
+    },
+
+    /// 响应解析失败
+    #[error("Binance decode error: {0}")]
+    Decode(String),
+}
+
+impl BinanceApiError {
+    /// 交易所错误码 (非业务错误时为 None)
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            Self::Api { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl From<BinanceApiError> for BrokerError {
+    fn from(e: BinanceApiError) -> Self {
+        let (status, code, msg, retry_after) = match e {
+            BinanceApiError::Http(e) => return e.into(),
+            BinanceApiError::Decode(msg) => return BrokerError::Other(msg),
+            BinanceApiError::Api {
+                status,
+                code,
+                msg,
+                retry_after,
+            } => (status, code, msg, retry_after),
+        };
+
+        match code {
+            // TOO_MANY_REQUESTS / TOO_MANY_ORDERS
+            -1003 | -1015 => BrokerError::RateLimited { retry_after },
+            -1121 => BrokerError::InvalidSymbol(msg),
+            -1022 | -2014 | -2015 => BrokerError::Auth(msg),
+            -2010 if msg.to_lowercase().contains("insufficient balance") => {
+                BrokerError::InsufficientFunds(msg)
+            }
+            -1013 | -1111 | -2010 | -2011 | -2013 => BrokerError::OrderRejected {
+                code: code.to_string(),
+                reason: msg,
+            },
+            _ if code == 0 || matches!(status, 418 | 429) || status >= 500 => {
+                BrokerError::from_http_status(status, retry_after, &msg)
+            }
+            _ => BrokerError::Exchange {
+                venue_code: code.to_string(),
+                message: msg,
+            },
+        }
+    }
+}
+
+/// Binance 签名 REST 客户端
+///
+/// - 公共接口直接 GET
+/// - 私有接口追加 `timestamp` / `recvWindow` 后对查询串做 HMAC-SHA256 签名
+/// - 首次私有请求前同步服务器时间, 遇到 -1021 时重新同步并重试一次
+pub struct BinanceRestClient {
+    http: Client,
+    base_url: String,
+    api_key: String,
+    api_secret: String,
+    recv_window: u64,
+
+    /// 服务器时间 - 本地时间 (毫秒)
+    time_offset_ms: AtomicI64,
+
+    /// 是否已同步过服务器时间
+    time_synced: AtomicBool,
+}
+
+impl BinanceRestClient {
+    /// 根据配置创建客户端
+    pub fn new(config: &BinanceConfig) -> Self {
+        let base_url = config.base_url.clone().unwrap_or_else(|| {
+            if config.testnet {
+                BINANCE_TESTNET_URL.to_string()
+            } else {
+                BINANCE_MAINNET_URL.to_string()
+            }
+        });
+
+        let http = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            api_secret: config.api_secret.clone(),
+            recv_window: config.recv_window,
+            time_offset_ms: AtomicI64::new(0),
+            time_synced: AtomicBool::new(false),
+        }
+    }
+
+    /// REST 基础地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // ========================================================================
+    // 签名与时间同步
+    // ========================================================================
+
+    /// 对查询串做 HMAC-SHA256 签名 (hex 小写)
+    pub fn sign(&self, query: &str) -> String {
+        sign_query(&self.api_secret, query)
+    }
+
+    /// 当前请求时间戳 (本地时间 + 服务器时间偏移)
+    pub fn timestamp(&self) -> i64 {
+        chrono::Utc::now().timestamp_millis() + self.time_offset_ms.load(Ordering::Relaxed)
+    }
+
+    /// 同步服务器时间, 返回偏移量 (毫秒)
+    pub async fn sync_server_time(&self) -> Result<i64, BinanceApiError> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ServerTime {
+            server_time: i64,
+        }
+
+        let before = chrono::Utc::now().timestamp_millis();
+        let time: ServerTime = self.public_get("/api/v3/time", &[]).await?;
+        let after = chrono::Utc::now().timestamp_millis();
+
+        // 以往返中点作为本地参考时间
+        let offset = time.server_time - (before + after) / 2;
+        self.time_offset_ms.store(offset, Ordering::Relaxed);
+        self.time_synced.store(true, Ordering::Relaxed);
+        debug!("Binance server time offset: {} ms", offset);
+
+        Ok(offset)
+    }
+
+    // ========================================================================
+    // 请求发送
+    // ========================================================================
+
+    /// 公共接口 (无需签名)
+    async fn public_get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, BinanceApiError> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.http.get(&url).query(params).send().await?;
+        Self::parse_response(response).await
+    }
+
+    /// 私有接口 (签名)
+    async fn signed_request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, BinanceApiError> {
+        if !self.time_synced.load(Ordering::Relaxed) {
+            self.sync_server_time().await?;
+        }
+
+        match self.send_signed(method.clone(), path, params).await {
+            Err(e) if e.code() == Some(ERR_TIMESTAMP_OUT_OF_WINDOW) => {
+                warn!("Binance timestamp outside recvWindow, resyncing server time");
+                self.sync_server_time().await?;
+                self.send_signed(method, path, params).await
+            }
+            result => result,
+        }
+    }
+
+    async fn send_signed<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<T, BinanceApiError> {
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params.iter().map(|(k, v)| (*k, v.as_str())))
+            .append_pair("recvWindow", &self.recv_window.to_string())
+            .append_pair("timestamp", &self.timestamp().to_string())
+            .finish();
+        let signature = self.sign(&query);
+
+        let url = format!(
+            "{}{}?{}&signature={}",
+            self.base_url, path, query, signature
+        );
+        let response = self
+            .http
+            .request(method, &url)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await?;
+        Self::parse_response(response).await
+    }
+
+    async fn parse_response<T: DeserializeOwned>(
+        response: reqwest::Response,
+    ) -> Result<T, BinanceApiError> {
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs);
+        let body = response.text().await?;
+
+        if !status.is_success() {
+            #[derive(Deserialize)]
+            struct ApiError {
+                code: i64,
+                msg: String,
+            }
+
+            return Err(match serde_json::from_str::<ApiError>(&body) {
+                Ok(err) => BinanceApiError::Api {
+                    status: status.as_u16(),
+                    code: err.code,
+                    msg: err.msg,
+                    retry_after,
+                },
+                Err(_) => BinanceApiError::Api {
+                    status: status.as_u16(),
+                    code: 0,
+                    msg: body,
+                    retry_after,
+                },
+            });
+        }
+
+        serde_json::from_str(&body).map_err(|e| BinanceApiError::Decode(e.to_string()))
+    }
+
+    // ========================================================================
+    // 行情接口
+    // ========================================================================
+
+    /// 最新价格
+    pub async fn get_prices(&self, symbols: &[String]) -> Result<Prices, BinanceApiError> {
+        let params = if symbols.is_empty() {
+            vec![]
+        } else {
+            let list = serde_json::to_string(symbols)
+                .map_err(|e| BinanceApiError::Decode(e.to_string()))?;
+            vec![("symbols", list)]
+        };
+
+        let tickers: Vec<RawTickerPrice> = self.public_get("/api/v3/ticker/price", &params).await?;
+        let prices = tickers
+            .into_iter()
+            .map(|t| (t.symbol, parse_f64(&t.price)))
+            .collect();
+
+        Ok(Prices { prices })
+    }
+
+    /// 深度
+    pub async fn get_orderbook(
+        &self,
+        symbol: &str,
+        limit: u32,
+    ) -> Result<Orderbook, BinanceApiError> {
+        let depth: RawDepth = self
+            .public_get(
+                "/api/v3/depth",
+                &[("symbol", symbol.to_string()), ("limit", limit.to_string())],
+            )
+            .await?;
+
+        Ok(Orderbook {
+            symbol: symbol.to_string(),
+            bids: depth.bids.iter().map(to_level).collect(),
+            asks: depth.asks.iter().map(to_level).collect(),
+            timestamp: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// 带 `lastUpdateId` 的深度快照 (用于本地订单簿重建)
+    pub async fn get_depth_snapshot(
+        &self,
+        symbol: &str,
+        limit: u32,
+    ) -> Result<DepthUpdate, BinanceApiError> {
+        let depth: RawDepth = self
+            .public_get(
+                "/api/v3/depth",
+                &[("symbol", symbol.to_string()), ("limit", limit.to_string())],
+            )
+            .await?;
+
+        Ok(DepthUpdate {
+            symbol: symbol.to_string(),
+            kind: DepthUpdateKind::Snapshot,
+            first_update_id: depth.last_update_id,
+            final_update_id: depth.last_update_id,
+            prev_update_id: None,
+            bids: depth.bids.iter().map(to_level).collect(),
+            asks: depth.asks.iter().map(to_level).collect(),
+            timestamp: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// K线
+    pub async fn get_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: Option<i32>,
+    ) -> Result<Klines, BinanceApiError> {
+        let mut params = vec![
+            ("symbol", symbol.to_string()),
+            ("interval", interval.to_string()),
+        ];
+        if let Some(limit) = limit {
+            params.push(("limit", limit.clamp(1, 1000).to_string()));
+        }
+        self.fetch_klines(symbol, interval, &params).await
+    }
+
+    /// 按时间区间获取K线 (`[start, end)`, 秒级时间戳, 单次最多 1000 根)
+    pub async fn get_klines_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+        limit: i32,
+    ) -> Result<Klines, BinanceApiError> {
+        let params = vec![
+            ("symbol", symbol.to_string()),
+            ("interval", interval.to_string()),
+            ("startTime", (start * 1000).to_string()),
+            // endTime 为闭区间
+            ("endTime", (end * 1000 - 1).to_string()),
+            ("limit", limit.clamp(1, 1000).to_string()),
+        ];
+        self.fetch_klines(symbol, interval, &params).await
+    }
+
+    async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        params: &[(&str, String)],
+    ) -> Result<Klines, BinanceApiError> {
+        let rows: Vec<Vec<serde_json::Value>> = self.public_get("/api/v3/klines", params).await?;
+        let klines = rows
+            .iter()
+            .map(|row| {
+                if row.len() < 6 {
+                    return Err(BinanceApiError::Decode(format!(
+                        "unexpected kline row: {:?}",
+                        row
+                    )));
+                }
+                Ok(Kline {
+                    timestamp: row[0].as_i64().unwrap_or_default() / 1000,
+                    open: value_f64(&row[1]),
+                    high: value_f64(&row[2]),
+                    low: value_f64(&row[3]),
+                    close: value_f64(&row[4]),
+                    volume: value_f64(&row[5]),
+                    open_interest: None,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Klines {
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+            klines,
+        })
+    }
+
+    /// 24小时行情
+    pub async fn get_ticker_24h(&self, symbol: &str) -> Result<Ticker24h, BinanceApiError> {
+        let ticker: RawTicker24h = self
+            .public_get("/api/v3/ticker/24hr", &[("symbol", symbol.to_string())])
+            .await?;
+
+        Ok(Ticker24h {
+            symbol: ticker.symbol,
+            last_price: parse_f64(&ticker.last_price),
+            change_24h: parse_f64(&ticker.price_change_percent),
+            high_24h: parse_f64(&ticker.high_price),
+            low_24h: parse_f64(&ticker.low_price),
+            volume_24h: parse_f64(&ticker.quote_volume),
+            open_interest: None,
+            timestamp: ticker.close_time / 1000,
+        })
+    }
+
+    // ========================================================================
+    // 交易接口
+    // ========================================================================
+
+    /// 下单
+    pub async fn place_order(
+        &self,
+        order: &OrderRequest,
+    ) -> Result<OrderResponse, BinanceApiError> {
+        let mut params = vec![
+            ("symbol", order.symbol.clone()),
+            ("side", side_to_str(&order.side).to_string()),
+            ("type", type_to_str(&order.order_type).to_string()),
+            ("quantity", order.quantity.to_string()),
+            ("newOrderRespType", "RESULT".to_string()),
+        ];
+
+        match order.order_type {
+            OrderType::Market => {}
+            OrderType::Limit | OrderType::StopLimit => {
+                let price = order.price.ok_or_else(|| {
+                    BinanceApiError::Decode("limit order requires a price".to_string())
+                })?;
+                params.push(("price", price.to_string()));
+                params.push((
+                    "timeInForce",
+                    order
+                        .time_in_force
+                        .clone()
+                        .unwrap_or_else(|| "GTC".to_string()),
+                ));
+                if matches!(order.order_type, OrderType::StopLimit) {
+                    params.push(("stopPrice", price.to_string()));
+                }
+            }
+            OrderType::Stop => {
+                let price = order.price.ok_or_else(|| {
+                    BinanceApiError::Decode("stop order requires a stop price".to_string())
+                })?;
+                params.push(("stopPrice", price.to_string()));
+            }
+        }
+
+        let ack: RawOrder = self
+            .signed_request(Method::POST, "/api/v3/order", &params)
+            .await?;
+
+        Ok(OrderResponse {
+            order_id: compose_order_id(&ack.symbol, ack.order_id),
+            status: parse_status(&ack.status),
+            timestamp: ack.transact_time.or(ack.time).unwrap_or_default() / 1000,
+        })
+    }
+
+    /// 撤单 (order_id 为 `SYMBOL:orderId`)
+    pub async fn cancel_order(&self, order_id: &str) -> Result<OrderResponse, BinanceApiError> {
+        let (symbol, id) = split_order_id(order_id)?;
+        let order: RawOrder = self
+            .signed_request(
+                Method::DELETE,
+                "/api/v3/order",
+                &[("symbol", symbol.to_string()), ("orderId", id.to_string())],
+            )
+            .await?;
+
+        Ok(OrderResponse {
+            order_id: compose_order_id(&order.symbol, order.order_id),
+            status: parse_status(&order.status),
+            timestamp: order
+                .transact_time
+                .unwrap_or_else(|| chrono::Utc::now().timestamp_millis())
+                / 1000,
+        })
+    }
+
+    /// 查询订单 (order_id 为 `SYMBOL:orderId`)
+    pub async fn get_order(&self, order_id: &str) -> Result<Order, BinanceApiError> {
+        let (symbol, id) = split_order_id(order_id)?;
+        let order: RawOrder = self
+            .signed_request(
+                Method::GET,
+                "/api/v3/order",
+                &[("symbol", symbol.to_string()), ("orderId", id.to_string())],
+            )
+            .await?;
+
+        Ok(order.into_order())
+    }
+
+    /// 当前挂单
+    pub async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Orders, BinanceApiError> {
+        let params: Vec<(&str, String)> = symbol
+            .map(|s| vec![("symbol", s.to_string())])
+            .unwrap_or_default();
+        let orders: Vec<RawOrder> = self
+            .signed_request(Method::GET, "/api/v3/openOrders", &params)
+            .await?;
+
+        Ok(Orders {
+            orders: orders.into_iter().map(RawOrder::into_order).collect(),
+        })
+    }
+
+    /// 成交记录 (Binance 要求逐个交易对查询)
+    pub async fn get_trades(&self, symbols: &[String]) -> Result<Trades, BinanceApiError> {
+        let mut trades = vec![];
+        for symbol in symbols {
+            let rows: Vec<RawTrade> = self
+                .signed_request(
+                    Method::GET,
+                    "/api/v3/myTrades",
+                    &[("symbol", symbol.clone()), ("limit", "100".to_string())],
+                )
+                .await?;
+
+            trades.extend(rows.into_iter().map(|t| Trade {
+                trade_id: t.id.to_string(),
+                order_id: compose_order_id(&t.symbol, t.order_id),
+                side: if t.is_buyer {
+                    OrderSide::Buy
+                } else {
+                    OrderSide::Sell
+                },
+                price: parse_f64(&t.price),
+                quantity: parse_f64(&t.qty),
+                fee: parse_f64(&t.commission),
+                timestamp: t.time / 1000,
+                symbol: t.symbol,
+            }));
+        }
+
+        trades.sort_by_key(|t| t.timestamp);
+        Ok(Trades { trades })
+    }
+
+    // ========================================================================
+    // 账户接口
+    // ========================================================================
+
+    /// 账户资产 (非零余额), asset -> (free, locked)
+    pub async fn get_account_balances(
+        &self,
+    ) -> Result<HashMap<String, (f64, f64)>, BinanceApiError> {
+        let account: RawAccount = self
+            .signed_request(Method::GET, "/api/v3/account", &[])
+            .await?;
+
+        Ok(account
+            .balances
+            .into_iter()
+            .map(|b| (b.asset, (parse_f64(&b.free), parse_f64(&b.locked))))
+            .filter(|(_, (free, locked))| *free > 0.0 || *locked > 0.0)
+            .collect())
+    }
+
+    /// 账户余额 (以 USDT 计价)
+    pub async fn get_balance(&self) -> Result<Balance, BinanceApiError> {
+        let balances = self.get_account_balances().await?;
+        let prices = self.get_prices(&[]).await?.prices;
+
+        let mut total = 0.0;
+        for (asset, (free, locked)) in &balances {
+            total += (free + locked) * asset_usdt_price(asset, &prices);
+        }
+        let (available, locked) = balances.get("USDT").copied().unwrap_or_default();
+
+        Ok(Balance {
+            total_balance: total,
+            available,
+            margin_used: None,
+            frozen_margin: Some(locked),
+            currency: "USDT".to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// 现货持仓 (非 USDT 资产折算为 `{ASSET}USDT` 多头持仓)
+    pub async fn get_positions(&self) -> Result<Positions, BinanceApiError> {
+        let balances = self.get_account_balances().await?;
+        let prices = self.get_prices(&[]).await?.prices;
+        let now = chrono::Utc::now().timestamp();
+
+        let positions = balances
+            .into_iter()
+            .filter(|(asset, _)| asset != "USDT")
+            .filter_map(|(asset, (free, locked))| {
+                let symbol = format!("{}USDT", asset);
+                let price = *prices.get(&symbol)?;
+                let quantity = free + locked;
+                Some((
+                    symbol.clone(),
+                    Position {
+                        symbol,
+                        // 现货账户不记录开仓均价, 以现价代替
+                        entry_price: price,
+                        current_price: price,
+                        quantity,
+                        unrealized_pnl: 0.0,
+                        direction: Some("long".to_string()),
+                        leverage: Some(1),
+                        margin: Some(price * quantity),
+                        timestamp: now,
+                    },
+                ))
+            })
+            .collect();
+
+        Ok(Positions { positions })
+    }
+}
+
+// ============================================================================
+// 原始响应结构
+// ============================================================================
+
+#[derive(Deserialize)]
+struct RawTickerPrice {
+    symbol: String,
+    price: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawDepth {
+    #[serde(default)]
+    last_update_id: u64,
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTicker24h {
+    symbol: String,
+    price_change_percent: String,
+    last_price: String,
+    high_price: String,
+    low_price: String,
+    quote_volume: String,
+    close_time: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawOrder {
+    symbol: String,
+    order_id: i64,
+    #[serde(default)]
+    price: String,
+    #[serde(default)]
+    orig_qty: String,
+    #[serde(default)]
+    executed_qty: String,
+    #[serde(default)]
+    cummulative_quote_qty: String,
+    status: String,
+    #[serde(rename = "type", default)]
+    order_type: String,
+    #[serde(default)]
+    side: String,
+    #[serde(default)]
+    transact_time: Option<i64>,
+    #[serde(default)]
+    time: Option<i64>,
+    #[serde(default)]
+    update_time: Option<i64>,
+}
+
+impl RawOrder {
+    fn into_order(self) -> Order {
+        let quantity = parse_f64(&self.orig_qty);
+        let filled_quantity = parse_f64(&self.executed_qty);
+        let quote = parse_f64(&self.cummulative_quote_qty);
+        let price = parse_f64(&self.price);
+        let created_at = self.time.or(self.transact_time).unwrap_or_default() / 1000;
+
+        Order {
+            order_id: compose_order_id(&self.symbol, self.order_id),
+            symbol: self.symbol,
+            side: if self.side == "SELL" {
+                OrderSide::Sell
+            } else {
+                OrderSide::Buy
+            },
+            order_type: parse_type(&self.order_type),
+            quantity,
+            filled_quantity,
+            price: (price > 0.0).then_some(price),
+            avg_price: (filled_quantity > 0.0).then(|| quote / filled_quantity),
+            status: parse_status(&self.status),
+            created_at,
+            updated_at: self.update_time.map(|t| t / 1000).unwrap_or(created_at),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTrade {
+    symbol: String,
+    id: i64,
+    order_id: i64,
+    price: String,
+    qty: String,
+    commission: String,
+    time: i64,
+    is_buyer: bool,
+}
+
+#[derive(Deserialize)]
+struct RawAccount {
+    balances: Vec<RawBalance>,
+}
+
+#[derive(Deserialize)]
+struct RawBalance {
+    asset: String,
+    free: String,
+    locked: String,
+}
+
+// ============================================================================
+// 辅助函数
+// ============================================================================
+
+/// HMAC-SHA256 签名
+pub fn sign_query(secret: &str, query: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(query.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// 组合订单ID: Binance 的查询/撤单需要交易对, 统一编码为 `SYMBOL:orderId`
+pub fn compose_order_id(symbol: &str, order_id: i64) -> String {
+    format!("{}:{}", symbol, order_id)
+}
+
+fn split_order_id(order_id: &str) -> Result<(&str, &str), BinanceApiError> {
+    order_id
+        .split_once(':')
+        .ok_or_else(|| BinanceApiError::Decode(format!("invalid order id: {}", order_id)))
+}
+
+pub(super) fn parse_f64(s: &str) -> f64 {
+    s.parse().unwrap_or_default()
+}
+
+fn value_f64(v: &serde_json::Value) -> f64 {
+    match v {
+        serde_json::Value::String(s) => parse_f64(s),
+        other => other.as_f64().unwrap_or_default(),
+    }
+}
+
+pub(super) fn to_level(level: &[String; 2]) -> OrderbookLevel {
+    OrderbookLevel {
+        price: parse_f64(&level[0]),
+        quantity: parse_f64(&level[1]),
+    }
+}
+
+fn asset_usdt_price(asset: &str, prices: &HashMap<String, f64>) -> f64 {
+    match asset {
+        "USDT" | "USDC" | "FDUSD" => 1.0,
+        _ => prices
+            .get(&format!("{}USDT", asset))
+            .copied()
+            .unwrap_or_default(),
+    }
+}
+
+fn side_to_str(side: &OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "BUY",
+        OrderSide::Sell => "SELL",
+    }
+}
+
+fn type_to_str(order_type: &OrderType) -> &'static str {
+    match order_type {
+        OrderType::Market => "MARKET",
+        OrderType::Limit => "LIMIT",
+        OrderType::Stop => "STOP_LOSS",
+        OrderType::StopLimit => "STOP_LOSS_LIMIT",
+    }
+}
+
+fn parse_type(s: &str) -> OrderType {
+    match s {
+        "MARKET" => OrderType::Market,
+        "STOP_LOSS" | "TAKE_PROFIT" => OrderType::Stop,
+        "STOP_LOSS_LIMIT" | "TAKE_PROFIT_LIMIT" => OrderType::StopLimit,
+        _ => OrderType::Limit,
+    }
+}
+
+fn parse_status(s: &str) -> OrderStatus {
+    match s {
+        "NEW" | "PENDING_CANCEL" => OrderStatus::Accepted,
+        "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+        "FILLED" => OrderStatus::Filled,
+        "CANCELED" | "EXPIRED" | "EXPIRED_IN_MATCH" => OrderStatus::Cancelled,
+        "REJECTED" => OrderStatus::Rejected,
+        _ => OrderStatus::Pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::RawQuery;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::get;
+    use axum::Router;
+
+    const API_KEY: &str = "test-key";
+    const API_SECRET: &str = "test-secret";
+
+    fn fixture(name: &str) -> &'static str {
+        match name {
+            "time" => include_str!("../../../fixtures/binance/time.json"),
+            "ticker_price" => include_str!("../../../fixtures/binance/ticker_price.json"),
+            "depth" => include_str!("../../../fixtures/binance/depth.json"),
+            "klines" => include_str!("../../../fixtures/binance/klines.json"),
+            "ticker_24hr" => include_str!("../../../fixtures/binance/ticker_24hr.json"),
+            "order_new" => include_str!("../../../fixtures/binance/order_new.json"),
+            "order_query" => include_str!("../../../fixtures/binance/order_query.json"),
+            "order_cancel" => include_str!("../../../fixtures/binance/order_cancel.json"),
+            "account" => include_str!("../../../fixtures/binance/account.json"),
+            _ => unreachable!(),
+        }
+    }
+
+    fn json(body: &'static str) -> Response {
+        ([("content-type", "application/json")], body).into_response()
+    }
+
+    /// 校验签名 / API Key, 失败时返回与 Binance 一致的错误响应
+    fn check_signed(headers: &HeaderMap, query: Option<String>) -> Option<Response> {
+        let query = query.unwrap_or_default();
+        let (payload, signature) = query.rsplit_once("&signature=").unwrap_or((&query, ""));
+
+        if headers.get("X-MBX-APIKEY").and_then(|v| v.to_str().ok()) != Some(API_KEY) {
+            return Some(
+                (
+                    StatusCode::UNAUTHORIZED,
+                    r#"{"code":-2015,"msg":"Invalid API-key, IP, or permissions for action."}"#,
+                )
+                    .into_response(),
+            );
+        }
+        if !payload.contains("recvWindow=5000") || !payload.contains("timestamp=") {
+            return Some(
+                (
+                    StatusCode::BAD_REQUEST,
+                    r#"{"code":-1102,"msg":"Mandatory parameter 'timestamp' was not sent."}"#,
+                )
+                    .into_response(),
+            );
+        }
+        if sign_query(API_SECRET, payload) != signature {
+            return Some(
+                (
+                    StatusCode::BAD_REQUEST,
+                    r#"{"code":-1022,"msg":"Signature for this request is not valid."}"#,
+                )
+                    .into_response(),
+            );
+        }
+        None
+    }
+
+    async fn signed(headers: HeaderMap, query: RawQuery, name: &'static str) -> Response {
+        check_signed(&headers, query.0).unwrap_or_else(|| json(fixture(name)))
+    }
+
+    /// 启动本地 Binance 替身, 回放录制的响应
+    async fn spawn_stand_in() -> String {
+        let app = Router::new()
+            .route("/api/v3/time", get(|| async { json(fixture("time")) }))
+            .route(
+                "/api/v3/ticker/price",
+                get(|| async { json(fixture("ticker_price")) }),
+            )
+            .route("/api/v3/depth", get(|| async { json(fixture("depth")) }))
+            .route("/api/v3/klines", get(|| async { json(fixture("klines")) }))
+            .route(
+                "/api/v3/ticker/24hr",
+                get(|| async { json(fixture("ticker_24hr")) }),
+            )
+            .route(
+                "/api/v3/order",
+                get(|h: HeaderMap, q: RawQuery| signed(h, q, "order_query"))
+                    .post(|h: HeaderMap, q: RawQuery| signed(h, q, "order_new"))
+                    .delete(|h: HeaderMap, q: RawQuery| signed(h, q, "order_cancel")),
+            )
+            .route(
+                "/api/v3/account",
+                get(|h: HeaderMap, q: RawQuery| signed(h, q, "account")),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    fn client(base_url: String, secret: &str) -> BinanceRestClient {
+        BinanceRestClient::new(&BinanceConfig {
+            api_key: API_KEY.to_string(),
+            api_secret: secret.to_string(),
+            base_url: Some(base_url),
+            mock_mode: false,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_sign_query_matches_binance_docs() {
+        // 官方文档示例
+        let secret = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+        let query = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        assert_eq!(
+            sign_query(secret, query),
+            "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_market_data_against_stand_in() {
+        let client = client(spawn_stand_in().await, API_SECRET);
+
+        let prices = client
+            .get_prices(&["BTCUSDT".to_string(), "ETHUSDT".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(prices.prices["BTCUSDT"], 106215.01);
+        assert_eq!(prices.prices["ETHUSDT"], 3841.5);
+
+        let book = client.get_orderbook("BTCUSDT", 5).await.unwrap();
+        assert_eq!(book.bids.len(), 5);
+        assert_eq!(book.bids[0].price, 106215.0);
+        assert_eq!(book.asks[0].quantity, 0.52141);
+
+        let snapshot = client.get_depth_snapshot("BTCUSDT", 5).await.unwrap();
+        assert_eq!(snapshot.final_update_id, 76253987410);
+        assert_eq!(snapshot.kind, DepthUpdateKind::Snapshot);
+
+        let klines = client.get_klines("BTCUSDT", "1h", Some(3)).await.unwrap();
+        assert_eq!(klines.klines.len(), 3);
+        assert_eq!(klines.klines[0].timestamp, 1761037200);
+        assert_eq!(klines.klines[2].close, 106215.01);
+
+        let ticker = client.get_ticker_24h("BTCUSDT").await.unwrap();
+        assert_eq!(ticker.last_price, 106215.01);
+        assert_eq!(ticker.change_24h, 1.249);
+    }
+
+    #[tokio::test]
+    async fn test_signed_trading_against_stand_in() {
+        let client = client(spawn_stand_in().await, API_SECRET);
+
+        let response = client
+            .place_order(&OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                order_type: OrderType::Limit,
+                quantity: 0.01,
+                price: Some(105000.0),
+                time_in_force: None,
+                reduce_only: false,
+            })
+            .await
+            .unwrap();
+        assert_eq!(response.order_id, "BTCUSDT:28457");
+        assert!(matches!(response.status, OrderStatus::Accepted));
+
+        let order = client.get_order(&response.order_id).await.unwrap();
+        assert!(matches!(order.status, OrderStatus::PartiallyFilled));
+        assert_eq!(order.filled_quantity, 0.004);
+        assert_eq!(order.avg_price, Some(105000.0));
+
+        let cancelled = client.cancel_order(&response.order_id).await.unwrap();
+        assert!(matches!(cancelled.status, OrderStatus::Cancelled));
+
+        let balance = client.get_balance().await.unwrap();
+        assert_eq!(balance.available, 9500.0);
+        assert_eq!(balance.frozen_margin, Some(500.0));
+        // 10000 USDT + 0.05 BTC @ 106215.01
+        assert!((balance.total_balance - 15310.7505).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_bad_signature_surfaces_api_error() {
+        let client = client(spawn_stand_in().await, "wrong-secret");
+
+        let err = client.get_balance().await.unwrap_err();
+        assert_eq!(err.code(), Some(-1022));
+        assert!(matches!(BrokerError::from(err), BrokerError::Auth(_)));
+    }
+
+    #[test]
+    fn test_api_error_mapping() {
+        let api = |status, code, msg: &str| BinanceApiError::Api {
+            status,
+            code,
+            msg: msg.to_string(),
+            retry_after: Some(Duration::from_secs(7)),
+        };
+
+        assert_eq!(
+            BrokerError::from(api(429, -1003, "Too many requests")).retry_after(),
+            Some(Duration::from_secs(7))
+        );
+        assert!(matches!(
+            BrokerError::from(api(
+                400,
+                -2010,
+                "Account has insufficient balance for requested action."
+            )),
+            BrokerError::InsufficientFunds(_)
+        ));
+        assert!(matches!(
+            BrokerError::from(api(400, -1121, "Invalid symbol.")),
+            BrokerError::InvalidSymbol(_)
+        ));
+        assert!(matches!(
+            BrokerError::from(api(400, -1013, "Filter failure: LOT_SIZE")),
+            BrokerError::OrderRejected { code, .. } if code == "-1013"
+        ));
+    }
+}
@@ -1,7 +1,11 @@
 // Binance (币安) broker implementation
 
 pub mod broker;
+pub mod client;
+pub mod stream;
 pub mod types;
 
-pub use broker::BinanceBroker;
+pub use broker::{BinanceBroker, BinanceMode};
+pub use client::{BinanceApiError, BinanceRestClient};
+pub use stream::BinanceMarketStream;
 pub use types::*;
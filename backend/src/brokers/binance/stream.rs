@@ -0,0 +1,527 @@
+// Binance WebSocket 行情推送
+// Binance combined-stream market data
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tracing::{debug, info, warn};
+
+use super::client::{parse_f64, to_level, BinanceRestClient};
+use super::types::BinanceConfig;
+use crate::brokers::error::BrokerError;
+use crate::brokers::stream::*;
+use crate::brokers::types::*;
+
+/// 主网 combined stream 地址
+pub const BINANCE_WS_MAINNET_URL: &str = "wss://stream.binance.com:9443/stream";
+
+/// 测试网 combined stream 地址
+pub const BINANCE_WS_TESTNET_URL: &str = "wss://stream.testnet.binance.vision/stream";
+
+/// 服务器每 20 秒发送 ping (由 tungstenite 自动回复 pong),
+/// 超过该时长没有任何帧视为连接失活
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 深度快照档数
+const SNAPSHOT_DEPTH: u32 = 1000;
+
+/// Binance 行情推送
+///
+/// 使用 `/stream` combined stream, 连接后发送 `SUBSCRIBE`; 断线重连时重新订阅,
+/// 并丢弃本地订单簿, 由 REST 快照 + 增量重新构建。
+pub struct BinanceMarketStream {
+    ws_url: String,
+    rest: Arc<BinanceRestClient>,
+}
+
+impl BinanceMarketStream {
+    pub fn new(config: &BinanceConfig) -> Self {
+        let ws_url = config.ws_url.clone().unwrap_or_else(|| {
+            if config.testnet {
+                BINANCE_WS_TESTNET_URL.to_string()
+            } else {
+                BINANCE_WS_MAINNET_URL.to_string()
+            }
+        });
+
+        Self {
+            ws_url,
+            rest: Arc::new(BinanceRestClient::new(config)),
+        }
+    }
+
+    /// 启动后台连接并返回事件流
+    pub fn subscribe(&self, subscription: StreamSubscription) -> MarketEventStream {
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let task = tokio::spawn(run(
+            self.ws_url.clone(),
+            self.rest.clone(),
+            subscription,
+            tx,
+        ));
+        MarketEventStream::new(rx, vec![task])
+    }
+}
+
+/// 订阅参数, 如 `btcusdt@trade` / `btcusdt@depth@100ms` / `btcusdt@kline_1m`
+pub fn stream_names(subscription: &StreamSubscription) -> Vec<String> {
+    let mut names = Vec::new();
+    for symbol in &subscription.symbols {
+        let symbol = symbol.to_lowercase();
+        for channel in &subscription.channels {
+            names.push(match channel {
+                MarketChannel::Ticker => format!("{}@ticker", symbol),
+                MarketChannel::Trades => format!("{}@trade", symbol),
+                MarketChannel::Depth => format!("{}@depth@100ms", symbol),
+                MarketChannel::Kline(interval) => format!("{}@kline_{}", symbol, interval),
+            });
+        }
+    }
+    names
+}
+
+/// 连接循环: 断线后按指数退避重连, 消费方 drop 流后退出
+async fn run(
+    ws_url: String,
+    rest: Arc<BinanceRestClient>,
+    subscription: StreamSubscription,
+    tx: mpsc::Sender<MarketEvent>,
+) {
+    let params = stream_names(&subscription);
+    let mut books: HashMap<String, LocalOrderBook> = HashMap::new();
+    let mut attempt = 0u32;
+
+    while !tx.is_closed() {
+        match run_session(&ws_url, &rest, &params, &mut books, &tx, &mut attempt).await {
+            Ok(()) => info!("Binance stream closed, reconnecting"),
+            Err(e) => warn!("Binance stream error: {}", e),
+        }
+        if tx.is_closed() {
+            break;
+        }
+        tokio::time::sleep(reconnect_delay(attempt)).await;
+        attempt = attempt.saturating_add(1);
+    }
+}
+
+async fn run_session(
+    ws_url: &str,
+    rest: &BinanceRestClient,
+    params: &[String],
+    books: &mut HashMap<String, LocalOrderBook>,
+    tx: &mpsc::Sender<MarketEvent>,
+    attempt: &mut u32,
+) -> Result<(), BrokerError> {
+    let mut ws = connect_ws(ws_url).await?;
+    *attempt = 0;
+
+    let request = serde_json::json!({ "method": "SUBSCRIBE", "params": params, "id": 1 });
+    ws.send(WsMessage::text(request.to_string()))
+        .await
+        .map_err(|e| BrokerError::Network(e.to_string()))?;
+    debug!("Binance stream subscribed: {:?}", params);
+
+    // 重连后增量序号不再连续, 必须重新拉取快照
+    books.values_mut().for_each(LocalOrderBook::reset);
+
+    loop {
+        let message = match tokio::time::timeout(IDLE_TIMEOUT, ws.next()).await {
+            Err(_) => return Err(BrokerError::Network("Binance stream idle timeout".into())),
+            Ok(None) => return Ok(()),
+            Ok(Some(message)) => message.map_err(|e| BrokerError::Network(e.to_string()))?,
+        };
+
+        let text = match message {
+            WsMessage::Text(text) => text,
+            WsMessage::Close(_) => return Ok(()),
+            _ => continue,
+        };
+
+        let Some(frame) = parse_frame(&text) else {
+            continue;
+        };
+
+        let events = match frame {
+            StreamFrame::Event(event) => vec![event],
+            StreamFrame::DepthDiff(diff) => sync_depth(rest, books, diff).await?,
+        };
+
+        for event in events {
+            if tx.send(event).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// 将增量应用到本地订单簿, 必要时先拉取快照
+///
+/// 返回需要推送的事件 (快照 + 已通过序号校验的增量)。
+async fn sync_depth(
+    rest: &BinanceRestClient,
+    books: &mut HashMap<String, LocalOrderBook>,
+    diff: DepthUpdate,
+) -> Result<Vec<MarketEvent>, BrokerError> {
+    let book = books
+        .entry(diff.symbol.clone())
+        .or_insert_with(|| LocalOrderBook::new(diff.symbol.clone()));
+
+    let mut events = Vec::new();
+    if !book.is_initialized() {
+        let snapshot = rest
+            .get_depth_snapshot(&diff.symbol, SNAPSHOT_DEPTH)
+            .await?;
+        book.apply(&snapshot).ok();
+        events.push(MarketEvent::Depth(snapshot));
+    }
+
+    match book.apply(&diff) {
+        Ok(true) => events.push(MarketEvent::Depth(diff)),
+        Ok(false) => {}
+        Err(e) => {
+            // 下一条增量到达时重新拉取快照
+            warn!("Binance depth {}, resyncing", e);
+            book.reset();
+        }
+    }
+    Ok(events)
+}
+
+// ============================================================================
+// 消息解析
+// ============================================================================
+
+/// 解析后的推送帧
+#[derive(Debug)]
+pub enum StreamFrame {
+    /// 可直接推送的事件
+    Event(MarketEvent),
+    /// 深度增量 (需经本地订单簿校验)
+    DepthDiff(DepthUpdate),
+}
+
+/// 解析 combined stream 帧 (`{"stream": ..., "data": {...}}`)
+///
+/// 订阅回执、未收盘的K线等返回 None。
+pub fn parse_frame(text: &str) -> Option<StreamFrame> {
+    let envelope: CombinedFrame = serde_json::from_str(text).ok()?;
+    let data = envelope.data?;
+
+    match data.get("e")?.as_str()? {
+        "24hrTicker" => {
+            let t: RawTicker = serde_json::from_value(data).ok()?;
+            Some(StreamFrame::Event(MarketEvent::Ticker(Ticker24h {
+                symbol: t.symbol,
+                last_price: parse_f64(&t.last_price),
+                change_24h: parse_f64(&t.price_change_percent),
+                high_24h: parse_f64(&t.high_price),
+                low_24h: parse_f64(&t.low_price),
+                volume_24h: parse_f64(&t.volume),
+                open_interest: None,
+                timestamp: t.event_time / 1000,
+            })))
+        }
+        "trade" => {
+            let t: RawTrade = serde_json::from_value(data).ok()?;
+            Some(StreamFrame::Event(MarketEvent::Trade(MarketTrade {
+                symbol: t.symbol,
+                trade_id: t.trade_id.to_string(),
+                price: parse_f64(&t.price),
+                quantity: parse_f64(&t.quantity),
+                // 买方是 maker 说明主动方是卖方
+                side: if t.buyer_is_maker {
+                    OrderSide::Sell
+                } else {
+                    OrderSide::Buy
+                },
+                timestamp: t.trade_time / 1000,
+            })))
+        }
+        "depthUpdate" => {
+            let d: RawDepthUpdate = serde_json::from_value(data).ok()?;
+            Some(StreamFrame::DepthDiff(DepthUpdate {
+                symbol: d.symbol,
+                kind: DepthUpdateKind::Diff,
+                first_update_id: d.first_update_id,
+                final_update_id: d.final_update_id,
+                prev_update_id: None,
+                bids: d.bids.iter().map(to_level).collect(),
+                asks: d.asks.iter().map(to_level).collect(),
+                timestamp: d.event_time / 1000,
+            }))
+        }
+        "kline" => {
+            let k: RawKlineEvent = serde_json::from_value(data).ok()?;
+            if !k.kline.closed {
+                return None;
+            }
+            Some(StreamFrame::Event(MarketEvent::KlineClosed {
+                symbol: k.symbol,
+                interval: k.kline.interval,
+                kline: Kline {
+                    timestamp: k.kline.open_time / 1000,
+                    open: parse_f64(&k.kline.open),
+                    high: parse_f64(&k.kline.high),
+                    low: parse_f64(&k.kline.low),
+                    close: parse_f64(&k.kline.close),
+                    volume: parse_f64(&k.kline.volume),
+                    open_interest: None,
+                },
+            }))
+        }
+        _ => None,
+    }
+}
+
+// ============================================================================
+// 推送原始结构
+// ============================================================================
+
+#[derive(Deserialize)]
+struct CombinedFrame {
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct RawTicker {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "P")]
+    price_change_percent: String,
+    #[serde(rename = "c")]
+    last_price: String,
+    #[serde(rename = "h")]
+    high_price: String,
+    #[serde(rename = "l")]
+    low_price: String,
+    #[serde(rename = "v")]
+    volume: String,
+}
+
+#[derive(Deserialize)]
+struct RawTrade {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "t")]
+    trade_id: i64,
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    quantity: String,
+    #[serde(rename = "T")]
+    trade_time: i64,
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,
+}
+
+#[derive(Deserialize)]
+struct RawDepthUpdate {
+    #[serde(rename = "E")]
+    event_time: i64,
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "U")]
+    first_update_id: u64,
+    #[serde(rename = "u")]
+    final_update_id: u64,
+    #[serde(rename = "b")]
+    bids: Vec<[String; 2]>,
+    #[serde(rename = "a")]
+    asks: Vec<[String; 2]>,
+}
+
+#[derive(Deserialize)]
+struct RawKlineEvent {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "k")]
+    kline: RawKline,
+}
+
+#[derive(Deserialize)]
+struct RawKline {
+    #[serde(rename = "t")]
+    open_time: i64,
+    #[serde(rename = "i")]
+    interval: String,
+    #[serde(rename = "o")]
+    open: String,
+    #[serde(rename = "c")]
+    close: String,
+    #[serde(rename = "h")]
+    high: String,
+    #[serde(rename = "l")]
+    low: String,
+    #[serde(rename = "v")]
+    volume: String,
+    #[serde(rename = "x")]
+    closed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::ws::{Message as AxumMessage, WebSocketUpgrade};
+    use axum::routing::get;
+    use axum::Router;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn fixture(name: &str) -> &'static str {
+        match name {
+            "depth" => include_str!("../../../fixtures/binance/depth.json"),
+            "ws_trade" => include_str!("../../../fixtures/binance/ws_trade.json"),
+            "ws_depth_update" => include_str!("../../../fixtures/binance/ws_depth_update.json"),
+            "ws_kline" => include_str!("../../../fixtures/binance/ws_kline.json"),
+            "ws_ticker" => include_str!("../../../fixtures/binance/ws_ticker.json"),
+            _ => unreachable!("unknown fixture {}", name),
+        }
+    }
+
+    /// 本地 WebSocket 替身: 第一次连接推送成交/深度/K线后主动断开,
+    /// 重连后推送 ticker, 用于验证断线重订阅
+    async fn spawn_stand_in(subscribes: Arc<AtomicUsize>) -> String {
+        let app = Router::new()
+            .route(
+                "/api/v3/depth",
+                get(|| async {
+                    (
+                        [(axum::http::header::CONTENT_TYPE, "application/json")],
+                        fixture("depth"),
+                    )
+                }),
+            )
+            .route(
+                "/stream",
+                get(move |ws: WebSocketUpgrade| {
+                    let subscribes = subscribes.clone();
+                    async move {
+                        ws.on_upgrade(move |mut socket| async move {
+                            let Some(Ok(AxumMessage::Text(request))) = socket.recv().await else {
+                                return;
+                            };
+                            assert!(request.contains("\"SUBSCRIBE\""));
+                            assert!(request.contains("btcusdt@depth@100ms"));
+
+                            let frames = if subscribes.fetch_add(1, Ordering::SeqCst) == 0 {
+                                vec!["ws_trade", "ws_depth_update", "ws_kline"]
+                            } else {
+                                vec!["ws_ticker"]
+                            };
+                            for name in frames {
+                                let _ = socket.send(AxumMessage::text(fixture(name))).await;
+                            }
+                            let _ = socket.send(AxumMessage::Close(None)).await;
+                        })
+                    }
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("{}", addr)
+    }
+
+    async fn next(events: &mut MarketEventStream) -> MarketEvent {
+        tokio::time::timeout(Duration::from_secs(5), events.next())
+            .await
+            .expect("stream timed out")
+            .expect("stream ended")
+    }
+
+    #[test]
+    fn test_stream_names() {
+        let sub = StreamSubscription::new(["BTCUSDT"])
+            .with_channel(MarketChannel::Ticker)
+            .with_channel(MarketChannel::Depth)
+            .with_channel(MarketChannel::Kline("1m".to_string()));
+        assert_eq!(
+            stream_names(&sub),
+            vec!["btcusdt@ticker", "btcusdt@depth@100ms", "btcusdt@kline_1m"]
+        );
+    }
+
+    #[test]
+    fn test_parse_frames() {
+        let Some(StreamFrame::Event(MarketEvent::Trade(trade))) = parse_frame(fixture("ws_trade"))
+        else {
+            panic!("expected trade");
+        };
+        assert_eq!(trade.trade_id, "5315822431");
+        assert!(matches!(trade.side, OrderSide::Sell));
+        assert_eq!(trade.quantity, 0.0035);
+
+        let Some(StreamFrame::DepthDiff(diff)) = parse_frame(fixture("ws_depth_update")) else {
+            panic!("expected depth diff");
+        };
+        assert_eq!(diff.first_update_id, 76253987405);
+        assert_eq!(diff.final_update_id, 76253987412);
+        assert_eq!(diff.bids[0].quantity, 0.0);
+
+        let Some(StreamFrame::Event(MarketEvent::Ticker(ticker))) =
+            parse_frame(fixture("ws_ticker"))
+        else {
+            panic!("expected ticker");
+        };
+        assert_eq!(ticker.change_24h, 1.249);
+
+        // 订阅回执与未收盘K线不产生事件
+        assert!(parse_frame(r#"{"result":null,"id":1}"#).is_none());
+        let open_kline = fixture("ws_kline").replace("\"x\":true", "\"x\":false");
+        assert!(parse_frame(&open_kline).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stream_rebuilds_book_and_resubscribes() {
+        let subscribes = Arc::new(AtomicUsize::new(0));
+        let addr = spawn_stand_in(subscribes.clone()).await;
+
+        let config = BinanceConfig {
+            base_url: Some(format!("http://{}", addr)),
+            ws_url: Some(format!("ws://{}/stream", addr)),
+            ..Default::default()
+        };
+        let stream = BinanceMarketStream::new(&config);
+        let mut events = stream.subscribe(
+            StreamSubscription::new(["BTCUSDT"])
+                .with_channel(MarketChannel::Ticker)
+                .with_channel(MarketChannel::Trades)
+                .with_channel(MarketChannel::Depth)
+                .with_channel(MarketChannel::Kline("1m".to_string())),
+        );
+
+        assert!(matches!(next(&mut events).await, MarketEvent::Trade(_)));
+
+        let mut book = LocalOrderBook::new("BTCUSDT");
+        let MarketEvent::Depth(snapshot) = next(&mut events).await else {
+            panic!("expected depth snapshot");
+        };
+        assert_eq!(snapshot.kind, DepthUpdateKind::Snapshot);
+        book.apply(&snapshot).unwrap();
+
+        let MarketEvent::Depth(diff) = next(&mut events).await else {
+            panic!("expected depth diff");
+        };
+        assert!(book.apply(&diff).unwrap());
+        assert_eq!(book.best_bid().unwrap().price, 106214.99);
+        assert_eq!(book.best_ask().unwrap().quantity, 0.6);
+
+        let MarketEvent::KlineClosed { kline, .. } = next(&mut events).await else {
+            panic!("expected closed kline");
+        };
+        assert_eq!(kline.close, 106215.01);
+
+        // 服务端断开后自动重连并重新订阅
+        assert!(matches!(next(&mut events).await, MarketEvent::Ticker(_)));
+        assert_eq!(subscribes.load(Ordering::SeqCst), 2);
+    }
+}
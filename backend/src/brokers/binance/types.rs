@@ -21,15 +21,23 @@ pub struct BinanceConfig {
     #[serde(default)]
     pub ws_url: Option<String>,
 
-    /// 是否启用模拟模式 (不连接真实Binance)
+    /// 是否启用模拟模式 (不连接真实Binance, 使用随机游走行情)
     #[serde(default = "default_true")]
     pub mock_mode: bool,
+
+    /// 签名请求的有效时间窗口 (毫秒)
+    #[serde(default = "default_recv_window")]
+    pub recv_window: u64,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_recv_window() -> u64 {
+    5000
+}
+
 impl Default for BinanceConfig {
     fn default() -> Self {
         Self {
@@ -39,6 +47,7 @@ impl Default for BinanceConfig {
             base_url: None,
             ws_url: None,
             mock_mode: true,
+            recv_window: default_recv_window(),
         }
     }
 }
@@ -68,6 +77,10 @@ impl BinanceConfig {
             if self.api_secret.is_empty() {
                 return Err("api_secret cannot be empty".to_string());
             }
+
+            if self.recv_window == 0 || self.recv_window > 60000 {
+                return Err("recv_window must be between 1 and 60000".to_string());
+            }
         }
 
         Ok(())
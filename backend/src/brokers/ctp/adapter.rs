@@ -134,6 +134,7 @@ impl CtpMarketAdapter {
                 open_interest: 85000,
                 highest_price: 3520.0,
                 lowest_price: 3480.0,
+                pre_settlement_price: 3488.6,
                 update_time: chrono::Local::now().format("%H:%M:%S").to_string(),
             },
         );
@@ -152,6 +153,7 @@ impl CtpMarketAdapter {
                 open_interest: 72000,
                 highest_price: 5220.0,
                 lowest_price: 5180.0,
+                pre_settlement_price: 5172.4,
                 update_time: chrono::Local::now().format("%H:%M:%S").to_string(),
             },
         );
@@ -170,6 +172,7 @@ impl CtpMarketAdapter {
                 open_interest: 56000,
                 highest_price: 2415.0,
                 lowest_price: 2385.0,
+                pre_settlement_price: 2392.2,
                 update_time: chrono::Local::now().format("%H:%M:%S").to_string(),
             },
         );
@@ -14,6 +14,7 @@ pub struct CtpBroker {
     name: String,
     config: CtpConfig,
     adapter: CtpMarketAdapter,
+    models: Vec<ModelInfo>,
 }
 
 #[allow(dead_code)]
@@ -25,9 +26,16 @@ impl CtpBroker {
             name,
             config,
             adapter,
+            models: Vec::new(),
         }
     }
 
+    /// 绑定到该经纪商的 AI 模型 (启动时由 agents.yaml 注入)
+    pub fn with_models(mut self, models: Vec<ModelInfo>) -> Self {
+        self.models = models;
+        self
+    }
+
     /// 获取 CTP 支持的期货合约列表 (更新到2025年合约)
     fn get_instruments(&self) -> Vec<String> {
         vec![
@@ -86,56 +94,12 @@ impl CtpBroker {
         }
     }
 
-    /// 获取模型配置（CTP 特定）- 增加第4个模型
+    /// 获取绑定到该经纪商的 AI 模型
     fn get_models(&self) -> Vec<ModelInfo> {
-        vec![
-            ModelInfo {
-                model_id: "ctp_trend_following".to_string(),
-                model_name: "趋势追踪AI".to_string(),
-                strategy: "Trend Following".to_string(),
-                description: "基于移动平均线的趋势跟踪策略，适合中长期持仓".to_string(),
-                risk_level: "MEDIUM".to_string(),
-                base_capital: 1000000.0,
-            },
-            ModelInfo {
-                model_id: "ctp_statistical_arbitrage".to_string(),
-                model_name: "跨品种套利AI".to_string(),
-                strategy: "Statistical Arbitrage".to_string(),
-                description: "利用相关品种间的价差进行统计套利，风险较低".to_string(),
-                risk_level: "LOW".to_string(),
-                base_capital: 2000000.0,
-            },
-            ModelInfo {
-                model_id: "ctp_momentum_breakout".to_string(),
-                model_name: "动量突破AI".to_string(),
-                strategy: "Momentum Breakout".to_string(),
-                description: "捕捉短期动量突破机会，适合高频交易".to_string(),
-                risk_level: "HIGH".to_string(),
-                base_capital: 800000.0,
-            },
-            ModelInfo {
-                model_id: "ctp_hedging_strategy".to_string(),
-                model_name: "对冲套保AI".to_string(),
-                strategy: "Hedging Strategy".to_string(),
-                description: "利用期货进行风险对冲，保护现货资产".to_string(),
-                risk_level: "LOW".to_string(),
-                base_capital: 1500000.0,
-            },
-        ]
+        self.models.clone()
     }
 }
 
-#[derive(Clone)]
-#[allow(dead_code)]
-struct ModelInfo {
-    model_id: String,
-    model_name: String,
-    strategy: String,
-    description: String,
-    risk_level: String,
-    base_capital: f64,
-}
-
 // ============================================================================
 // Broker Trait Implementation
 // ============================================================================
@@ -155,7 +119,7 @@ impl Broker for CtpBroker {
 impl MarketData for CtpBroker {
     fn get_prices(
         &self,
-    ) -> impl std::future::Future<Output = Result<Prices, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Prices, BrokerError>> + Send {
         async move {
             let instruments = self.get_instruments();
             let mut prices = HashMap::new();
@@ -174,7 +138,7 @@ impl MarketData for CtpBroker {
     fn get_orderbook(
         &self,
         symbol: &str,
-    ) -> impl std::future::Future<Output = Result<Orderbook, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Orderbook, BrokerError>> + Send
     {
         let symbol = symbol.to_string();
         async move {
@@ -212,7 +176,7 @@ impl MarketData for CtpBroker {
         symbol: &str,
         interval: &str,
         limit: Option<i32>,
-    ) -> impl std::future::Future<Output = Result<Klines, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Klines, BrokerError>> + Send {
         let symbol = symbol.to_string();
         let interval = interval.to_string();
         async move {
@@ -266,7 +230,7 @@ impl MarketData for CtpBroker {
     fn get_ticker_24h(
         &self,
         symbol: &str,
-    ) -> impl std::future::Future<Output = Result<Ticker24h, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Ticker24h, BrokerError>> + Send
     {
         let symbol = symbol.to_string();
         async move {
@@ -297,7 +261,7 @@ impl Trading for CtpBroker {
     fn place_order(
         &self,
         _order: OrderRequest,
-    ) -> impl std::future::Future<Output = Result<OrderResponse, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send
     {
         async move {
             let order_id = format!("CTP_{}", chrono::Utc::now().timestamp_millis());
@@ -312,7 +276,7 @@ impl Trading for CtpBroker {
     fn cancel_order(
         &self,
         order_id: &str,
-    ) -> impl std::future::Future<Output = Result<OrderResponse, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send
     {
         let order_id = order_id.to_string();
         async move {
@@ -327,7 +291,7 @@ impl Trading for CtpBroker {
     fn get_order(
         &self,
         order_id: &str,
-    ) -> impl std::future::Future<Output = Result<Order, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Order, BrokerError>> + Send {
         let order_id = order_id.to_string();
         async move {
             Ok(Order {
@@ -349,13 +313,13 @@ impl Trading for CtpBroker {
     fn get_orders(
         &self,
         _symbol: Option<&str>,
-    ) -> impl std::future::Future<Output = Result<Orders, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Orders, BrokerError>> + Send {
         async move { Ok(Orders { orders: vec![] }) }
     }
 
     fn get_trades(
         &self,
-    ) -> impl std::future::Future<Output = Result<Trades, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Trades, BrokerError>> + Send {
         async move { Ok(Trades { trades: vec![] }) }
     }
 }
@@ -367,7 +331,7 @@ impl AccountManagement for CtpBroker {
     fn get_account_totals(
         &self,
         _last_marker: Option<i32>,
-    ) -> impl std::future::Future<Output = Result<AccountTotals, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<AccountTotals, BrokerError>> + Send
     {
         async move {
             let mut rng = rand::thread_rng();
@@ -437,7 +401,7 @@ impl AccountManagement for CtpBroker {
 
     fn get_model_accounts(
         &self,
-    ) -> impl std::future::Future<Output = Result<ModelAccounts, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<ModelAccounts, BrokerError>> + Send
     {
         async move {
             let mut rng = rand::thread_rng();
@@ -503,7 +467,7 @@ impl AccountManagement for CtpBroker {
     fn get_positions(
         &self,
         _limit: Option<i32>,
-    ) -> impl std::future::Future<Output = Result<Positions, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Positions, BrokerError>> + Send
     {
         async move {
             let instruments = self.get_instruments();
@@ -547,7 +511,7 @@ impl AccountManagement for CtpBroker {
 
     fn get_balance(
         &self,
-    ) -> impl std::future::Future<Output = Result<Balance, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Balance, BrokerError>> + Send {
         async move {
             let mut rng = rand::thread_rng();
             let total = rng.gen_range(500000.0..3000000.0); // 期货账户资金较大
@@ -568,7 +532,7 @@ impl AccountManagement for CtpBroker {
 
     fn get_broker_account(
         &self,
-    ) -> impl std::future::Future<Output = Result<BrokerAccount, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<BrokerAccount, BrokerError>> + Send
     {
         async move {
             Ok(BrokerAccount {
@@ -588,46 +552,23 @@ impl AccountManagement for CtpBroker {
 impl Analytics for CtpBroker {
     fn get_analytics(
         &self,
-    ) -> impl std::future::Future<Output = Result<AnalyticsData, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<AnalyticsData, BrokerError>> + Send
     {
-        async move {
-            let mut metrics = HashMap::new();
-            metrics.insert("total_models".to_string(), 4.0); // 4个模型
-            metrics.insert("total_volume_24h".to_string(), 50000000.0);
-            metrics.insert("avg_trades_per_model".to_string(), 150.0);
-
-            Ok(AnalyticsData { metrics })
-        }
+        // 交易所账户不区分模型, 模型分析由引擎按成交与权益快照计算
+        async move { Ok(AnalyticsData::default()) }
     }
 
     fn get_leaderboard(
         &self,
-    ) -> impl std::future::Future<Output = Result<Leaderboard, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Leaderboard, BrokerError>> + Send
     {
-        async move {
-            let mut rng = rand::thread_rng();
-            let models = self.get_models();
-            let mut entries = vec![];
-
-            for (rank, model) in models.iter().enumerate() {
-                entries.push(LeaderboardEntry {
-                    rank: (rank + 1) as i32,
-                    model_id: model.model_id.clone(),
-                    model_name: model.model_name.clone(),
-                    return_pct: rng.gen_range(-6.0..10.0), // 期货收益波动大
-                    sharpe_ratio: rng.gen_range(0.4..1.8),
-                    total_trades: rng.gen_range(80..400),
-                    win_rate: rng.gen_range(0.45..0.65),
-                });
-            }
-
-            Ok(Leaderboard { leaderboard: entries })
-        }
+        // 排行榜由 leaderboard 模块跨经纪商汇总计算
+        async move { Ok(Leaderboard::default()) }
     }
 
     fn get_since_inception_values(
         &self,
-    ) -> impl std::future::Future<Output = Result<SinceInceptionValues, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<SinceInceptionValues, BrokerError>> + Send
     {
         async move {
             Ok(SinceInceptionValues {
@@ -638,29 +579,18 @@ impl Analytics for CtpBroker {
 
     fn get_conversations(
         &self,
-    ) -> impl std::future::Future<Output = Result<Conversations, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Conversations, BrokerError>> + Send
     {
         async move { Ok(Conversations { conversations: vec![] }) }
     }
 
     fn get_models_list(
         &self,
-    ) -> impl std::future::Future<Output = Result<Models, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Models, BrokerError>> + Send {
         async move {
-            let models_data = self.get_models();
-            let models = models_data
-                .into_iter()
-                .map(|m| crate::brokers::ModelInfo {
-                    model_id: m.model_id,
-                    model_name: m.model_name,
-                    strategy: m.strategy,
-                    description: m.description,
-                    risk_level: m.risk_level,
-                    base_capital: m.base_capital,
-                })
-                .collect();
-
-            Ok(Models { models })
+            Ok(Models {
+                models: self.get_models(),
+            })
         }
     }
 }
@@ -3,6 +3,9 @@
 
 use std::collections::HashMap;
 use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::brokers::error::BrokerError;
 
 /// CTP错误码映射表 (全局单例)
 static ERROR_CODE_MAP: OnceLock<HashMap<i32, &'static str>> = OnceLock::new();
@@ -128,6 +131,37 @@ pub fn should_reconnect(error_code: i32) -> bool {
     is_network_error(error_code) || matches!(error_code, 80 | 81 | 82)
 }
 
+/// 将CTP错误码转换为统一的经纪商错误
+pub fn to_broker_error(error_code: i32, error_msg: Option<&str>) -> BrokerError {
+    let message = format_ctp_error(error_code, error_msg);
+
+    if is_flow_control_error(error_code) {
+        // CTP 默认流控为每秒若干笔, 等待 1 秒后重试
+        return BrokerError::RateLimited {
+            retry_after: Some(Duration::from_secs(1)),
+        };
+    }
+    if should_reconnect(error_code) || matches!(error_code, -2 | 26) {
+        return BrokerError::Network(message);
+    }
+    if is_auth_error(error_code) {
+        return BrokerError::Auth(message);
+    }
+
+    match error_code {
+        51 | 64 | 65 => BrokerError::InsufficientFunds(message),
+        70 | 74 => BrokerError::InvalidSymbol(message),
+        22 | 31 | 36 | 37 | 40..=44 | 52..=58 | 71..=73 => BrokerError::OrderRejected {
+            code: error_code.to_string(),
+            reason: message,
+        },
+        _ => BrokerError::Exchange {
+            venue_code: error_code.to_string(),
+            message,
+        },
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,4 +195,37 @@ mod tests {
         assert!(should_reconnect(80));
         assert!(!should_reconnect(8));
     }
+
+    #[test]
+    fn test_to_broker_error() {
+        assert!(matches!(
+            to_broker_error(51, None),
+            BrokerError::InsufficientFunds(_)
+        ));
+        assert!(matches!(
+            to_broker_error(64, Some("可用资金不足")),
+            BrokerError::InsufficientFunds(_)
+        ));
+        assert!(matches!(
+            to_broker_error(70, None),
+            BrokerError::InvalidSymbol(_)
+        ));
+        assert_eq!(
+            to_broker_error(-4, None).retry_after(),
+            Some(Duration::from_secs(1))
+        );
+        assert!(matches!(to_broker_error(8, None), BrokerError::Auth(_)));
+        assert!(matches!(
+            to_broker_error(100, None),
+            BrokerError::Network(_)
+        ));
+        assert!(matches!(
+            to_broker_error(44, None),
+            BrokerError::OrderRejected { code, .. } if code == "44"
+        ));
+        assert!(matches!(
+            to_broker_error(999, None),
+            BrokerError::Exchange { .. }
+        ));
+    }
 }
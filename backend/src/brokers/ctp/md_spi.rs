@@ -90,6 +90,7 @@ impl CtpMdSpi {
             open_interest: ctp_data.OpenInterest as i32,
             highest_price: ctp_data.HighestPrice,
             lowest_price: ctp_data.LowestPrice,
+            pre_settlement_price: ctp_data.PreSettlementPrice,
             update_time,
         })
     }
@@ -287,7 +288,7 @@ impl MdSpi for CtpMdSpi {
                         market_data.instrument_id, market_data.last_price, market_data.update_time
                     );
 
-                    // 发送行情数据到通道
+                    // 发送行情数据到通道 (RealCtpConnection 会转换为统一的 MarketEvent 广播)
                     if let Err(e) = self.market_data_tx.send(market_data) {
                         error!("Failed to send market data: {}", e);
                     }
@@ -6,12 +6,16 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
 
 use super::error_codes;
 use super::types::{
     CtpAccount, CtpConfig, CtpMarketData, CtpOrderRequest, CtpOrderResponse, CtpPosition,
 };
+use crate::brokers::error::BrokerError;
+use crate::brokers::stream::{
+    MarketDataStream, MarketEvent, MarketEventStream, StreamSubscription, STREAM_BUFFER,
+};
 
 #[cfg(feature = "ctp-real")]
 use ctp2rs::v1alpha1::{
@@ -72,6 +76,9 @@ pub struct RealCtpConnection {
     market_data_tx: mpsc::UnboundedSender<CtpMarketData>,
     market_data_rx: Option<mpsc::UnboundedReceiver<CtpMarketData>>,
 
+    // 统一行情事件广播 (由 on_rtn_depth_market_data 驱动)
+    market_event_tx: broadcast::Sender<MarketEvent>,
+
     // 行情SPI通道 (用于SPI回调)
     #[cfg(feature = "ctp-real")]
     md_connected_tx: Option<mpsc::Sender<bool>>,
@@ -139,6 +146,7 @@ impl RealCtpConnection {
     /// 创建新的CTP真实连接
     pub fn new(config: CtpConfig) -> Self {
         let (market_data_tx, market_data_rx) = mpsc::unbounded_channel();
+        let (market_event_tx, _) = broadcast::channel(STREAM_BUFFER);
 
         #[cfg(feature = "ctp-real")]
         {
@@ -169,6 +177,7 @@ impl RealCtpConnection {
                 td_logged_in: Arc::new(RwLock::new(false)),
                 market_data_tx: market_data_tx.clone(),
                 market_data_rx: Some(market_data_rx),
+                market_event_tx: market_event_tx.clone(),
                 md_connected_tx: Some(md_connected_tx),
                 md_connected_rx: Some(md_connected_rx),
                 md_login_tx: Some(md_login_tx),
@@ -214,6 +223,7 @@ impl RealCtpConnection {
                 td_logged_in: Arc::new(RwLock::new(false)),
                 market_data_tx,
                 market_data_rx: Some(market_data_rx),
+                market_event_tx: market_event_tx.clone(),
                 // 重连机制字段
                 md_reconnect_attempts: Arc::new(AtomicI32::new(0)),
                 td_reconnect_attempts: Arc::new(AtomicI32::new(0)),
@@ -393,6 +403,7 @@ impl RealCtpConnection {
     #[cfg(feature = "ctp-real")]
     fn start_market_data_processor(&self, mut rx: mpsc::UnboundedReceiver<CtpMarketData>) {
         let market_data = self.market_data.clone();
+        let market_event_tx = self.market_event_tx.clone();
 
         tokio::spawn(async move {
             use tracing::debug;
@@ -403,6 +414,11 @@ impl RealCtpConnection {
                     data.instrument_id, data.last_price
                 );
 
+                // 推送统一行情事件 (没有订阅者时发送失败, 忽略即可)
+                for event in data.to_market_events() {
+                    let _ = market_event_tx.send(event);
+                }
+
                 // 更新缓存
                 market_data
                     .write()
@@ -1024,6 +1040,48 @@ impl RealCtpConnection {
             .cloned()
             .ok_or_else(|| anyhow!("Market data not found for {}", instrument_id))
     }
+
+    /// 订阅统一行情事件流 (只转发本地广播, 不向CTP发送订阅请求)
+    pub fn market_events(&self, subscription: StreamSubscription) -> MarketEventStream {
+        let mut events = self.market_event_tx.subscribe();
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+
+        let task = tokio::spawn(async move {
+            use tracing::warn;
+
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        if subscription.matches(&event) && tx.send(event).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("CTP market event stream lagged, skipped {} events", skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        MarketEventStream::new(rx, vec![task])
+    }
+}
+
+impl MarketDataStream for RealCtpConnection {
+    fn subscribe_market(
+        &self,
+        subscription: StreamSubscription,
+    ) -> impl std::future::Future<Output = Result<MarketEventStream, BrokerError>> + Send {
+        async move {
+            // 先订阅本地广播, 避免错过订阅成功后的第一笔行情
+            let stream = self.market_events(subscription.clone());
+            self.subscribe_market_data(subscription.symbols)
+                .await
+                .map_err(|e| BrokerError::Other(e.to_string()))?;
+            Ok(stream)
+        }
+    }
 }
 
 // 实现Drop trait以确保资源释放
@@ -1382,6 +1440,53 @@ mod tests {
         assert!(market_data_result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_market_events_from_depth_data() {
+        use crate::brokers::stream::{DepthUpdateKind, MarketChannel};
+        use futures_util::StreamExt;
+
+        let conn = RealCtpConnection::new(create_test_config());
+        let mut stream = conn.market_events(
+            StreamSubscription::new(["IF2501"])
+                .with_channel(MarketChannel::Ticker)
+                .with_channel(MarketChannel::Depth),
+        );
+
+        let data = |instrument_id: &str| CtpMarketData {
+            instrument_id: instrument_id.to_string(),
+            last_price: 3535.0,
+            bid_price: 3534.8,
+            ask_price: 3535.2,
+            bid_volume: 10,
+            ask_volume: 15,
+            volume: 125000,
+            open_interest: 85000,
+            highest_price: 3540.0,
+            lowest_price: 3480.0,
+            pre_settlement_price: 3500.0,
+            update_time: "10:15:00".to_string(),
+        };
+        // 未订阅的合约被过滤
+        for market_data in [data("IC2501"), data("IF2501")] {
+            for event in market_data.to_market_events() {
+                conn.market_event_tx.send(event).unwrap();
+            }
+        }
+
+        let Some(MarketEvent::Ticker(ticker)) = stream.next().await else {
+            panic!("expected ticker");
+        };
+        assert_eq!(ticker.symbol, "IF2501");
+        assert!((ticker.change_24h - 1.0).abs() < 1e-9);
+        assert_eq!(ticker.open_interest, Some(85000));
+
+        let Some(MarketEvent::Depth(depth)) = stream.next().await else {
+            panic!("expected depth");
+        };
+        assert_eq!(depth.kind, DepthUpdateKind::Snapshot);
+        assert_eq!(depth.asks[0].quantity, 15.0);
+    }
+
     #[tokio::test]
     async fn test_is_connected() {
         let config = create_test_config();
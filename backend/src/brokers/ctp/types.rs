@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+use crate::brokers::stream::{DepthUpdate, DepthUpdateKind, MarketEvent};
+use crate::brokers::types::{OrderbookLevel, Ticker24h};
+
 /// CTP连接配置
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CtpConfig {
@@ -203,10 +206,57 @@ pub struct CtpMarketData {
     /// 最低价
     pub lowest_price: f64,
 
+    /// 昨结算价
+    #[serde(default)]
+    pub pre_settlement_price: f64,
+
     /// 更新时间
     pub update_time: String,
 }
 
+impl CtpMarketData {
+    /// 转换为统一行情事件: ticker + 一档深度快照
+    ///
+    /// CTP 每次推送都是完整的盘口, 没有序号, 深度以快照形式下发。
+    pub fn to_market_events(&self) -> Vec<MarketEvent> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let change_24h = if self.pre_settlement_price > 0.0 {
+            (self.last_price - self.pre_settlement_price) / self.pre_settlement_price * 100.0
+        } else {
+            0.0
+        };
+
+        vec![
+            MarketEvent::Ticker(Ticker24h {
+                symbol: self.instrument_id.clone(),
+                last_price: self.last_price,
+                change_24h,
+                high_24h: self.highest_price,
+                low_24h: self.lowest_price,
+                volume_24h: self.volume as f64,
+                open_interest: Some(self.open_interest as i64),
+                timestamp,
+            }),
+            MarketEvent::Depth(DepthUpdate {
+                symbol: self.instrument_id.clone(),
+                kind: DepthUpdateKind::Snapshot,
+                first_update_id: 0,
+                final_update_id: 0,
+                prev_update_id: None,
+                bids: vec![OrderbookLevel {
+                    price: self.bid_price,
+                    quantity: self.bid_volume as f64,
+                }],
+                asks: vec![OrderbookLevel {
+                    price: self.ask_price,
+                    quantity: self.ask_volume as f64,
+                }],
+                timestamp,
+            }),
+        ]
+    }
+}
+
 /// CTP持仓信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CtpPosition {
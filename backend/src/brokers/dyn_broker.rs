@@ -0,0 +1,231 @@
+// 对象安全的动态经纪商接口
+// Object-safe broker trait for runtime registration
+
+use futures_util::future::BoxFuture;
+
+use super::error::BrokerResult;
+use super::types::*;
+use super::{AccountManagement, Analytics, Broker, MarketData, Trading};
+
+/// 对象安全的经纪商接口
+///
+/// `Broker` 及其子 trait 使用 `impl Future` 返回值, 无法作为 trait object 使用。
+/// `DynBroker` 以 `BoxFuture` 暴露同样的方法, 任何实现了 `Broker` 的类型
+/// 都通过下方的 blanket impl 自动获得 `DynBroker`, 可以放入
+/// `Arc<dyn DynBroker>` 注册到 `BrokerRegistry`。
+///
+/// 注意: 同时导入 `DynBroker` 与静态 trait 时, 在具体类型上调用同名方法
+/// 会产生歧义, 此时使用 `MarketData::get_prices(&broker)` 形式调用。
+pub trait DynBroker: Send + Sync {
+    /// 获取经纪商 ID
+    fn broker_id(&self) -> &str;
+
+    /// 获取经纪商名称
+    fn broker_name(&self) -> &str;
+
+    // ========== 行情 ==========
+
+    /// 获取实时价格
+    fn get_prices(&self) -> BoxFuture<'_, BrokerResult<Prices>>;
+
+    /// 获取市场深度
+    fn get_orderbook<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, BrokerResult<Orderbook>>;
+
+    /// 获取K线数据
+    fn get_klines<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        limit: Option<i32>,
+    ) -> BoxFuture<'a, BrokerResult<Klines>>;
+
+    /// 获取24小时ticker
+    fn get_ticker_24h<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, BrokerResult<Ticker24h>>;
+
+    // ========== 交易 ==========
+
+    /// 下单
+    fn place_order(&self, order: OrderRequest) -> BoxFuture<'_, BrokerResult<OrderResponse>>;
+
+    /// 撤单
+    fn cancel_order<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, BrokerResult<OrderResponse>>;
+
+    /// 查询订单
+    fn get_order<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, BrokerResult<Order>>;
+
+    /// 查询所有订单
+    fn get_orders<'a>(&'a self, symbol: Option<&'a str>) -> BoxFuture<'a, BrokerResult<Orders>>;
+
+    /// 查询成交记录
+    fn get_trades(&self) -> BoxFuture<'_, BrokerResult<Trades>>;
+
+    /// 设置品种的杠杆倍数
+    fn set_leverage<'a>(
+        &'a self,
+        symbol: &'a str,
+        leverage: f64,
+    ) -> BoxFuture<'a, BrokerResult<()>>;
+
+    // ========== 账户 ==========
+
+    /// 获取账户历史数据（用于图表）
+    fn get_account_totals(
+        &self,
+        last_marker: Option<i32>,
+    ) -> BoxFuture<'_, BrokerResult<AccountTotals>>;
+
+    /// 获取模型账户摘要
+    fn get_model_accounts(&self) -> BoxFuture<'_, BrokerResult<ModelAccounts>>;
+
+    /// 获取持仓数据
+    fn get_positions(&self, limit: Option<i32>) -> BoxFuture<'_, BrokerResult<Positions>>;
+
+    /// 获取账户余额
+    fn get_balance(&self) -> BoxFuture<'_, BrokerResult<Balance>>;
+
+    /// 获取经纪商总账户信息
+    fn get_broker_account(&self) -> BoxFuture<'_, BrokerResult<BrokerAccount>>;
+
+    // ========== 分析 ==========
+
+    /// 获取分析数据
+    fn get_analytics(&self) -> BoxFuture<'_, BrokerResult<AnalyticsData>>;
+
+    /// 获取排行榜数据
+    fn get_leaderboard(&self) -> BoxFuture<'_, BrokerResult<Leaderboard>>;
+
+    /// 获取初始值数据
+    fn get_since_inception_values(&self) -> BoxFuture<'_, BrokerResult<SinceInceptionValues>>;
+
+    /// 获取 AI 对话记录
+    fn get_conversations(&self) -> BoxFuture<'_, BrokerResult<Conversations>>;
+
+    /// 获取模型列表
+    fn get_models_list(&self) -> BoxFuture<'_, BrokerResult<Models>>;
+}
+
+impl<T: Broker> DynBroker for T {
+    fn broker_id(&self) -> &str {
+        Broker::broker_id(self)
+    }
+
+    fn broker_name(&self) -> &str {
+        Broker::broker_name(self)
+    }
+
+    fn get_prices(&self) -> BoxFuture<'_, BrokerResult<Prices>> {
+        Box::pin(MarketData::get_prices(self))
+    }
+
+    fn get_orderbook<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, BrokerResult<Orderbook>> {
+        Box::pin(MarketData::get_orderbook(self, symbol))
+    }
+
+    fn get_klines<'a>(
+        &'a self,
+        symbol: &'a str,
+        interval: &'a str,
+        limit: Option<i32>,
+    ) -> BoxFuture<'a, BrokerResult<Klines>> {
+        Box::pin(MarketData::get_klines(self, symbol, interval, limit))
+    }
+
+    fn get_ticker_24h<'a>(&'a self, symbol: &'a str) -> BoxFuture<'a, BrokerResult<Ticker24h>> {
+        Box::pin(MarketData::get_ticker_24h(self, symbol))
+    }
+
+    fn place_order(&self, order: OrderRequest) -> BoxFuture<'_, BrokerResult<OrderResponse>> {
+        Box::pin(Trading::place_order(self, order))
+    }
+
+    fn cancel_order<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, BrokerResult<OrderResponse>> {
+        Box::pin(Trading::cancel_order(self, order_id))
+    }
+
+    fn get_order<'a>(&'a self, order_id: &'a str) -> BoxFuture<'a, BrokerResult<Order>> {
+        Box::pin(Trading::get_order(self, order_id))
+    }
+
+    fn get_orders<'a>(&'a self, symbol: Option<&'a str>) -> BoxFuture<'a, BrokerResult<Orders>> {
+        Box::pin(Trading::get_orders(self, symbol))
+    }
+
+    fn get_trades(&self) -> BoxFuture<'_, BrokerResult<Trades>> {
+        Box::pin(Trading::get_trades(self))
+    }
+
+    fn set_leverage<'a>(
+        &'a self,
+        symbol: &'a str,
+        leverage: f64,
+    ) -> BoxFuture<'a, BrokerResult<()>> {
+        Box::pin(Trading::set_leverage(self, symbol, leverage))
+    }
+
+    fn get_account_totals(
+        &self,
+        last_marker: Option<i32>,
+    ) -> BoxFuture<'_, BrokerResult<AccountTotals>> {
+        Box::pin(AccountManagement::get_account_totals(self, last_marker))
+    }
+
+    fn get_model_accounts(&self) -> BoxFuture<'_, BrokerResult<ModelAccounts>> {
+        Box::pin(AccountManagement::get_model_accounts(self))
+    }
+
+    fn get_positions(&self, limit: Option<i32>) -> BoxFuture<'_, BrokerResult<Positions>> {
+        Box::pin(AccountManagement::get_positions(self, limit))
+    }
+
+    fn get_balance(&self) -> BoxFuture<'_, BrokerResult<Balance>> {
+        Box::pin(AccountManagement::get_balance(self))
+    }
+
+    fn get_broker_account(&self) -> BoxFuture<'_, BrokerResult<BrokerAccount>> {
+        Box::pin(AccountManagement::get_broker_account(self))
+    }
+
+    fn get_analytics(&self) -> BoxFuture<'_, BrokerResult<AnalyticsData>> {
+        Box::pin(Analytics::get_analytics(self))
+    }
+
+    fn get_leaderboard(&self) -> BoxFuture<'_, BrokerResult<Leaderboard>> {
+        Box::pin(Analytics::get_leaderboard(self))
+    }
+
+    fn get_since_inception_values(&self) -> BoxFuture<'_, BrokerResult<SinceInceptionValues>> {
+        Box::pin(Analytics::get_since_inception_values(self))
+    }
+
+    fn get_conversations(&self) -> BoxFuture<'_, BrokerResult<Conversations>> {
+        Box::pin(Analytics::get_conversations(self))
+    }
+
+    fn get_models_list(&self) -> BoxFuture<'_, BrokerResult<Models>> {
+        Box::pin(Analytics::get_models_list(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::{BrokerRegistry, MockBroker};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_registry_dispatches_through_trait_object() {
+        let mut registry = BrokerRegistry::new();
+        registry.register(MockBroker::new());
+
+        let broker: Arc<dyn DynBroker> = registry.get("mock").unwrap();
+        assert_eq!(broker.broker_name(), "Mock Broker");
+
+        let ticker = broker.get_ticker_24h("BTCUSDT").await.unwrap();
+        assert_eq!(ticker.symbol, "BTCUSDT");
+
+        // trait object 可以跨任务使用
+        let handle =
+            tokio::spawn(async move { broker.get_balance().await.map(|b| b.currency).ok() });
+        assert!(handle.await.unwrap().is_some());
+    }
+}
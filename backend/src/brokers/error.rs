@@ -0,0 +1,118 @@
+// 经纪商统一错误类型
+// Typed broker errors shared by every venue
+
+use std::time::Duration;
+use thiserror::Error;
+
+/// 经纪商接口调用结果
+pub type BrokerResult<T> = Result<T, BrokerError>;
+
+/// 经纪商错误
+///
+/// 各交易所的原始错误 (CTP 错误码 / HTTP 状态码 / 交易所业务码) 统一映射到这里,
+/// 调用方可以据此决定重试、降级或直接拒绝。
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum BrokerError {
+    /// 触发限频, `retry_after` 为交易所建议的等待时间
+    #[error("Rate limited (retry after {retry_after:?})")]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// 资金或保证金不足
+    #[error("Insufficient funds: {0}")]
+    InsufficientFunds(String),
+
+    /// 交易对/合约不存在
+    #[error("Invalid symbol: {0}")]
+    InvalidSymbol(String),
+
+    /// 订单被交易所拒绝
+    #[error("Order rejected [{code}]: {reason}")]
+    OrderRejected { code: String, reason: String },
+
+    /// 网络/连接错误
+    #[error("Network error: {0}")]
+    Network(String),
+
+    /// 认证失败 (API Key、签名、密码等)
+    #[error("Authentication failed: {0}")]
+    Auth(String),
+
+    /// 经纪商不支持该操作
+    #[error("Not supported: {0}")]
+    NotSupported(String),
+
+    /// 其他交易所业务错误
+    #[error("Exchange error [{venue_code}]: {message}")]
+    Exchange { venue_code: String, message: String },
+
+    /// 本地错误 (参数、解析等)
+    #[error("Broker error: {0}")]
+    Other(String),
+}
+
+impl BrokerError {
+    /// 是否可以稍后重试
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RateLimited { .. } | Self::Network(_))
+    }
+
+    /// 建议的重试等待时间
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// 根据 HTTP 状态码和响应体映射错误 (交易所业务码无法识别时使用)
+    pub fn from_http_status(status: u16, retry_after: Option<Duration>, body: &str) -> Self {
+        match status {
+            // Binance 在持续超频后返回 418 (IP 被封禁)
+            429 | 418 => Self::RateLimited { retry_after },
+            401 | 403 => Self::Auth(body.to_string()),
+            404 => Self::NotSupported(body.to_string()),
+            500..=599 => Self::Network(format!("HTTP {}: {}", status, body)),
+            _ => Self::Exchange {
+                venue_code: status.to_string(),
+                message: body.to_string(),
+            },
+        }
+    }
+}
+
+impl From<reqwest::Error> for BrokerError {
+    fn from(e: reqwest::Error) -> Self {
+        match e.status() {
+            Some(status) => Self::from_http_status(status.as_u16(), None, &e.to_string()),
+            None => Self::Network(e.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for BrokerError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Other(format!("decode error: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_status_mapping() {
+        let err = BrokerError::from_http_status(429, Some(Duration::from_secs(3)), "");
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(3)));
+        assert!(err.is_retryable());
+
+        assert!(matches!(
+            BrokerError::from_http_status(401, None, "bad key"),
+            BrokerError::Auth(_)
+        ));
+        assert!(BrokerError::from_http_status(503, None, "").is_retryable());
+        assert!(matches!(
+            BrokerError::from_http_status(400, None, "bad"),
+            BrokerError::Exchange { .. }
+        ));
+    }
+}
@@ -0,0 +1,382 @@
+use anyhow::{anyhow, bail, Result};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use super::binance::BinanceConfig;
+use super::ctp::CtpConfig;
+use super::okex::OkexConfig;
+use super::paper::PaperConfig;
+use super::{BinanceBroker, CtpBroker, DynBroker, DynMarketDataStream, OkexBroker, PaperBroker};
+use crate::config::{AgentsConfig, BrokerConfig, BrokersConfig};
+use crate::db::PersistentBroker;
+use crate::history::{CachedBroker, MarketDataStore};
+
+/// 经纪商实现类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrokerKind {
+    Binance,
+    Okex,
+    Ctp,
+    /// 模拟盘, 行情取自 `config.feed` 指定的经纪商
+    Paper,
+}
+
+impl BrokerKind {
+    /// 由 `kind` (为空时取 `id`) 推断实现类型, 纯展示用的条目返回 None
+    pub fn of(config: &BrokerConfig) -> Option<Self> {
+        match config.kind.as_deref().unwrap_or(&config.id) {
+            "binance" => Some(Self::Binance),
+            "okex" | "okx" => Some(Self::Okex),
+            "ctp" => Some(Self::Ctp),
+            "paper" => Some(Self::Paper),
+            _ => None,
+        }
+    }
+}
+
+/// 启动时创建的经纪商
+#[derive(Default)]
+pub struct BuiltBrokers {
+    /// 全部经纪商 (含模拟盘)
+    pub brokers: Vec<Arc<dyn DynBroker>>,
+    /// 模拟盘, 需定期 [`PaperBroker::sync`] 撮合挂单
+    pub paper: Vec<Arc<PaperBroker>>,
+    /// 提供推送行情的经纪商 (经纪商 ID, 行情源), 模拟盘使用其行情源的推送
+    pub streams: Vec<(String, Arc<dyn DynMarketDataStream>)>,
+}
+
+/// 交易所经纪商及其推送行情源
+pub type LiveBroker = (Arc<dyn DynBroker>, Option<Arc<dyn DynMarketDataStream>>);
+
+/// 经纪商工厂: 按 brokers.yaml 创建经纪商, 注入 agents.yaml 中绑定的模型
+pub struct BrokerFactory<'a> {
+    agents: &'a AgentsConfig,
+    kline_cache: Option<Arc<dyn MarketDataStore>>,
+    pool: Option<Arc<PgPool>>,
+}
+
+impl<'a> BrokerFactory<'a> {
+    pub fn new(agents: &'a AgentsConfig) -> Self {
+        Self {
+            agents,
+            kline_cache: None,
+            pool: None,
+        }
+    }
+
+    /// 连接真实交易所的经纪商以 [`CachedBroker`] 包装, K线优先读本地缓存
+    /// (模拟模式的随机行情不写入缓存)
+    pub fn with_kline_cache(mut self, store: Arc<dyn MarketDataStore>) -> Self {
+        self.kline_cache = Some(store);
+        self
+    }
+
+    /// 以 [`PersistentBroker`] 包装全部经纪商, 订单、成交与持仓写入数据库
+    pub fn with_persistence(mut self, pool: Arc<PgPool>) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// 创建全部已启用的经纪商
+    ///
+    /// 先创建交易所, 再创建以其为行情源的模拟盘; 创建失败的经纪商会被跳过。
+    pub fn build(&self, config: &BrokersConfig) -> BuiltBrokers {
+        let mut built = BuiltBrokers::default();
+        let enabled = config.get_enabled_brokers();
+        let is_paper = |b: &&BrokerConfig| BrokerKind::of(b) == Some(BrokerKind::Paper);
+
+        for broker in enabled.iter().filter(|b| !is_paper(b)) {
+            match self.build_broker(broker) {
+                Ok(Some((instance, stream))) => {
+                    if let Some(stream) = stream {
+                        built.streams.push((broker.id.clone(), stream));
+                    }
+                    built.brokers.push(instance);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Skipping broker {}: {:#}", broker.id, e),
+            }
+        }
+
+        for broker in enabled.iter().filter(is_paper) {
+            match self.build_paper(broker, &built.brokers) {
+                Ok(paper) => {
+                    let stream = built
+                        .streams
+                        .iter()
+                        .find(|(id, _)| Some(id.as_str()) == feed_id(broker))
+                        .map(|(_, stream)| stream.clone());
+                    if let Some(stream) = stream {
+                        built.streams.push((broker.id.clone(), stream));
+                    }
+                    built.brokers.push(self.persistent(paper.clone()));
+                    built.paper.push(paper);
+                }
+                Err(e) => warn!("Skipping paper broker {}: {:#}", broker.id, e),
+            }
+        }
+
+        info!(
+            "Built {} brokers from config: {:?}",
+            built.brokers.len(),
+            built
+                .brokers
+                .iter()
+                .map(|b| b.broker_id())
+                .collect::<Vec<_>>()
+        );
+        built
+    }
+
+    /// 创建单个交易所经纪商及其推送行情源 (模拟盘由 [`BrokerFactory::build`] 创建)
+    ///
+    /// 连接参数取自 `config`, 未配置的凭证从环境变量读取。模拟模式与 CTP 没有推送行情。
+    /// 未配置数据库时拒绝多个 Agent 共用的实盘经纪商: 子账户账本无法在重启后重建。
+    pub fn build_broker(&self, config: &BrokerConfig) -> Result<Option<LiveBroker>> {
+        let (id, name) = (config.id.clone(), config.name.clone());
+        let models = self.agents.models_for_broker(&config.id);
+
+        let (broker, simulated, stream): (
+            Arc<dyn DynBroker>,
+            bool,
+            Option<Arc<dyn DynMarketDataStream>>,
+        ) = match BrokerKind::of(config) {
+            Some(BrokerKind::Binance) => {
+                let mut base = BinanceConfig::default();
+                set_from_env(&mut base.api_key, "BINANCE_API_KEY");
+                set_from_env(&mut base.api_secret, "BINANCE_API_SECRET");
+                let settings: BinanceConfig = merged(base, config.config.as_ref())?;
+                let simulated = settings.mock_mode;
+                let broker = Arc::new(BinanceBroker::new(id, name, settings).with_models(models));
+                let stream = (!simulated).then(|| broker.clone() as Arc<dyn DynMarketDataStream>);
+                (broker, simulated, stream)
+            }
+            Some(BrokerKind::Okex) => {
+                let mut base = OkexConfig::default();
+                set_from_env(&mut base.api_key, "OKX_API_KEY");
+                set_from_env(&mut base.api_secret, "OKX_API_SECRET");
+                set_from_env(&mut base.passphrase, "OKX_PASSPHRASE");
+                let settings: OkexConfig = merged(base, config.config.as_ref())?;
+                let simulated = settings.mock_mode;
+                let broker = Arc::new(OkexBroker::new(id, name, settings).with_models(models));
+                let stream = (!simulated).then(|| broker.clone() as Arc<dyn DynMarketDataStream>);
+                (broker, simulated, stream)
+            }
+            Some(BrokerKind::Ctp) => {
+                let mut base = CtpConfig::default();
+                set_from_env(&mut base.investor_id, "CTP_INVESTOR_ID");
+                set_from_env(&mut base.password, "CTP_PASSWORD");
+                let settings: CtpConfig = merged(base, config.config.as_ref())?;
+                let simulated = settings.mock_mode;
+                let broker = CtpBroker::new(id, name, settings).with_models(models);
+                (Arc::new(broker), simulated, None)
+            }
+            Some(BrokerKind::Paper) => bail!("paper broker {} requires a feed", config.id),
+            None => return Ok(None),
+        };
+
+        let sharing = self
+            .agents
+            .agents
+            .iter()
+            .filter(|a| a.enabled && a.broker == config.id)
+            .count();
+        if self.pool.is_none() && !simulated && sharing > 1 {
+            bail!(
+                "broker {} is shared by {} agents; sub-account ledgers require DATABASE_URL",
+                config.id,
+                sharing
+            );
+        }
+
+        let broker: Arc<dyn DynBroker> = match &self.kline_cache {
+            Some(store) if !simulated => {
+                Arc::new(CachedBroker::new(broker, store.clone(), config.id.clone()))
+            }
+            _ => broker,
+        };
+        Ok(Some((self.persistent(broker), stream)))
+    }
+
+    /// 持久化包装; 经纪商只绑定一个 Agent 时记录其模型 ID
+    fn persistent(&self, broker: Arc<dyn DynBroker>) -> Arc<dyn DynBroker> {
+        let Some(pool) = &self.pool else {
+            return broker;
+        };
+        let owners: Vec<_> = self
+            .agents
+            .agents
+            .iter()
+            .filter(|a| a.enabled && a.broker == broker.broker_id())
+            .collect();
+
+        let persistent = PersistentBroker::new(broker, pool.clone());
+        Arc::new(match owners.as_slice() {
+            [owner] => persistent.with_model(owner.id.clone()),
+            _ => persistent,
+        })
+    }
+
+    /// 创建模拟盘, 行情源为已创建的 `config.feed` 经纪商
+    fn build_paper(
+        &self,
+        config: &BrokerConfig,
+        feeds: &[Arc<dyn DynBroker>],
+    ) -> Result<Arc<PaperBroker>> {
+        let feed_id = feed_id(config).ok_or_else(|| anyhow!("missing config.feed"))?;
+        let feed = feeds
+            .iter()
+            .find(|b| b.broker_id() == feed_id)
+            .cloned()
+            .ok_or_else(|| anyhow!("feed broker {} is not enabled", feed_id))?;
+        let settings = merged(PaperConfig::default(), config.config.as_ref())?;
+        let models = self.agents.models_for_broker(&config.id);
+
+        Ok(Arc::new(
+            PaperBroker::new(config.id.clone(), config.name.clone(), feed, settings)
+                .with_models(models),
+        ))
+    }
+}
+
+/// 模拟盘的行情源经纪商 ID (`config.feed`)
+fn feed_id(config: &BrokerConfig) -> Option<&str> {
+    config
+        .config
+        .as_ref()
+        .and_then(|c| c.get("feed"))
+        .and_then(|f| f.as_str())
+}
+
+fn set_from_env(field: &mut String, var: &str) {
+    if let Ok(value) = std::env::var(var) {
+        if !value.is_empty() {
+            *field = value;
+        }
+    }
+}
+
+/// 以 `overrides` 中的字段覆盖默认配置
+fn merged<T: Serialize + DeserializeOwned>(
+    base: T,
+    overrides: Option<&serde_json::Value>,
+) -> Result<T> {
+    let mut value = serde_json::to_value(base)?;
+    if let (Some(target), Some(serde_json::Value::Object(fields))) =
+        (value.as_object_mut(), overrides)
+    {
+        for (key, field) in fields {
+            target.insert(key.clone(), field.clone());
+        }
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::Kline;
+
+    const BROKERS: &str = r#"
+brokers:
+  - { id: crypto, name: Crypto, name_en: Crypto, description: "", icon: "", color: "", enabled: true, status: "", route: /crypto, api_endpoint: "", features: [] }
+  - { id: binance, name: Binance, name_en: Binance, description: "", icon: "", color: "", enabled: true, status: "", route: /binance, api_endpoint: "", features: [], config: { recv_window: 3000 } }
+  - { id: ctp, name: CTP, name_en: CTP, description: "", icon: "", color: "", enabled: false, status: "", route: /ctp, api_endpoint: "", features: [] }
+  - { id: paper, kind: paper, name: Paper, name_en: Paper, description: "", icon: "", color: "", enabled: true, status: "", route: /paper, api_endpoint: "", features: [], config: { feed: binance, initial_balance: 5000 } }
+settings:
+  refresh_interval: 5
+  auto_connect: false
+  websocket: { reconnect_interval: 3, max_reconnect_attempts: 5 }
+  theme: { default: dark, available: [dark] }
+"#;
+
+    #[tokio::test]
+    async fn test_build_brokers_from_config() {
+        let config: BrokersConfig = serde_yaml::from_str(BROKERS).unwrap();
+        let agents = AgentsConfig::from_yaml_str(
+            r#"
+agents:
+  - { id: paper_trend, name: Paper Trend, llm: { provider: scripted, model: offline }, broker: paper, symbols: [BTCUSDT], initial_capital: 5000 }
+"#,
+        )
+        .unwrap();
+        let built = BrokerFactory::new(&agents).build(&config);
+
+        let ids: Vec<&str> = built.brokers.iter().map(|b| b.broker_id()).collect();
+        assert_eq!(ids, ["binance", "paper"]);
+        assert_eq!(built.paper.len(), 1);
+        // 模拟模式没有推送行情
+        assert!(built.streams.is_empty());
+
+        let balance = built.brokers[1].get_balance().await.unwrap();
+        assert_eq!(balance.total_balance, 5000.0);
+
+        // 模拟盘返回绑定到它的模型
+        let models = built.brokers[1].get_models_list().await.unwrap().models;
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].model_id, "paper_trend");
+    }
+
+    #[tokio::test]
+    async fn test_live_brokers_read_klines_from_cache() {
+        let root = std::env::temp_dir().join(format!("nof0-factory-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let store = Arc::new(crate::history::CsvMarketDataStore::new(&root));
+
+        // 当前分钟前后的已收盘K线, 命中缓存时不会访问 (不可达的) 交易所
+        let now = chrono::Utc::now().timestamp();
+        let end = now - now % 60;
+        let klines: Vec<Kline> = (-4..=1)
+            .map(|i| Kline {
+                timestamp: end + i * 60,
+                open: 100.0,
+                high: 101.0,
+                low: 99.0,
+                close: 100.5,
+                volume: 1.0,
+                open_interest: None,
+            })
+            .collect();
+        store
+            .upsert_klines("binance", "BTCUSDT", "1m", &klines)
+            .await
+            .unwrap();
+
+        let mut config: BrokersConfig = serde_yaml::from_str(BROKERS).unwrap();
+        config.brokers[1].config = Some(serde_json::json!({
+            "mock_mode": false,
+            "base_url": "http://127.0.0.1:1",
+        }));
+        let agents = AgentsConfig::default();
+        let built = BrokerFactory::new(&agents)
+            .with_kline_cache(store)
+            .build(&config);
+
+        let cached = built.brokers[0]
+            .get_klines("BTCUSDT", "1m", Some(3))
+            .await
+            .unwrap();
+        assert_eq!(cached.klines.len(), 3);
+
+        // 模拟盘使用行情源的推送行情
+        let streams: Vec<&str> = built.streams.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(streams, ["binance", "paper"]);
+
+        // 未配置数据库时, 多个 Agent 共用的实盘经纪商不会创建
+        let sharing = AgentsConfig::from_yaml_str(
+            r#"
+agents:
+  - { id: a, name: A, llm: { provider: scripted, model: offline }, broker: binance, symbols: [BTCUSDT], initial_capital: 5000 }
+  - { id: b, name: B, llm: { provider: scripted, model: offline }, broker: binance, symbols: [ETHUSDT], initial_capital: 5000 }
+"#,
+        )
+        .unwrap();
+        let built = BrokerFactory::new(&sharing).build(&config);
+        assert!(built.brokers.iter().all(|b| b.broker_id() != "binance"));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
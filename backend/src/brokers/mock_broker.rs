@@ -32,7 +32,7 @@ impl Broker for MockBroker {
 }
 
 impl MarketData for MockBroker {
-    fn get_prices(&self) -> impl std::future::Future<Output = Result<Prices, Box<dyn std::error::Error>>> + Send {
+    fn get_prices(&self) -> impl std::future::Future<Output = Result<Prices, BrokerError>> + Send {
         async move {
             let mut prices = HashMap::new();
             prices.insert("BTC".to_string(), 50000.0);
@@ -41,7 +41,7 @@ impl MarketData for MockBroker {
         }
     }
 
-    fn get_orderbook(&self, symbol: &str) -> impl std::future::Future<Output = Result<Orderbook, Box<dyn std::error::Error>>> + Send {
+    fn get_orderbook(&self, symbol: &str) -> impl std::future::Future<Output = Result<Orderbook, BrokerError>> + Send {
         let symbol = symbol.to_string();
         async move {
             let mut rng = rand::thread_rng();
@@ -52,7 +52,7 @@ impl MarketData for MockBroker {
         }
     }
 
-    fn get_klines(&self, symbol: &str, interval: &str, limit: Option<i32>) -> impl std::future::Future<Output = Result<Klines, Box<dyn std::error::Error>>> + Send {
+    fn get_klines(&self, symbol: &str, interval: &str, limit: Option<i32>) -> impl std::future::Future<Output = Result<Klines, BrokerError>> + Send {
         let (symbol, interval) = (symbol.to_string(), interval.to_string());
         async move {
             let mut rng = rand::thread_rng();
@@ -65,7 +65,7 @@ impl MarketData for MockBroker {
         }
     }
 
-    fn get_ticker_24h(&self, symbol: &str) -> impl std::future::Future<Output = Result<Ticker24h, Box<dyn std::error::Error>>> + Send {
+    fn get_ticker_24h(&self, symbol: &str) -> impl std::future::Future<Output = Result<Ticker24h, BrokerError>> + Send {
         let symbol = symbol.to_string();
         async move {
             let mut rng = rand::thread_rng();
@@ -75,69 +75,73 @@ impl MarketData for MockBroker {
 }
 
 impl Trading for MockBroker {
-    fn place_order(&self, _order: OrderRequest) -> impl std::future::Future<Output = Result<OrderResponse, Box<dyn std::error::Error>>> + Send {
+    fn place_order(&self, _order: OrderRequest) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send {
         async move { Ok(OrderResponse { order_id: format!("ORDER_{}", chrono::Utc::now().timestamp_millis()), status: OrderStatus::Accepted, timestamp: chrono::Utc::now().timestamp() }) }
     }
 
-    fn cancel_order(&self, order_id: &str) -> impl std::future::Future<Output = Result<OrderResponse, Box<dyn std::error::Error>>> + Send {
+    fn cancel_order(&self, order_id: &str) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send {
         let order_id = order_id.to_string();
         async move { Ok(OrderResponse { order_id, status: OrderStatus::Cancelled, timestamp: chrono::Utc::now().timestamp() }) }
     }
 
-    fn get_order(&self, order_id: &str) -> impl std::future::Future<Output = Result<Order, Box<dyn std::error::Error>>> + Send {
+    fn get_order(&self, order_id: &str) -> impl std::future::Future<Output = Result<Order, BrokerError>> + Send {
         let order_id = order_id.to_string();
         async move { Ok(Order { order_id, symbol: "BTCUSDT".to_string(), side: OrderSide::Buy, order_type: OrderType::Limit, quantity: 0.1, filled_quantity: 0.1, price: Some(50000.0), avg_price: Some(50000.0), status: OrderStatus::Filled, created_at: chrono::Utc::now().timestamp(), updated_at: chrono::Utc::now().timestamp() }) }
     }
 
-    fn get_orders(&self, _symbol: Option<&str>) -> impl std::future::Future<Output = Result<Orders, Box<dyn std::error::Error>>> + Send {
+    fn get_orders(&self, _symbol: Option<&str>) -> impl std::future::Future<Output = Result<Orders, BrokerError>> + Send {
         async move { Ok(Orders { orders: vec![] }) }
     }
 
-    fn get_trades(&self) -> impl std::future::Future<Output = Result<Trades, Box<dyn std::error::Error>>> + Send {
+    fn get_trades(&self) -> impl std::future::Future<Output = Result<Trades, BrokerError>> + Send {
         async move { Ok(Trades { trades: vec![] }) }
     }
+
+    fn set_leverage(&self, _symbol: &str, _leverage: f64) -> impl std::future::Future<Output = Result<(), BrokerError>> + Send {
+        async move { Ok(()) }
+    }
 }
 
 impl AccountManagement for MockBroker {
-    fn get_account_totals(&self, _: Option<i32>) -> impl std::future::Future<Output = Result<AccountTotals, Box<dyn std::error::Error>>> + Send {
+    fn get_account_totals(&self, _: Option<i32>) -> impl std::future::Future<Output = Result<AccountTotals, BrokerError>> + Send {
         async move { Ok(AccountTotals { account_totals: vec![] }) }
     }
 
-    fn get_model_accounts(&self) -> impl std::future::Future<Output = Result<ModelAccounts, Box<dyn std::error::Error>>> + Send {
+    fn get_model_accounts(&self) -> impl std::future::Future<Output = Result<ModelAccounts, BrokerError>> + Send {
         async move { Ok(ModelAccounts { accounts: vec![] }) }
     }
 
-    fn get_positions(&self, _: Option<i32>) -> impl std::future::Future<Output = Result<Positions, Box<dyn std::error::Error>>> + Send {
+    fn get_positions(&self, _: Option<i32>) -> impl std::future::Future<Output = Result<Positions, BrokerError>> + Send {
         async move { Ok(Positions { positions: HashMap::new() }) }
     }
 
-    fn get_balance(&self) -> impl std::future::Future<Output = Result<Balance, Box<dyn std::error::Error>>> + Send {
+    fn get_balance(&self) -> impl std::future::Future<Output = Result<Balance, BrokerError>> + Send {
         async move { Ok(Balance { total_balance: 105000.0, available: 50000.0, margin_used: None, frozen_margin: None, currency: "USDT".to_string(), timestamp: chrono::Utc::now().timestamp() }) }
     }
 
-    fn get_broker_account(&self) -> impl std::future::Future<Output = Result<BrokerAccount, Box<dyn std::error::Error>>> + Send {
+    fn get_broker_account(&self) -> impl std::future::Future<Output = Result<BrokerAccount, BrokerError>> + Send {
         async move { Ok(BrokerAccount { broker_id: "mock".to_string(), broker_name: "Mock".to_string(), broker_type: "mock".to_string(), protocol: None, timestamp: chrono::Utc::now().timestamp() }) }
     }
 }
 
 impl Analytics for MockBroker {
-    fn get_analytics(&self) -> impl std::future::Future<Output = Result<AnalyticsData, Box<dyn std::error::Error>>> + Send {
-        async move { Ok(AnalyticsData { metrics: HashMap::new() }) }
+    fn get_analytics(&self) -> impl std::future::Future<Output = Result<AnalyticsData, BrokerError>> + Send {
+        async move { Ok(AnalyticsData::default()) }
     }
 
-    fn get_leaderboard(&self) -> impl std::future::Future<Output = Result<Leaderboard, Box<dyn std::error::Error>>> + Send {
+    fn get_leaderboard(&self) -> impl std::future::Future<Output = Result<Leaderboard, BrokerError>> + Send {
         async move { Ok(Leaderboard { leaderboard: vec![] }) }
     }
 
-    fn get_since_inception_values(&self) -> impl std::future::Future<Output = Result<SinceInceptionValues, Box<dyn std::error::Error>>> + Send {
+    fn get_since_inception_values(&self) -> impl std::future::Future<Output = Result<SinceInceptionValues, BrokerError>> + Send {
         async move { Ok(SinceInceptionValues { since_inception: vec![] }) }
     }
 
-    fn get_conversations(&self) -> impl std::future::Future<Output = Result<Conversations, Box<dyn std::error::Error>>> + Send {
+    fn get_conversations(&self) -> impl std::future::Future<Output = Result<Conversations, BrokerError>> + Send {
         async move { Ok(Conversations { conversations: vec![] }) }
     }
 
-    fn get_models_list(&self) -> impl std::future::Future<Output = Result<Models, Box<dyn std::error::Error>>> + Send {
+    fn get_models_list(&self) -> impl std::future::Future<Output = Result<Models, BrokerError>> + Send {
         async move { Ok(Models { models: vec![] }) }
     }
 }
@@ -2,8 +2,19 @@ use crate::brokers::*;
 use rand::Rng;
 use std::collections::HashMap;
 
+use super::client::OkexRestClient;
+use super::stream::OkexMarketStream;
 use super::types::*;
 
+/// OKEX 运行模式
+pub enum OkexMode {
+    /// 模拟模式: 随机游走生成行情与账户数据 (`mock_mode: true`)
+    Simulated,
+
+    /// 真实模式: 通过 v5 签名 REST API 访问 OKX (实盘或模拟盘)
+    Rest(Box<OkexRestClient>),
+}
+
 /// OKEX 经纪商实现
 /// 支持现货、合约、期权等多种交易产品
 #[allow(dead_code)]
@@ -11,12 +22,66 @@ pub struct OkexBroker {
     id: String,
     name: String,
     config: OkexConfig,
+    mode: OkexMode,
+    models: Vec<ModelInfo>,
 }
 
 #[allow(dead_code)]
 impl OkexBroker {
+    /// 创建经纪商实例, 按 `config.mock_mode` 选择模拟模式或 REST 模式
     pub fn new(id: String, name: String, config: OkexConfig) -> Self {
-        Self { id, name, config }
+        let mode = if config.mock_mode {
+            OkexMode::Simulated
+        } else {
+            OkexMode::Rest(Box::new(OkexRestClient::new(&config)))
+        };
+
+        Self {
+            id,
+            name,
+            config,
+            mode,
+            models: Vec::new(),
+        }
+    }
+
+    /// 绑定到该经纪商的 AI 模型 (启动时由 agents.yaml 注入)
+    pub fn with_models(mut self, models: Vec<ModelInfo>) -> Self {
+        self.models = models;
+        self
+    }
+
+    /// 是否为模拟模式
+    pub fn is_simulated(&self) -> bool {
+        matches!(self.mode, OkexMode::Simulated)
+    }
+
+    /// REST 客户端 (模拟模式下为 None)
+    pub fn rest_client(&self) -> Option<&OkexRestClient> {
+        match &self.mode {
+            OkexMode::Rest(client) => Some(client),
+            OkexMode::Simulated => None,
+        }
+    }
+
+    /// 改单 (OKX 特有, 不在 Trading trait 中)
+    pub async fn amend_order(
+        &self,
+        order_id: &str,
+        new_quantity: Option<f64>,
+        new_price: Option<f64>,
+    ) -> Result<OrderResponse, BrokerError> {
+        match self.rest_client() {
+            Some(rest) => rest
+                .amend_order(order_id, new_quantity, new_price)
+                .await
+                .map_err(Into::into),
+            None => Ok(OrderResponse {
+                order_id: order_id.to_string(),
+                status: OrderStatus::Accepted,
+                timestamp: chrono::Utc::now().timestamp(),
+            }),
+        }
     }
 
     /// 获取OKEX支持的加密货币列表
@@ -52,48 +117,12 @@ impl OkexBroker {
         }
     }
 
-    /// 获取OKEX的AI模型配置
+    /// 获取绑定到该经纪商的 AI 模型
     fn get_models(&self) -> Vec<ModelInfo> {
-        vec![
-            ModelInfo {
-                model_id: "okex_futures_arbitrage".to_string(),
-                model_name: "期现套利AI".to_string(),
-                strategy: "Futures-Spot Arbitrage".to_string(),
-                description: "利用现货和期货价差进行套利".to_string(),
-                risk_level: "LOW".to_string(),
-                base_capital: 120000.0,
-            },
-            ModelInfo {
-                model_id: "okex_swing_trader".to_string(),
-                model_name: "波段交易AI".to_string(),
-                strategy: "Swing Trading".to_string(),
-                description: "捕捉中期波段交易机会".to_string(),
-                risk_level: "MEDIUM".to_string(),
-                base_capital: 90000.0,
-            },
-            ModelInfo {
-                model_id: "okex_options_strategy".to_string(),
-                model_name: "期权策略AI".to_string(),
-                strategy: "Options Strategy".to_string(),
-                description: "利用期权进行对冲和收益增强".to_string(),
-                risk_level: "MEDIUM".to_string(),
-                base_capital: 150000.0,
-            },
-        ]
+        self.models.clone()
     }
 }
 
-#[derive(Clone)]
-#[allow(dead_code)]
-struct ModelInfo {
-    model_id: String,
-    model_name: String,
-    strategy: String,
-    description: String,
-    risk_level: String,
-    base_capital: f64,
-}
-
 // ============================================================================
 // Broker Trait Implementation
 // ============================================================================
@@ -113,8 +142,18 @@ impl Broker for OkexBroker {
 impl MarketData for OkexBroker {
     fn get_prices(
         &self,
-    ) -> impl std::future::Future<Output = Result<Prices, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Prices, BrokerError>> + Send {
         async move {
+            if let Some(rest) = self.rest_client() {
+                // 以内部交易对 (如 BTCUSDT) 为键, 与持仓、订单一致
+                let symbols: Vec<String> =
+                    self.get_symbols().iter().map(|s| s.replace('-', "")).collect();
+                return rest
+                    .get_prices(&symbols)
+                    .await
+                    .map_err(Into::into);
+            }
+
             let symbols = self.get_symbols();
             let mut prices = HashMap::new();
             let mut rng = rand::thread_rng();
@@ -132,10 +171,14 @@ impl MarketData for OkexBroker {
     fn get_orderbook(
         &self,
         symbol: &str,
-    ) -> impl std::future::Future<Output = Result<Orderbook, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Orderbook, BrokerError>> + Send
     {
         let symbol = symbol.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_orderbook(&symbol, 20).await.map_err(Into::into);
+            }
+
             let mut rng = rand::thread_rng();
             let base_price = self.get_base_price(&symbol);
 
@@ -170,10 +213,17 @@ impl MarketData for OkexBroker {
         symbol: &str,
         interval: &str,
         limit: Option<i32>,
-    ) -> impl std::future::Future<Output = Result<Klines, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Klines, BrokerError>> + Send {
         let symbol = symbol.to_string();
         let interval = interval.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest
+                    .get_klines(&symbol, &interval, limit)
+                    .await
+                    .map_err(Into::into);
+            }
+
             let mut rng = rand::thread_rng();
             let base_price = self.get_base_price(&symbol);
             let limit = limit.unwrap_or(300);
@@ -225,10 +275,14 @@ impl MarketData for OkexBroker {
     fn get_ticker_24h(
         &self,
         symbol: &str,
-    ) -> impl std::future::Future<Output = Result<Ticker24h, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Ticker24h, BrokerError>> + Send
     {
         let symbol = symbol.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_ticker_24h(&symbol).await.map_err(Into::into);
+            }
+
             let mut rng = rand::thread_rng();
             let price = self.get_base_price(&symbol);
             let change_pct = rng.gen_range(-7.0..7.0);
@@ -248,16 +302,40 @@ impl MarketData for OkexBroker {
     }
 }
 
+// ============================================================================
+// MarketDataStream Trait Implementation (行情推送实现)
+// ============================================================================
+impl MarketDataStream for OkexBroker {
+    fn subscribe_market(
+        &self,
+        subscription: StreamSubscription,
+    ) -> impl std::future::Future<Output = Result<MarketEventStream, BrokerError>> + Send
+    {
+        async move {
+            if self.is_simulated() {
+                return Err(BrokerError::NotSupported(
+                    "market stream is not available in mock mode".to_string(),
+                ));
+            }
+            Ok(OkexMarketStream::new(&self.config).subscribe(subscription))
+        }
+    }
+}
+
 // ============================================================================
 // Trading Trait Implementation (交易接口实现)
 // ============================================================================
 impl Trading for OkexBroker {
     fn place_order(
         &self,
-        _order: OrderRequest,
-    ) -> impl std::future::Future<Output = Result<OrderResponse, Box<dyn std::error::Error>>> + Send
+        order: OrderRequest,
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send
     {
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.place_order(&order).await.map_err(Into::into);
+            }
+
             let order_id = format!("OKEX_{}", chrono::Utc::now().timestamp_millis());
             Ok(OrderResponse {
                 order_id,
@@ -270,10 +348,14 @@ impl Trading for OkexBroker {
     fn cancel_order(
         &self,
         order_id: &str,
-    ) -> impl std::future::Future<Output = Result<OrderResponse, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send
     {
         let order_id = order_id.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.cancel_order(&order_id).await.map_err(Into::into);
+            }
+
             Ok(OrderResponse {
                 order_id,
                 status: OrderStatus::Cancelled,
@@ -285,9 +367,13 @@ impl Trading for OkexBroker {
     fn get_order(
         &self,
         order_id: &str,
-    ) -> impl std::future::Future<Output = Result<Order, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Order, BrokerError>> + Send {
         let order_id = order_id.to_string();
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_order(&order_id).await.map_err(Into::into);
+            }
+
             Ok(Order {
                 order_id,
                 symbol: "BTC-USDT".to_string(),
@@ -306,15 +392,46 @@ impl Trading for OkexBroker {
 
     fn get_orders(
         &self,
-        _symbol: Option<&str>,
-    ) -> impl std::future::Future<Output = Result<Orders, Box<dyn std::error::Error>>> + Send {
-        async move { Ok(Orders { orders: vec![] }) }
+        symbol: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<Orders, BrokerError>> + Send {
+        let symbol = symbol.map(|s| s.to_string());
+        async move {
+            if let Some(rest) = self.rest_client() {
+                return rest
+                    .get_open_orders(symbol.as_deref())
+                    .await
+                    .map_err(Into::into);
+            }
+            Ok(Orders { orders: vec![] })
+        }
     }
 
     fn get_trades(
         &self,
-    ) -> impl std::future::Future<Output = Result<Trades, Box<dyn std::error::Error>>> + Send {
-        async move { Ok(Trades { trades: vec![] }) }
+    ) -> impl std::future::Future<Output = Result<Trades, BrokerError>> + Send {
+        async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_trades().await.map_err(Into::into);
+            }
+            Ok(Trades { trades: vec![] })
+        }
+    }
+
+    fn set_leverage(
+        &self,
+        symbol: &str,
+        leverage: f64,
+    ) -> impl std::future::Future<Output = Result<(), BrokerError>> + Send {
+        let symbol = symbol.to_string();
+        async move {
+            if let Some(rest) = self.rest_client() {
+                return rest
+                    .set_leverage(&symbol, leverage)
+                    .await
+                    .map_err(Into::into);
+            }
+            Ok(())
+        }
     }
 }
 
@@ -325,7 +442,7 @@ impl AccountManagement for OkexBroker {
     fn get_account_totals(
         &self,
         _last_marker: Option<i32>,
-    ) -> impl std::future::Future<Output = Result<AccountTotals, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<AccountTotals, BrokerError>> + Send
     {
         async move {
             let mut rng = rand::thread_rng();
@@ -394,7 +511,7 @@ impl AccountManagement for OkexBroker {
 
     fn get_model_accounts(
         &self,
-    ) -> impl std::future::Future<Output = Result<ModelAccounts, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<ModelAccounts, BrokerError>> + Send
     {
         async move {
             let mut rng = rand::thread_rng();
@@ -458,9 +575,13 @@ impl AccountManagement for OkexBroker {
     fn get_positions(
         &self,
         _limit: Option<i32>,
-    ) -> impl std::future::Future<Output = Result<Positions, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Positions, BrokerError>> + Send
     {
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_positions().await.map_err(Into::into);
+            }
+
             let symbols = self.get_symbols();
             let mut rng = rand::thread_rng();
             let now = chrono::Utc::now().timestamp();
@@ -501,8 +622,12 @@ impl AccountManagement for OkexBroker {
 
     fn get_balance(
         &self,
-    ) -> impl std::future::Future<Output = Result<Balance, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Balance, BrokerError>> + Send {
         async move {
+            if let Some(rest) = self.rest_client() {
+                return rest.get_balance().await.map_err(Into::into);
+            }
+
             let mut rng = rand::thread_rng();
             let total = rng.gen_range(80000.0..250000.0);
             let margin_used = total * rng.gen_range(0.25..0.65);
@@ -521,7 +646,7 @@ impl AccountManagement for OkexBroker {
 
     fn get_broker_account(
         &self,
-    ) -> impl std::future::Future<Output = Result<BrokerAccount, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<BrokerAccount, BrokerError>> + Send
     {
         async move {
             Ok(BrokerAccount {
@@ -541,46 +666,23 @@ impl AccountManagement for OkexBroker {
 impl Analytics for OkexBroker {
     fn get_analytics(
         &self,
-    ) -> impl std::future::Future<Output = Result<AnalyticsData, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<AnalyticsData, BrokerError>> + Send
     {
-        async move {
-            let mut metrics = HashMap::new();
-            metrics.insert("total_models".to_string(), 3.0);
-            metrics.insert("total_volume_24h".to_string(), 120000000.0);
-            metrics.insert("avg_trades_per_model".to_string(), 180.0);
-
-            Ok(AnalyticsData { metrics })
-        }
+        // 交易所账户不区分模型, 模型分析由引擎按成交与权益快照计算
+        async move { Ok(AnalyticsData::default()) }
     }
 
     fn get_leaderboard(
         &self,
-    ) -> impl std::future::Future<Output = Result<Leaderboard, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Leaderboard, BrokerError>> + Send
     {
-        async move {
-            let mut rng = rand::thread_rng();
-            let models = self.get_models();
-            let mut entries = vec![];
-
-            for (rank, model) in models.iter().enumerate() {
-                entries.push(LeaderboardEntry {
-                    rank: (rank + 1) as i32,
-                    model_id: model.model_id.clone(),
-                    model_name: model.model_name.clone(),
-                    return_pct: rng.gen_range(-4.0..12.0),
-                    sharpe_ratio: rng.gen_range(0.6..2.3),
-                    total_trades: rng.gen_range(80..450),
-                    win_rate: rng.gen_range(0.48..0.66),
-                });
-            }
-
-            Ok(Leaderboard { leaderboard: entries })
-        }
+        // 排行榜由 leaderboard 模块跨经纪商汇总计算
+        async move { Ok(Leaderboard::default()) }
     }
 
     fn get_since_inception_values(
         &self,
-    ) -> impl std::future::Future<Output = Result<SinceInceptionValues, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<SinceInceptionValues, BrokerError>> + Send
     {
         async move {
             Ok(SinceInceptionValues {
@@ -591,29 +693,18 @@ impl Analytics for OkexBroker {
 
     fn get_conversations(
         &self,
-    ) -> impl std::future::Future<Output = Result<Conversations, Box<dyn std::error::Error>>> + Send
+    ) -> impl std::future::Future<Output = Result<Conversations, BrokerError>> + Send
     {
         async move { Ok(Conversations { conversations: vec![] }) }
     }
 
     fn get_models_list(
         &self,
-    ) -> impl std::future::Future<Output = Result<Models, Box<dyn std::error::Error>>> + Send {
+    ) -> impl std::future::Future<Output = Result<Models, BrokerError>> + Send {
         async move {
-            let models_data = self.get_models();
-            let models = models_data
-                .into_iter()
-                .map(|m| crate::brokers::ModelInfo {
-                    model_id: m.model_id,
-                    model_name: m.model_name,
-                    strategy: m.strategy,
-                    description: m.description,
-                    risk_level: m.risk_level,
-                    base_capital: m.base_capital,
-                })
-                .collect();
-
-            Ok(Models { models })
+            Ok(Models {
+                models: self.get_models(),
+            })
         }
     }
 }
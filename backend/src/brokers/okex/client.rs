@@ -0,0 +1,1551 @@
+// OKX v5 REST API 客户端
+// OKX v5 REST API Client (ISO 时间戳 + base64 HMAC-SHA256 签名)
+
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::types::OkexConfig;
+use crate::brokers::error::BrokerError;
+use crate::brokers::types::*;
+
+/// OKX REST 地址 (实盘与模拟盘共用, 模拟盘通过 `x-simulated-trading` 区分)
+pub const OKX_REST_URL: &str = "https://www.okx.com";
+
+/// 请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// OKX 客户端错误
+#[derive(Debug, thiserror::Error)]
+pub enum OkexApiError {
+    /// 网络/传输错误
+    #[error("OKX HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// 交易所返回的业务错误 ({"code": "51001", "msg": "Instrument ID does not exist"})
+    #[error("OKX API error {code} (HTTP {status}): {msg}")]
+    Api {
+        status: u16,
+        code: String,
+        msg: String,
+    },
+
+    /// 本接口不支持的请求
+    #[error("OKX unsupported request: {0}")]
+    Unsupported(String),
+
+    /// 下单参数不满足产品规格 (如低于最小下单量)
+    #[error("OKX invalid order: {0}")]
+    InvalidOrder(String),
+
+    /// 响应解析失败
+    #[error("OKX decode error: {0}")]
+    Decode(String),
+}
+
+impl OkexApiError {
+    /// 交易所错误码 (非业务错误时为 None)
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::Api { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+}
+
+impl From<OkexApiError> for BrokerError {
+    fn from(e: OkexApiError) -> Self {
+        let (status, code, msg) = match e {
+            OkexApiError::Http(e) => return e.into(),
+            OkexApiError::Decode(msg) | OkexApiError::InvalidOrder(msg) => {
+                return BrokerError::Other(msg)
+            }
+            OkexApiError::Unsupported(msg) => return BrokerError::NotSupported(msg),
+            OkexApiError::Api { status, code, msg } => (status, code, msg),
+        };
+
+        match code.as_str() {
+            "50011" | "50061" => BrokerError::RateLimited { retry_after: None },
+            "50100" | "50101" | "50102" | "50103" | "50104" | "50105" | "50111" | "50112"
+            | "50113" | "50114" => BrokerError::Auth(msg),
+            "51001" => BrokerError::InvalidSymbol(msg),
+            "51008" | "51127" | "51131" => BrokerError::InsufficientFunds(msg),
+            c if c.starts_with("51") => BrokerError::OrderRejected { code, reason: msg },
+            "" => BrokerError::from_http_status(status, None, &msg),
+            _ => BrokerError::Exchange {
+                venue_code: code,
+                message: msg,
+            },
+        }
+    }
+}
+
+/// OKX v5 签名 REST 客户端
+///
+/// 签名串为 `timestamp + METHOD + requestPath(含查询串) + body`,
+/// 以 API Secret 做 HMAC-SHA256 后 base64 编码, 放入 `OK-ACCESS-SIGN` 头。
+///
+/// 对外的数量均为币数, 合约的张数按产品的 `ctVal` 换算; 返回结果以调用方的交易对为键。
+pub struct OkexRestClient {
+    http: Client,
+    base_url: String,
+    api_key: String,
+    api_secret: String,
+    passphrase: String,
+    simulated: bool,
+    inst_type: String,
+    td_mode: String,
+    /// 产品规格缓存 (instId -> 规格)
+    instruments: Mutex<HashMap<String, Instrument>>,
+    /// 账户持仓模式缓存 (true 为双向持仓)
+    hedge_mode: Mutex<Option<bool>>,
+}
+
+/// 产品规格
+#[derive(Debug, Clone)]
+struct Instrument {
+    /// 每张合约对应的币数 (现货为 1)
+    ct_val: f64,
+    /// 下单数量精度 (张)
+    lot_sz: f64,
+    lot_decimals: usize,
+    /// 最小下单数量 (张)
+    min_sz: f64,
+}
+
+impl Instrument {
+    /// 币数 -> 下单张数 (按 lotSz 向下取整)
+    fn to_size(&self, quantity: f64) -> Result<String, OkexApiError> {
+        let lots = (quantity / self.ct_val / self.lot_sz + 1e-9).floor();
+        let size = lots * self.lot_sz;
+        if size <= 0.0 || size + 1e-12 < self.min_sz {
+            return Err(OkexApiError::InvalidOrder(format!(
+                "quantity {} is below the minimum order size {}",
+                quantity,
+                self.min_sz * self.ct_val
+            )));
+        }
+        let size = format!("{:.*}", self.lot_decimals, size);
+        Ok(if size.contains('.') {
+            size.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            size
+        })
+    }
+}
+
+impl OkexRestClient {
+    /// 根据配置创建客户端
+    pub fn new(config: &OkexConfig) -> Self {
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| OKX_REST_URL.to_string());
+
+        let http = Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| Client::new());
+
+        Self {
+            http,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+            api_secret: config.api_secret.clone(),
+            passphrase: config.passphrase.clone(),
+            simulated: config.simulated,
+            inst_type: config.inst_type.clone(),
+            td_mode: config.td_mode.clone(),
+            instruments: Mutex::new(HashMap::new()),
+            hedge_mode: Mutex::new(None),
+        }
+    }
+
+    /// REST 基础地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 将内部交易对转换为 OKX 产品ID
+    pub fn inst_id(&self, symbol: &str) -> String {
+        to_inst_id(symbol, &self.inst_type)
+    }
+
+    // ========================================================================
+    // 请求发送
+    // ========================================================================
+
+    /// 公共接口 (无需签名)
+    async fn public_get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<Vec<T>, OkexApiError> {
+        let request_path = with_query(path, params);
+        let url = format!("{}{}", self.base_url, request_path);
+
+        let mut request = self.http.get(&url);
+        if self.simulated {
+            request = request.header("x-simulated-trading", "1");
+        }
+        Self::parse_response(request.send().await?).await
+    }
+
+    /// 私有接口 (签名)
+    async fn signed_request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &[(&str, String)],
+        body: Option<serde_json::Value>,
+    ) -> Result<Vec<T>, OkexApiError> {
+        let request_path = with_query(path, params);
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let timestamp = iso_timestamp();
+        let signature = sign_request(
+            &self.api_secret,
+            &timestamp,
+            method.as_str(),
+            &request_path,
+            &body,
+        );
+
+        let url = format!("{}{}", self.base_url, request_path);
+        let mut request = self
+            .http
+            .request(method, &url)
+            .header("OK-ACCESS-KEY", &self.api_key)
+            .header("OK-ACCESS-SIGN", signature)
+            .header("OK-ACCESS-TIMESTAMP", timestamp)
+            .header("OK-ACCESS-PASSPHRASE", &self.passphrase)
+            .header("Content-Type", "application/json");
+        if self.simulated {
+            request = request.header("x-simulated-trading", "1");
+        }
+        if !body.is_empty() {
+            request = request.body(body);
+        }
+
+        Self::parse_response(request.send().await?).await
+    }
+
+    async fn parse_response<T: DeserializeOwned>(
+        response: reqwest::Response,
+    ) -> Result<Vec<T>, OkexApiError> {
+        #[derive(Deserialize)]
+        struct Envelope {
+            code: String,
+            #[serde(default)]
+            msg: String,
+            #[serde(default)]
+            data: serde_json::Value,
+        }
+
+        let status = response.status();
+        let body = response.text().await?;
+
+        let envelope: Envelope = match serde_json::from_str(&body) {
+            Ok(envelope) => envelope,
+            Err(_) if !status.is_success() => {
+                return Err(OkexApiError::Api {
+                    status: status.as_u16(),
+                    code: String::new(),
+                    msg: body,
+                })
+            }
+            Err(e) => return Err(OkexApiError::Decode(e.to_string())),
+        };
+
+        if envelope.code != "0" {
+            // 批量/下单类接口的具体错误在 data[].sCode / sMsg 中
+            let detail = envelope
+                .data
+                .as_array()
+                .and_then(|items| items.first())
+                .and_then(|item| {
+                    let code = item.get("sCode")?.as_str()?;
+                    let msg = item.get("sMsg")?.as_str()?;
+                    (code != "0").then(|| (code.to_string(), msg.to_string()))
+                });
+            let (code, msg) = detail.unwrap_or((envelope.code, envelope.msg));
+
+            return Err(OkexApiError::Api {
+                status: status.as_u16(),
+                code,
+                msg,
+            });
+        }
+
+        serde_json::from_value(envelope.data).map_err(|e| OkexApiError::Decode(e.to_string()))
+    }
+
+    // ========================================================================
+    // 行情接口
+    // ========================================================================
+
+    /// 最新价格 (按产品类型批量获取后筛选)
+    pub async fn get_prices(&self, symbols: &[String]) -> Result<Prices, OkexApiError> {
+        let tickers: Vec<RawTicker> = self
+            .public_get(
+                "/api/v5/market/tickers",
+                &[("instType", self.inst_type.clone())],
+            )
+            .await?;
+
+        // 以调用方传入的交易对为键
+        let wanted: HashMap<String, &String> =
+            symbols.iter().map(|s| (self.inst_id(s), s)).collect();
+        let prices = tickers
+            .into_iter()
+            .filter_map(|t| {
+                let symbol = if wanted.is_empty() {
+                    from_inst_id(&t.inst_id)
+                } else {
+                    wanted.get(&t.inst_id)?.to_string()
+                };
+                Some((symbol, parse_f64(&t.last)))
+            })
+            .collect();
+
+        Ok(Prices { prices })
+    }
+
+    /// 深度
+    pub async fn get_orderbook(&self, symbol: &str, depth: u32) -> Result<Orderbook, OkexApiError> {
+        let inst_id = self.inst_id(symbol);
+        let books: Vec<RawBook> = self
+            .public_get(
+                "/api/v5/market/books",
+                &[("instId", inst_id.clone()), ("sz", depth.to_string())],
+            )
+            .await?;
+        let book = first(books)?;
+
+        Ok(Orderbook {
+            symbol: symbol.to_string(),
+            bids: book.bids.iter().filter_map(|l| to_level(l)).collect(),
+            asks: book.asks.iter().filter_map(|l| to_level(l)).collect(),
+            timestamp: parse_i64(&book.ts) / 1000,
+        })
+    }
+
+    /// K线 (OKX 按时间倒序返回, 这里转为正序)
+    pub async fn get_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: Option<i32>,
+    ) -> Result<Klines, OkexApiError> {
+        let mut params = vec![
+            ("instId", self.inst_id(symbol)),
+            ("bar", to_bar(interval).to_string()),
+        ];
+        if let Some(limit) = limit {
+            params.push(("limit", limit.clamp(1, 300).to_string()));
+        }
+        self.fetch_klines(symbol, interval, "/api/v5/market/candles", &params)
+            .await
+    }
+
+    /// 按时间区间获取历史K线 (`[start, end)`, 秒级时间戳, 单次最多 100 根)
+    ///
+    /// `history-candles` 以 `after` 取更早的数据, 以 `before` 取更新的数据, 两者均为开区间。
+    pub async fn get_klines_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+        limit: i32,
+    ) -> Result<Klines, OkexApiError> {
+        let params = vec![
+            ("instId", self.inst_id(symbol)),
+            ("bar", to_bar(interval).to_string()),
+            ("after", (end * 1000).to_string()),
+            ("before", (start * 1000 - 1).to_string()),
+            ("limit", limit.clamp(1, 100).to_string()),
+        ];
+        self.fetch_klines(symbol, interval, "/api/v5/market/history-candles", &params)
+            .await
+    }
+
+    async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        path: &str,
+        params: &[(&str, String)],
+    ) -> Result<Klines, OkexApiError> {
+        let rows: Vec<Vec<String>> = self.public_get(path, params).await?;
+        let mut klines = rows
+            .iter()
+            .map(|row| {
+                if row.len() < 6 {
+                    return Err(OkexApiError::Decode(format!(
+                        "unexpected candle row: {:?}",
+                        row
+                    )));
+                }
+                Ok(Kline {
+                    timestamp: parse_i64(&row[0]) / 1000,
+                    open: parse_f64(&row[1]),
+                    high: parse_f64(&row[2]),
+                    low: parse_f64(&row[3]),
+                    close: parse_f64(&row[4]),
+                    volume: parse_f64(&row[5]),
+                    open_interest: None,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        klines.reverse();
+
+        Ok(Klines {
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+            klines,
+        })
+    }
+
+    /// 24小时行情
+    pub async fn get_ticker_24h(&self, symbol: &str) -> Result<Ticker24h, OkexApiError> {
+        let tickers: Vec<RawTicker> = self
+            .public_get("/api/v5/market/ticker", &[("instId", self.inst_id(symbol))])
+            .await?;
+        let ticker = first(tickers)?;
+
+        let last = parse_f64(&ticker.last);
+        let open = parse_f64(&ticker.open24h);
+        Ok(Ticker24h {
+            last_price: last,
+            change_24h: if open > 0.0 {
+                (last - open) / open * 100.0
+            } else {
+                0.0
+            },
+            high_24h: parse_f64(&ticker.high24h),
+            low_24h: parse_f64(&ticker.low24h),
+            volume_24h: parse_f64(&ticker.vol_ccy24h),
+            open_interest: None,
+            timestamp: parse_i64(&ticker.ts) / 1000,
+            symbol: symbol.to_string(),
+        })
+    }
+
+    // ========================================================================
+    // 交易接口
+    // ========================================================================
+
+    /// 下单 (quantity 为币数, 合约按 ctVal 换算为张数)
+    ///
+    /// 衍生品在买卖模式下对反向且不超过持仓的订单设置 `reduceOnly`,
+    /// 双向持仓模式下以 `posSide` 区分开平仓。
+    pub async fn place_order(&self, order: &OrderRequest) -> Result<OrderResponse, OkexApiError> {
+        let inst_id = self.inst_id(&order.symbol);
+        let ord_type = match (&order.order_type, order.time_in_force.as_deref()) {
+            (OrderType::Market, _) => "market",
+            (OrderType::Limit, Some("IOC")) => "ioc",
+            (OrderType::Limit, Some("FOK")) => "fok",
+            (OrderType::Limit, Some("GTX" | "POST_ONLY")) => "post_only",
+            (OrderType::Limit, _) => "limit",
+            (OrderType::Stop | OrderType::StopLimit, _) => {
+                return Err(OkexApiError::Unsupported(
+                    "stop orders must be placed via the algo order API".to_string(),
+                ))
+            }
+        };
+
+        let instrument = self.instrument(&inst_id).await?;
+        let sz = instrument.to_size(order.quantity)?;
+        let mut body = json!({
+            "instId": inst_id,
+            "tdMode": self.td_mode_for(&inst_id),
+            "side": side_to_str(&order.side),
+            "ordType": ord_type,
+            "sz": sz,
+        });
+        if ord_type != "market" {
+            let price = order
+                .price
+                .ok_or_else(|| OkexApiError::Decode("limit order requires a price".to_string()))?;
+            body["px"] = json!(price.to_string());
+        }
+
+        if is_spot(&inst_id) {
+            // 现货市价单默认以计价币计量, 统一按基础币数下单
+            if ord_type == "market" {
+                body["tgtCcy"] = json!("base_ccy");
+            }
+        } else {
+            let (held_long, held_short) = self.held_contracts(&inst_id).await?;
+            let held = match order.side {
+                OrderSide::Buy => held_short,
+                OrderSide::Sell => held_long,
+            };
+            // 只减仓单总是平仓, 超出持仓的部分由交易所拒绝
+            let closes = order.reduce_only || (held > 0.0 && parse_f64(&sz) <= held + 1e-9);
+
+            if self.is_hedge_mode().await? {
+                let pos_side = match (&order.side, closes) {
+                    (OrderSide::Buy, true) | (OrderSide::Sell, false) => "short",
+                    (OrderSide::Buy, false) | (OrderSide::Sell, true) => "long",
+                };
+                body["posSide"] = json!(pos_side);
+            } else if closes {
+                body["reduceOnly"] = json!(true);
+            }
+        }
+
+        let acks: Vec<RawOrderAck> = self
+            .signed_request(Method::POST, "/api/v5/trade/order", &[], Some(body))
+            .await?;
+        let ack = first(acks)?;
+
+        Ok(OrderResponse {
+            order_id: compose_order_id(&inst_id, &ack.ord_id),
+            status: OrderStatus::Accepted,
+            timestamp: parse_i64(&ack.ts) / 1000,
+        })
+    }
+
+    /// 撤单 (order_id 为 `instId:ordId`)
+    pub async fn cancel_order(&self, order_id: &str) -> Result<OrderResponse, OkexApiError> {
+        let (inst_id, ord_id) = split_order_id(order_id)?;
+        let acks: Vec<RawOrderAck> = self
+            .signed_request(
+                Method::POST,
+                "/api/v5/trade/cancel-order",
+                &[],
+                Some(json!({ "instId": inst_id, "ordId": ord_id })),
+            )
+            .await?;
+        let ack = first(acks)?;
+
+        Ok(OrderResponse {
+            order_id: compose_order_id(inst_id, &ack.ord_id),
+            status: OrderStatus::Cancelled,
+            timestamp: parse_i64(&ack.ts) / 1000,
+        })
+    }
+
+    /// 改单 (数量和价格至少修改一项, 数量为币数)
+    pub async fn amend_order(
+        &self,
+        order_id: &str,
+        new_quantity: Option<f64>,
+        new_price: Option<f64>,
+    ) -> Result<OrderResponse, OkexApiError> {
+        if new_quantity.is_none() && new_price.is_none() {
+            return Err(OkexApiError::Unsupported(
+                "amend requires a new quantity or price".to_string(),
+            ));
+        }
+
+        let (inst_id, ord_id) = split_order_id(order_id)?;
+        let mut body = json!({ "instId": inst_id, "ordId": ord_id });
+        if let Some(quantity) = new_quantity {
+            body["newSz"] = json!(self.instrument(inst_id).await?.to_size(quantity)?);
+        }
+        if let Some(price) = new_price {
+            body["newPx"] = json!(price.to_string());
+        }
+
+        let acks: Vec<RawOrderAck> = self
+            .signed_request(Method::POST, "/api/v5/trade/amend-order", &[], Some(body))
+            .await?;
+        let ack = first(acks)?;
+
+        Ok(OrderResponse {
+            order_id: compose_order_id(inst_id, &ack.ord_id),
+            status: OrderStatus::Accepted,
+            timestamp: parse_i64(&ack.ts) / 1000,
+        })
+    }
+
+    /// 设置衍生品杠杆倍数 (现货只接受 1 倍)
+    ///
+    /// 双向持仓的逐仓模式下多空两侧分别设置。
+    pub async fn set_leverage(&self, symbol: &str, leverage: f64) -> Result<(), OkexApiError> {
+        let inst_id = self.inst_id(symbol);
+        if is_spot(&inst_id) {
+            if leverage == 1.0 {
+                return Ok(());
+            }
+            return Err(OkexApiError::Unsupported(format!(
+                "{}x leverage on spot instrument {}",
+                leverage, inst_id
+            )));
+        }
+
+        let mgn_mode = self.td_mode_for(&inst_id);
+        let pos_sides = if mgn_mode == "isolated" && self.is_hedge_mode().await? {
+            vec![Some("long"), Some("short")]
+        } else {
+            vec![None]
+        };
+        for pos_side in pos_sides {
+            let mut body = json!({
+                "instId": inst_id,
+                "lever": leverage.to_string(),
+                "mgnMode": mgn_mode,
+            });
+            if let Some(pos_side) = pos_side {
+                body["posSide"] = json!(pos_side);
+            }
+            let _: Vec<serde_json::Value> = self
+                .signed_request(
+                    Method::POST,
+                    "/api/v5/account/set-leverage",
+                    &[],
+                    Some(body),
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// 查询订单 (order_id 为 `instId:ordId`)
+    pub async fn get_order(&self, order_id: &str) -> Result<Order, OkexApiError> {
+        let (inst_id, ord_id) = split_order_id(order_id)?;
+        let orders: Vec<RawOrder> = self
+            .signed_request(
+                Method::GET,
+                "/api/v5/trade/order",
+                &[
+                    ("instId", inst_id.to_string()),
+                    ("ordId", ord_id.to_string()),
+                ],
+                None,
+            )
+            .await?;
+        let ct_val = self.instrument(inst_id).await?.ct_val;
+
+        Ok(first(orders)?.into_order(ct_val))
+    }
+
+    /// 当前挂单
+    pub async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Orders, OkexApiError> {
+        let mut params = vec![("instType", self.inst_type.clone())];
+        if let Some(symbol) = symbol {
+            params.push(("instId", self.inst_id(symbol)));
+        }
+
+        let rows: Vec<RawOrder> = self
+            .signed_request(Method::GET, "/api/v5/trade/orders-pending", &params, None)
+            .await?;
+
+        let mut orders = Vec::with_capacity(rows.len());
+        for row in rows {
+            let ct_val = self.instrument(&row.inst_id).await?.ct_val;
+            orders.push(row.into_order(ct_val));
+        }
+        Ok(Orders { orders })
+    }
+
+    /// 近三天成交明细
+    pub async fn get_trades(&self) -> Result<Trades, OkexApiError> {
+        let fills: Vec<RawFill> = self
+            .signed_request(
+                Method::GET,
+                "/api/v5/trade/fills",
+                &[("instType", self.inst_type.clone())],
+                None,
+            )
+            .await?;
+
+        let mut trades = Vec::with_capacity(fills.len());
+        for f in fills {
+            let ct_val = self.instrument(&f.inst_id).await?.ct_val;
+            trades.push(Trade {
+                trade_id: f.trade_id,
+                order_id: compose_order_id(&f.inst_id, &f.ord_id),
+                side: parse_side(&f.side),
+                price: parse_f64(&f.fill_px),
+                quantity: parse_f64(&f.fill_sz) * ct_val,
+                // OKX 手续费为负数表示扣除
+                fee: -parse_f64(&f.fee),
+                timestamp: parse_i64(&f.ts) / 1000,
+                symbol: from_inst_id(&f.inst_id),
+            });
+        }
+        trades.sort_by_key(|t| t.timestamp);
+
+        Ok(Trades { trades })
+    }
+
+    // ========================================================================
+    // 账户接口
+    // ========================================================================
+
+    /// 持仓
+    pub async fn get_positions(&self) -> Result<Positions, OkexApiError> {
+        let rows: Vec<RawPosition> = self
+            .signed_request(
+                Method::GET,
+                "/api/v5/account/positions",
+                &[("instType", self.inst_type.clone())],
+                None,
+            )
+            .await?;
+
+        // 双向持仓的多空两腿按品种轧差为一条净持仓, 盈亏与保证金合计
+        let mut net: HashMap<String, (f64, Position)> = HashMap::new();
+        for p in rows.into_iter().filter(|p| parse_f64(&p.pos) != 0.0) {
+            let ct_val = self.instrument(&p.inst_id).await?.ct_val;
+            let quantity = parse_f64(&p.pos).abs() * ct_val;
+            let signed = if p.direction() == "short" {
+                -quantity
+            } else {
+                quantity
+            };
+            let margin = parse_f64(&p.margin);
+            let margin = if margin > 0.0 {
+                margin
+            } else {
+                parse_f64(&p.imr)
+            };
+            let leg = Position {
+                symbol: from_inst_id(&p.inst_id),
+                entry_price: parse_f64(&p.avg_px),
+                current_price: parse_f64(&p.mark_px),
+                quantity,
+                unrealized_pnl: parse_f64(&p.upl),
+                direction: None,
+                leverage: p.lever.parse::<f64>().ok().map(|l| l.round() as i32),
+                margin: Some(margin),
+                timestamp: parse_i64(&p.u_time) / 1000,
+            };
+
+            match net.get_mut(&leg.symbol) {
+                Some((total, position)) => {
+                    *total += signed;
+                    // 净头寸沿用较大一腿的开仓均价
+                    if leg.quantity > position.quantity {
+                        position.entry_price = leg.entry_price;
+                        position.quantity = leg.quantity;
+                    }
+                    position.unrealized_pnl += leg.unrealized_pnl;
+                    position.margin = Some(position.margin.unwrap_or(0.0) + margin);
+                    position.timestamp = position.timestamp.max(leg.timestamp);
+                }
+                None => {
+                    net.insert(leg.symbol.clone(), (signed, leg));
+                }
+            }
+        }
+
+        let positions = net
+            .into_iter()
+            .filter(|(_, (total, _))| total.abs() > 1e-12)
+            .map(|(symbol, (total, mut position))| {
+                position.quantity = total.abs();
+                position.direction = Some(if total < 0.0 { "short" } else { "long" }.to_string());
+                (symbol, position)
+            })
+            .collect();
+
+        Ok(Positions { positions })
+    }
+
+    /// 账户余额 (以 USDT 计价)
+    pub async fn get_balance(&self) -> Result<Balance, OkexApiError> {
+        let accounts: Vec<RawAccount> = self
+            .signed_request(
+                Method::GET,
+                "/api/v5/account/balance",
+                &[("ccy", "USDT".to_string())],
+                None,
+            )
+            .await?;
+        let account = first(accounts)?;
+        let usdt = account.details.iter().find(|d| d.ccy == "USDT");
+
+        Ok(Balance {
+            total_balance: parse_f64(&account.total_eq),
+            available: usdt.map(|d| parse_f64(&d.avail_bal)).unwrap_or_default(),
+            margin_used: Some(parse_f64(&account.imr)),
+            frozen_margin: usdt.map(|d| parse_f64(&d.frozen_bal)),
+            currency: "USDT".to_string(),
+            timestamp: parse_i64(&account.u_time) / 1000,
+        })
+    }
+
+    /// 现货用 cash 模式, 衍生品使用配置中的保证金模式
+    fn td_mode_for(&self, inst_id: &str) -> &str {
+        if is_spot(inst_id) {
+            "cash"
+        } else {
+            &self.td_mode
+        }
+    }
+
+    /// 产品规格 (首次查询后缓存)
+    async fn instrument(&self, inst_id: &str) -> Result<Instrument, OkexApiError> {
+        if let Some(instrument) = self.instruments.lock().unwrap().get(inst_id) {
+            return Ok(instrument.clone());
+        }
+
+        let rows: Vec<RawInstrument> = self
+            .public_get(
+                "/api/v5/public/instruments",
+                &[
+                    ("instType", inst_type_of(inst_id).to_string()),
+                    ("instId", inst_id.to_string()),
+                ],
+            )
+            .await?;
+        let raw = rows
+            .into_iter()
+            .find(|r| r.inst_id == inst_id)
+            .ok_or_else(|| OkexApiError::Decode(format!("unknown instrument: {}", inst_id)))?;
+
+        let ct_val = parse_f64(&raw.ct_val);
+        let lot_sz = parse_f64(&raw.lot_sz);
+        let instrument = Instrument {
+            ct_val: if ct_val > 0.0 { ct_val } else { 1.0 },
+            lot_sz: if lot_sz > 0.0 { lot_sz } else { 1.0 },
+            lot_decimals: raw.lot_sz.split_once('.').map_or(0, |(_, d)| d.len()),
+            min_sz: parse_f64(&raw.min_sz),
+        };
+        self.instruments
+            .lock()
+            .unwrap()
+            .insert(inst_id.to_string(), instrument.clone());
+        Ok(instrument)
+    }
+
+    /// 账户是否为双向持仓模式 (首次查询后缓存)
+    async fn is_hedge_mode(&self) -> Result<bool, OkexApiError> {
+        if let Some(hedge) = *self.hedge_mode.lock().unwrap() {
+            return Ok(hedge);
+        }
+
+        let configs: Vec<RawAccountConfig> = self
+            .signed_request(Method::GET, "/api/v5/account/config", &[], None)
+            .await?;
+        let hedge = first(configs)?.pos_mode == "long_short_mode";
+        *self.hedge_mode.lock().unwrap() = Some(hedge);
+        Ok(hedge)
+    }
+
+    /// 指定产品当前的多头与空头持仓张数
+    async fn held_contracts(&self, inst_id: &str) -> Result<(f64, f64), OkexApiError> {
+        let rows: Vec<RawPosition> = self
+            .signed_request(
+                Method::GET,
+                "/api/v5/account/positions",
+                &[("instId", inst_id.to_string())],
+                None,
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .filter(|p| p.inst_id == inst_id)
+            .fold((0.0, 0.0), |(long, short), p| {
+                let size = parse_f64(&p.pos).abs();
+                match p.direction() {
+                    "short" => (long, short + size),
+                    _ => (long + size, short),
+                }
+            }))
+    }
+}
+
+// ============================================================================
+// 原始响应结构
+// ============================================================================
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTicker {
+    inst_id: String,
+    last: String,
+    #[serde(default)]
+    open24h: String,
+    #[serde(default)]
+    high24h: String,
+    #[serde(default)]
+    low24h: String,
+    #[serde(default)]
+    vol_ccy24h: String,
+    ts: String,
+}
+
+#[derive(Deserialize)]
+struct RawBook {
+    asks: Vec<Vec<String>>,
+    bids: Vec<Vec<String>>,
+    ts: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawOrderAck {
+    ord_id: String,
+    #[serde(default)]
+    ts: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawOrder {
+    inst_id: String,
+    ord_id: String,
+    #[serde(default)]
+    px: String,
+    sz: String,
+    #[serde(default)]
+    acc_fill_sz: String,
+    #[serde(default)]
+    avg_px: String,
+    state: String,
+    ord_type: String,
+    side: String,
+    c_time: String,
+    #[serde(default)]
+    u_time: String,
+}
+
+impl RawOrder {
+    /// 转换为内部订单 (张数按 ct_val 换算为币数)
+    fn into_order(self, ct_val: f64) -> Order {
+        let price = parse_f64(&self.px);
+        let avg_price = parse_f64(&self.avg_px);
+        let created_at = parse_i64(&self.c_time) / 1000;
+        let updated_at = parse_i64(&self.u_time) / 1000;
+
+        Order {
+            order_id: compose_order_id(&self.inst_id, &self.ord_id),
+            symbol: from_inst_id(&self.inst_id),
+            side: parse_side(&self.side),
+            order_type: if self.ord_type == "market" {
+                OrderType::Market
+            } else {
+                OrderType::Limit
+            },
+            quantity: parse_f64(&self.sz) * ct_val,
+            filled_quantity: parse_f64(&self.acc_fill_sz) * ct_val,
+            price: (price > 0.0).then_some(price),
+            avg_price: (avg_price > 0.0).then_some(avg_price),
+            status: parse_state(&self.state),
+            created_at,
+            updated_at: if updated_at > 0 {
+                updated_at
+            } else {
+                created_at
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawFill {
+    inst_id: String,
+    trade_id: String,
+    ord_id: String,
+    fill_px: String,
+    fill_sz: String,
+    side: String,
+    fee: String,
+    ts: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPosition {
+    inst_id: String,
+    pos: String,
+    #[serde(default)]
+    pos_side: String,
+    #[serde(default)]
+    avg_px: String,
+    #[serde(default)]
+    mark_px: String,
+    #[serde(default)]
+    upl: String,
+    #[serde(default)]
+    lever: String,
+    #[serde(default)]
+    margin: String,
+    #[serde(default)]
+    imr: String,
+    #[serde(default)]
+    u_time: String,
+}
+
+impl RawPosition {
+    /// 持仓方向 (买卖模式下以持仓数量的正负区分)
+    fn direction(&self) -> &str {
+        match self.pos_side.as_str() {
+            "long" | "short" => &self.pos_side,
+            _ if parse_f64(&self.pos) < 0.0 => "short",
+            _ => "long",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawInstrument {
+    inst_id: String,
+    #[serde(default)]
+    ct_val: String,
+    #[serde(default)]
+    lot_sz: String,
+    #[serde(default)]
+    min_sz: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAccountConfig {
+    pos_mode: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawAccount {
+    total_eq: String,
+    #[serde(default)]
+    imr: String,
+    #[serde(default)]
+    u_time: String,
+    details: Vec<RawBalanceDetail>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBalanceDetail {
+    ccy: String,
+    #[serde(default)]
+    avail_bal: String,
+    #[serde(default)]
+    frozen_bal: String,
+}
+
+// ============================================================================
+// 辅助函数
+// ============================================================================
+
+/// OKX 签名: base64(HMAC-SHA256(secret, timestamp + method + requestPath + body))
+pub fn sign_request(
+    secret: &str,
+    timestamp: &str,
+    method: &str,
+    request_path: &str,
+    body: &str,
+) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.as_bytes());
+    mac.update(method.to_uppercase().as_bytes());
+    mac.update(request_path.as_bytes());
+    mac.update(body.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(mac.finalize().into_bytes())
+}
+
+/// ISO 8601 毫秒时间戳, 如 `2020-12-08T09:08:57.715Z`
+fn iso_timestamp() -> String {
+    chrono::Utc::now()
+        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        .to_string()
+}
+
+/// 内部交易对 -> OKX 产品ID
+///
+/// - `BTC-USDT-SWAP` 等完整ID原样返回
+/// - `BTCUSDT` / `BTC-USDT` 按产品类型补全, SWAP 为 `BTC-USDT-SWAP`, SPOT 为 `BTC-USDT`
+pub fn to_inst_id(symbol: &str, inst_type: &str) -> String {
+    let symbol = symbol.to_uppercase();
+    let pair = if symbol.contains('-') {
+        if symbol.matches('-').count() >= 2 {
+            return symbol;
+        }
+        symbol
+    } else {
+        match ["USDT", "USDC", "USD"]
+            .iter()
+            .find(|quote| symbol.len() > quote.len() && symbol.ends_with(*quote))
+        {
+            Some(quote) => format!("{}-{}", &symbol[..symbol.len() - quote.len()], quote),
+            None => symbol,
+        }
+    };
+
+    match inst_type {
+        "SWAP" => format!("{}-SWAP", pair),
+        _ => pair,
+    }
+}
+
+/// OKX 产品ID -> 内部交易对 (现货与永续合约为 `BTCUSDT`, 交割合约原样返回, 与 [`to_inst_id`] 互逆)
+pub fn from_inst_id(inst_id: &str) -> String {
+    let pair = inst_id.strip_suffix("-SWAP").unwrap_or(inst_id);
+    if is_spot(pair) {
+        pair.replace('-', "")
+    } else {
+        pair.to_string()
+    }
+}
+
+fn is_spot(inst_id: &str) -> bool {
+    inst_id.matches('-').count() == 1
+}
+
+/// 由产品ID推断产品类型
+fn inst_type_of(inst_id: &str) -> &'static str {
+    if is_spot(inst_id) {
+        "SPOT"
+    } else if inst_id.ends_with("-SWAP") {
+        "SWAP"
+    } else {
+        "FUTURES"
+    }
+}
+
+/// 组合订单ID: OKX 的查询/撤单需要产品ID, 统一编码为 `instId:ordId`
+pub fn compose_order_id(inst_id: &str, ord_id: &str) -> String {
+    format!("{}:{}", inst_id, ord_id)
+}
+
+fn split_order_id(order_id: &str) -> Result<(&str, &str), OkexApiError> {
+    order_id
+        .split_once(':')
+        .ok_or_else(|| OkexApiError::Decode(format!("invalid order id: {}", order_id)))
+}
+
+fn with_query(path: &str, params: &[(&str, String)]) -> String {
+    if params.is_empty() {
+        return path.to_string();
+    }
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(params.iter().map(|(k, v)| (*k, v.as_str())))
+        .finish();
+    format!("{}?{}", path, query)
+}
+
+fn first<T>(items: Vec<T>) -> Result<T, OkexApiError> {
+    items
+        .into_iter()
+        .next()
+        .ok_or_else(|| OkexApiError::Decode("empty data".to_string()))
+}
+
+pub(super) fn parse_f64(s: &str) -> f64 {
+    s.parse().unwrap_or_default()
+}
+
+pub(super) fn parse_i64(s: &str) -> i64 {
+    s.parse().unwrap_or_default()
+}
+
+pub(super) fn to_level(level: &[String]) -> Option<OrderbookLevel> {
+    Some(OrderbookLevel {
+        price: parse_f64(level.first()?),
+        quantity: parse_f64(level.get(1)?),
+    })
+}
+
+/// K线周期映射 (OKX 小时及以上周期为大写)
+pub(super) fn to_bar(interval: &str) -> &str {
+    match interval {
+        "1h" => "1H",
+        "2h" => "2H",
+        "4h" => "4H",
+        "6h" => "6H",
+        "12h" => "12H",
+        "1d" => "1D",
+        "1w" => "1W",
+        "1M" => "1M",
+        other => other,
+    }
+}
+
+fn side_to_str(side: &OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    }
+}
+
+pub(super) fn parse_side(s: &str) -> OrderSide {
+    if s == "sell" {
+        OrderSide::Sell
+    } else {
+        OrderSide::Buy
+    }
+}
+
+fn parse_state(s: &str) -> OrderStatus {
+    match s {
+        "live" => OrderStatus::Accepted,
+        "partially_filled" => OrderStatus::PartiallyFilled,
+        "filled" => OrderStatus::Filled,
+        "canceled" | "mmp_canceled" => OrderStatus::Cancelled,
+        _ => OrderStatus::Pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Bytes;
+    use axum::http::{HeaderMap, StatusCode, Uri};
+    use axum::response::{IntoResponse, Response};
+    use axum::routing::{get, post};
+    use axum::Router;
+    use std::sync::Arc;
+
+    const API_KEY: &str = "test-key";
+    const API_SECRET: &str = "test-secret";
+    const PASSPHRASE: &str = "test-passphrase";
+
+    fn fixture(name: &str) -> &'static str {
+        match name {
+            "tickers" => include_str!("../../../fixtures/okex/tickers.json"),
+            "ticker" => include_str!("../../../fixtures/okex/ticker.json"),
+            "books" => include_str!("../../../fixtures/okex/books.json"),
+            "candles" => include_str!("../../../fixtures/okex/candles.json"),
+            "order" => include_str!("../../../fixtures/okex/order.json"),
+            "cancel_order" => include_str!("../../../fixtures/okex/cancel_order.json"),
+            "amend_order" => include_str!("../../../fixtures/okex/amend_order.json"),
+            "order_query" => include_str!("../../../fixtures/okex/order_query.json"),
+            "positions" => include_str!("../../../fixtures/okex/positions.json"),
+            "positions_hedge" => include_str!("../../../fixtures/okex/positions_hedge.json"),
+            "balance" => include_str!("../../../fixtures/okex/balance.json"),
+            "order_rejected" => include_str!("../../../fixtures/okex/order_rejected.json"),
+            "instruments" => include_str!("../../../fixtures/okex/instruments.json"),
+            "account_config" => include_str!("../../../fixtures/okex/account_config.json"),
+            "set_leverage" => include_str!("../../../fixtures/okex/set_leverage.json"),
+            _ => unreachable!(),
+        }
+    }
+
+    fn json(body: &'static str) -> Response {
+        ([("content-type", "application/json")], body).into_response()
+    }
+
+    /// 校验签名头, 失败时返回与 OKX 一致的错误响应
+    fn check_signed(method: &str, uri: &Uri, headers: &HeaderMap, body: &[u8]) -> Option<Response> {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let request_path = uri.path_and_query().map(|p| p.as_str()).unwrap_or("");
+        let body = std::str::from_utf8(body).unwrap_or_default();
+
+        if header("OK-ACCESS-KEY") != Some(API_KEY)
+            || header("OK-ACCESS-PASSPHRASE") != Some(PASSPHRASE)
+        {
+            return Some(
+                (
+                    StatusCode::UNAUTHORIZED,
+                    r#"{"msg":"Invalid OK-ACCESS-KEY","code":"50111"}"#,
+                )
+                    .into_response(),
+            );
+        }
+        if header("x-simulated-trading") != Some("1") {
+            return Some((
+                StatusCode::UNAUTHORIZED,
+                r#"{"msg":"The current APIKey does not match the current environment.","code":"50101"}"#,
+            )
+                .into_response());
+        }
+        let timestamp = header("OK-ACCESS-TIMESTAMP").unwrap_or_default();
+        if header("OK-ACCESS-SIGN")
+            != Some(&sign_request(
+                API_SECRET,
+                timestamp,
+                method,
+                request_path,
+                body,
+            ))
+        {
+            return Some(
+                (
+                    StatusCode::UNAUTHORIZED,
+                    r#"{"msg":"Invalid Sign","code":"50113"}"#,
+                )
+                    .into_response(),
+            );
+        }
+        None
+    }
+
+    fn signed_get(name: &'static str) -> axum::routing::MethodRouter {
+        get(move |uri: Uri, headers: HeaderMap| async move {
+            check_signed("GET", &uri, &headers, b"").unwrap_or_else(|| json(fixture(name)))
+        })
+    }
+
+    /// 替身收到的交易请求体
+    type RequestLog = Arc<std::sync::Mutex<Vec<serde_json::Value>>>;
+
+    fn signed_post(name: &'static str, log: RequestLog) -> axum::routing::MethodRouter {
+        post(
+            move |uri: Uri, headers: HeaderMap, body: Bytes| async move {
+                if let Some(resp) = check_signed("POST", &uri, &headers, &body) {
+                    return resp;
+                }
+                let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                log.lock().unwrap().push(value.clone());
+                // 超过 10000 张的订单回放拒单响应
+                let sz = value.get("sz").and_then(|v| v.as_str()).unwrap_or_default();
+                if parse_f64(sz) > 10000.0 {
+                    return json(fixture("order_rejected"));
+                }
+                json(fixture(name))
+            },
+        )
+    }
+
+    /// 启动本地 OKX 替身, 回放录制的响应 (pos_mode 为账户持仓模式, 双向持仓时同时持有多空两腿)
+    async fn spawn_stand_in(pos_mode: &'static str) -> (String, RequestLog) {
+        let log = RequestLog::default();
+        let account_config = get(move |uri: Uri, headers: HeaderMap| async move {
+            check_signed("GET", &uri, &headers, b"").unwrap_or_else(|| {
+                let body = fixture("account_config").replace("net_mode", pos_mode);
+                ([("content-type", "application/json")], body).into_response()
+            })
+        });
+        let positions = if pos_mode == "long_short_mode" {
+            "positions_hedge"
+        } else {
+            "positions"
+        };
+        let app = Router::new()
+            .route(
+                "/api/v5/market/tickers",
+                get(|| async { json(fixture("tickers")) }),
+            )
+            .route(
+                "/api/v5/market/ticker",
+                get(|| async { json(fixture("ticker")) }),
+            )
+            .route(
+                "/api/v5/market/books",
+                get(|| async { json(fixture("books")) }),
+            )
+            .route(
+                "/api/v5/market/candles",
+                get(|| async { json(fixture("candles")) }),
+            )
+            .route(
+                "/api/v5/public/instruments",
+                get(|| async { json(fixture("instruments")) }),
+            )
+            .route(
+                "/api/v5/trade/order",
+                signed_post("order", log.clone()).merge(signed_get("order_query")),
+            )
+            .route(
+                "/api/v5/trade/cancel-order",
+                signed_post("cancel_order", log.clone()),
+            )
+            .route(
+                "/api/v5/trade/amend-order",
+                signed_post("amend_order", log.clone()),
+            )
+            .route("/api/v5/account/positions", signed_get(positions))
+            .route("/api/v5/account/balance", signed_get("balance"))
+            .route(
+                "/api/v5/account/set-leverage",
+                signed_post("set_leverage", log.clone()),
+            )
+            .route("/api/v5/account/config", account_config);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{}", addr), log)
+    }
+
+    fn limit_order(side: OrderSide, quantity: f64) -> OrderRequest {
+        OrderRequest {
+            symbol: "BTC-USDT".to_string(),
+            side,
+            order_type: OrderType::Limit,
+            quantity,
+            price: Some(105000.0),
+            time_in_force: None,
+            reduce_only: false,
+        }
+    }
+
+    fn client(base_url: String) -> OkexRestClient {
+        OkexRestClient::new(&OkexConfig {
+            api_key: API_KEY.to_string(),
+            api_secret: API_SECRET.to_string(),
+            passphrase: PASSPHRASE.to_string(),
+            base_url: Some(base_url),
+            mock_mode: false,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_inst_id_mapping() {
+        assert_eq!(to_inst_id("BTCUSDT", "SWAP"), "BTC-USDT-SWAP");
+        assert_eq!(to_inst_id("BTC-USDT", "SWAP"), "BTC-USDT-SWAP");
+        assert_eq!(to_inst_id("eth-usdt-swap", "SWAP"), "ETH-USDT-SWAP");
+        assert_eq!(to_inst_id("BTC-USD-251226", "FUTURES"), "BTC-USD-251226");
+        assert_eq!(to_inst_id("SOLUSDT", "SPOT"), "SOL-USDT");
+        assert_eq!(from_inst_id("BTC-USDT-SWAP"), "BTCUSDT");
+        assert_eq!(from_inst_id("SOL-USDT"), "SOLUSDT");
+        assert_eq!(from_inst_id("BTC-USD-251226"), "BTC-USD-251226");
+    }
+
+    #[tokio::test]
+    async fn test_market_data_against_stand_in() {
+        let client = client(spawn_stand_in("net_mode").await.0);
+
+        // 结果以调用方的交易对为键
+        let prices = client.get_prices(&["BTCUSDT".to_string()]).await.unwrap();
+        assert_eq!(prices.prices.len(), 1);
+        assert_eq!(prices.prices["BTCUSDT"], 106188.3);
+
+        let book = client.get_orderbook("BTC-USDT-SWAP", 5).await.unwrap();
+        assert_eq!(book.bids[0].price, 106188.2);
+        assert_eq!(book.asks[0].quantity, 85.0);
+
+        let klines = client.get_klines("BTCUSDT", "1h", Some(3)).await.unwrap();
+        assert_eq!(klines.klines.len(), 3);
+        // 倒序返回的K线转为正序
+        assert!(klines.klines[0].timestamp < klines.klines[2].timestamp);
+        assert_eq!(klines.klines[2].close, 106188.3);
+
+        let ticker = client.get_ticker_24h("BTC-USDT").await.unwrap();
+        assert_eq!(ticker.symbol, "BTC-USDT");
+        assert!((ticker.change_24h - 1.2474256).abs() < 1e-6);
+    }
+
+    #[tokio::test]
+    async fn test_signed_trading_against_stand_in() {
+        let (base_url, log) = spawn_stand_in("net_mode").await;
+        let client = client(base_url);
+
+        // 0.02 BTC = 2 张 (ctVal 0.01), 不超过 3 张空头持仓, 为减仓单
+        let response = client
+            .place_order(&limit_order(OrderSide::Buy, 0.02))
+            .await
+            .unwrap();
+        assert_eq!(response.order_id, "BTC-USDT-SWAP:2950213541325959168");
+        let body = log.lock().unwrap()[0].clone();
+        assert_eq!(body["sz"], "2");
+        assert_eq!(body["reduceOnly"], true);
+        assert!(body.get("posSide").is_none());
+
+        let amended = client
+            .amend_order(&response.order_id, None, Some(105100.0))
+            .await
+            .unwrap();
+        assert_eq!(amended.order_id, response.order_id);
+
+        let order = client.get_order(&response.order_id).await.unwrap();
+        assert!(matches!(order.status, OrderStatus::PartiallyFilled));
+        // 订单、持仓以内部交易对为键
+        assert_eq!(order.symbol, "BTCUSDT");
+        assert!((order.quantity - 0.02).abs() < 1e-12);
+        assert!((order.filled_quantity - 0.01).abs() < 1e-12);
+
+        let cancelled = client.cancel_order(&response.order_id).await.unwrap();
+        assert!(matches!(cancelled.status, OrderStatus::Cancelled));
+
+        let positions = client.get_positions().await.unwrap();
+        let btc = &positions.positions["BTCUSDT"];
+        assert_eq!(btc.direction.as_deref(), Some("short"));
+        assert!((btc.quantity - 0.03).abs() < 1e-12);
+        assert_eq!(btc.leverage, Some(5));
+
+        let balance = client.get_balance().await.unwrap();
+        assert_eq!(balance.total_balance, 25012.5);
+        assert_eq!(balance.available, 18650.4);
+
+        client.set_leverage("BTCUSDT", 3.0).await.unwrap();
+        let body = log.lock().unwrap().last().cloned().unwrap();
+        assert_eq!(body["instId"], "BTC-USDT-SWAP");
+        assert_eq!(body["lever"], "3");
+        assert_eq!(body["mgnMode"], "cross");
+    }
+
+    #[tokio::test]
+    async fn test_hedge_mode_sets_pos_side() {
+        let (base_url, log) = spawn_stand_in("long_short_mode").await;
+        let client = client(base_url);
+
+        // 多空两腿轧差为一条净空头, 以内部交易对为键
+        let positions = client.get_positions().await.unwrap();
+        assert_eq!(positions.positions.len(), 1);
+        let btc = &positions.positions["BTCUSDT"];
+        assert_eq!(btc.direction.as_deref(), Some("short"));
+        assert!((btc.quantity - 0.01).abs() < 1e-12);
+        assert_eq!(btc.entry_price, 106500.2);
+        assert!((btc.unrealized_pnl - 17.116).abs() < 1e-9);
+        assert!((btc.margin.unwrap() - 1061.885).abs() < 1e-9);
+
+        // 不超过对应持仓的订单平仓 (3 张空头, 2 张多头), 超过时开仓
+        client
+            .place_order(&limit_order(OrderSide::Buy, 0.03))
+            .await
+            .unwrap();
+        client
+            .place_order(&limit_order(OrderSide::Buy, 0.05))
+            .await
+            .unwrap();
+        client
+            .place_order(&limit_order(OrderSide::Sell, 0.01))
+            .await
+            .unwrap();
+        client
+            .place_order(&OrderRequest {
+                reduce_only: true,
+                ..limit_order(OrderSide::Buy, 0.05)
+            })
+            .await
+            .unwrap();
+
+        let bodies = log.lock().unwrap().clone();
+        let pos_sides: Vec<_> = bodies.iter().map(|b| b["posSide"].clone()).collect();
+        assert_eq!(pos_sides, ["short", "long", "long", "short"]);
+        assert!(bodies.iter().all(|b| b.get("reduceOnly").is_none()));
+        assert_eq!(bodies[1]["sz"], "5");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_order_surfaces_s_code() {
+        let client = client(spawn_stand_in("net_mode").await.0);
+
+        // 低于最小下单量时本地拒绝
+        let err = client
+            .place_order(&limit_order(OrderSide::Sell, 0.00001))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, OkexApiError::InvalidOrder(_)));
+
+        let err = client
+            .place_order(&OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Sell,
+                order_type: OrderType::Market,
+                quantity: 1000.0,
+                price: None,
+                time_in_force: None,
+                reduce_only: false,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Some("51008"));
+        assert!(matches!(
+            BrokerError::from(err),
+            BrokerError::InsufficientFunds(_)
+        ));
+    }
+}
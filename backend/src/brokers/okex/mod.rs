@@ -1,7 +1,11 @@
 // OKEX broker implementation
 
 pub mod broker;
+pub mod client;
+pub mod stream;
 pub mod types;
 
-pub use broker::OkexBroker;
+pub use broker::{OkexBroker, OkexMode};
+pub use client::{OkexApiError, OkexRestClient};
+pub use stream::OkexMarketStream;
 pub use types::*;
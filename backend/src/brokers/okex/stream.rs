@@ -0,0 +1,640 @@
+// OKX WebSocket 行情推送
+// OKX v5 public / business WebSocket market data
+
+use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+use tracing::{debug, info, warn};
+
+use super::client::{parse_f64, parse_i64, parse_side, to_bar, to_inst_id, to_level};
+use super::types::OkexConfig;
+use crate::brokers::error::BrokerError;
+use crate::brokers::stream::*;
+use crate::brokers::types::*;
+
+/// 实盘 public 频道地址 (ticker / 成交 / 深度)
+pub const OKX_WS_PUBLIC_URL: &str = "wss://ws.okx.com:8443/ws/v5/public";
+
+/// 实盘 business 频道地址 (K线)
+pub const OKX_WS_BUSINESS_URL: &str = "wss://ws.okx.com:8443/ws/v5/business";
+
+/// 模拟盘 public 频道地址
+pub const OKX_WS_DEMO_PUBLIC_URL: &str = "wss://wspap.okx.com:8443/ws/v5/public";
+
+/// 模拟盘 business 频道地址
+pub const OKX_WS_DEMO_BUSINESS_URL: &str = "wss://wspap.okx.com:8443/ws/v5/business";
+
+/// 心跳间隔: 30 秒内无消息服务器会断开连接, 空闲超过该时长发送 `ping`
+const PING_INTERVAL: Duration = Duration::from_secs(25);
+
+/// OKX 行情推送
+///
+/// ticker / 成交 / 深度走 public 频道, K线走 business 频道, 各自维护一条连接。
+/// 断线重连后重新订阅; 深度序号 (`prevSeqId`) 不连续时单独重订阅该合约的 `books`
+/// 以获取新快照。
+pub struct OkexMarketStream {
+    public_url: String,
+    business_url: String,
+    inst_type: String,
+    ping_interval: Duration,
+}
+
+/// 订阅上下文: 将推送中的 instId / 频道名还原为调用方使用的代码
+#[derive(Debug, Default)]
+struct SubscriptionContext {
+    /// instId -> 订阅时的 symbol
+    symbols: HashMap<String, String>,
+    /// 频道名 (candle1H) -> 周期 (1h)
+    intervals: HashMap<String, String>,
+}
+
+impl OkexMarketStream {
+    pub fn new(config: &OkexConfig) -> Self {
+        let (public_url, business_url) = match &config.ws_url {
+            // 自定义地址视为 public 地址, business 地址按路径推导
+            Some(url) => (
+                url.clone(),
+                match url.strip_suffix("/public") {
+                    Some(base) => format!("{}/business", base),
+                    None => url.clone(),
+                },
+            ),
+            None if config.simulated => (
+                OKX_WS_DEMO_PUBLIC_URL.to_string(),
+                OKX_WS_DEMO_BUSINESS_URL.to_string(),
+            ),
+            None => (
+                OKX_WS_PUBLIC_URL.to_string(),
+                OKX_WS_BUSINESS_URL.to_string(),
+            ),
+        };
+
+        Self {
+            public_url,
+            business_url,
+            inst_type: config.inst_type.clone(),
+            ping_interval: PING_INTERVAL,
+        }
+    }
+
+    /// 自定义心跳间隔
+    pub fn with_ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// 启动后台连接并返回事件流
+    pub fn subscribe(&self, subscription: StreamSubscription) -> MarketEventStream {
+        let mut context = SubscriptionContext::default();
+        let mut public_args = Vec::new();
+        let mut business_args = Vec::new();
+
+        for symbol in &subscription.symbols {
+            let inst_id = to_inst_id(symbol, &self.inst_type);
+            context.symbols.insert(inst_id.clone(), symbol.clone());
+
+            for channel in &subscription.channels {
+                let name = match channel {
+                    MarketChannel::Ticker => "tickers".to_string(),
+                    MarketChannel::Trades => "trades".to_string(),
+                    MarketChannel::Depth => "books".to_string(),
+                    MarketChannel::Kline(interval) => {
+                        let name = format!("candle{}", to_bar(interval));
+                        context.intervals.insert(name.clone(), interval.clone());
+                        name
+                    }
+                };
+                let arg = json!({ "channel": name, "instId": inst_id });
+                if name.starts_with("candle") {
+                    business_args.push(arg);
+                } else {
+                    public_args.push(arg);
+                }
+            }
+        }
+
+        let context = Arc::new(context);
+        let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+        let mut tasks = Vec::new();
+        for (url, args) in [
+            (&self.public_url, public_args),
+            (&self.business_url, business_args),
+        ] {
+            if !args.is_empty() {
+                tasks.push(tokio::spawn(run(
+                    url.clone(),
+                    args,
+                    context.clone(),
+                    self.ping_interval,
+                    tx.clone(),
+                )));
+            }
+        }
+        MarketEventStream::new(rx, tasks)
+    }
+}
+
+/// 连接循环: 断线后按指数退避重连, 消费方 drop 流后退出
+async fn run(
+    url: String,
+    args: Vec<serde_json::Value>,
+    context: Arc<SubscriptionContext>,
+    ping_interval: Duration,
+    tx: mpsc::Sender<MarketEvent>,
+) {
+    let mut attempt = 0u32;
+
+    while !tx.is_closed() {
+        match run_session(&url, &args, &context, ping_interval, &tx, &mut attempt).await {
+            Ok(()) => info!("OKX stream {} closed, reconnecting", url),
+            Err(e) => warn!("OKX stream {} error: {}", url, e),
+        }
+        if tx.is_closed() {
+            break;
+        }
+        tokio::time::sleep(reconnect_delay(attempt)).await;
+        attempt = attempt.saturating_add(1);
+    }
+}
+
+async fn run_session(
+    url: &str,
+    args: &[serde_json::Value],
+    context: &SubscriptionContext,
+    ping_interval: Duration,
+    tx: &mpsc::Sender<MarketEvent>,
+    attempt: &mut u32,
+) -> Result<(), BrokerError> {
+    let mut ws = connect_ws(url).await?;
+    *attempt = 0;
+
+    send_op(&mut ws, "subscribe", args).await?;
+    debug!("OKX stream subscribed: {:?}", args);
+
+    // 每次连接都从快照重新构建
+    let mut books: HashMap<String, LocalOrderBook> = HashMap::new();
+    let mut awaiting_pong = false;
+
+    loop {
+        let message = match tokio::time::timeout(ping_interval, ws.next()).await {
+            Err(_) if awaiting_pong => {
+                return Err(BrokerError::Network("OKX heartbeat timeout".into()));
+            }
+            Err(_) => {
+                ws.send(WsMessage::text("ping"))
+                    .await
+                    .map_err(|e| BrokerError::Network(e.to_string()))?;
+                awaiting_pong = true;
+                continue;
+            }
+            Ok(None) => return Ok(()),
+            Ok(Some(message)) => message.map_err(|e| BrokerError::Network(e.to_string()))?,
+        };
+        awaiting_pong = false;
+
+        let text = match message {
+            WsMessage::Text(text) => text,
+            WsMessage::Close(_) => return Ok(()),
+            _ => continue,
+        };
+        if text.as_str() == "pong" {
+            continue;
+        }
+
+        let events = match parse_frame(&text, context) {
+            Some(PushFrame::Events(events)) => events,
+            Some(PushFrame::Depth { inst_id, update }) => {
+                let book = books
+                    .entry(inst_id.clone())
+                    .or_insert_with(|| LocalOrderBook::new(update.symbol.clone()));
+                match book.apply(&update) {
+                    Ok(true) => vec![MarketEvent::Depth(update)],
+                    Ok(false) => vec![],
+                    // 等待重新订阅后的快照
+                    Err(OrderBookError::NotInitialized { .. }) => vec![],
+                    Err(e) => {
+                        warn!("OKX depth {}, resubscribing {}", e, inst_id);
+                        book.reset();
+                        let arg = [json!({ "channel": "books", "instId": inst_id })];
+                        send_op(&mut ws, "unsubscribe", &arg).await?;
+                        send_op(&mut ws, "subscribe", &arg).await?;
+                        vec![]
+                    }
+                }
+            }
+            Some(PushFrame::Error { code, msg }) => {
+                warn!("OKX stream error {}: {}", code, msg);
+                vec![]
+            }
+            None => vec![],
+        };
+
+        for event in events {
+            if tx.send(event).await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+async fn send_op(
+    ws: &mut WsStream,
+    op: &str,
+    args: &[serde_json::Value],
+) -> Result<(), BrokerError> {
+    let request = json!({ "op": op, "args": args });
+    ws.send(WsMessage::text(request.to_string()))
+        .await
+        .map_err(|e| BrokerError::Network(e.to_string()))
+}
+
+// ============================================================================
+// 消息解析
+// ============================================================================
+
+/// 解析后的推送帧
+#[derive(Debug)]
+enum PushFrame {
+    /// 可直接推送的事件
+    Events(Vec<MarketEvent>),
+    /// 深度快照/增量 (需经本地订单簿校验)
+    Depth {
+        inst_id: String,
+        update: DepthUpdate,
+    },
+    /// `{"event":"error"}` 回执
+    Error { code: String, msg: String },
+}
+
+fn parse_frame(text: &str, context: &SubscriptionContext) -> Option<PushFrame> {
+    let push: RawPush = serde_json::from_str(text).ok()?;
+
+    if let Some(event) = push.event {
+        return (event == "error").then_some(PushFrame::Error {
+            code: push.code,
+            msg: push.msg,
+        });
+    }
+
+    let arg = push.arg?;
+    let data = push.data?;
+    let symbol = context
+        .symbols
+        .get(&arg.inst_id)
+        .cloned()
+        .unwrap_or_else(|| arg.inst_id.clone());
+
+    match arg.channel.as_str() {
+        "tickers" => {
+            let tickers: Vec<RawTicker> = serde_json::from_value(data).ok()?;
+            let events = tickers
+                .into_iter()
+                .map(|t| {
+                    let last = parse_f64(&t.last);
+                    let open = parse_f64(&t.open24h);
+                    MarketEvent::Ticker(Ticker24h {
+                        symbol: symbol.clone(),
+                        last_price: last,
+                        change_24h: if open > 0.0 {
+                            (last - open) / open * 100.0
+                        } else {
+                            0.0
+                        },
+                        high_24h: parse_f64(&t.high24h),
+                        low_24h: parse_f64(&t.low24h),
+                        volume_24h: parse_f64(&t.vol_ccy24h),
+                        open_interest: None,
+                        timestamp: parse_i64(&t.ts) / 1000,
+                    })
+                })
+                .collect();
+            Some(PushFrame::Events(events))
+        }
+        "trades" => {
+            let trades: Vec<RawTrade> = serde_json::from_value(data).ok()?;
+            let events = trades
+                .into_iter()
+                .map(|t| {
+                    MarketEvent::Trade(MarketTrade {
+                        symbol: symbol.clone(),
+                        trade_id: t.trade_id,
+                        price: parse_f64(&t.px),
+                        quantity: parse_f64(&t.sz),
+                        side: parse_side(&t.side),
+                        timestamp: parse_i64(&t.ts) / 1000,
+                    })
+                })
+                .collect();
+            Some(PushFrame::Events(events))
+        }
+        "books" => {
+            let books: Vec<RawBook> = serde_json::from_value(data).ok()?;
+            let book = books.into_iter().next()?;
+            let snapshot = push.action.as_deref() == Some("snapshot");
+            let seq_id = book.seq_id.max(0) as u64;
+            Some(PushFrame::Depth {
+                inst_id: arg.inst_id,
+                update: DepthUpdate {
+                    symbol,
+                    kind: if snapshot {
+                        DepthUpdateKind::Snapshot
+                    } else {
+                        DepthUpdateKind::Diff
+                    },
+                    first_update_id: seq_id,
+                    final_update_id: seq_id,
+                    prev_update_id: (!snapshot).then_some(book.prev_seq_id.max(0) as u64),
+                    bids: book.bids.iter().filter_map(|l| to_level(l)).collect(),
+                    asks: book.asks.iter().filter_map(|l| to_level(l)).collect(),
+                    timestamp: parse_i64(&book.ts) / 1000,
+                },
+            })
+        }
+        channel if channel.starts_with("candle") => {
+            let interval = context
+                .intervals
+                .get(channel)
+                .cloned()
+                .unwrap_or_else(|| channel.trim_start_matches("candle").to_string());
+            let rows: Vec<Vec<String>> = serde_json::from_value(data).ok()?;
+            let events = rows
+                .iter()
+                // 第 9 列 confirm = "1" 表示K线已收盘
+                .filter(|row| row.get(8).map(String::as_str) == Some("1"))
+                .map(|row| MarketEvent::KlineClosed {
+                    symbol: symbol.clone(),
+                    interval: interval.clone(),
+                    kline: Kline {
+                        timestamp: parse_i64(&row[0]) / 1000,
+                        open: parse_f64(&row[1]),
+                        high: parse_f64(&row[2]),
+                        low: parse_f64(&row[3]),
+                        close: parse_f64(&row[4]),
+                        volume: parse_f64(&row[5]),
+                        open_interest: None,
+                    },
+                })
+                .collect();
+            Some(PushFrame::Events(events))
+        }
+        _ => None,
+    }
+}
+
+// ============================================================================
+// 推送原始结构
+// ============================================================================
+
+#[derive(Deserialize)]
+struct RawPush {
+    #[serde(default)]
+    event: Option<String>,
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    msg: String,
+    #[serde(default)]
+    arg: Option<RawArg>,
+    #[serde(default)]
+    action: Option<String>,
+    #[serde(default)]
+    data: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawArg {
+    channel: String,
+    #[serde(default)]
+    inst_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTicker {
+    last: String,
+    #[serde(default)]
+    open24h: String,
+    #[serde(default)]
+    high24h: String,
+    #[serde(default)]
+    low24h: String,
+    #[serde(default)]
+    vol_ccy24h: String,
+    ts: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawTrade {
+    trade_id: String,
+    px: String,
+    sz: String,
+    side: String,
+    ts: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawBook {
+    asks: Vec<Vec<String>>,
+    bids: Vec<Vec<String>>,
+    ts: String,
+    #[serde(default)]
+    seq_id: i64,
+    #[serde(default)]
+    prev_seq_id: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::ws::{Message as AxumMessage, WebSocket, WebSocketUpgrade};
+    use axum::routing::get;
+    use axum::Router;
+    use std::sync::Mutex;
+
+    fn fixture(name: &str) -> &'static str {
+        match name {
+            "ws_tickers" => include_str!("../../../fixtures/okex/ws_tickers.json"),
+            "ws_trades" => include_str!("../../../fixtures/okex/ws_trades.json"),
+            "ws_books_snapshot" => include_str!("../../../fixtures/okex/ws_books_snapshot.json"),
+            "ws_books_update" => include_str!("../../../fixtures/okex/ws_books_update.json"),
+            "ws_books_gap" => include_str!("../../../fixtures/okex/ws_books_gap.json"),
+            "ws_candle" => include_str!("../../../fixtures/okex/ws_candle.json"),
+            _ => unreachable!("unknown fixture {}", name),
+        }
+    }
+
+    fn context() -> SubscriptionContext {
+        let mut context = SubscriptionContext::default();
+        context
+            .symbols
+            .insert("BTC-USDT-SWAP".to_string(), "BTCUSDT".to_string());
+        context
+            .intervals
+            .insert("candle1m".to_string(), "1m".to_string());
+        context
+    }
+
+    /// 本地 WebSocket 替身, 记录客户端发来的所有文本帧
+    ///
+    /// - public: 订阅后推送 ticker / 成交 / 快照 / 增量 / 缺口增量,
+    ///   收到 books 重订阅后重新推送快照; 收到 `ping` 回复 `pong`
+    /// - business: 推送一根已收盘K线
+    async fn spawn_stand_in(received: Arc<Mutex<Vec<String>>>) -> String {
+        async fn serve(mut socket: WebSocket, business: bool, received: Arc<Mutex<Vec<String>>>) {
+            while let Some(Ok(AxumMessage::Text(text))) = socket.recv().await {
+                received.lock().unwrap().push(text.to_string());
+                let frames: Vec<&str> = if text == "ping" {
+                    vec!["pong"]
+                } else if business {
+                    vec![fixture("ws_candle")]
+                } else if text.contains("\"unsubscribe\"") {
+                    vec![]
+                } else if received.lock().unwrap().len() > 1 {
+                    vec![fixture("ws_books_snapshot")]
+                } else {
+                    vec![
+                        r#"{"event":"subscribe","arg":{"channel":"books","instId":"BTC-USDT-SWAP"},"connId":"a4d3ae55"}"#,
+                        fixture("ws_tickers"),
+                        fixture("ws_trades"),
+                        fixture("ws_books_snapshot"),
+                        fixture("ws_books_update"),
+                        fixture("ws_books_gap"),
+                    ]
+                };
+                for frame in frames {
+                    let _ = socket.send(AxumMessage::text(frame)).await;
+                }
+            }
+        }
+
+        let public = received.clone();
+        let app = Router::new()
+            .route(
+                "/ws/v5/public",
+                get(move |ws: WebSocketUpgrade| {
+                    let received = public.clone();
+                    async move { ws.on_upgrade(move |socket| serve(socket, false, received)) }
+                }),
+            )
+            .route(
+                "/ws/v5/business",
+                get(move |ws: WebSocketUpgrade| {
+                    let received = Arc::new(Mutex::new(Vec::new()));
+                    async move { ws.on_upgrade(move |socket| serve(socket, true, received)) }
+                }),
+            );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        format!("{}", addr)
+    }
+
+    #[test]
+    fn test_parse_frames() {
+        let context = context();
+
+        let Some(PushFrame::Events(events)) = parse_frame(fixture("ws_tickers"), &context) else {
+            panic!("expected ticker");
+        };
+        let MarketEvent::Ticker(ticker) = &events[0] else {
+            panic!("expected ticker");
+        };
+        assert_eq!(ticker.symbol, "BTCUSDT");
+        assert!((ticker.change_24h - 1.2474256).abs() < 1e-6);
+
+        let Some(PushFrame::Depth { update, .. }) =
+            parse_frame(fixture("ws_books_update"), &context)
+        else {
+            panic!("expected books update");
+        };
+        assert_eq!(update.kind, DepthUpdateKind::Diff);
+        assert_eq!(update.prev_update_id, Some(1432789012));
+        assert_eq!(update.final_update_id, 1432789020);
+
+        let Some(PushFrame::Events(events)) = parse_frame(fixture("ws_candle"), &context) else {
+            panic!("expected candle");
+        };
+        assert!(matches!(
+            &events[0],
+            MarketEvent::KlineClosed { interval, kline, .. } if interval == "1m" && kline.close == 106188.3
+        ));
+
+        // 未收盘K线不产生事件
+        let open_candle = fixture("ws_candle").replace(r#","1"]"#, r#","0"]"#);
+        let Some(PushFrame::Events(events)) = parse_frame(&open_candle, &context) else {
+            panic!("expected candle");
+        };
+        assert!(events.is_empty());
+
+        assert!(matches!(
+            parse_frame(r#"{"event":"error","code":"60018","msg":"Wrong URL or channel"}"#, &context),
+            Some(PushFrame::Error { code, .. }) if code == "60018"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stream_against_stand_in() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let addr = spawn_stand_in(received.clone()).await;
+
+        let config = OkexConfig {
+            ws_url: Some(format!("ws://{}/ws/v5/public", addr)),
+            ..Default::default()
+        };
+        let stream = OkexMarketStream::new(&config).with_ping_interval(Duration::from_millis(200));
+        let mut events = stream.subscribe(
+            StreamSubscription::new(["BTCUSDT"])
+                .with_channel(MarketChannel::Ticker)
+                .with_channel(MarketChannel::Trades)
+                .with_channel(MarketChannel::Depth)
+                .with_channel(MarketChannel::Kline("1m".to_string())),
+        );
+
+        let mut book = LocalOrderBook::new("BTCUSDT");
+        let mut snapshots = 0;
+        let mut seen_ticker = false;
+        let mut seen_trade = false;
+        let mut seen_kline = false;
+
+        while snapshots < 2 || !(seen_ticker && seen_trade && seen_kline) {
+            let event = tokio::time::timeout(Duration::from_secs(5), events.next())
+                .await
+                .expect("stream timed out")
+                .expect("stream ended");
+            match event {
+                MarketEvent::Ticker(t) => seen_ticker = t.symbol == "BTCUSDT",
+                MarketEvent::Trade(t) => seen_trade = matches!(t.side, OrderSide::Buy),
+                MarketEvent::KlineClosed { .. } => seen_kline = true,
+                MarketEvent::Depth(update) => {
+                    // 缺口增量不会被推送
+                    assert!(book.apply(&update).unwrap());
+                    if update.kind == DepthUpdateKind::Snapshot {
+                        snapshots += 1;
+                    } else {
+                        assert_eq!(book.best_ask().unwrap().price, 106188.45);
+                        assert_eq!(book.best_bid().unwrap().price, 106188.3);
+                    }
+                }
+            }
+        }
+
+        // 缺口后重新订阅 books, 空闲时发送心跳
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let received = received.lock().unwrap();
+        assert!(received[0].contains("\"subscribe\""));
+        assert!(received[1].contains("\"unsubscribe\"") && received[1].contains("books"));
+        assert!(received[2].contains("\"subscribe\"") && received[2].contains("books"));
+        assert!(received.iter().any(|m| m == "ping"));
+    }
+}
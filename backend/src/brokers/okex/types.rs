@@ -24,15 +24,31 @@ pub struct OkexConfig {
     #[serde(default)]
     pub ws_url: Option<String>,
 
-    /// 是否启用模拟模式 (不连接真实OKEX)
+    /// 是否启用模拟模式 (不连接真实OKEX, 使用随机游走行情)
     #[serde(default = "default_true")]
     pub mock_mode: bool,
+
+    /// 产品类型: SPOT / SWAP / FUTURES
+    #[serde(default = "default_inst_type")]
+    pub inst_type: String,
+
+    /// 衍生品保证金模式: cross (全仓) / isolated (逐仓)
+    #[serde(default = "default_td_mode")]
+    pub td_mode: String,
 }
 
 fn default_true() -> bool {
     true
 }
 
+fn default_inst_type() -> String {
+    "SWAP".to_string()
+}
+
+fn default_td_mode() -> String {
+    "cross".to_string()
+}
+
 impl Default for OkexConfig {
     fn default() -> Self {
         Self {
@@ -43,6 +59,8 @@ impl Default for OkexConfig {
             base_url: None,
             ws_url: None,
             mock_mode: true,
+            inst_type: default_inst_type(),
+            td_mode: default_td_mode(),
         }
     }
 }
@@ -76,6 +94,14 @@ impl OkexConfig {
             if self.passphrase.is_empty() {
                 return Err("passphrase cannot be empty".to_string());
             }
+
+            if !matches!(self.inst_type.as_str(), "SPOT" | "SWAP" | "FUTURES") {
+                return Err(format!("unsupported inst_type: {}", self.inst_type));
+            }
+
+            if !matches!(self.td_mode.as_str(), "cross" | "isolated") {
+                return Err(format!("unsupported td_mode: {}", self.td_mode));
+            }
         }
 
         Ok(())
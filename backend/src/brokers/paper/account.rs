@@ -0,0 +1,924 @@
+use std::collections::{BTreeSet, HashMap};
+
+use super::types::{PaperAccountSummary, PaperConfig, PaperPosition};
+use crate::brokers::{
+    Balance, BrokerError, BrokerResult, Kline, LocalOrderBook, MarketEvent, Order, OrderRequest,
+    OrderResponse, OrderSide, OrderStatus, OrderType, Orderbook, OrderbookLevel, Position,
+    Positions, Trade,
+};
+
+/// 数量比较容差
+const EPSILON: f64 = 1e-9;
+
+/// 单次补收资金费的最大期数（避免长时间停机后一次性循环过多）
+const MAX_FUNDING_PERIODS: i64 = 1000;
+
+struct PaperOrder {
+    order: Order,
+    /// 是否仍在撮合（挂单中或等待触发）
+    open: bool,
+    /// 止损单是否已触发（非止损单恒为 true）
+    triggered: bool,
+}
+
+impl PaperOrder {
+    fn remaining(&self) -> f64 {
+        (self.order.quantity - self.order.filled_quantity).max(0.0)
+    }
+
+    fn is_buy(&self) -> bool {
+        matches!(self.order.side, OrderSide::Buy)
+    }
+
+    /// 挂单价格是否可以与 `price` 成交
+    fn crosses(&self, price: f64) -> bool {
+        match self.order.price {
+            Some(limit) if self.is_buy() => price <= limit + EPSILON,
+            Some(limit) => price >= limit - EPSILON,
+            None => true,
+        }
+    }
+}
+
+/// 模拟盘账户与撮合引擎
+///
+/// 维护资金、净持仓、订单与成交, 基于最新订单簿与成交价撮合:
+/// - 市价单按对手盘逐档吃单, 每档叠加滑点, 深度不足时剩余部分撤销
+/// - 限价单先以 taker 身份吃掉可成交的档位, 剩余部分挂单, 之后被订单簿或成交价穿越时按限价成交
+/// - 止损单在最新价穿越触发价后转为市价单 (`Stop`) 或限价单 (`StopLimit`), 触发价与限价均取 `price`
+/// - 永续合约按配置的费率周期性收取资金费
+///
+/// 没有订单簿时（仅有成交价/ticker）, taker 订单按最新价加滑点全部成交。
+pub struct PaperAccount {
+    config: PaperConfig,
+    cash: f64,
+    positions: HashMap<String, PaperPosition>,
+    orders: Vec<PaperOrder>,
+    trades: Vec<Trade>,
+    books: HashMap<String, Orderbook>,
+    local_books: HashMap<String, LocalOrderBook>,
+    last_prices: HashMap<String, f64>,
+    fees_paid: f64,
+    /// 平仓成交的盈利/亏损笔数
+    winning_trades: i32,
+    losing_trades: i32,
+    funding_epoch: Option<i64>,
+    next_order_id: u64,
+    next_trade_id: u64,
+}
+
+impl PaperAccount {
+    pub fn new(config: PaperConfig) -> Self {
+        Self {
+            cash: config.initial_balance,
+            config,
+            positions: HashMap::new(),
+            orders: Vec::new(),
+            trades: Vec::new(),
+            books: HashMap::new(),
+            local_books: HashMap::new(),
+            last_prices: HashMap::new(),
+            fees_paid: 0.0,
+            winning_trades: 0,
+            losing_trades: 0,
+            funding_epoch: None,
+            next_order_id: 1,
+            next_trade_id: 1,
+        }
+    }
+
+    pub fn config(&self) -> &PaperConfig {
+        &self.config
+    }
+
+    // ========== 行情输入 ==========
+
+    /// 更新订单簿快照并撮合
+    pub fn update_book(&mut self, book: Orderbook, now: i64) {
+        let symbol = book.symbol.clone();
+        self.books.insert(symbol.clone(), book);
+        self.process_symbol(&symbol, now);
+    }
+
+    /// 更新最新价（ticker / K线收盘）并撮合
+    pub fn update_price(&mut self, symbol: &str, price: f64, now: i64) {
+        self.last_prices.insert(symbol.to_string(), price);
+        self.process_symbol(symbol, now);
+    }
+
+    /// 处理一笔市场成交: 价格穿越的挂单按限价成交, 成交量以该笔成交数量为上限
+    pub fn on_trade(&mut self, symbol: &str, price: f64, quantity: f64, now: i64) {
+        self.last_prices.insert(symbol.to_string(), price);
+
+        let mut liquidity = quantity;
+        for idx in self.open_orders(symbol) {
+            if liquidity <= EPSILON {
+                break;
+            }
+            let order = &self.orders[idx];
+            if !order.triggered || order.order.price.is_none() || !order.crosses(price) {
+                continue;
+            }
+            let limit = order.order.price.unwrap_or(price);
+            let qty = order.remaining().min(liquidity);
+            self.fill(idx, limit, qty, true, now);
+            liquidity -= qty;
+        }
+
+        self.process_symbol(symbol, now);
+    }
+
+    /// 处理一根已收盘的K线
+    ///
+    /// 按 开→低→高→收（阴线为 开→高→低→收）的路径回放, 挂单在价格穿越时按限价成交,
+    /// 成交量以K线成交量为上限; 止损单在极值处触发, 最后以收盘价更新最新价。
+    pub fn on_bar(&mut self, symbol: &str, bar: &Kline, now: i64) {
+        let path = if bar.close >= bar.open {
+            [bar.low, bar.high]
+        } else {
+            [bar.high, bar.low]
+        };
+        for price in path {
+            self.on_trade(symbol, price, bar.volume, now);
+        }
+        self.update_price(symbol, bar.close, now);
+    }
+
+    /// 应用推送或回放的行情事件, 并按事件时间结算资金费
+    pub fn apply_event(&mut self, event: &MarketEvent) {
+        let now = match event {
+            MarketEvent::Ticker(t) => {
+                self.update_price(&t.symbol, t.last_price, t.timestamp);
+                t.timestamp
+            }
+            MarketEvent::Trade(t) => {
+                self.on_trade(&t.symbol, t.price, t.quantity, t.timestamp);
+                t.timestamp
+            }
+            MarketEvent::KlineClosed { symbol, kline, .. } => {
+                self.on_bar(symbol, kline, kline.timestamp);
+                kline.timestamp
+            }
+            MarketEvent::Depth(update) => {
+                let depth = self.config.depth_levels;
+                let book = self
+                    .local_books
+                    .entry(update.symbol.clone())
+                    .or_insert_with(|| LocalOrderBook::new(update.symbol.clone()));
+                match book.apply(update) {
+                    Ok(true) => {
+                        let snapshot = book.to_orderbook(depth);
+                        self.update_book(snapshot, update.timestamp);
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        tracing::warn!("Paper order book out of sync: {}", e);
+                        book.reset();
+                    }
+                }
+                update.timestamp
+            }
+        };
+        self.settle_funding(now);
+    }
+
+    // ========== 交易 ==========
+
+    /// 提交订单
+    pub fn submit(&mut self, mut request: OrderRequest, now: i64) -> BrokerResult<OrderResponse> {
+        if request.quantity <= 0.0 || !request.quantity.is_finite() {
+            return Err(BrokerError::OrderRejected {
+                code: "INVALID_QUANTITY".to_string(),
+                reason: format!("quantity must be positive, got {}", request.quantity),
+            });
+        }
+
+        let is_market = matches!(request.order_type, OrderType::Market);
+        if !is_market && !request.price.is_some_and(|p| p > 0.0) {
+            return Err(BrokerError::OrderRejected {
+                code: "INVALID_PRICE".to_string(),
+                reason: format!("{:?} order requires a positive price", request.order_type),
+            });
+        }
+
+        if request.reduce_only {
+            let current = self
+                .positions
+                .get(&request.symbol)
+                .map(|p| p.quantity)
+                .unwrap_or(0.0);
+            let held = match request.side {
+                OrderSide::Buy => (-current).max(0.0),
+                OrderSide::Sell => current.max(0.0),
+            };
+            if held <= EPSILON {
+                return Err(BrokerError::OrderRejected {
+                    code: "REDUCE_ONLY".to_string(),
+                    reason: format!("no {} position to reduce", request.symbol),
+                });
+            }
+            // 只减仓单最多平掉现有持仓
+            request.quantity = request.quantity.min(held);
+        }
+
+        let reference = match request.price {
+            Some(price) if !is_market => Some(price),
+            _ => self.taker_reference(&request.symbol, &request.side),
+        }
+        .ok_or_else(|| BrokerError::Other(format!("No market data for {}", request.symbol)))?;
+        self.check_margin(&request, reference)?;
+
+        let is_stop = matches!(request.order_type, OrderType::Stop | OrderType::StopLimit);
+        let order_id = format!("PAPER-{}", self.next_order_id);
+        self.next_order_id += 1;
+        self.orders.push(PaperOrder {
+            order: Order {
+                order_id: order_id.clone(),
+                symbol: request.symbol.clone(),
+                side: request.side,
+                order_type: request.order_type,
+                quantity: request.quantity,
+                filled_quantity: 0.0,
+                price: request.price,
+                avg_price: None,
+                status: if is_stop {
+                    OrderStatus::Pending
+                } else {
+                    OrderStatus::Accepted
+                },
+                created_at: now,
+                updated_at: now,
+            },
+            open: true,
+            triggered: !is_stop,
+        });
+
+        let idx = self.orders.len() - 1;
+        if !is_stop {
+            self.execute_taker(idx, now);
+        }
+        self.process_symbol(&request.symbol, now);
+
+        let order = &self.orders[idx].order;
+        Ok(OrderResponse {
+            order_id,
+            status: order.status.clone(),
+            timestamp: now,
+        })
+    }
+
+    /// 撤单
+    pub fn cancel(&mut self, order_id: &str, now: i64) -> BrokerResult<OrderResponse> {
+        let order = self
+            .orders
+            .iter_mut()
+            .find(|o| o.order.order_id == order_id)
+            .ok_or_else(|| BrokerError::OrderRejected {
+                code: "UNKNOWN_ORDER".to_string(),
+                reason: format!("order {} not found", order_id),
+            })?;
+
+        if !order.open {
+            return Err(BrokerError::OrderRejected {
+                code: "ORDER_CLOSED".to_string(),
+                reason: format!("order {} is {:?}", order_id, order.order.status),
+            });
+        }
+
+        order.open = false;
+        order.order.status = OrderStatus::Cancelled;
+        order.order.updated_at = now;
+        Ok(OrderResponse {
+            order_id: order_id.to_string(),
+            status: OrderStatus::Cancelled,
+            timestamp: now,
+        })
+    }
+
+    // ========== 资金费 ==========
+
+    /// 按费率对某品种持仓收取一次资金费, 返回支付金额（负数为收入）
+    pub fn apply_funding(&mut self, symbol: &str, rate: f64) -> f64 {
+        let Some(mark) = self.mark_price(symbol) else {
+            return 0.0;
+        };
+        let Some(position) = self.positions.get_mut(symbol) else {
+            return 0.0;
+        };
+
+        let payment = position.quantity * mark * rate;
+        position.funding_paid += payment;
+        self.cash -= payment;
+        payment
+    }
+
+    /// 结算到 `now` 为止的资金费周期（首次调用只记录当前周期）
+    pub fn settle_funding(&mut self, now: i64) {
+        let interval = self.config.funding_interval_secs;
+        if interval <= 0 || self.config.perpetuals.is_empty() {
+            return;
+        }
+
+        let epoch = now.div_euclid(interval);
+        let periods = match self.funding_epoch {
+            Some(last) => (epoch - last).clamp(0, MAX_FUNDING_PERIODS),
+            None => 0,
+        };
+        self.funding_epoch = Some(epoch.max(self.funding_epoch.unwrap_or(epoch)));
+
+        let rates: Vec<(String, f64)> = self
+            .config
+            .perpetuals
+            .iter()
+            .map(|(s, r)| (s.clone(), *r))
+            .collect();
+        for _ in 0..periods {
+            for (symbol, rate) in &rates {
+                self.apply_funding(symbol, *rate);
+            }
+        }
+    }
+
+    // ========== 查询 ==========
+
+    pub fn order(&self, order_id: &str) -> Option<Order> {
+        self.orders
+            .iter()
+            .find(|o| o.order.order_id == order_id)
+            .map(|o| o.order.clone())
+    }
+
+    pub fn orders(&self, symbol: Option<&str>) -> Vec<Order> {
+        self.orders
+            .iter()
+            .filter(|o| symbol.is_none_or(|s| o.order.symbol == s))
+            .map(|o| o.order.clone())
+            .collect()
+    }
+
+    pub fn trades(&self) -> Vec<Trade> {
+        self.trades.clone()
+    }
+
+    pub fn position(&self, symbol: &str) -> Option<&PaperPosition> {
+        self.positions.get(symbol)
+    }
+
+    /// 有挂单或持仓的品种（需要持续获取行情）
+    pub fn active_symbols(&self) -> Vec<String> {
+        let mut symbols: BTreeSet<String> = self
+            .orders
+            .iter()
+            .filter(|o| o.open)
+            .map(|o| o.order.symbol.clone())
+            .collect();
+        symbols.extend(
+            self.positions
+                .values()
+                .filter(|p| p.quantity.abs() > EPSILON)
+                .map(|p| p.symbol.clone()),
+        );
+        symbols.into_iter().collect()
+    }
+
+    /// 标记价格: 优先取订单簿中间价, 其次最新价
+    pub fn mark_price(&self, symbol: &str) -> Option<f64> {
+        let mid = self.books.get(symbol).and_then(|b| {
+            let bid = b.bids.first()?.price;
+            let ask = b.asks.first()?.price;
+            Some((bid + ask) / 2.0)
+        });
+        mid.or_else(|| self.last_prices.get(symbol).copied())
+    }
+
+    pub fn summary(&self) -> PaperAccountSummary {
+        let leverage = self.config.leverage.max(1) as f64;
+        let mut unrealized = 0.0;
+        let mut margin_used = 0.0;
+        let mut realized = 0.0;
+        let mut funding = 0.0;
+
+        for p in self.positions.values() {
+            realized += p.realized_pnl;
+            funding += p.funding_paid;
+            if p.quantity.abs() <= EPSILON {
+                continue;
+            }
+            let mark = self.mark_price(&p.symbol).unwrap_or(p.entry_price);
+            unrealized += p.quantity * (mark - p.entry_price);
+            margin_used += p.quantity.abs() * mark / leverage;
+        }
+
+        let order_margin: f64 = self
+            .orders
+            .iter()
+            .filter(|o| o.open && o.triggered)
+            .filter_map(|o| o.order.price.map(|p| o.remaining() * p / leverage))
+            .sum();
+
+        let equity = self.cash + unrealized;
+        PaperAccountSummary {
+            cash: self.cash,
+            equity,
+            unrealized_pnl: unrealized,
+            realized_pnl: realized,
+            fees_paid: self.fees_paid,
+            funding_paid: funding,
+            margin_used,
+            order_margin,
+            available: equity - margin_used - order_margin,
+            winning_trades: self.winning_trades,
+            losing_trades: self.losing_trades,
+        }
+    }
+
+    pub fn balance(&self, now: i64) -> Balance {
+        let summary = self.summary();
+        Balance {
+            total_balance: summary.equity,
+            available: summary.available,
+            margin_used: Some(summary.margin_used),
+            frozen_margin: Some(summary.order_margin),
+            currency: self.config.currency.clone(),
+            timestamp: now,
+        }
+    }
+
+    pub fn positions(&self, now: i64) -> Positions {
+        let leverage = self.config.leverage.max(1);
+        let positions = self
+            .positions
+            .values()
+            .filter(|p| p.quantity.abs() > EPSILON)
+            .map(|p| {
+                let mark = self.mark_price(&p.symbol).unwrap_or(p.entry_price);
+                let position = Position {
+                    symbol: p.symbol.clone(),
+                    entry_price: p.entry_price,
+                    current_price: mark,
+                    quantity: p.quantity.abs(),
+                    unrealized_pnl: p.quantity * (mark - p.entry_price),
+                    direction: Some(if p.quantity > 0.0 { "long" } else { "short" }.to_string()),
+                    leverage: Some(leverage as i32),
+                    margin: Some(p.quantity.abs() * mark / leverage as f64),
+                    timestamp: now,
+                };
+                (p.symbol.clone(), position)
+            })
+            .collect();
+        Positions { positions }
+    }
+
+    // ========== 撮合 ==========
+
+    fn open_orders(&self, symbol: &str) -> Vec<usize> {
+        self.orders
+            .iter()
+            .enumerate()
+            .filter(|(_, o)| o.open && o.order.symbol == symbol)
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// 触发止损单, 并让被订单簿穿越的挂单成交
+    fn process_symbol(&mut self, symbol: &str, now: i64) {
+        for idx in self.open_orders(symbol) {
+            if !self.orders[idx].open {
+                continue;
+            }
+
+            if !self.orders[idx].triggered {
+                let last = self
+                    .last_prices
+                    .get(symbol)
+                    .copied()
+                    .or_else(|| self.mark_price(symbol));
+                let order = &self.orders[idx];
+                let stop = order.order.price.unwrap_or_default();
+                let hit = last.is_some_and(|price| {
+                    if order.is_buy() {
+                        price >= stop
+                    } else {
+                        price <= stop
+                    }
+                });
+                if !hit {
+                    continue;
+                }
+
+                let order = &mut self.orders[idx];
+                order.triggered = true;
+                order.order.status = OrderStatus::Accepted;
+                order.order.updated_at = now;
+                self.execute_taker(idx, now);
+                continue;
+            }
+
+            if self.orders[idx].order.price.is_some() {
+                self.execute_maker(idx, now);
+            }
+        }
+    }
+
+    /// 以 taker 身份吃对手盘; 市价单剩余部分撤销, 限价单剩余部分挂单
+    fn execute_taker(&mut self, idx: usize, now: i64) {
+        let order = &self.orders[idx];
+        let symbol = order.order.symbol.clone();
+        let is_buy = order.is_buy();
+        let is_market = matches!(order.order.order_type, OrderType::Market | OrderType::Stop);
+        let slippage = self.config.slippage_bps / 10_000.0;
+        let slip = |price: f64| {
+            let slipped = if is_buy {
+                price * (1.0 + slippage)
+            } else {
+                price * (1.0 - slippage)
+            };
+            // 滑点不能突破限价
+            match order.order.price {
+                Some(limit) if !is_market && is_buy => slipped.min(limit),
+                Some(limit) if !is_market => slipped.max(limit),
+                _ => slipped,
+            }
+        };
+
+        let mut fills = Vec::new();
+        let mut remaining = order.remaining();
+        match self.books.get_mut(&symbol) {
+            Some(book) if !(if is_buy { &book.asks } else { &book.bids }).is_empty() => {
+                let levels = if is_buy {
+                    &mut book.asks
+                } else {
+                    &mut book.bids
+                };
+                for level in levels.iter_mut().take(self.config.depth_levels) {
+                    if remaining <= EPSILON {
+                        break;
+                    }
+                    if !is_market && !order.crosses(level.price) {
+                        break;
+                    }
+                    let qty = remaining.min(level.quantity);
+                    fills.push((slip(level.price), qty));
+                    level.quantity -= qty;
+                    remaining -= qty;
+                }
+                // 被吃掉的档位从快照中移除, 同一快照内的后续订单不会重复成交
+                levels.retain(|l| l.quantity > EPSILON);
+            }
+            _ => {
+                if let Some(last) = self.last_prices.get(&symbol).copied() {
+                    if is_market || order.crosses(last) {
+                        fills.push((slip(last), remaining));
+                    }
+                }
+            }
+        }
+
+        for (price, qty) in fills {
+            self.fill(idx, price, qty, false, now);
+        }
+
+        let order = &mut self.orders[idx];
+        if is_market {
+            order.open = false;
+            if order.order.filled_quantity <= EPSILON {
+                order.order.status = OrderStatus::Rejected;
+            }
+        }
+    }
+
+    /// 挂单被订单簿穿越时按限价成交（maker）
+    fn execute_maker(&mut self, idx: usize, now: i64) {
+        let order = &self.orders[idx];
+        let Some(limit) = order.order.price else {
+            return;
+        };
+        let Some(book) = self.books.get_mut(&order.order.symbol) else {
+            return;
+        };
+
+        let levels = if order.is_buy() {
+            &mut book.asks
+        } else {
+            &mut book.bids
+        };
+        let mut remaining = order.remaining();
+        let mut filled = 0.0;
+        for level in levels.iter_mut() {
+            if remaining <= EPSILON || !order.crosses(level.price) {
+                break;
+            }
+            let qty = remaining.min(level.quantity);
+            level.quantity -= qty;
+            remaining -= qty;
+            filled += qty;
+        }
+        levels.retain(|l| l.quantity > EPSILON);
+
+        if filled > EPSILON {
+            self.fill(idx, limit, filled, true, now);
+        }
+    }
+
+    /// 记录一笔成交, 更新持仓、资金与订单状态
+    fn fill(&mut self, idx: usize, price: f64, quantity: f64, maker: bool, now: i64) {
+        if quantity <= EPSILON {
+            return;
+        }
+
+        let rate = if maker {
+            self.config.maker_fee_rate
+        } else {
+            self.config.taker_fee_rate
+        };
+        let fee = price * quantity * rate;
+
+        let order = &mut self.orders[idx];
+        let signed = if order.is_buy() { quantity } else { -quantity };
+        let position = self
+            .positions
+            .entry(order.order.symbol.clone())
+            .or_insert_with(|| PaperPosition {
+                symbol: order.order.symbol.clone(),
+                ..Default::default()
+            });
+
+        let old = position.quantity;
+        let realized = if old.abs() <= EPSILON || old.signum() == signed.signum() {
+            let total = old.abs() + quantity;
+            position.entry_price = (position.entry_price * old.abs() + price * quantity) / total;
+            position.quantity += signed;
+            0.0
+        } else {
+            let closing = quantity.min(old.abs());
+            let pnl = closing * (price - position.entry_price) * old.signum();
+            position.quantity += signed;
+            if position.quantity.abs() <= EPSILON {
+                position.quantity = 0.0;
+                position.entry_price = 0.0;
+            } else if position.quantity.signum() != old.signum() {
+                // 反手, 剩余部分以成交价开新仓
+                position.entry_price = price;
+            }
+            pnl
+        };
+        position.realized_pnl += realized;
+        if realized > EPSILON {
+            self.winning_trades += 1;
+        } else if realized < -EPSILON {
+            self.losing_trades += 1;
+        }
+        self.cash += realized - fee;
+        self.fees_paid += fee;
+
+        let filled = order.order.filled_quantity;
+        let avg = order.order.avg_price.unwrap_or(0.0);
+        order.order.avg_price = Some((avg * filled + price * quantity) / (filled + quantity));
+        order.order.filled_quantity = filled + quantity;
+        order.order.updated_at = now;
+        if order.remaining() <= EPSILON {
+            order.order.status = OrderStatus::Filled;
+            order.open = false;
+        } else {
+            order.order.status = OrderStatus::PartiallyFilled;
+        }
+
+        self.trades.push(Trade {
+            trade_id: format!("PAPER-T{}", self.next_trade_id),
+            order_id: order.order.order_id.clone(),
+            symbol: order.order.symbol.clone(),
+            side: order.order.side.clone(),
+            price,
+            quantity,
+            fee,
+            timestamp: now,
+        });
+        self.next_trade_id += 1;
+    }
+
+    /// taker 参考价: 对手盘一档, 没有订单簿时取标记价格
+    fn taker_reference(&self, symbol: &str, side: &OrderSide) -> Option<f64> {
+        let best = self.books.get(symbol).and_then(|b| {
+            let levels: &[OrderbookLevel] = match side {
+                OrderSide::Buy => &b.asks,
+                OrderSide::Sell => &b.bids,
+            };
+            levels.first().map(|l| l.price)
+        });
+        best.or_else(|| self.mark_price(symbol))
+    }
+
+    /// 校验可用保证金是否足以覆盖新增敞口与手续费
+    fn check_margin(&self, request: &OrderRequest, reference: f64) -> BrokerResult<()> {
+        let current = self
+            .positions
+            .get(&request.symbol)
+            .map(|p| p.quantity)
+            .unwrap_or(0.0);
+        let signed = match request.side {
+            OrderSide::Buy => request.quantity,
+            OrderSide::Sell => -request.quantity,
+        };
+        let increase = ((current + signed).abs() - current.abs()).max(0.0);
+
+        let leverage = self.config.leverage.max(1) as f64;
+        let required = increase * reference / leverage
+            + request.quantity * reference * self.config.taker_fee_rate;
+        let available = self.summary().available;
+        if required > available + EPSILON {
+            return Err(BrokerError::InsufficientFunds(format!(
+                "order requires {:.2} {}, available {:.2}",
+                required, self.config.currency, available
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: f64, quantity: f64) -> OrderbookLevel {
+        OrderbookLevel { price, quantity }
+    }
+
+    fn book(bids: Vec<OrderbookLevel>, asks: Vec<OrderbookLevel>) -> Orderbook {
+        Orderbook {
+            symbol: "BTCUSDT".to_string(),
+            bids,
+            asks,
+            timestamp: 0,
+        }
+    }
+
+    fn order(
+        side: OrderSide,
+        order_type: OrderType,
+        quantity: f64,
+        price: Option<f64>,
+    ) -> OrderRequest {
+        OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side,
+            order_type,
+            quantity,
+            price,
+            time_in_force: None,
+            reduce_only: false,
+        }
+    }
+
+    fn account() -> PaperAccount {
+        let mut account = PaperAccount::new(PaperConfig {
+            initial_balance: 10_000.0,
+            maker_fee_rate: 0.0,
+            taker_fee_rate: 0.001,
+            slippage_bps: 10.0,
+            ..Default::default()
+        });
+        account.update_book(
+            book(
+                vec![level(99.0, 5.0), level(98.0, 5.0)],
+                vec![level(100.0, 1.0), level(101.0, 2.0)],
+            ),
+            1,
+        );
+        account
+    }
+
+    #[test]
+    fn test_market_order_walks_depth_with_slippage() {
+        let mut account = account();
+
+        let response = account
+            .submit(order(OrderSide::Buy, OrderType::Market, 2.0, None), 2)
+            .unwrap();
+        assert!(matches!(response.status, OrderStatus::Filled));
+
+        let trades = account.trades();
+        assert_eq!(trades.len(), 2);
+        assert!((trades[0].price - 100.1).abs() < 1e-9);
+        assert!((trades[1].price - 101.101).abs() < 1e-9);
+
+        let position = account.position("BTCUSDT").unwrap();
+        assert_eq!(position.quantity, 2.0);
+        assert!((position.entry_price - 100.6005).abs() < 1e-9);
+        let fees: f64 = trades.iter().map(|t| t.fee).sum();
+        assert!((account.summary().cash - (10_000.0 - fees)).abs() < 1e-9);
+
+        // 剩余深度只有 1 手, 市价单部分成交后剩余撤销
+        let partial = account
+            .submit(order(OrderSide::Buy, OrderType::Market, 5.0, None), 3)
+            .unwrap();
+        assert!(matches!(partial.status, OrderStatus::PartiallyFilled));
+        let partial = account.order(&partial.order_id).unwrap();
+        assert_eq!(partial.filled_quantity, 1.0);
+        assert!(account.active_symbols().contains(&"BTCUSDT".to_string()));
+        assert!(account.orders(Some("BTCUSDT")).len() == 2);
+
+        // 只减仓单最多平掉现有持仓, 无持仓时拒绝
+        let reduce = OrderRequest {
+            reduce_only: true,
+            ..order(OrderSide::Sell, OrderType::Market, 10.0, None)
+        };
+        let closed = account.submit(reduce.clone(), 4).unwrap();
+        assert_eq!(account.order(&closed.order_id).unwrap().quantity, 3.0);
+        assert!(account.position("BTCUSDT").unwrap().quantity.abs() < 1e-9);
+        assert!(account.submit(reduce, 5).is_err());
+    }
+
+    #[test]
+    fn test_limit_order_rests_until_crossed() {
+        let mut account = account();
+        let response = account
+            .submit(order(OrderSide::Buy, OrderType::Limit, 3.0, Some(97.0)), 2)
+            .unwrap();
+        assert!(matches!(response.status, OrderStatus::Accepted));
+        assert!(account.summary().order_margin > 0.0);
+
+        // 成交价穿越, 按该笔成交量部分成交
+        account.on_trade("BTCUSDT", 96.5, 1.0, 3);
+        let resting = account.order(&response.order_id).unwrap();
+        assert!(matches!(resting.status, OrderStatus::PartiallyFilled));
+        assert_eq!(resting.avg_price, Some(97.0));
+
+        // 订单簿穿越, 剩余部分按限价成交
+        account.update_book(book(vec![level(95.0, 1.0)], vec![level(96.0, 10.0)]), 4);
+        let filled = account.order(&response.order_id).unwrap();
+        assert!(matches!(filled.status, OrderStatus::Filled));
+        assert_eq!(account.position("BTCUSDT").unwrap().quantity, 3.0);
+        assert!(account
+            .trades()
+            .iter()
+            .all(|t| t.price == 97.0 && t.fee == 0.0));
+
+        let cancelled = account
+            .submit(
+                order(OrderSide::Sell, OrderType::Limit, 1.0, Some(200.0)),
+                5,
+            )
+            .unwrap();
+        account.cancel(&cancelled.order_id, 6).unwrap();
+        assert!(account.cancel(&cancelled.order_id, 7).is_err());
+    }
+
+    #[test]
+    fn test_stop_order_triggers_and_closes_position() {
+        let mut account = account();
+        account
+            .submit(order(OrderSide::Buy, OrderType::Market, 1.0, None), 2)
+            .unwrap();
+
+        let stop = account
+            .submit(order(OrderSide::Sell, OrderType::Stop, 1.0, Some(95.0)), 3)
+            .unwrap();
+        assert!(matches!(stop.status, OrderStatus::Pending));
+
+        account.update_price("BTCUSDT", 96.0, 4);
+        assert!(matches!(
+            account.order(&stop.order_id).unwrap().status,
+            OrderStatus::Pending
+        ));
+
+        account.update_price("BTCUSDT", 94.0, 5);
+        let stop = account.order(&stop.order_id).unwrap();
+        assert!(matches!(stop.status, OrderStatus::Filled));
+        assert!(account.position("BTCUSDT").unwrap().quantity.abs() < 1e-9);
+        assert!(account.position("BTCUSDT").unwrap().realized_pnl < 0.0);
+        assert!(account.positions(5).positions.is_empty());
+    }
+
+    #[test]
+    fn test_margin_and_funding() {
+        let mut account = PaperAccount::new(PaperConfig {
+            initial_balance: 1_000.0,
+            leverage: 10,
+            taker_fee_rate: 0.0,
+            slippage_bps: 0.0,
+            perpetuals: HashMap::from([("BTCUSDT".to_string(), 0.001)]),
+            funding_interval_secs: 100,
+            ..Default::default()
+        });
+        account.update_price("BTCUSDT", 100.0, 0);
+
+        // 1000 USDT * 10 倍最多开 100 手
+        assert!(matches!(
+            account.submit(order(OrderSide::Buy, OrderType::Market, 150.0, None), 1),
+            Err(BrokerError::InsufficientFunds(_))
+        ));
+        account
+            .submit(order(OrderSide::Buy, OrderType::Market, 50.0, None), 1)
+            .unwrap();
+        let balance = account.balance(1);
+        assert_eq!(balance.margin_used, Some(500.0));
+        assert_eq!(balance.available, 500.0);
+
+        // 跨过两个资金费周期, 多头每期支付 50 * 100 * 0.001 = 5
+        account.settle_funding(50);
+        account.settle_funding(250);
+        let summary = account.summary();
+        assert!((summary.funding_paid - 10.0).abs() < 1e-9);
+        assert!((summary.cash - 990.0).abs() < 1e-9);
+    }
+}
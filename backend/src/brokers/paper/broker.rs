@@ -0,0 +1,428 @@
+use super::account::PaperAccount;
+use super::types::{PaperAccountSummary, PaperConfig};
+use crate::brokers::*;
+use crate::engine::Clock;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// 模拟盘经纪商
+///
+/// 行情查询直接转发给底层行情源（真实交易所或录制数据）, 订单、持仓、保证金、
+/// 手续费与盈亏由本地 [`PaperAccount`] 维护。行情可通过 [`PaperBroker::sync`]
+/// 轮询获取, 也可由 [`PaperBroker::run_stream`] / [`PaperBroker::apply_event`] 推送。
+#[derive(Clone)]
+pub struct PaperBroker {
+    id: String,
+    name: String,
+    feed: Arc<dyn DynBroker>,
+    account: Arc<Mutex<PaperAccount>>,
+    clock: Clock,
+    models: Vec<ModelInfo>,
+}
+
+impl PaperBroker {
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        feed: Arc<dyn DynBroker>,
+        config: PaperConfig,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            feed,
+            account: Arc::new(Mutex::new(PaperAccount::new(config))),
+            clock: Clock::system(),
+            models: Vec::new(),
+        }
+    }
+
+    /// 使用指定时钟记录订单与成交时间（回测时为模拟时钟）
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 绑定到该经纪商的 AI 模型 (启动时由 agents.yaml 注入)
+    pub fn with_models(mut self, models: Vec<ModelInfo>) -> Self {
+        self.models = models;
+        self
+    }
+
+    fn account(&self) -> MutexGuard<'_, PaperAccount> {
+        self.account.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 账户统计
+    pub fn summary(&self) -> PaperAccountSummary {
+        self.account().summary()
+    }
+
+    /// 全部成交记录
+    pub fn trades(&self) -> Vec<Trade> {
+        self.account().trades()
+    }
+
+    /// 应用一条推送或回放的行情事件
+    pub fn apply_event(&self, event: &MarketEvent) {
+        self.account().apply_event(event);
+    }
+
+    /// 回放一根已收盘的K线, 成交时间取当前时钟
+    pub fn apply_bar(&self, symbol: &str, bar: &Kline) {
+        let now = self.clock.now();
+        let mut account = self.account();
+        account.on_bar(symbol, bar, now);
+        account.settle_funding(now);
+    }
+
+    /// 持续消费行情流, 直到流结束
+    pub async fn run_stream(&self, mut stream: MarketEventStream) {
+        while let Some(event) = stream.recv().await {
+            self.apply_event(&event);
+        }
+    }
+
+    /// 从行情源拉取有挂单或持仓品种的订单簿, 撮合并结算资金费
+    pub async fn sync(&self) -> BrokerResult<()> {
+        let symbols = self.account().active_symbols();
+        for symbol in symbols {
+            self.refresh(&symbol).await?;
+        }
+        self.account().settle_funding(self.clock.now());
+        Ok(())
+    }
+
+    /// 按固定间隔 [`PaperBroker::sync`], 直到 `shutdown` 被取消
+    pub async fn run_sync(self: Arc<Self>, interval: Duration, shutdown: CancellationToken) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => {
+                    if let Err(e) = self.sync().await {
+                        warn!("Paper broker {} failed to sync: {}", self.id, e);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 拉取单个品种的订单簿; 行情源不支持深度时退回到 ticker 最新价
+    async fn refresh(&self, symbol: &str) -> BrokerResult<()> {
+        let now = self.clock.now();
+        match self.feed.get_orderbook(symbol).await {
+            Ok(book) => {
+                self.account().update_book(book, now);
+                Ok(())
+            }
+            Err(BrokerError::NotSupported(_)) => {
+                let ticker = self.feed.get_ticker_24h(symbol).await?;
+                self.account().update_price(symbol, ticker.last_price, now);
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Broker for PaperBroker {
+    fn broker_id(&self) -> &str {
+        &self.id
+    }
+
+    fn broker_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl MarketData for PaperBroker {
+    fn get_prices(&self) -> impl std::future::Future<Output = Result<Prices, BrokerError>> + Send {
+        self.feed.get_prices()
+    }
+
+    fn get_orderbook(
+        &self,
+        symbol: &str,
+    ) -> impl std::future::Future<Output = Result<Orderbook, BrokerError>> + Send {
+        let symbol = symbol.to_string();
+        async move { self.feed.get_orderbook(&symbol).await }
+    }
+
+    fn get_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: Option<i32>,
+    ) -> impl std::future::Future<Output = Result<Klines, BrokerError>> + Send {
+        let (symbol, interval) = (symbol.to_string(), interval.to_string());
+        async move { self.feed.get_klines(&symbol, &interval, limit).await }
+    }
+
+    fn get_ticker_24h(
+        &self,
+        symbol: &str,
+    ) -> impl std::future::Future<Output = Result<Ticker24h, BrokerError>> + Send {
+        let symbol = symbol.to_string();
+        async move { self.feed.get_ticker_24h(&symbol).await }
+    }
+}
+
+impl Trading for PaperBroker {
+    fn place_order(
+        &self,
+        order: OrderRequest,
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send {
+        async move {
+            // 下单前刷新该品种行情, 以最新深度撮合
+            if let Err(e) = self.refresh(&order.symbol).await {
+                warn!("Paper broker failed to refresh {}: {}", order.symbol, e);
+            }
+            self.account().submit(order, self.clock.now())
+        }
+    }
+
+    fn cancel_order(
+        &self,
+        order_id: &str,
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send {
+        let order_id = order_id.to_string();
+        async move { self.account().cancel(&order_id, self.clock.now()) }
+    }
+
+    fn get_order(
+        &self,
+        order_id: &str,
+    ) -> impl std::future::Future<Output = Result<Order, BrokerError>> + Send {
+        let order_id = order_id.to_string();
+        async move {
+            self.account()
+                .order(&order_id)
+                .ok_or_else(|| BrokerError::OrderRejected {
+                    code: "UNKNOWN_ORDER".to_string(),
+                    reason: format!("order {} not found", order_id),
+                })
+        }
+    }
+
+    fn get_orders(
+        &self,
+        symbol: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<Orders, BrokerError>> + Send {
+        let symbol = symbol.map(str::to_string);
+        async move {
+            Ok(Orders {
+                orders: self.account().orders(symbol.as_deref()),
+            })
+        }
+    }
+
+    fn get_trades(&self) -> impl std::future::Future<Output = Result<Trades, BrokerError>> + Send {
+        async move {
+            Ok(Trades {
+                trades: self.account().trades(),
+            })
+        }
+    }
+
+    /// 模拟盘以账户配置的统一杠杆撮合, 只接受该倍数
+    fn set_leverage(
+        &self,
+        symbol: &str,
+        leverage: f64,
+    ) -> impl std::future::Future<Output = Result<(), BrokerError>> + Send {
+        let configured = self.account().config().leverage.max(1);
+        let result = if leverage == configured as f64 {
+            Ok(())
+        } else {
+            Err(BrokerError::NotSupported(format!(
+                "{}x leverage for {}: paper account trades at {}x",
+                leverage, symbol, configured
+            )))
+        };
+        std::future::ready(result)
+    }
+}
+
+impl AccountManagement for PaperBroker {
+    fn get_account_totals(
+        &self,
+        _: Option<i32>,
+    ) -> impl std::future::Future<Output = Result<AccountTotals, BrokerError>> + Send {
+        async move {
+            Ok(AccountTotals {
+                account_totals: vec![],
+            })
+        }
+    }
+
+    fn get_model_accounts(
+        &self,
+    ) -> impl std::future::Future<Output = Result<ModelAccounts, BrokerError>> + Send {
+        async move {
+            let (summary, initial, trades) = {
+                let account = self.account();
+                (
+                    account.summary(),
+                    account.config().initial_balance,
+                    account.trades().len() as i32,
+                )
+            };
+            let return_pct = if initial > 0.0 {
+                (summary.equity - initial) / initial * 100.0
+            } else {
+                0.0
+            };
+
+            Ok(ModelAccounts {
+                accounts: vec![ModelAccount {
+                    model_id: self.id.clone(),
+                    model_name: self.name.clone(),
+                    strategy: "paper".to_string(),
+                    risk_level: "simulated".to_string(),
+                    broker_id: self.id.clone(),
+                    timestamp: self.clock.now(),
+                    account_value: summary.equity,
+                    dollar_equity: summary.equity,
+                    equity: summary.equity,
+                    realized_pnl: summary.realized_pnl,
+                    unrealized_pnl: summary.unrealized_pnl,
+                    total_unrealized_pnl: summary.unrealized_pnl,
+                    return_pct,
+                    cum_pnl_pct: return_pct,
+                    sharpe_ratio: 0.0,
+                    win_rate: summary.win_rate(),
+                    total_trades: trades,
+                    winning_trades: summary.winning_trades,
+                    losing_trades: summary.losing_trades,
+                }],
+            })
+        }
+    }
+
+    fn get_positions(
+        &self,
+        _: Option<i32>,
+    ) -> impl std::future::Future<Output = Result<Positions, BrokerError>> + Send {
+        async move { Ok(self.account().positions(self.clock.now())) }
+    }
+
+    fn get_balance(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Balance, BrokerError>> + Send {
+        async move { Ok(self.account().balance(self.clock.now())) }
+    }
+
+    fn get_broker_account(
+        &self,
+    ) -> impl std::future::Future<Output = Result<BrokerAccount, BrokerError>> + Send {
+        async move {
+            Ok(BrokerAccount {
+                broker_id: self.id.clone(),
+                broker_name: self.name.clone(),
+                broker_type: "paper".to_string(),
+                protocol: Some(format!("paper/{}", self.feed.broker_id())),
+                timestamp: self.clock.now(),
+            })
+        }
+    }
+}
+
+impl Analytics for PaperBroker {
+    fn get_analytics(
+        &self,
+    ) -> impl std::future::Future<Output = Result<AnalyticsData, BrokerError>> + Send {
+        async move {
+            let report = crate::analytics::PerformanceAnalyzer::new(self.id.clone())
+                .analyze(&self.trades(), self.clock.now());
+            Ok(AnalyticsData {
+                analytics: vec![report],
+            })
+        }
+    }
+
+    fn get_leaderboard(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Leaderboard, BrokerError>> + Send {
+        async move {
+            Ok(Leaderboard {
+                leaderboard: vec![],
+            })
+        }
+    }
+
+    fn get_since_inception_values(
+        &self,
+    ) -> impl std::future::Future<Output = Result<SinceInceptionValues, BrokerError>> + Send {
+        async move {
+            Ok(SinceInceptionValues {
+                since_inception: vec![],
+            })
+        }
+    }
+
+    fn get_conversations(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Conversations, BrokerError>> + Send {
+        async move {
+            Ok(Conversations {
+                conversations: vec![],
+            })
+        }
+    }
+
+    fn get_models_list(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Models, BrokerError>> + Send {
+        async move {
+            Ok(Models {
+                models: self.models.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_paper_broker_over_mock_feed() {
+        let feed: Arc<dyn DynBroker> = Arc::new(MockBroker::new());
+        let broker = PaperBroker::new("paper", "Paper", feed, PaperConfig::default());
+
+        let response = Trading::place_order(
+            &broker,
+            OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                order_type: OrderType::Market,
+                quantity: 0.05,
+                price: None,
+                time_in_force: None,
+                reduce_only: false,
+            },
+        )
+        .await
+        .unwrap();
+        assert!(matches!(response.status, OrderStatus::Filled));
+
+        let positions = AccountManagement::get_positions(&broker, None)
+            .await
+            .unwrap();
+        assert_eq!(positions.positions["BTCUSDT"].quantity, 0.05);
+
+        let balance = AccountManagement::get_balance(&broker).await.unwrap();
+        assert!(balance.total_balance < 100000.0);
+        assert!(balance.margin_used.unwrap() > 0.0);
+        assert_eq!(Trading::get_trades(&broker).await.unwrap().trades.len(), 1);
+
+        broker.sync().await.unwrap();
+        assert!(Trading::cancel_order(&broker, &response.order_id)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,9 @@
+// Paper trading broker implementation
+
+pub mod account;
+pub mod broker;
+pub mod types;
+
+pub use account::PaperAccount;
+pub use broker::PaperBroker;
+pub use types::*;
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 模拟盘配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperConfig {
+    /// 初始资金
+    #[serde(default = "default_initial_balance")]
+    pub initial_balance: f64,
+
+    /// 计价币种
+    #[serde(default = "default_currency")]
+    pub currency: String,
+
+    /// Maker 手续费率 (挂单被动成交)
+    #[serde(default = "default_maker_fee_rate")]
+    pub maker_fee_rate: f64,
+
+    /// Taker 手续费率 (市价单/立即成交的限价单)
+    #[serde(default = "default_taker_fee_rate")]
+    pub taker_fee_rate: f64,
+
+    /// Taker 成交滑点 (基点), 在每一档成交价上叠加
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: f64,
+
+    /// 杠杆倍数, 保证金 = 名义价值 / 杠杆
+    #[serde(default = "default_leverage")]
+    pub leverage: u32,
+
+    /// 撮合使用的深度档数
+    #[serde(default = "default_depth_levels")]
+    pub depth_levels: usize,
+
+    /// 永续合约及其每期资金费率, 正费率多头支付空头
+    #[serde(default)]
+    pub perpetuals: HashMap<String, f64>,
+
+    /// 资金费结算间隔（秒）
+    #[serde(default = "default_funding_interval_secs")]
+    pub funding_interval_secs: i64,
+}
+
+fn default_initial_balance() -> f64 {
+    100000.0
+}
+
+fn default_currency() -> String {
+    "USDT".to_string()
+}
+
+fn default_maker_fee_rate() -> f64 {
+    0.0002
+}
+
+fn default_taker_fee_rate() -> f64 {
+    0.0005
+}
+
+fn default_slippage_bps() -> f64 {
+    1.0
+}
+
+fn default_leverage() -> u32 {
+    1
+}
+
+fn default_depth_levels() -> usize {
+    20
+}
+
+fn default_funding_interval_secs() -> i64 {
+    8 * 3600
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            initial_balance: default_initial_balance(),
+            currency: default_currency(),
+            maker_fee_rate: default_maker_fee_rate(),
+            taker_fee_rate: default_taker_fee_rate(),
+            slippage_bps: default_slippage_bps(),
+            leverage: default_leverage(),
+            depth_levels: default_depth_levels(),
+            perpetuals: HashMap::new(),
+            funding_interval_secs: default_funding_interval_secs(),
+        }
+    }
+}
+
+/// 模拟盘持仓（净持仓, 正数为多头）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaperPosition {
+    pub symbol: String,
+    pub quantity: f64,
+    pub entry_price: f64,
+    /// 该品种累计已实现盈亏（不含手续费）
+    pub realized_pnl: f64,
+    /// 该品种累计资金费（正数为支出）
+    pub funding_paid: f64,
+}
+
+/// 模拟盘账户统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperAccountSummary {
+    pub cash: f64,
+    pub equity: f64,
+    pub unrealized_pnl: f64,
+    pub realized_pnl: f64,
+    pub fees_paid: f64,
+    pub funding_paid: f64,
+    pub margin_used: f64,
+    pub order_margin: f64,
+    pub available: f64,
+    /// 平仓盈利笔数
+    pub winning_trades: i32,
+    /// 平仓亏损笔数
+    pub losing_trades: i32,
+}
+
+impl PaperAccountSummary {
+    /// 平仓胜率 (0~1)
+    pub fn win_rate(&self) -> f64 {
+        let closed = self.winning_trades + self.losing_trades;
+        if closed == 0 {
+            0.0
+        } else {
+            self.winning_trades as f64 / closed as f64
+        }
+    }
+}
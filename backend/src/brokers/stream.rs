@@ -0,0 +1,552 @@
+// 实时行情推送
+// Real-time market data streaming
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use super::error::BrokerError;
+use super::types::{Kline, OrderSide, Orderbook, OrderbookLevel, Ticker24h};
+
+/// 事件通道容量 (消费方处理过慢时 WebSocket 任务会等待)
+pub const STREAM_BUFFER: usize = 1024;
+
+/// 重连退避上限
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// 订阅与事件
+// ============================================================================
+
+/// 行情频道
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketChannel {
+    /// 24小时 ticker
+    Ticker,
+    /// 逐笔成交
+    Trades,
+    /// 深度 (快照 + 增量)
+    Depth,
+    /// K线收盘, 参数为周期 (1m / 5m / 1h ...)
+    Kline(String),
+}
+
+/// 行情订阅请求
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreamSubscription {
+    /// 交易对/合约 (与 REST 接口使用相同的代码)
+    pub symbols: Vec<String>,
+    /// 订阅的频道
+    pub channels: Vec<MarketChannel>,
+}
+
+impl StreamSubscription {
+    pub fn new<S: Into<String>>(symbols: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            symbols: symbols.into_iter().map(Into::into).collect(),
+            channels: Vec::new(),
+        }
+    }
+
+    /// 追加频道
+    pub fn with_channel(mut self, channel: MarketChannel) -> Self {
+        if !self.channels.contains(&channel) {
+            self.channels.push(channel);
+        }
+        self
+    }
+
+    /// 事件是否属于本订阅 (symbols 为空表示全部)
+    pub fn matches(&self, event: &MarketEvent) -> bool {
+        let symbol_ok = self.symbols.is_empty()
+            || self
+                .symbols
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(event.symbol()));
+        symbol_ok && self.channels.contains(&event.channel())
+    }
+}
+
+/// 逐笔成交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketTrade {
+    pub symbol: String,
+    pub trade_id: String,
+    pub price: f64,
+    pub quantity: f64,
+    /// 主动成交方向 (taker)
+    pub side: OrderSide,
+    pub timestamp: i64,
+}
+
+/// 深度更新类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepthUpdateKind {
+    /// 全量快照, 替换本地订单簿
+    Snapshot,
+    /// 增量更新, 数量为 0 表示删除该价位
+    Diff,
+}
+
+/// 深度更新
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepthUpdate {
+    pub symbol: String,
+    pub kind: DepthUpdateKind,
+    /// 本次更新的起始序号 (Binance `U`, OKX `seqId`)
+    pub first_update_id: u64,
+    /// 本次更新的结束序号 (Binance `u` / `lastUpdateId`, OKX `seqId`)
+    pub final_update_id: u64,
+    /// 上一条更新的序号 (OKX `prevSeqId`, Binance 为 None)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_update_id: Option<u64>,
+    pub bids: Vec<OrderbookLevel>,
+    pub asks: Vec<OrderbookLevel>,
+    pub timestamp: i64,
+}
+
+/// 行情事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum MarketEvent {
+    /// 24小时 ticker
+    Ticker(Ticker24h),
+    /// 逐笔成交
+    Trade(MarketTrade),
+    /// 深度快照/增量 (已经过序号校验, 可直接应用到 `LocalOrderBook`)
+    Depth(DepthUpdate),
+    /// K线收盘
+    KlineClosed {
+        symbol: String,
+        interval: String,
+        kline: Kline,
+    },
+}
+
+impl MarketEvent {
+    pub fn symbol(&self) -> &str {
+        match self {
+            Self::Ticker(t) => &t.symbol,
+            Self::Trade(t) => &t.symbol,
+            Self::Depth(d) => &d.symbol,
+            Self::KlineClosed { symbol, .. } => symbol,
+        }
+    }
+
+    pub fn channel(&self) -> MarketChannel {
+        match self {
+            Self::Ticker(_) => MarketChannel::Ticker,
+            Self::Trade(_) => MarketChannel::Trades,
+            Self::Depth(_) => MarketChannel::Depth,
+            Self::KlineClosed { interval, .. } => MarketChannel::Kline(interval.clone()),
+        }
+    }
+}
+
+// ============================================================================
+// 推送接口
+// ============================================================================
+
+/// 实时行情推送接口
+///
+/// 与轮询式的 `MarketData` 互补: 返回的 `MarketEventStream` 在后台维护连接,
+/// 断线后自动重连并重新订阅, 深度数据已按序号校验 (出现缺口时重新拉取快照)。
+pub trait MarketDataStream: Send + Sync {
+    /// 订阅行情, 丢弃返回的流即取消订阅
+    fn subscribe_market(
+        &self,
+        subscription: StreamSubscription,
+    ) -> impl Future<Output = Result<MarketEventStream, BrokerError>> + Send;
+}
+
+/// 对象安全的行情推送接口, 实现了 [`MarketDataStream`] 的类型自动获得
+pub trait DynMarketDataStream: Send + Sync {
+    /// 订阅行情, 丢弃返回的流即取消订阅
+    fn subscribe_market(
+        &self,
+        subscription: StreamSubscription,
+    ) -> BoxFuture<'_, Result<MarketEventStream, BrokerError>>;
+}
+
+impl<T: MarketDataStream> DynMarketDataStream for T {
+    fn subscribe_market(
+        &self,
+        subscription: StreamSubscription,
+    ) -> BoxFuture<'_, Result<MarketEventStream, BrokerError>> {
+        Box::pin(MarketDataStream::subscribe_market(self, subscription))
+    }
+}
+
+/// 行情事件流
+///
+/// 持有后台连接任务, drop 时终止这些任务。
+pub struct MarketEventStream {
+    rx: mpsc::Receiver<MarketEvent>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl MarketEventStream {
+    pub fn new(rx: mpsc::Receiver<MarketEvent>, tasks: Vec<JoinHandle<()>>) -> Self {
+        Self { rx, tasks }
+    }
+
+    /// 等待下一条事件 (流结束时返回 None)
+    pub async fn recv(&mut self) -> Option<MarketEvent> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for MarketEventStream {
+    type Item = MarketEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl Drop for MarketEventStream {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+// ============================================================================
+// WebSocket 工具
+// ============================================================================
+
+pub(crate) type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// 建立 WebSocket 连接
+pub(crate) async fn connect_ws(url: &str) -> Result<WsStream, BrokerError> {
+    let (ws, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .map_err(|e| BrokerError::Network(format!("WebSocket connect {}: {}", url, e)))?;
+    Ok(ws)
+}
+
+/// 第 `attempt` 次重连前的等待时间 (1s 起指数退避, 上限 30s)
+pub(crate) fn reconnect_delay(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.min(5)).min(MAX_RECONNECT_DELAY)
+}
+
+// ============================================================================
+// 本地订单簿
+// ============================================================================
+
+/// 订单簿错误
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum OrderBookError {
+    /// 尚未收到快照
+    #[error("order book {symbol} has no snapshot yet")]
+    NotInitialized { symbol: String },
+
+    /// 序号不连续, 需要重新获取快照
+    #[error("sequence gap on {symbol}: expected {expected}, got {got}")]
+    SequenceGap {
+        symbol: String,
+        expected: u64,
+        got: u64,
+    },
+}
+
+/// 可排序的价格 (f64 按 total_cmp 比较)
+#[derive(Debug, Clone, Copy)]
+struct PriceKey(f64);
+
+impl PartialEq for PriceKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for PriceKey {}
+
+impl PartialOrd for PriceKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PriceKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+/// 本地订单簿
+///
+/// 由快照初始化, 随后按序应用增量。序号规则:
+/// - 带 `prev_update_id` (OKX): 必须等于本地最后序号
+/// - 不带 `prev_update_id` (Binance): `final <= last` 的旧数据直接丢弃,
+///   `first > last + 1` 视为缺口
+#[derive(Debug, Clone)]
+pub struct LocalOrderBook {
+    symbol: String,
+    bids: BTreeMap<PriceKey, f64>,
+    asks: BTreeMap<PriceKey, f64>,
+    last_update_id: Option<u64>,
+    timestamp: i64,
+}
+
+impl LocalOrderBook {
+    pub fn new(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_update_id: None,
+            timestamp: 0,
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// 是否已应用快照
+    pub fn is_initialized(&self) -> bool {
+        self.last_update_id.is_some()
+    }
+
+    /// 最后应用的序号
+    pub fn last_update_id(&self) -> Option<u64> {
+        self.last_update_id
+    }
+
+    /// 清空订单簿, 等待下一次快照
+    pub fn reset(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.last_update_id = None;
+    }
+
+    /// 应用深度更新
+    ///
+    /// 返回 `Ok(true)` 表示已应用, `Ok(false)` 表示旧数据被丢弃。
+    pub fn apply(&mut self, update: &DepthUpdate) -> Result<bool, OrderBookError> {
+        if update.kind == DepthUpdateKind::Snapshot {
+            self.reset();
+            Self::merge(&mut self.bids, &update.bids);
+            Self::merge(&mut self.asks, &update.asks);
+            self.last_update_id = Some(update.final_update_id);
+            self.timestamp = update.timestamp;
+            return Ok(true);
+        }
+
+        let last = self
+            .last_update_id
+            .ok_or_else(|| OrderBookError::NotInitialized {
+                symbol: self.symbol.clone(),
+            })?;
+
+        match update.prev_update_id {
+            Some(prev) if prev != last => {
+                return Err(OrderBookError::SequenceGap {
+                    symbol: self.symbol.clone(),
+                    expected: last,
+                    got: prev,
+                });
+            }
+            Some(_) => {}
+            None => {
+                if update.final_update_id <= last {
+                    return Ok(false);
+                }
+                if update.first_update_id > last + 1 {
+                    return Err(OrderBookError::SequenceGap {
+                        symbol: self.symbol.clone(),
+                        expected: last + 1,
+                        got: update.first_update_id,
+                    });
+                }
+            }
+        }
+
+        Self::merge(&mut self.bids, &update.bids);
+        Self::merge(&mut self.asks, &update.asks);
+        self.last_update_id = Some(update.final_update_id);
+        self.timestamp = update.timestamp;
+        Ok(true)
+    }
+
+    /// 买一
+    pub fn best_bid(&self) -> Option<OrderbookLevel> {
+        self.bids.iter().next_back().map(|(p, q)| OrderbookLevel {
+            price: p.0,
+            quantity: *q,
+        })
+    }
+
+    /// 卖一
+    pub fn best_ask(&self) -> Option<OrderbookLevel> {
+        self.asks.iter().next().map(|(p, q)| OrderbookLevel {
+            price: p.0,
+            quantity: *q,
+        })
+    }
+
+    /// 导出前 `depth` 档
+    pub fn to_orderbook(&self, depth: usize) -> Orderbook {
+        let level = |(p, q): (&PriceKey, &f64)| OrderbookLevel {
+            price: p.0,
+            quantity: *q,
+        };
+        Orderbook {
+            symbol: self.symbol.clone(),
+            bids: self.bids.iter().rev().take(depth).map(level).collect(),
+            asks: self.asks.iter().take(depth).map(level).collect(),
+            timestamp: self.timestamp,
+        }
+    }
+
+    fn merge(side: &mut BTreeMap<PriceKey, f64>, levels: &[OrderbookLevel]) {
+        for level in levels {
+            if level.quantity > 0.0 {
+                side.insert(PriceKey(level.price), level.quantity);
+            } else {
+                side.remove(&PriceKey(level.price));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(price: f64, quantity: f64) -> OrderbookLevel {
+        OrderbookLevel { price, quantity }
+    }
+
+    fn update(kind: DepthUpdateKind, first: u64, last: u64, prev: Option<u64>) -> DepthUpdate {
+        DepthUpdate {
+            symbol: "BTCUSDT".to_string(),
+            kind,
+            first_update_id: first,
+            final_update_id: last,
+            prev_update_id: prev,
+            bids: vec![],
+            asks: vec![],
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_order_book_snapshot_and_diff() {
+        let mut book = LocalOrderBook::new("BTCUSDT");
+        let mut diff = update(DepthUpdateKind::Diff, 101, 102, None);
+        assert!(matches!(
+            book.apply(&diff),
+            Err(OrderBookError::NotInitialized { .. })
+        ));
+
+        let mut snapshot = update(DepthUpdateKind::Snapshot, 100, 100, None);
+        snapshot.bids = vec![level(99.0, 1.0), level(98.0, 2.0)];
+        snapshot.asks = vec![level(101.0, 1.0), level(102.0, 3.0)];
+        assert!(book.apply(&snapshot).unwrap());
+
+        // 旧数据丢弃
+        assert!(!book
+            .apply(&update(DepthUpdateKind::Diff, 90, 100, None))
+            .unwrap());
+
+        diff.bids = vec![level(99.0, 0.0), level(99.5, 4.0)];
+        diff.asks = vec![level(100.5, 2.0)];
+        assert!(book.apply(&diff).unwrap());
+        assert_eq!(book.last_update_id(), Some(102));
+        assert_eq!(book.best_bid().unwrap().price, 99.5);
+        assert_eq!(book.best_ask().unwrap().price, 100.5);
+
+        let ob = book.to_orderbook(2);
+        assert_eq!(ob.bids.len(), 2);
+        assert_eq!(ob.bids[1].price, 98.0);
+        assert_eq!(ob.asks[1].price, 101.0);
+
+        // 跨越快照的首条增量 (U <= last + 1 <= u) 也应被接受
+        assert!(book
+            .apply(&update(DepthUpdateKind::Diff, 100, 105, None))
+            .unwrap());
+
+        assert_eq!(
+            book.apply(&update(DepthUpdateKind::Diff, 110, 111, None)),
+            Err(OrderBookError::SequenceGap {
+                symbol: "BTCUSDT".to_string(),
+                expected: 106,
+                got: 110,
+            })
+        );
+    }
+
+    #[test]
+    fn test_order_book_prev_sequence() {
+        let mut book = LocalOrderBook::new("BTC-USDT-SWAP");
+        book.apply(&update(DepthUpdateKind::Snapshot, 10, 10, None))
+            .unwrap();
+        assert!(book
+            .apply(&update(DepthUpdateKind::Diff, 15, 15, Some(10)))
+            .unwrap());
+        assert!(matches!(
+            book.apply(&update(DepthUpdateKind::Diff, 20, 20, Some(16))),
+            Err(OrderBookError::SequenceGap {
+                expected: 15,
+                got: 16,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_subscription_matches() {
+        let sub = StreamSubscription::new(["BTCUSDT"])
+            .with_channel(MarketChannel::Trades)
+            .with_channel(MarketChannel::Kline("1m".to_string()));
+
+        let trade = MarketEvent::Trade(MarketTrade {
+            symbol: "btcusdt".to_string(),
+            trade_id: "1".to_string(),
+            price: 1.0,
+            quantity: 1.0,
+            side: OrderSide::Buy,
+            timestamp: 0,
+        });
+        assert!(sub.matches(&trade));
+
+        let kline = |interval: &str| MarketEvent::KlineClosed {
+            symbol: "BTCUSDT".to_string(),
+            interval: interval.to_string(),
+            kline: Kline {
+                timestamp: 0,
+                open: 1.0,
+                high: 1.0,
+                low: 1.0,
+                close: 1.0,
+                volume: 0.0,
+                open_interest: None,
+            },
+        };
+        assert!(sub.matches(&kline("1m")));
+        assert!(!sub.matches(&kline("1h")));
+        assert!(!sub.matches(&MarketEvent::Depth(update(
+            DepthUpdateKind::Snapshot,
+            1,
+            1,
+            None
+        ))));
+    }
+}
@@ -83,6 +83,9 @@ pub struct OrderRequest {
     pub price: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_in_force: Option<String>,
+    /// 只减仓, 不会加仓或开出反向持仓 (经纪商支持时)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reduce_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -275,27 +278,192 @@ pub struct BrokerAccount {
 // 分析统计数据类型
 // ============================================================================
 
-/// 分析数据
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 分析数据 (对应 `/api/analytics`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AnalyticsData {
-    // TODO: 添加具体的分析字段
-    pub metrics: std::collections::HashMap<String, f64>,
+    pub analytics: Vec<ModelAnalytics>,
 }
 
-/// 排行榜条目
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 单个模型的绩效分析, 字段与 nof1 `analytics` 接口一致
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ModelAnalytics {
+    pub id: String,
+    pub model_id: String,
+    /// 计算时间 (秒)
+    pub updated_at: f64,
+    pub fee_pnl_moves_breakdown_table: FeePnlBreakdown,
+    pub winners_losers_breakdown_table: WinnersLosersBreakdown,
+    pub longs_shorts_breakdown_table: LongsShortsBreakdown,
+    pub overall_trades_overview_table: TradesOverview,
+    pub invocation_breakdown_table: InvocationBreakdown,
+    /// 权益曲线指标 (nof1 无此表)
+    pub performance_table: PerformanceMetrics,
+    /// 持仓时长分布 (nof1 无此表)
+    pub holding_period_distribution: Vec<HoldingPeriodBucket>,
+    /// 按品种归因 (nof1 无此表)
+    pub symbol_breakdown_table: Vec<SymbolAttribution>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_trade_exit_time: Option<f64>,
+}
+
+/// 手续费与盈亏分解
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FeePnlBreakdown {
+    pub overall_pnl_with_fees: f64,
+    pub overall_pnl_without_fees: f64,
+    pub total_fees_paid: f64,
+    pub total_fees_as_pct_of_pnl: f64,
+    /// 资金费净支出 (负数为净收入)
+    pub total_funding_paid: f64,
+    pub avg_taker_fee: f64,
+    pub std_taker_fee: f64,
+    pub avg_gross_pnl: f64,
+    pub std_gross_pnl: f64,
+    pub avg_net_pnl: f64,
+    pub std_net_pnl: f64,
+    pub biggest_net_gain: f64,
+    pub biggest_net_loss: f64,
+}
+
+/// 盈利单与亏损单统计 (持仓时长单位: 分钟)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WinnersLosersBreakdown {
+    /// 胜率 (百分比)
+    pub win_rate: f64,
+    pub profit_factor: f64,
+    pub expectancy: f64,
+    pub avg_winners_net_pnl: f64,
+    pub std_winners_net_pnl: f64,
+    pub avg_losers_net_pnl: f64,
+    pub std_losers_net_pnl: f64,
+    pub avg_winners_notional: f64,
+    pub std_winners_notional: f64,
+    pub avg_losers_notional: f64,
+    pub std_losers_notional: f64,
+    pub avg_winners_holding_period: f64,
+    pub std_winners_holding_period: f64,
+    pub avg_losers_holding_period: f64,
+    pub std_losers_holding_period: f64,
+}
+
+/// 多空交易统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LongsShortsBreakdown {
+    pub num_long_trades: usize,
+    pub num_short_trades: usize,
+    /// 多单占全部交易的比例
+    pub long_short_trades_ratio: f64,
+    pub avg_longs_net_pnl: f64,
+    pub std_longs_net_pnl: f64,
+    pub avg_shorts_net_pnl: f64,
+    pub std_shorts_net_pnl: f64,
+    pub avg_longs_notional: f64,
+    pub std_longs_notional: f64,
+    pub avg_shorts_notional: f64,
+    pub std_shorts_notional: f64,
+    pub avg_longs_holding_period: f64,
+    pub std_longs_holding_period: f64,
+    pub avg_shorts_holding_period: f64,
+    pub std_shorts_holding_period: f64,
+}
+
+/// 交易概览
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TradesOverview {
+    pub total_trades: usize,
+    pub avg_holding_period_mins: f64,
+    pub median_holding_period_mins: f64,
+    pub std_holding_period_mins: f64,
+    pub avg_size_of_trade_notional: f64,
+    pub median_size_of_trade_notional: f64,
+    pub std_size_of_trade_notional: f64,
+}
+
+/// 决策调用节奏
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InvocationBreakdown {
+    pub num_invocations: u64,
+    pub avg_invocation_break_mins: f64,
+    pub std_invocation_break_mins: f64,
+    pub min_invocation_break_mins: f64,
+    pub max_invocation_break_mins: f64,
+}
+
+/// 权益曲线绩效指标 (收益与波动为年化百分比, 无风险利率取 0)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PerformanceMetrics {
+    pub total_return_pct: f64,
+    pub annualized_return_pct: f64,
+    pub annualized_volatility_pct: f64,
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    pub calmar_ratio: f64,
+    pub max_drawdown_pct: f64,
+    /// 最长回撤持续时间 (秒, 含尚未恢复的回撤)
+    pub max_drawdown_duration_secs: i64,
+}
+
+/// 持仓时长分布区间
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HoldingPeriodBucket {
+    pub label: String,
+    /// 区间上限 (分钟), 最后一个区间为 None
+    pub max_mins: Option<f64>,
+    pub num_trades: usize,
+    pub net_pnl: f64,
+}
+
+/// 单个品种的盈亏归因
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SymbolAttribution {
+    pub symbol: String,
+    pub num_trades: usize,
+    pub win_rate: f64,
+    pub gross_pnl: f64,
+    pub fees_paid: f64,
+    pub funding_paid: f64,
+    pub net_pnl: f64,
+    /// 占全部净盈亏绝对值之和的比例 (百分比)
+    pub pnl_contribution_pct: f64,
+}
+
+/// 排行榜条目 (序列化字段名与 nof1 `leaderboard` 接口一致)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct LeaderboardEntry {
     pub rank: i32,
+    #[serde(rename = "id")]
     pub model_id: String,
     pub model_name: String,
+    #[serde(default)]
+    pub broker_id: String,
+    /// 权益 (已折算为 `currency`)
+    #[serde(default)]
+    pub equity: f64,
+    #[serde(default)]
+    pub currency: String,
     pub return_pct: f64,
+    #[serde(rename = "sharpe")]
     pub sharpe_ratio: f64,
+    #[serde(default)]
+    pub max_drawdown_pct: f64,
+    #[serde(rename = "num_trades")]
     pub total_trades: i32,
+    #[serde(default)]
+    pub num_wins: i32,
+    #[serde(default)]
+    pub num_losses: i32,
+    /// 盈利交易净盈亏合计
+    #[serde(default)]
+    pub win_dollars: f64,
+    /// 亏损交易净盈亏合计 (负数)
+    #[serde(default)]
+    pub lose_dollars: f64,
+    /// 胜率 (0~1)
     pub win_rate: f64,
 }
 
 /// 排行榜
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Leaderboard {
     pub leaderboard: Vec<LeaderboardEntry>,
 }
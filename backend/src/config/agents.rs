@@ -0,0 +1,337 @@
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use crate::brokers::ModelInfo;
+use crate::engine::CronSchedule;
+use crate::llm::ResilienceConfig;
+use crate::risk::RiskConfig;
+
+/// Agent LLM 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentLlmConfig {
+    /// Provider 类型: openai / anthropic / gemini / deepseek / qwen / xai / ollama / openai_compatible / scripted
+    pub provider: String,
+
+    /// 模型名称
+    pub model: String,
+
+    /// 采样温度
+    #[serde(default)]
+    pub temperature: Option<f32>,
+
+    /// 最大生成 token 数
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+
+    /// 自定义 API 地址
+    #[serde(default)]
+    pub base_url: Option<String>,
+
+    /// 读取 API Key 的环境变量（默认按 provider 推断）
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+
+    /// 脚本路径（scripted provider）
+    #[serde(default)]
+    pub script: Option<String>,
+
+    /// 超时、重试、限流与熔断配置, 未配置时不包装
+    #[serde(default)]
+    pub resilience: Option<ResilienceConfig>,
+
+    /// 主 Provider 不可用时的降级配置
+    #[serde(default)]
+    pub fallback: Option<Box<AgentLlmConfig>>,
+}
+
+impl AgentLlmConfig {
+    /// Provider 注册名, 相同 provider 与模型的 Agent 共享同一实例
+    pub fn registry_key(&self) -> String {
+        match &self.script {
+            Some(script) => format!("{}:{}:{}", self.provider, self.model, script),
+            None => format!("{}:{}", self.provider, self.model),
+        }
+    }
+}
+
+/// 单个参赛 Agent 配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentConfig {
+    /// Agent ID（同时作为模型 ID）
+    pub id: String,
+
+    /// 显示名称
+    pub name: String,
+
+    /// 策略名称
+    #[serde(default)]
+    pub strategy: String,
+
+    /// 描述
+    #[serde(default)]
+    pub description: String,
+
+    /// 是否启用
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// LLM 配置
+    pub llm: AgentLlmConfig,
+
+    /// 绑定的经纪商 ID
+    pub broker: String,
+
+    /// 交易品种
+    pub symbols: Vec<String>,
+
+    /// 初始资金
+    pub initial_capital: f64,
+
+    /// 决策间隔（秒）, 为空时使用引擎默认值
+    #[serde(default)]
+    pub decision_interval_secs: Option<u64>,
+
+    /// Cron 调度表达式, 优先于 `decision_interval_secs`
+    #[serde(default)]
+    pub schedule: Option<String>,
+
+    /// System Prompt 模板, 覆盖 Prompt 模板的 system 部分;
+    /// 支持 {{id}} {{name}} {{broker}} {{symbols}} {{initial_capital}} 占位符及全部模板变量
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+
+    /// Prompt 模板 (`id` 或 `id@version`, 见 prompts.yaml), 多个时按决策周期轮换做 A/B 对比
+    #[serde(default)]
+    pub prompts: Vec<String>,
+
+    /// Prompt token 预算, 超出时裁剪上下文; 为空时使用默认值
+    #[serde(default)]
+    pub prompt_token_budget: Option<usize>,
+
+    /// 允许使用的工具（为空时允许全部）
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+
+    /// 每个周期必须输出结构化交易决策 (submit_decisions 或 JSON 回复)
+    #[serde(default)]
+    pub require_decision: bool,
+
+    /// 风险等级（展示用）: VERY_LOW / LOW / MEDIUM / HIGH / VERY_HIGH
+    #[serde(default = "default_risk_level")]
+    pub risk_level: String,
+
+    /// 风控配置覆盖, 为空时使用全局风控配置
+    #[serde(default)]
+    pub risk: Option<RiskConfig>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_risk_level() -> String {
+    "MEDIUM".to_string()
+}
+
+impl AgentConfig {
+    /// 转换为对外展示的模型信息
+    pub fn to_model_info(&self) -> ModelInfo {
+        ModelInfo {
+            model_id: self.id.clone(),
+            model_name: self.name.clone(),
+            strategy: self.strategy.clone(),
+            description: self.description.clone(),
+            risk_level: self.risk_level.clone(),
+            base_capital: self.initial_capital,
+        }
+    }
+}
+
+/// Agent 配置文件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AgentsConfig {
+    pub agents: Vec<AgentConfig>,
+}
+
+impl AgentsConfig {
+    /// 从文件加载配置
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::from_yaml_str(&content)
+    }
+
+    /// 从 YAML 字符串加载并校验
+    pub fn from_yaml_str(yaml: &str) -> Result<Self> {
+        let config: AgentsConfig = serde_yaml::from_str(yaml)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// 从默认路径加载配置, 找不到文件时返回空配置
+    pub fn load_default() -> Result<Self> {
+        let possible_paths = [
+            "config/agents.yaml",
+            "backend/config/agents.yaml",
+            "../config/agents.yaml",
+            "./agents.yaml",
+        ];
+
+        for path in possible_paths {
+            if Path::new(path).exists() {
+                return Self::from_file(path);
+            }
+        }
+
+        tracing::warn!("No agents config file found, no agents loaded");
+        Ok(Self::default())
+    }
+
+    /// 校验配置
+    pub fn validate(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        for agent in &self.agents {
+            if agent.id.is_empty() {
+                bail!("Agent id must not be empty");
+            }
+            if !ids.insert(agent.id.as_str()) {
+                bail!("Duplicate agent id: {}", agent.id);
+            }
+            if agent.symbols.is_empty() {
+                bail!("Agent {} has no symbols", agent.id);
+            }
+            if agent.initial_capital <= 0.0 {
+                bail!("Agent {} must have positive initial_capital", agent.id);
+            }
+            if agent.decision_interval_secs == Some(0) {
+                bail!("Agent {} has zero decision_interval_secs", agent.id);
+            }
+            if let Some(schedule) = &agent.schedule {
+                CronSchedule::parse(schedule)
+                    .map_err(|e| anyhow!("Agent {} has invalid schedule: {}", agent.id, e))?;
+            }
+            if let Some(t) = agent.llm.temperature {
+                if !(0.0..=2.0).contains(&t) {
+                    bail!("Agent {} temperature {} out of range [0, 2]", agent.id, t);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 获取已启用的 Agent
+    pub fn enabled_agents(&self) -> Vec<AgentConfig> {
+        self.agents.iter().filter(|a| a.enabled).cloned().collect()
+    }
+
+    /// 根据 ID 查找 Agent
+    pub fn get_agent(&self, id: &str) -> Option<&AgentConfig> {
+        self.agents.iter().find(|a| a.id == id)
+    }
+
+    /// 绑定到指定经纪商的模型列表
+    pub fn models_for_broker(&self, broker_id: &str) -> Vec<ModelInfo> {
+        self.agents
+            .iter()
+            .filter(|a| a.broker == broker_id)
+            .map(AgentConfig::to_model_info)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+agents:
+  - id: btc_trend
+    name: BTC Trend
+    strategy: Trend Following
+    llm:
+      provider: deepseek
+      model: deepseek-chat
+      temperature: 0.3
+    broker: binance
+    symbols: [BTCUSDT, ETHUSDT]
+    initial_capital: 10000
+    decision_interval_secs: 300
+    system_prompt: "You are {{name}} trading {{symbols}}."
+    allowed_tools: [get_market_snapshot, place_order]
+    risk_level: HIGH
+    risk:
+      order_size_limits:
+        max_order_value: 2000
+  - id: ctp_arb
+    name: CTP Arb
+    enabled: false
+    llm:
+      provider: scripted
+      model: offline
+      script: fixtures/llm/trading_dialogue.yaml
+    broker: ctp
+    symbols: [IF2504]
+    initial_capital: 1000000
+"#;
+
+    #[test]
+    fn test_parse_agents_config() {
+        let config = AgentsConfig::from_yaml_str(SAMPLE).unwrap();
+        assert_eq!(config.agents.len(), 2);
+        assert_eq!(config.enabled_agents().len(), 1);
+
+        let agent = config.get_agent("btc_trend").unwrap();
+        assert_eq!(agent.llm.temperature, Some(0.3));
+        assert_eq!(agent.llm.registry_key(), "deepseek:deepseek-chat");
+        assert_eq!(agent.decision_interval_secs, Some(300));
+        assert_eq!(agent.allowed_tools.len(), 2);
+
+        // 未覆盖的风控字段使用默认值
+        let risk = agent.risk.as_ref().unwrap();
+        assert_eq!(risk.order_size_limits.max_order_value, 2000.0);
+        assert_eq!(risk.order_size_limits.min_order_value, 10.0);
+        assert!(risk.enabled);
+
+        let ctp = config.get_agent("ctp_arb").unwrap();
+        assert_eq!(ctp.risk_level, "MEDIUM");
+        assert!(ctp.risk.is_none());
+
+        let models = config.models_for_broker("binance");
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].model_id, "btc_trend");
+        assert_eq!(models[0].base_capital, 10000.0);
+        assert!(config.models_for_broker("okex").is_empty());
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_agents() {
+        let duplicate = SAMPLE.replace("id: ctp_arb", "id: btc_trend");
+        assert!(AgentsConfig::from_yaml_str(&duplicate)
+            .unwrap_err()
+            .to_string()
+            .contains("Duplicate"));
+
+        let no_capital = SAMPLE.replace("initial_capital: 10000", "initial_capital: 0");
+        assert!(AgentsConfig::from_yaml_str(&no_capital).is_err());
+
+        let hot = SAMPLE.replace("temperature: 0.3", "temperature: 3.5");
+        assert!(AgentsConfig::from_yaml_str(&hot).is_err());
+
+        let bad_cron = SAMPLE.replace("decision_interval_secs: 300", "schedule: \"*/5 * * *\"");
+        assert!(AgentsConfig::from_yaml_str(&bad_cron)
+            .unwrap_err()
+            .to_string()
+            .contains("schedule"));
+    }
+
+    #[test]
+    fn test_repo_agents_yaml_is_valid() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/agents.yaml");
+        let config = AgentsConfig::from_file(path).unwrap();
+        for broker in ["binance", "okex", "ctp"] {
+            assert!(!config.models_for_broker(broker).is_empty());
+        }
+    }
+}
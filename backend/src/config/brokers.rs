@@ -9,6 +9,10 @@ pub struct BrokerConfig {
     /// 经纪商ID
     pub id: String,
 
+    /// 实现类型: binance / okex / ctp / paper, 为空时按 ID 推断
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+
     /// 经纪商名称
     pub name: String,
 
@@ -39,6 +43,10 @@ pub struct BrokerConfig {
     /// 功能列表
     pub features: Vec<String>,
 
+    /// 通过 MCP 开放下单的交易品种, 为空时不开放下单
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mcp_order_symbols: Vec<String>,
+
     /// 特定配置（可选）
     #[serde(default)]
     pub config: Option<serde_json::Value>,
@@ -142,6 +150,7 @@ impl Default for BrokersConfig {
             brokers: vec![
                 BrokerConfig {
                     id: "crypto".to_string(),
+                    kind: None,
                     name: "数字货币经纪商".to_string(),
                     name_en: "Crypto Broker".to_string(),
                     description: "实时监控数字货币交易账户".to_string(),
@@ -156,10 +165,12 @@ impl Default for BrokersConfig {
                         "持仓管理".to_string(),
                         "成交记录".to_string(),
                     ],
+                    mcp_order_symbols: Vec::new(),
                     config: None,
                 },
                 BrokerConfig {
                     id: "ctp".to_string(),
+                    kind: None,
                     name: "CTP 期货经纪商".to_string(),
                     name_en: "CTP Futures Broker".to_string(),
                     description: "中国期货市场交易终端".to_string(),
@@ -174,6 +185,7 @@ impl Default for BrokersConfig {
                         "持仓监控".to_string(),
                         "交易执行".to_string(),
                     ],
+                    mcp_order_symbols: Vec::new(),
                     config: None,
                 },
             ],
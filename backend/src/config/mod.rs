@@ -1,5 +1,7 @@
+pub mod agents;
 pub mod brokers;
 
+pub use agents::{AgentConfig, AgentLlmConfig, AgentsConfig};
 pub use brokers::{BrokerConfig, BrokersConfig, GlobalSettings};
 
 use serde::{Deserialize, Serialize};
@@ -32,9 +34,13 @@ pub struct RedisConfig {
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct LlmProviderConfig {
+    /// Provider 预设名或别名: openai / anthropic / gemini / deepseek / qwen / xai / ollama / openai_compatible
     pub name: String,
     pub model: String,
+    /// 为空时从预设的默认环境变量读取
+    #[serde(default)]
     pub api_key: String,
+    #[serde(default)]
     pub base_url: Option<String>,
 }
 
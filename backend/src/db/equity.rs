@@ -0,0 +1,136 @@
+use crate::brokers::AccountTotal;
+use crate::engine::EquityStore;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+/// 权益快照仓储 (`account_equity_snapshots` 表)
+pub struct PgEquityStore {
+    pool: Arc<PgPool>,
+}
+
+impl PgEquityStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EquityStore for PgEquityStore {
+    async fn append(&self, total: &AccountTotal) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO account_equity_snapshots
+                (broker_id, model_id, model_name, strategy, risk_level, ts, equity,
+                 realized_pnl, unrealized_pnl, total_unrealized_pnl, return_pct,
+                 cum_pnl_pct, sharpe_ratio, minute_marker, hourly_marker)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            "#,
+        )
+        .bind(&total.broker_id)
+        .bind(&total.model_id)
+        .bind(&total.model_name)
+        .bind(&total.strategy)
+        .bind(&total.risk_level)
+        .bind(total.timestamp)
+        .bind(total.equity)
+        .bind(total.realized_pnl)
+        .bind(total.unrealized_pnl)
+        .bind(total.total_unrealized_pnl)
+        .bind(total.return_pct)
+        .bind(total.cum_pnl_pct)
+        .bind(total.sharpe_ratio)
+        .bind(total.since_inception_minute_marker)
+        .bind(total.since_inception_hourly_marker)
+        .execute(self.pool.as_ref())
+        .await
+        .context("Failed to insert equity snapshot")?;
+        Ok(())
+    }
+
+    async fn load(
+        &self,
+        model_id: Option<&str>,
+        from_hourly_marker: Option<i32>,
+    ) -> Result<Vec<AccountTotal>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM account_equity_snapshots
+            WHERE ($1::VARCHAR IS NULL OR model_id = $1)
+              AND ($2::INTEGER IS NULL OR hourly_marker >= $2)
+            ORDER BY ts ASC, id ASC
+            "#,
+        )
+        .bind(model_id)
+        .bind(from_hourly_marker)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to load equity snapshots")?;
+
+        Ok(rows.iter().map(total_from_row).collect())
+    }
+
+    async fn first(&self, model_id: &str) -> Result<Option<AccountTotal>> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM account_equity_snapshots
+            WHERE model_id = $1
+            ORDER BY ts ASC, id ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(model_id)
+        .fetch_optional(self.pool.as_ref())
+        .await
+        .context("Failed to load first equity snapshot")?;
+
+        Ok(row.as_ref().map(total_from_row))
+    }
+
+    async fn latest(&self) -> Result<Vec<AccountTotal>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT DISTINCT ON (model_id) * FROM account_equity_snapshots
+            ORDER BY model_id, ts DESC, id DESC
+            "#,
+        )
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to load latest equity snapshots")?;
+
+        Ok(rows.iter().map(total_from_row).collect())
+    }
+
+    async fn inception(&self) -> Result<Option<i64>> {
+        let row = sqlx::query("SELECT MIN(ts) AS inception FROM account_equity_snapshots")
+            .fetch_one(self.pool.as_ref())
+            .await
+            .context("Failed to load inception time")?;
+        Ok(row.get("inception"))
+    }
+}
+
+fn total_from_row(row: &PgRow) -> AccountTotal {
+    let equity: f64 = row.get("equity");
+    AccountTotal {
+        model_id: row.get("model_id"),
+        model_name: row.get("model_name"),
+        strategy: row.get("strategy"),
+        risk_level: row.get("risk_level"),
+        broker_id: row.get("broker_id"),
+        timestamp: row.get("ts"),
+        account_value: equity,
+        dollar_equity: equity,
+        equity,
+        realized_pnl: row.get("realized_pnl"),
+        unrealized_pnl: row.get("unrealized_pnl"),
+        total_unrealized_pnl: row.get("total_unrealized_pnl"),
+        return_pct: row.get("return_pct"),
+        cum_pnl_pct: row.get("cum_pnl_pct"),
+        sharpe_ratio: row.get("sharpe_ratio"),
+        since_inception_minute_marker: row.get("minute_marker"),
+        since_inception_hourly_marker: row.get("hourly_marker"),
+    }
+}
@@ -0,0 +1,124 @@
+use super::orders::{parse_side, side_str};
+use crate::brokers::Trade;
+use anyhow::{Context, Result};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+/// 成交仓储 (`fills` 表, 以 broker_id + trade_id 唯一, 重复写入忽略)
+pub struct FillRepository {
+    pool: Arc<PgPool>,
+}
+
+impl FillRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// 写入成交, 返回是否为新记录
+    pub async fn insert(
+        &self,
+        broker_id: &str,
+        model_id: Option<&str>,
+        trade: &Trade,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO fills
+                (broker_id, trade_id, order_id, model_id, symbol, side, price, quantity, fee, ts)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (broker_id, trade_id) DO NOTHING
+            "#,
+        )
+        .bind(broker_id)
+        .bind(&trade.trade_id)
+        .bind(&trade.order_id)
+        .bind(model_id)
+        .bind(&trade.symbol)
+        .bind(side_str(&trade.side))
+        .bind(trade.price)
+        .bind(trade.quantity)
+        .bind(trade.fee)
+        .bind(trade.timestamp)
+        .execute(self.pool.as_ref())
+        .await
+        .context("Failed to insert fill")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 按时间倒序列出成交
+    pub async fn list(
+        &self,
+        broker_id: &str,
+        symbol: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Trade>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM fills
+            WHERE broker_id = $1 AND ($2::VARCHAR IS NULL OR symbol = $2)
+            ORDER BY ts DESC, trade_id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(broker_id)
+        .bind(symbol)
+        .bind(limit)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to list fills")?;
+
+        Ok(rows.iter().map(fill_from_row).collect())
+    }
+
+    /// 某订单的全部成交
+    pub async fn for_order(&self, broker_id: &str, order_id: &str) -> Result<Vec<Trade>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM fills
+            WHERE broker_id = $1 AND order_id = $2
+            ORDER BY ts ASC, trade_id ASC
+            "#,
+        )
+        .bind(broker_id)
+        .bind(order_id)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to list fills")?;
+
+        Ok(rows.iter().map(fill_from_row).collect())
+    }
+
+    /// 某经纪商上归属某模型的订单的全部成交 (按订单表的 model_id 归属)
+    pub async fn for_model_orders(&self, broker_id: &str, model_id: &str) -> Result<Vec<Trade>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT f.* FROM fills f
+            JOIN orders o ON o.broker_id = f.broker_id AND o.order_id = f.order_id
+            WHERE f.broker_id = $1 AND o.model_id = $2
+            ORDER BY f.ts ASC, f.trade_id ASC
+            "#,
+        )
+        .bind(broker_id)
+        .bind(model_id)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to list fills")?;
+
+        Ok(rows.iter().map(fill_from_row).collect())
+    }
+}
+
+fn fill_from_row(row: &PgRow) -> Trade {
+    Trade {
+        trade_id: row.get("trade_id"),
+        order_id: row.get("order_id"),
+        symbol: row.get("symbol"),
+        side: parse_side(row.get("side")),
+        price: row.get("price"),
+        quantity: row.get("quantity"),
+        fee: row.get("fee"),
+        timestamp: row.get("ts"),
+    }
+}
@@ -0,0 +1,89 @@
+use crate::brokers::LeaderboardEntry;
+use crate::leaderboard::{LeaderboardSnapshot, LeaderboardStore};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use sqlx::types::Json;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+/// 排行榜快照仓储 (`leaderboard_snapshots` 表, 每天每个模型一行)
+pub struct PgLeaderboardStore {
+    pool: Arc<PgPool>,
+}
+
+impl PgLeaderboardStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LeaderboardStore for PgLeaderboardStore {
+    async fn save(&self, snapshot: &LeaderboardSnapshot) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+        // 覆盖当天快照, 已下线的模型不再保留
+        sqlx::query("DELETE FROM leaderboard_snapshots WHERE snapshot_date = $1")
+            .bind(snapshot.date)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear leaderboard snapshot")?;
+        for entry in &snapshot.entries {
+            sqlx::query(
+                r#"
+                INSERT INTO leaderboard_snapshots
+                    (snapshot_date, model_id, rank, taken_at, currency, entry)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(snapshot.date)
+            .bind(&entry.model_id)
+            .bind(entry.rank)
+            .bind(snapshot.taken_at)
+            .bind(&snapshot.currency)
+            .bind(Json(entry))
+            .execute(&mut *tx)
+            .await
+            .context("Failed to insert leaderboard snapshot")?;
+        }
+        tx.commit()
+            .await
+            .context("Failed to commit leaderboard snapshot")?;
+        Ok(())
+    }
+
+    async fn load(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<LeaderboardSnapshot>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT snapshot_date, taken_at, currency, entry FROM leaderboard_snapshots
+            WHERE snapshot_date BETWEEN $1 AND $2
+            ORDER BY snapshot_date ASC, rank ASC, model_id ASC
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to load leaderboard snapshots")?;
+
+        let mut snapshots: Vec<LeaderboardSnapshot> = Vec::new();
+        for row in rows {
+            let date: NaiveDate = row.get("snapshot_date");
+            let Json(entry): Json<LeaderboardEntry> = row.get("entry");
+            match snapshots.last_mut() {
+                Some(snapshot) if snapshot.date == date => snapshot.entries.push(entry),
+                _ => snapshots.push(LeaderboardSnapshot {
+                    date,
+                    taken_at: row.get("taken_at"),
+                    currency: row.get("currency"),
+                    entries: vec![entry],
+                }),
+            }
+        }
+        Ok(snapshots)
+    }
+}
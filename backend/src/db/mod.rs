@@ -0,0 +1,80 @@
+// Postgres persistence: migrations and trading history repositories
+
+pub mod broker;
+pub mod equity;
+pub mod fills;
+pub mod leaderboard;
+pub mod orders;
+pub mod positions;
+pub mod usage;
+
+pub use broker::PersistentBroker;
+pub use equity::PgEquityStore;
+pub use fills::FillRepository;
+pub use leaderboard::PgLeaderboardStore;
+pub use orders::OrderRepository;
+pub use positions::PositionSnapshotRepository;
+pub use usage::PgUsageStore;
+
+use anyhow::{Context, Result};
+use sqlx::migrate::Migrator;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tracing::info;
+
+/// 内嵌的数据库迁移 (backend/migrations, 按版本号顺序执行)
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// 连接数据库并执行未应用的迁移
+pub async fn connect(url: &str, max_connections: u32) -> Result<Arc<PgPool>> {
+    let pool = PgPoolOptions::new()
+        .max_connections(max_connections)
+        .connect(url)
+        .await
+        .context("Failed to connect to database")?;
+    MIGRATOR
+        .run(&pool)
+        .await
+        .context("Failed to run database migrations")?;
+    info!("Database ready ({} migrations)", MIGRATOR.iter().count());
+    Ok(Arc::new(pool))
+}
+
+/// 设置了 `DATABASE_URL` 时连接数据库, 否则返回 None (以无数据库模式运行)
+pub async fn connect_from_env() -> Result<Option<Arc<PgPool>>> {
+    match std::env::var("DATABASE_URL") {
+        Ok(url) if !url.is_empty() => connect(&url, 10).await.map(Some),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_ordered() {
+        let versions: Vec<i64> = MIGRATOR.iter().map(|m| m.version).collect();
+        assert_eq!(versions, vec![1, 2, 3, 4, 5, 6, 7, 8]);
+
+        let sql: String = MIGRATOR.iter().map(|m| m.sql.as_ref()).collect();
+        for table in [
+            "agents",
+            "conversations",
+            "conversation_messages",
+            "orders",
+            "fills",
+            "position_snapshots",
+            "account_equity_snapshots",
+            "llm_calls",
+            "leaderboard_snapshots",
+        ] {
+            assert!(
+                sql.contains(&format!("CREATE TABLE IF NOT EXISTS {} (", table)),
+                "missing table {}",
+                table
+            );
+        }
+    }
+}
@@ -0,0 +1,291 @@
+use crate::brokers::{Order, OrderSide, OrderStatus, OrderType};
+use anyhow::{Context, Result};
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use tracing::debug;
+
+/// 订单仓储 (`orders` 表, 以 broker_id + order_id 唯一)
+pub struct OrderRepository {
+    pool: Arc<PgPool>,
+}
+
+impl OrderRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// 写入或更新订单
+    pub async fn upsert(
+        &self,
+        broker_id: &str,
+        model_id: Option<&str>,
+        order: &Order,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO orders
+                (broker_id, order_id, model_id, symbol, side, order_type, quantity,
+                 filled_quantity, price, avg_price, status, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (broker_id, order_id) DO UPDATE SET
+                model_id = COALESCE(EXCLUDED.model_id, orders.model_id),
+                filled_quantity = EXCLUDED.filled_quantity,
+                price = COALESCE(EXCLUDED.price, orders.price),
+                avg_price = COALESCE(EXCLUDED.avg_price, orders.avg_price),
+                status = EXCLUDED.status,
+                updated_at = GREATEST(EXCLUDED.updated_at, orders.updated_at)
+            "#,
+        )
+        .bind(broker_id)
+        .bind(&order.order_id)
+        .bind(model_id)
+        .bind(&order.symbol)
+        .bind(side_str(&order.side))
+        .bind(type_str(&order.order_type))
+        .bind(order.quantity)
+        .bind(order.filled_quantity)
+        .bind(order.price)
+        .bind(order.avg_price)
+        .bind(status_str(&order.status))
+        .bind(order.created_at)
+        .bind(order.updated_at)
+        .execute(self.pool.as_ref())
+        .await
+        .context("Failed to upsert order")?;
+
+        debug!("Stored order {} on {}", order.order_id, broker_id);
+        Ok(())
+    }
+
+    /// 更新订单状态, 返回是否存在该订单
+    pub async fn update_status(
+        &self,
+        broker_id: &str,
+        order_id: &str,
+        status: &OrderStatus,
+        timestamp: i64,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE orders SET status = $3, updated_at = $4
+            WHERE broker_id = $1 AND order_id = $2
+            "#,
+        )
+        .bind(broker_id)
+        .bind(order_id)
+        .bind(status_str(status))
+        .bind(timestamp)
+        .execute(self.pool.as_ref())
+        .await
+        .context("Failed to update order status")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 查询订单
+    pub async fn get(&self, broker_id: &str, order_id: &str) -> Result<Option<Order>> {
+        let row = sqlx::query(
+            r#"
+            SELECT * FROM orders WHERE broker_id = $1 AND order_id = $2
+            "#,
+        )
+        .bind(broker_id)
+        .bind(order_id)
+        .fetch_optional(self.pool.as_ref())
+        .await
+        .context("Failed to get order")?;
+
+        Ok(row.as_ref().map(order_from_row))
+    }
+
+    /// 按创建时间倒序列出订单
+    pub async fn list(
+        &self,
+        broker_id: &str,
+        symbol: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Order>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM orders
+            WHERE broker_id = $1 AND ($2::VARCHAR IS NULL OR symbol = $2)
+            ORDER BY created_at DESC, order_id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(broker_id)
+        .bind(symbol)
+        .bind(limit)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to list orders")?;
+
+        Ok(rows.iter().map(order_from_row).collect())
+    }
+
+    /// 设置订单归属的模型, 返回是否存在该订单
+    pub async fn assign_model(
+        &self,
+        broker_id: &str,
+        order_id: &str,
+        model_id: &str,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE orders SET model_id = $3
+            WHERE broker_id = $1 AND order_id = $2
+            "#,
+        )
+        .bind(broker_id)
+        .bind(order_id)
+        .bind(model_id)
+        .execute(self.pool.as_ref())
+        .await
+        .context("Failed to assign order model")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 某经纪商上归属某模型的全部订单 ID
+    pub async fn ids_for_model(&self, broker_id: &str, model_id: &str) -> Result<Vec<String>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT order_id FROM orders WHERE broker_id = $1 AND model_id = $2
+            "#,
+        )
+        .bind(broker_id)
+        .bind(model_id)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to list orders")?;
+
+        Ok(rows.iter().map(|row| row.get("order_id")).collect())
+    }
+
+    /// 某模型的订单 (跨经纪商)
+    pub async fn list_by_model(&self, model_id: &str, limit: i64) -> Result<Vec<Order>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM orders
+            WHERE model_id = $1
+            ORDER BY created_at DESC, order_id DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(model_id)
+        .bind(limit)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to list orders")?;
+
+        Ok(rows.iter().map(order_from_row).collect())
+    }
+}
+
+fn order_from_row(row: &PgRow) -> Order {
+    Order {
+        order_id: row.get("order_id"),
+        symbol: row.get("symbol"),
+        side: parse_side(row.get("side")),
+        order_type: parse_type(row.get("order_type")),
+        quantity: row.get("quantity"),
+        filled_quantity: row.get("filled_quantity"),
+        price: row.get("price"),
+        avg_price: row.get("avg_price"),
+        status: parse_status(row.get("status")),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    }
+}
+
+pub(crate) fn side_str(side: &OrderSide) -> &'static str {
+    match side {
+        OrderSide::Buy => "buy",
+        OrderSide::Sell => "sell",
+    }
+}
+
+pub(crate) fn parse_side(s: &str) -> OrderSide {
+    match s {
+        "sell" => OrderSide::Sell,
+        _ => OrderSide::Buy,
+    }
+}
+
+fn type_str(order_type: &OrderType) -> &'static str {
+    match order_type {
+        OrderType::Market => "market",
+        OrderType::Limit => "limit",
+        OrderType::Stop => "stop",
+        OrderType::StopLimit => "stop_limit",
+    }
+}
+
+fn parse_type(s: &str) -> OrderType {
+    match s {
+        "limit" => OrderType::Limit,
+        "stop" => OrderType::Stop,
+        "stop_limit" => OrderType::StopLimit,
+        _ => OrderType::Market,
+    }
+}
+
+fn status_str(status: &OrderStatus) -> &'static str {
+    match status {
+        OrderStatus::Pending => "pending",
+        OrderStatus::Accepted => "accepted",
+        OrderStatus::PartiallyFilled => "partially_filled",
+        OrderStatus::Filled => "filled",
+        OrderStatus::Cancelled => "cancelled",
+        OrderStatus::Rejected => "rejected",
+    }
+}
+
+fn parse_status(s: &str) -> OrderStatus {
+    match s {
+        "accepted" => OrderStatus::Accepted,
+        "partially_filled" => OrderStatus::PartiallyFilled,
+        "filled" => OrderStatus::Filled,
+        "cancelled" => OrderStatus::Cancelled,
+        "rejected" => OrderStatus::Rejected,
+        _ => OrderStatus::Pending,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enum_columns_round_trip() {
+        for status in [
+            OrderStatus::Pending,
+            OrderStatus::Accepted,
+            OrderStatus::PartiallyFilled,
+            OrderStatus::Filled,
+            OrderStatus::Cancelled,
+            OrderStatus::Rejected,
+        ] {
+            assert_eq!(
+                status_str(&parse_status(status_str(&status))),
+                status_str(&status)
+            );
+        }
+        for order_type in [
+            OrderType::Market,
+            OrderType::Limit,
+            OrderType::Stop,
+            OrderType::StopLimit,
+        ] {
+            assert_eq!(
+                type_str(&parse_type(type_str(&order_type))),
+                type_str(&order_type)
+            );
+        }
+        assert!(matches!(
+            parse_side(side_str(&OrderSide::Sell)),
+            OrderSide::Sell
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::brokers::{Position, Positions};
+use anyhow::{Context, Result};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// 持仓快照仓储 (`position_snapshots` 表)
+pub struct PositionSnapshotRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PositionSnapshotRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// 记录一次持仓快照
+    pub async fn record(
+        &self,
+        broker_id: &str,
+        model_id: Option<&str>,
+        positions: &Positions,
+        timestamp: i64,
+    ) -> Result<()> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("Failed to begin transaction")?;
+        for position in positions.positions.values() {
+            sqlx::query(
+                r#"
+                INSERT INTO position_snapshots
+                    (broker_id, model_id, snapshot_ts, symbol, entry_price, current_price,
+                     quantity, unrealized_pnl, direction, leverage, margin)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                "#,
+            )
+            .bind(broker_id)
+            .bind(model_id)
+            .bind(timestamp)
+            .bind(&position.symbol)
+            .bind(position.entry_price)
+            .bind(position.current_price)
+            .bind(position.quantity)
+            .bind(position.unrealized_pnl)
+            .bind(&position.direction)
+            .bind(position.leverage)
+            .bind(position.margin)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to insert position snapshot")?;
+        }
+        tx.commit()
+            .await
+            .context("Failed to commit position snapshot")?;
+        Ok(())
+    }
+
+    /// 最近一次快照
+    pub async fn latest(&self, broker_id: &str) -> Result<Positions> {
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM position_snapshots
+            WHERE broker_id = $1 AND snapshot_ts = (
+                SELECT MAX(snapshot_ts) FROM position_snapshots WHERE broker_id = $1
+            )
+            "#,
+        )
+        .bind(broker_id)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to load position snapshot")?;
+
+        let positions: HashMap<String, Position> = rows
+            .iter()
+            .map(|row| {
+                let position = Position {
+                    symbol: row.get("symbol"),
+                    entry_price: row.get("entry_price"),
+                    current_price: row.get("current_price"),
+                    quantity: row.get("quantity"),
+                    unrealized_pnl: row.get("unrealized_pnl"),
+                    direction: row.get("direction"),
+                    leverage: row.get("leverage"),
+                    margin: row.get("margin"),
+                    timestamp: row.get("snapshot_ts"),
+                };
+                (position.symbol.clone(), position)
+            })
+            .collect();
+        Ok(Positions { positions })
+    }
+}
@@ -0,0 +1,79 @@
+use crate::usage::{LlmCallRecord, UsageStore};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+/// LLM 调用记录仓储 (`llm_calls` 表)
+pub struct PgUsageStore {
+    pool: Arc<PgPool>,
+}
+
+impl PgUsageStore {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UsageStore for PgUsageStore {
+    async fn record(&self, call: &LlmCallRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO llm_calls
+                (model_id, cycle_id, provider, model, prompt_tokens, completion_tokens,
+                 latency_ms, cost_usd, error, ts)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+        )
+        .bind(&call.model_id)
+        .bind(&call.cycle_id)
+        .bind(&call.provider)
+        .bind(&call.model)
+        .bind(call.prompt_tokens as i32)
+        .bind(call.completion_tokens as i32)
+        .bind(call.latency_ms as i64)
+        .bind(call.cost_usd)
+        .bind(&call.error)
+        .bind(call.ts)
+        .execute(self.pool.as_ref())
+        .await
+        .context("Failed to insert LLM call")?;
+        Ok(())
+    }
+
+    async fn load(&self, model_id: Option<&str>, from: i64, to: i64) -> Result<Vec<LlmCallRecord>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT model_id, cycle_id, provider, model, prompt_tokens, completion_tokens,
+                   latency_ms, cost_usd, error, ts
+            FROM llm_calls
+            WHERE ($1::VARCHAR IS NULL OR model_id = $1) AND ts BETWEEN $2 AND $3
+            ORDER BY ts ASC, id ASC
+            "#,
+        )
+        .bind(model_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(self.pool.as_ref())
+        .await
+        .context("Failed to load LLM calls")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| LlmCallRecord {
+                model_id: row.get::<Option<String>, _>("model_id").unwrap_or_default(),
+                cycle_id: row.get("cycle_id"),
+                provider: row.get("provider"),
+                model: row.get("model"),
+                prompt_tokens: row.get::<Option<i32>, _>("prompt_tokens").unwrap_or(0) as u32,
+                completion_tokens: row.get::<Option<i32>, _>("completion_tokens").unwrap_or(0)
+                    as u32,
+                latency_ms: row.get::<Option<i64>, _>("latency_ms").unwrap_or(0) as u64,
+                cost_usd: row.get("cost_usd"),
+                error: row.get("error"),
+                ts: row.get("ts"),
+            })
+            .collect())
+    }
+}
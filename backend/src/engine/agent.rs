@@ -1,3 +1,5 @@
+use crate::config::AgentConfig;
+use crate::risk::RiskConfig;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,6 +10,39 @@ pub struct Agent {
     pub market: String,
     pub symbols: Vec<String>,
     pub enabled: bool,
+    /// 采样温度（为空时使用引擎默认值）
+    #[serde(default)]
+    pub temperature: Option<f32>,
+    /// 最大生成 token 数（为空时使用引擎默认值）
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    /// 初始资金
+    #[serde(default)]
+    pub initial_capital: f64,
+    /// 决策间隔（秒）, 为空时使用引擎默认值
+    #[serde(default)]
+    pub decision_interval_secs: Option<u64>,
+    /// Cron 调度表达式, 优先于 `decision_interval_secs`
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// System Prompt 模板
+    #[serde(default)]
+    pub system_prompt: Option<String>,
+    /// Prompt 模板版本, 多个时按决策周期轮换
+    #[serde(default)]
+    pub prompts: Vec<String>,
+    /// Prompt token 预算
+    #[serde(default)]
+    pub prompt_token_budget: Option<usize>,
+    /// 允许使用的工具（为空时允许全部）
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// 每个周期必须输出结构化交易决策, 缺失或不合法时追加修正轮
+    #[serde(default)]
+    pub require_decision: bool,
+    /// 风控配置覆盖
+    #[serde(default)]
+    pub risk: Option<RiskConfig>,
 }
 
 impl Agent {
@@ -19,6 +54,45 @@ impl Agent {
             market,
             symbols: Vec::new(),
             enabled: true,
+            temperature: None,
+            max_tokens: None,
+            initial_capital: 0.0,
+            decision_interval_secs: None,
+            schedule: None,
+            system_prompt: None,
+            prompts: Vec::new(),
+            prompt_token_budget: None,
+            allowed_tools: Vec::new(),
+            require_decision: false,
+            risk: None,
         }
     }
+
+    /// 从 agents.yaml 配置创建 Agent, Provider 按 `AgentLlmConfig::registry_key` 引用
+    pub fn from_config(config: &AgentConfig) -> Self {
+        Self {
+            id: config.id.clone(),
+            name: config.name.clone(),
+            llm_provider: config.llm.registry_key(),
+            market: config.broker.clone(),
+            symbols: config.symbols.clone(),
+            enabled: config.enabled,
+            temperature: config.llm.temperature,
+            max_tokens: config.llm.max_tokens,
+            initial_capital: config.initial_capital,
+            decision_interval_secs: config.decision_interval_secs,
+            schedule: config.schedule.clone(),
+            system_prompt: config.system_prompt.clone(),
+            prompts: config.prompts.clone(),
+            prompt_token_budget: config.prompt_token_budget,
+            allowed_tools: config.allowed_tools.clone(),
+            require_decision: config.require_decision,
+            risk: config.risk.clone(),
+        }
+    }
+
+    /// 是否允许使用指定工具
+    pub fn allows_tool(&self, name: &str) -> bool {
+        self.allowed_tools.is_empty() || self.allowed_tools.iter().any(|t| t == name)
+    }
 }
@@ -0,0 +1,222 @@
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// 交易时段, `end` 早于 `start` 时表示跨夜（夜盘）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradingSession {
+    pub name: String,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl TradingSession {
+    /// 是否为跨夜时段
+    pub fn is_overnight(&self) -> bool {
+        self.end <= self.start
+    }
+}
+
+/// 交易日历文件格式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CalendarFile {
+    name: String,
+    /// UTC 偏移, 例如 "+08:00"
+    timezone: String,
+    sessions: Vec<SessionFile>,
+    #[serde(default)]
+    holidays: Vec<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SessionFile {
+    name: String,
+    /// "HH:MM"
+    start: String,
+    /// "HH:MM"
+    end: String,
+}
+
+/// 交易所交易日历
+///
+/// 周一至周五且不在节假日列表中的日期为交易日。
+/// 夜盘归属于开始当天, 仅当下一个工作日也是交易日时才开盘（长假前最后一个交易日无夜盘）。
+#[derive(Debug, Clone)]
+pub struct TradingCalendar {
+    name: String,
+    offset: FixedOffset,
+    sessions: Vec<TradingSession>,
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl TradingCalendar {
+    pub fn new(
+        name: impl Into<String>,
+        offset: FixedOffset,
+        sessions: Vec<TradingSession>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            offset,
+            sessions,
+            holidays: BTreeSet::new(),
+        }
+    }
+
+    /// 添加节假日
+    pub fn with_holidays(mut self, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    /// 从 YAML 文件加载
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read calendar {}", path.display()))?;
+        Self::from_yaml_str(&content)
+    }
+
+    /// 从默认路径加载 (`config/trading_calendar.yaml`), 找不到文件时返回 None
+    pub fn load_default() -> Result<Option<Self>> {
+        let possible_paths = [
+            "config/trading_calendar.yaml",
+            "backend/config/trading_calendar.yaml",
+            "../config/trading_calendar.yaml",
+        ];
+
+        for path in possible_paths {
+            if Path::new(path).exists() {
+                return Self::from_file(path).map(Some);
+            }
+        }
+
+        tracing::warn!("No trading calendar file found, agents run around the clock");
+        Ok(None)
+    }
+
+    /// 从 YAML 字符串加载
+    pub fn from_yaml_str(yaml: &str) -> Result<Self> {
+        let file: CalendarFile = serde_yaml::from_str(yaml)?;
+        let offset: FixedOffset = file
+            .timezone
+            .parse()
+            .map_err(|_| anyhow!("Invalid timezone offset: {}", file.timezone))?;
+
+        let sessions = file
+            .sessions
+            .into_iter()
+            .map(|s| {
+                Ok(TradingSession {
+                    start: parse_time(&s.start)?,
+                    end: parse_time(&s.end)?,
+                    name: s.name,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self::new(file.name, offset, sessions).with_holidays(file.holidays))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 交易所时区
+    pub fn offset(&self) -> FixedOffset {
+        self.offset
+    }
+
+    /// 是否为交易日
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+
+    /// 指定时刻所在的交易时段
+    pub fn session_at(&self, at: DateTime<Utc>) -> Option<&TradingSession> {
+        let local = at.with_timezone(&self.offset).naive_local();
+        let (date, time) = (local.date(), local.time());
+
+        self.sessions.iter().find(|s| {
+            if !s.is_overnight() {
+                s.start <= time && time < s.end && self.is_trading_day(date)
+            } else if time >= s.start {
+                self.has_night_session(date)
+            } else {
+                time < s.end && self.has_night_session(date - Duration::days(1))
+            }
+        })
+    }
+
+    /// 指定时刻是否处于交易时段
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        self.session_at(at).is_some()
+    }
+
+    /// 当天晚上是否有夜盘
+    fn has_night_session(&self, date: NaiveDate) -> bool {
+        if !self.is_trading_day(date) {
+            return false;
+        }
+
+        let mut next = date + Duration::days(1);
+        while matches!(next.weekday(), Weekday::Sat | Weekday::Sun) {
+            next += Duration::days(1);
+        }
+        !self.holidays.contains(&next)
+    }
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M:%S"))
+        .map_err(|_| anyhow!("Invalid session time: {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beijing(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("{}+08:00", s))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_ctp_sessions() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/trading_calendar.yaml");
+        let calendar = TradingCalendar::from_file(path).unwrap();
+        assert_eq!(calendar.name(), "ctp");
+
+        // 2025-03-12 周三
+        assert!(calendar.is_open(beijing("2025-03-12T09:30:00")));
+        assert!(!calendar.is_open(beijing("2025-03-12T12:00:00")));
+        assert!(!calendar.is_open(beijing("2025-03-12T15:00:00")));
+        assert_eq!(
+            calendar
+                .session_at(beijing("2025-03-12T22:00:00"))
+                .unwrap()
+                .name,
+            "night"
+        );
+        // 夜盘跨过午夜
+        assert!(calendar.is_open(beijing("2025-03-13T01:00:00")));
+        assert!(!calendar.is_open(beijing("2025-03-13T03:00:00")));
+
+        // 周五夜盘延续到周六凌晨, 周六白天与周日休市
+        assert!(calendar.is_open(beijing("2025-03-14T21:30:00")));
+        assert!(calendar.is_open(beijing("2025-03-15T01:00:00")));
+        assert!(!calendar.is_open(beijing("2025-03-15T10:00:00")));
+        assert!(!calendar.is_open(beijing("2025-03-16T21:30:00")));
+
+        // 国庆: 节前最后一个交易日无夜盘, 假期休市
+        assert!(calendar.is_open(beijing("2025-09-30T10:00:00")));
+        assert!(!calendar.is_open(beijing("2025-09-30T21:30:00")));
+        assert!(!calendar.is_open(beijing("2025-10-03T10:00:00")));
+        assert!(calendar.is_open(beijing("2025-10-09T10:00:00")));
+    }
+}
@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// 引擎时钟（秒级时间戳）
+///
+/// 默认读取系统时间; 回测时使用模拟时钟, 由回测驱动推进,
+/// 所有克隆共享同一时间。
+#[derive(Debug, Clone, Default)]
+pub struct Clock {
+    simulated: Option<Arc<AtomicI64>>,
+}
+
+impl Clock {
+    /// 系统时钟
+    pub fn system() -> Self {
+        Self::default()
+    }
+
+    /// 从 `start` 开始的模拟时钟
+    pub fn simulated(start: i64) -> Self {
+        Self {
+            simulated: Some(Arc::new(AtomicI64::new(start))),
+        }
+    }
+
+    pub fn is_simulated(&self) -> bool {
+        self.simulated.is_some()
+    }
+
+    /// 当前时间
+    pub fn now(&self) -> i64 {
+        match &self.simulated {
+            Some(now) => now.load(Ordering::SeqCst),
+            None => chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// 设置模拟时间（系统时钟忽略）
+    pub fn set(&self, timestamp: i64) {
+        if let Some(now) = &self.simulated {
+            now.store(timestamp, Ordering::SeqCst);
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, TimeZone, Timelike, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+/// 向后搜索下一次触发时间的最大天数
+const MAX_SEARCH_DAYS: i64 = 366 * 5;
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// Cron 表达式
+///
+/// 支持 5 段 (`分 时 日 月 周`) 与 6 段 (`秒 分 时 日 月 周`) 格式,
+/// 每段支持 `*`、`a-b`、`*/n`、`a-b/n` 与逗号列表, 月份与星期支持英文缩写 (`JAN`, `MON-FRI`)。
+/// 日与周同时受限时, 任一匹配即触发 (与标准 cron 一致)。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expr: String,
+    seconds: u64,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+    offset: FixedOffset,
+}
+
+impl CronSchedule {
+    /// 解析 Cron 表达式（默认按 UTC 计算）
+    pub fn parse(expr: &str) -> Result<Self> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let (seconds, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            n => bail!(
+                "Invalid cron expression '{}': expected 5 or 6 fields, got {}",
+                expr,
+                n
+            ),
+        };
+
+        let field = |value: &str, min: u32, max: u32, names: &[&str]| {
+            parse_field(value, min, max, names)
+                .map_err(|e| anyhow!("Invalid cron expression '{}': {}", expr, e))
+        };
+
+        let mut days_of_week = field(rest[4], 0, 7, &WEEKDAY_NAMES)?;
+        // 7 与 0 均表示周日
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            expr: expr.to_string(),
+            seconds: field(seconds, 0, 59, &[])?,
+            minutes: field(rest[0], 0, 59, &[])?,
+            hours: field(rest[1], 0, 23, &[])?,
+            days_of_month: field(rest[2], 1, 31, &[])?,
+            months: field(rest[3], 1, 12, &MONTH_NAMES)?,
+            days_of_week,
+            dom_restricted: rest[2] != "*",
+            dow_restricted: rest[4] != "*",
+            offset: FixedOffset::east_opt(0).expect("zero offset"),
+        })
+    }
+
+    /// 按指定时区计算触发时间, 例如 `FixedOffset::east_opt(8 * 3600)` 表示北京时间
+    pub fn with_offset(mut self, offset: FixedOffset) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// 原始表达式
+    pub fn expression(&self) -> &str {
+        &self.expr
+    }
+
+    /// `after` 之后（不含）的下一次触发时间
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = (after.with_nanosecond(0)? + Duration::seconds(1))
+            .with_timezone(&self.offset)
+            .naive_local();
+        let start_date = start.date();
+
+        for offset in 0..MAX_SEARCH_DAYS {
+            let date = start_date + Duration::days(offset);
+            if !self.matches_date(date) {
+                continue;
+            }
+
+            for hour in bits(self.hours, 0, 23) {
+                for minute in bits(self.minutes, 0, 59) {
+                    for second in bits(self.seconds, 0, 59) {
+                        let candidate = date.and_hms_opt(hour, minute, second)?;
+                        if candidate < start {
+                            continue;
+                        }
+                        if let Some(local) = self.offset.from_local_datetime(&candidate).single() {
+                            return Some(local.with_timezone(&Utc));
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if !has_bit(self.months, date.month()) {
+            return false;
+        }
+
+        let dom = has_bit(self.days_of_month, date.day());
+        let dow = has_bit(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
+
+fn has_bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn bits(mask: u64, min: u32, max: u32) -> impl Iterator<Item = u32> {
+    (min..=max).filter(move |v| has_bit(mask, *v))
+}
+
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| anyhow!("invalid step '{}'", step))?;
+                if step == 0 {
+                    bail!("step must be positive in '{}'", part);
+                }
+                (range, Some(step))
+            }
+            None => (part, None),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_value(a, min, names)?, parse_value(b, min, names)?)
+        } else {
+            let value = parse_value(range, min, names)?;
+            // `a/n` 表示从 a 开始到最大值
+            (value, if step.is_some() { max } else { value })
+        };
+
+        if start < min || end > max || start > end {
+            bail!("value out of range [{}, {}] in '{}'", min, max, part);
+        }
+
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+fn parse_value(value: &str, min: u32, names: &[&str]) -> Result<u32> {
+    if let Some(index) = names.iter().position(|n| n.eq_ignore_ascii_case(value)) {
+        // 月份从 1 开始, 星期从 0 开始
+        return Ok(index as u32 + min);
+    }
+    value
+        .parse()
+        .map_err(|_| anyhow!("invalid value '{}'", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_and_next() {
+        let every_15 = CronSchedule::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            every_15.next_after(utc("2025-03-10T10:07:30Z")),
+            Some(utc("2025-03-10T10:15:00Z"))
+        );
+        // 恰好在触发点时返回下一次
+        assert_eq!(
+            every_15.next_after(utc("2025-03-10T10:15:00Z")),
+            Some(utc("2025-03-10T10:30:00Z"))
+        );
+
+        let seconds = CronSchedule::parse("*/10 * * * * *").unwrap();
+        assert_eq!(
+            seconds.next_after(utc("2025-03-10T10:00:01Z")),
+            Some(utc("2025-03-10T10:00:10Z"))
+        );
+
+        // 工作日 9:30 北京时间, 周五之后跳到下周一
+        let weekdays = CronSchedule::parse("30 9 * * MON-FRI")
+            .unwrap()
+            .with_offset(FixedOffset::east_opt(8 * 3600).unwrap());
+        assert_eq!(
+            weekdays.next_after(utc("2025-03-14T02:00:00Z")),
+            Some(utc("2025-03-17T01:30:00Z"))
+        );
+
+        let sunday = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert_eq!(
+            sunday.next_after(utc("2025-03-10T00:00:00Z")),
+            Some(utc("2025-03-16T00:00:00Z"))
+        );
+
+        let leap = CronSchedule::parse("0 12 29 FEB *").unwrap();
+        assert_eq!(
+            leap.next_after(utc("2025-01-01T00:00:00Z")),
+            Some(utc("2028-02-29T12:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for expr in [
+            "* * * *",
+            "60 * * * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "* * * * FOO",
+        ] {
+            assert!(CronSchedule::parse(expr).is_err(), "{}", expr);
+        }
+        // 2 月 31 日永不触发
+        let never = CronSchedule::parse("0 0 31 2 *").unwrap();
+        assert_eq!(never.next_after(Utc::now()), None);
+    }
+}
@@ -0,0 +1,708 @@
+use super::trading_tools::{outcome_json, RiskCheckedOrderTool};
+use crate::brokers::{DynBroker, OrderRequest, OrderSide, OrderType, Position};
+use crate::mcp::{McpTool, ToolHandler};
+use crate::risk::RiskConfig;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// 决策缺失或不合法时最多追加的修正轮数
+pub const MAX_DECISION_REPAIRS: usize = 2;
+
+/// 决策动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionAction {
+    #[serde(alias = "OPEN", alias = "entry")]
+    Open,
+    #[serde(alias = "CLOSE", alias = "exit")]
+    Close,
+    #[serde(alias = "HOLD")]
+    Hold,
+    #[serde(alias = "ADJUST")]
+    Adjust,
+}
+
+/// 持仓方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PositionSide {
+    #[serde(alias = "LONG", alias = "buy")]
+    Long,
+    #[serde(alias = "SHORT", alias = "sell")]
+    Short,
+}
+
+impl PositionSide {
+    /// 开仓方向对应的订单方向
+    pub fn open_order_side(&self) -> OrderSide {
+        match self {
+            PositionSide::Long => OrderSide::Buy,
+            PositionSide::Short => OrderSide::Sell,
+        }
+    }
+}
+
+/// 退出计划, 与 nof1 持仓数据中的 `exit_plan` 一致
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExitPlan {
+    #[serde(default)]
+    pub profit_target: Option<f64>,
+    #[serde(default)]
+    pub stop_loss: Option<f64>,
+    #[serde(default)]
+    pub invalidation_condition: Option<String>,
+}
+
+/// LLM 输出的结构化交易决策
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeDecision {
+    pub action: DecisionAction,
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub side: Option<PositionSide>,
+    /// 开仓数量, 与 `risk_usd` 二选一
+    #[serde(default)]
+    pub quantity: Option<f64>,
+    /// 止损触发时愿意承担的亏损(USD), 按止损距离换算数量
+    #[serde(default)]
+    pub risk_usd: Option<f64>,
+    #[serde(default)]
+    pub leverage: Option<f64>,
+    /// 置信度 0~1
+    pub confidence: f64,
+    #[serde(default)]
+    pub exit_plan: Option<ExitPlan>,
+    #[serde(default)]
+    pub justification: Option<String>,
+}
+
+impl TradeDecision {
+    /// 开仓数量: 优先使用 `quantity`, 否则由 `risk_usd / |price - stop_loss|` 计算
+    pub fn resolved_quantity(&self, price: f64) -> Option<f64> {
+        if let Some(quantity) = self.quantity {
+            return Some(quantity);
+        }
+        let risk_usd = self.risk_usd?;
+        let stop_loss = self.exit_plan.as_ref()?.stop_loss?;
+        let distance = (price - stop_loss).abs();
+        if distance > 0.0 {
+            Some(risk_usd / distance)
+        } else {
+            None
+        }
+    }
+
+    /// 单条决策的 JSON Schema
+    pub fn json_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["open", "close", "hold", "adjust"]
+                },
+                "symbol": { "type": "string", "description": "Trading symbol (not required for hold)" },
+                "side": { "type": "string", "enum": ["long", "short"], "description": "Required for open" },
+                "quantity": { "type": "number", "description": "Position size; use either quantity or risk_usd" },
+                "risk_usd": { "type": "number", "description": "USD lost if the stop loss is hit; requires exit_plan.stop_loss" },
+                "leverage": { "type": "number", "description": "Set on the broker before opening; rejected if the broker cannot apply it" },
+                "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+                "exit_plan": {
+                    "type": "object",
+                    "properties": {
+                        "profit_target": { "type": "number" },
+                        "stop_loss": { "type": "number" },
+                        "invalidation_condition": { "type": "string" }
+                    }
+                },
+                "justification": { "type": "string" }
+            },
+            "required": ["action", "confidence"]
+        })
+    }
+}
+
+/// 决策周期内已接受的决策（在工具与引擎之间共享）
+pub type DecisionLog = Arc<Mutex<Vec<TradeDecision>>>;
+
+/// 从 LLM 文本回复中提取 JSON: 依次尝试 ```json 代码块、全文、首个 `{` 到末个 `}`
+pub fn extract_json(text: &str) -> Option<serde_json::Value> {
+    let mut candidates = Vec::new();
+    if let Some(start) = text.find("```") {
+        let body = &text[start + 3..];
+        let body = body.strip_prefix("json").unwrap_or(body);
+        if let Some(end) = body.find("```") {
+            candidates.push(&body[..end]);
+        }
+    }
+    candidates.push(text);
+    if let (Some(start), Some(end)) = (text.find('{'), text.rfind('}')) {
+        if start < end {
+            candidates.push(&text[start..=end]);
+        }
+    }
+
+    candidates
+        .into_iter()
+        .find_map(|c| serde_json::from_str(c.trim()).ok())
+}
+
+/// 解析决策, 接受 `{"decisions": [...]}`、数组或单个对象; 返回全部解析错误
+pub fn parse_decisions(value: &serde_json::Value) -> Result<Vec<TradeDecision>, Vec<String>> {
+    let items = match value.get("decisions").unwrap_or(value) {
+        serde_json::Value::Array(items) => items.clone(),
+        item @ serde_json::Value::Object(_) => vec![item.clone()],
+        _ => {
+            return Err(vec![
+                "expected a decision object or an array of decisions".into()
+            ])
+        }
+    };
+    if items.is_empty() {
+        return Err(vec![
+            "no decisions submitted; use action \"hold\" to stay flat".into(),
+        ]);
+    }
+
+    let mut decisions = Vec::with_capacity(items.len());
+    let mut errors = Vec::new();
+    for (i, item) in items.into_iter().enumerate() {
+        match serde_json::from_value(item) {
+            Ok(decision) => decisions.push(decision),
+            Err(e) => errors.push(format!("decisions[{}]: {}", i, e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(decisions)
+    } else {
+        Err(errors)
+    }
+}
+
+/// 按交易品种与风控限额校验决策
+#[derive(Debug, Clone)]
+pub struct DecisionValidator {
+    symbols: Vec<String>,
+    risk: RiskConfig,
+}
+
+impl DecisionValidator {
+    pub fn new(symbols: Vec<String>, risk: RiskConfig) -> Self {
+        Self { symbols, risk }
+    }
+
+    fn in_universe(&self, symbol: &str) -> bool {
+        self.symbols.is_empty() || self.symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol))
+    }
+
+    /// 校验单条决策, 返回全部错误; `price` 为最新价, `position` 为该品种当前持仓
+    pub fn validate(
+        &self,
+        decision: &TradeDecision,
+        price: Option<f64>,
+        position: Option<&Position>,
+    ) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if !(0.0..=1.0).contains(&decision.confidence) {
+            errors.push(format!(
+                "confidence {} must be between 0 and 1",
+                decision.confidence
+            ));
+        }
+
+        let symbol = match decision.symbol.as_deref() {
+            Some(symbol) if !self.in_universe(symbol) => {
+                errors.push(format!("symbol {} is not in the agent's universe", symbol));
+                return errors;
+            }
+            Some(symbol) => symbol,
+            None if decision.action == DecisionAction::Hold => return errors,
+            None => {
+                errors.push("symbol is required".to_string());
+                return errors;
+            }
+        };
+
+        match decision.action {
+            DecisionAction::Open => self.validate_open(decision, symbol, price, &mut errors),
+            DecisionAction::Close if position.is_none() => {
+                errors.push(format!("no open position in {} to close", symbol));
+            }
+            DecisionAction::Adjust => {
+                if decision.exit_plan.is_none() {
+                    errors.push("adjust requires an exit_plan".to_string());
+                }
+                if position.is_none() {
+                    errors.push(format!("no open position in {} to adjust", symbol));
+                }
+            }
+            _ => {}
+        }
+
+        errors
+    }
+
+    fn validate_open(
+        &self,
+        decision: &TradeDecision,
+        symbol: &str,
+        price: Option<f64>,
+        errors: &mut Vec<String>,
+    ) {
+        let Some(side) = decision.side else {
+            errors.push("open requires side (long or short)".to_string());
+            return;
+        };
+        let Some(price) = price.filter(|p| *p > 0.0) else {
+            errors.push(format!("no market price for {}", symbol));
+            return;
+        };
+
+        let exit_plan = decision.exit_plan.clone().unwrap_or_default();
+        if let Some(stop) = exit_plan.stop_loss {
+            let valid = match side {
+                PositionSide::Long => stop < price,
+                PositionSide::Short => stop > price,
+            };
+            if !valid {
+                errors.push(format!(
+                    "stop_loss {} is on the wrong side of price {} for a {:?} position",
+                    stop, price, side
+                ));
+            }
+        }
+        if let Some(target) = exit_plan.profit_target {
+            let valid = match side {
+                PositionSide::Long => target > price,
+                PositionSide::Short => target < price,
+            };
+            if !valid {
+                errors.push(format!(
+                    "profit_target {} is on the wrong side of price {} for a {:?} position",
+                    target, price, side
+                ));
+            }
+        }
+
+        if decision.quantity.is_none() && decision.risk_usd.is_none() {
+            errors.push("open requires quantity or risk_usd".to_string());
+        }
+        if decision.quantity.is_some_and(|q| q <= 0.0) {
+            errors.push("quantity must be positive".to_string());
+        }
+        if decision.risk_usd.is_some_and(|r| r <= 0.0) {
+            errors.push("risk_usd must be positive".to_string());
+        }
+        if decision.quantity.is_none()
+            && decision.risk_usd.is_some()
+            && exit_plan.stop_loss.is_none()
+        {
+            errors.push("risk_usd requires exit_plan.stop_loss".to_string());
+        }
+        if let Some(leverage) = decision.leverage {
+            if leverage < 1.0 {
+                errors.push(format!("leverage {} must be at least 1", leverage));
+            }
+        }
+
+        if !self.risk.enabled || !errors.is_empty() {
+            return;
+        }
+
+        let limits = &self.risk;
+        if let Some(leverage) = decision.leverage {
+            if leverage > limits.position_limits.max_leverage {
+                errors.push(format!(
+                    "leverage {} exceeds max leverage {}",
+                    leverage, limits.position_limits.max_leverage
+                ));
+            }
+        }
+        if let Some(risk_usd) = decision.risk_usd {
+            if risk_usd > limits.loss_limits.max_loss_per_trade {
+                errors.push(format!(
+                    "risk_usd {} exceeds max loss per trade {}",
+                    risk_usd, limits.loss_limits.max_loss_per_trade
+                ));
+            }
+        }
+        if let Some(quantity) = decision.resolved_quantity(price) {
+            let value = quantity * price;
+            let sizes = &limits.order_size_limits;
+            if value < sizes.min_order_value || value > sizes.max_order_value {
+                errors.push(format!(
+                    "order value {:.2} must be between {} and {}",
+                    value, sizes.min_order_value, sizes.max_order_value
+                ));
+            }
+        }
+    }
+}
+
+/// 工具: 提交结构化交易决策
+///
+/// 整批决策先解析、校验并确定每笔订单的数量与参考价, 任一不合法 (含经纪商无法应用的杠杆)
+/// 时全部不执行并把错误返回给 LLM 修正; 之后开仓/平仓逐笔经 `RiskCheckedOrderTool` 下单,
+/// 持有与调整仅记录。
+/// 单笔下单失败不影响其他决策, 各决策的结果一并返回, 只有已执行的决策写入决策记录。
+#[derive(Clone)]
+pub struct SubmitDecisionsTool {
+    broker: Arc<dyn DynBroker>,
+    orders: RiskCheckedOrderTool,
+    validator: DecisionValidator,
+    log: DecisionLog,
+    submitted: Arc<AtomicBool>,
+}
+
+impl SubmitDecisionsTool {
+    pub fn new(
+        broker: Arc<dyn DynBroker>,
+        orders: RiskCheckedOrderTool,
+        validator: DecisionValidator,
+        log: DecisionLog,
+    ) -> Self {
+        Self {
+            broker,
+            orders,
+            validator,
+            log,
+            submitted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 是否已有一批决策通过校验并执行 (含部分订单失败的情况)
+    pub fn submitted(&self) -> bool {
+        self.submitted.load(Ordering::SeqCst)
+    }
+
+    pub fn schema() -> McpTool {
+        McpTool {
+            name: "submit_decisions".to_string(),
+            description: "Submit this cycle's trading decisions (open/close/hold/adjust) with \
+                          confidence and exit plan; invalid decisions are returned with errors \
+                          and nothing is executed"
+                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "decisions": {
+                        "type": "array",
+                        "items": TradeDecision::json_schema()
+                    }
+                },
+                "required": ["decisions"]
+            }),
+        }
+    }
+
+    /// 确定单条已校验决策的订单与参考价, 持有与调整无需下单
+    fn plan(
+        &self,
+        decision: &mut TradeDecision,
+        price: Option<f64>,
+        position: Option<&Position>,
+    ) -> Result<Option<(OrderRequest, f64)>, anyhow::Error> {
+        let symbol = match (decision.action, decision.symbol.clone()) {
+            (DecisionAction::Open | DecisionAction::Close, Some(symbol)) => symbol,
+            _ => return Ok(None),
+        };
+
+        if decision.action == DecisionAction::Open {
+            let side = decision.side.expect("validated open has a side");
+            let price = price.ok_or_else(|| anyhow::anyhow!("no market price for {}", symbol))?;
+            let quantity = decision
+                .resolved_quantity(price)
+                .ok_or_else(|| anyhow::anyhow!("cannot size position for {}", symbol))?;
+            decision.quantity = Some(quantity);
+            let request = OrderRequest {
+                symbol,
+                side: side.open_order_side(),
+                order_type: OrderType::Market,
+                quantity,
+                price: None,
+                time_in_force: None,
+                reduce_only: false,
+            };
+            return Ok(Some((request, price)));
+        }
+
+        let position =
+            position.ok_or_else(|| anyhow::anyhow!("no open position in {} to close", symbol))?;
+        let short = position.direction.as_deref() == Some("short") || position.quantity < 0.0;
+        let request = OrderRequest {
+            symbol,
+            side: if short {
+                OrderSide::Buy
+            } else {
+                OrderSide::Sell
+            },
+            order_type: OrderType::Market,
+            quantity: position.quantity.abs(),
+            price: None,
+            time_in_force: None,
+            reduce_only: false,
+        };
+        Ok(Some((request, price.unwrap_or(position.current_price))))
+    }
+}
+
+#[async_trait]
+impl ToolHandler for SubmitDecisionsTool {
+    async fn execute(&self, input: serde_json::Value) -> Result<serde_json::Value, anyhow::Error> {
+        let mut decisions = parse_decisions(&input)
+            .map_err(|errors| anyhow::anyhow!("Invalid decisions:\n- {}", errors.join("\n- ")))?;
+
+        let positions: HashMap<String, Position> = self
+            .broker
+            .get_positions(None)
+            .await?
+            .positions
+            .into_values()
+            .map(|p| (p.symbol.to_uppercase(), p))
+            .collect();
+        let mut prices = HashMap::new();
+        for symbol in decisions.iter().filter_map(|d| d.symbol.as_deref()) {
+            if let Entry::Vacant(entry) = prices.entry(symbol.to_uppercase()) {
+                let ticker = self.broker.get_ticker_24h(symbol).await;
+                entry.insert(ticker.ok().map(|t| t.last_price));
+            }
+        }
+        let lookup = |d: &TradeDecision| {
+            let key = d.symbol.as_deref().unwrap_or_default().to_uppercase();
+            (prices.get(&key).copied().flatten(), positions.get(&key))
+        };
+
+        // 下单前确定全部订单, 避免部分决策已下单后整批被要求重新提交
+        let mut errors = Vec::new();
+        let mut plans = Vec::with_capacity(decisions.len());
+        for (i, decision) in decisions.iter_mut().enumerate() {
+            let (price, position) = lookup(decision);
+            let mut decision_errors = self.validator.validate(decision, price, position);
+            if decision_errors.is_empty() {
+                match self.plan(decision, price, position) {
+                    Ok(plan) => plans.push(plan),
+                    Err(e) => decision_errors.push(e.to_string()),
+                }
+            }
+            errors.extend(
+                decision_errors
+                    .into_iter()
+                    .map(|e| format!("decisions[{}]: {}", i, e)),
+            );
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("Invalid decisions:\n- {}", errors.join("\n- "));
+        }
+
+        // 开仓前在经纪商设置杠杆, 无法应用时整批不执行
+        for (i, decision) in decisions.iter().enumerate() {
+            let (DecisionAction::Open, Some(symbol), Some(leverage)) = (
+                decision.action,
+                decision.symbol.as_deref(),
+                decision.leverage,
+            ) else {
+                continue;
+            };
+            if let Err(e) = self.broker.set_leverage(symbol, leverage).await {
+                errors.push(format!(
+                    "decisions[{}]: leverage {} cannot be applied: {}",
+                    i, leverage, e
+                ));
+            }
+        }
+        if !errors.is_empty() {
+            anyhow::bail!("Invalid decisions:\n- {}", errors.join("\n- "));
+        }
+        self.submitted.store(true, Ordering::SeqCst);
+
+        let mut results = Vec::with_capacity(decisions.len());
+        let mut executed = Vec::with_capacity(decisions.len());
+        for (decision, plan) in decisions.into_iter().zip(plans) {
+            let (order, done) = match plan {
+                Some((request, price)) => match self.orders.submit_at(request, price).await {
+                    Ok(outcome) => (outcome_json(&outcome), outcome.is_executed()),
+                    Err(e) => (
+                        serde_json::json!({ "status": "failed", "error": e.to_string() }),
+                        false,
+                    ),
+                },
+                None => (serde_json::Value::Null, true),
+            };
+            results.push(serde_json::json!({
+                "action": decision.action,
+                "symbol": decision.symbol,
+                "order": order,
+            }));
+            if done {
+                executed.push(decision);
+            }
+        }
+        let accepted = executed.len();
+        self.log.lock().await.extend(executed);
+
+        Ok(serde_json::json!({ "accepted": accepted, "results": results }))
+    }
+}
+
+/// 决策缺失或不合法时追加给 LLM 的修正提示
+pub fn repair_prompt(error: &str) -> String {
+    format!(
+        "Your trading decision could not be accepted:\n{}\n\nCall submit_decisions again with \
+         corrected decisions, or reply with only a JSON object {{\"decisions\": [...]}} where each \
+         item matches this schema:\n{}",
+        error,
+        TradeDecision::json_schema()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::MockBroker;
+    use crate::engine::OrderLog;
+    use crate::risk::RiskManager;
+
+    fn tool(log: DecisionLog, orders: OrderLog) -> SubmitDecisionsTool {
+        let broker: Arc<dyn DynBroker> = Arc::new(MockBroker::new());
+        let symbols = vec!["BTCUSDT".to_string()];
+        let order_tool = RiskCheckedOrderTool::new(
+            broker.clone(),
+            Arc::new(RiskManager::new(RiskConfig::default())),
+            "agent-1".to_string(),
+            symbols.clone(),
+            orders,
+        );
+        SubmitDecisionsTool::new(
+            broker,
+            order_tool,
+            DecisionValidator::new(symbols, RiskConfig::default()),
+            log,
+        )
+    }
+
+    #[test]
+    fn test_extract_and_parse_decisions() {
+        let text = "Plan below.\n```json\n{\"decisions\": [{\"action\": \"OPEN\", \"symbol\": \"BTCUSDT\", \"side\": \"LONG\", \"risk_usd\": 200, \"confidence\": 0.7, \"exit_plan\": {\"stop_loss\": 49000, \"profit_target\": 53000, \"invalidation_condition\": \"4h close below 48500\"}}]}\n```";
+        let decisions = parse_decisions(&extract_json(text).unwrap()).unwrap();
+        assert_eq!(decisions[0].action, DecisionAction::Open);
+        assert_eq!(decisions[0].side, Some(PositionSide::Long));
+        // 200 USD / (50000 - 49000) = 0.2
+        assert!((decisions[0].resolved_quantity(50000.0).unwrap() - 0.2).abs() < 1e-9);
+
+        // 裸 JSON 混在文本中也可提取
+        let value =
+            extract_json("I will hold. {\"action\": \"hold\", \"confidence\": 0.4}").unwrap();
+        assert_eq!(
+            parse_decisions(&value).unwrap()[0].action,
+            DecisionAction::Hold
+        );
+
+        let errors =
+            parse_decisions(&serde_json::json!([{"action": "yolo", "confidence": 1}])).unwrap_err();
+        assert!(errors[0].starts_with("decisions[0]"));
+    }
+
+    #[tokio::test]
+    async fn test_submit_decisions_validates_then_executes() {
+        let log = DecisionLog::default();
+        let orders = OrderLog::default();
+        let tool = tool(log.clone(), orders.clone());
+
+        // 品种不在范围、止损方向错误、杠杆超限: 整批拒绝且不下单
+        let err = tool
+            .execute(serde_json::json!({"decisions": [
+                {"action": "open", "symbol": "ETHUSDT", "side": "long", "quantity": 1, "confidence": 0.5},
+                {"action": "open", "symbol": "BTCUSDT", "side": "long", "quantity": 0.1, "leverage": 10,
+                 "confidence": 0.5, "exit_plan": {"stop_loss": 51000}},
+                {"action": "close", "symbol": "BTCUSDT", "confidence": 0.5}
+            ]}))
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("decisions[0]: symbol ETHUSDT is not in the agent's universe"));
+        assert!(err.contains("stop_loss 51000 is on the wrong side"));
+        assert!(err.contains("decisions[2]: no open position"));
+        assert!(orders.lock().await.is_empty());
+        assert!(log.lock().await.is_empty());
+
+        let result = tool
+            .execute(serde_json::json!({"decisions": [
+                {"action": "open", "symbol": "BTCUSDT", "side": "long", "risk_usd": 100, "leverage": 2,
+                 "confidence": 0.6, "exit_plan": {"stop_loss": 49000, "profit_target": 52000}},
+                {"action": "hold", "confidence": 0.3}
+            ]}))
+            .await
+            .unwrap();
+        assert_eq!(result["accepted"], 2);
+        assert_eq!(result["results"][0]["order"]["status"], "executed");
+
+        let log = log.lock().await;
+        assert_eq!(log.len(), 2);
+        assert!((log[0].quantity.unwrap() - 0.1).abs() < 1e-9);
+        assert_eq!(orders.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_partial_execution_is_logged() {
+        // 模拟盘只够第一笔开仓的保证金, 第二笔下单失败
+        let feed: Arc<dyn DynBroker> = Arc::new(MockBroker::new());
+        let config = crate::brokers::paper::PaperConfig {
+            initial_balance: 1_000.0,
+            ..Default::default()
+        };
+        let broker: Arc<dyn DynBroker> = Arc::new(crate::brokers::PaperBroker::new(
+            "paper", "Paper", feed, config,
+        ));
+        let symbols = vec!["BTCUSDT".to_string()];
+        let log = DecisionLog::default();
+        let order_tool = RiskCheckedOrderTool::new(
+            broker.clone(),
+            Arc::new(RiskManager::new(RiskConfig::default())),
+            "agent-1".to_string(),
+            symbols.clone(),
+            OrderLog::default(),
+        );
+        let tool = SubmitDecisionsTool::new(
+            broker,
+            order_tool,
+            DecisionValidator::new(symbols, RiskConfig::default()),
+            log.clone(),
+        );
+
+        // 模拟盘以 1 倍杠杆撮合, 要求 2 倍的决策被拒绝且不下单
+        let err = tool
+            .execute(
+                serde_json::json!({"action": "open", "symbol": "BTCUSDT", "side": "long",
+                                        "quantity": 0.015, "leverage": 2, "confidence": 0.6}),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("leverage 2 cannot be applied"));
+        assert!(!tool.submitted());
+
+        let open = serde_json::json!({"action": "open", "symbol": "BTCUSDT", "side": "long",
+                                      "quantity": 0.015, "confidence": 0.6});
+        let result = tool
+            .execute(serde_json::json!({"decisions": [
+                open.clone(), open, {"action": "hold", "confidence": 0.3}
+            ]}))
+            .await
+            .unwrap();
+        assert_eq!(result["results"][0]["order"]["status"], "executed");
+        assert_eq!(result["results"][1]["order"]["status"], "failed");
+        assert_eq!(result["accepted"], 2);
+        assert!(tool.submitted());
+
+        let log = log.lock().await;
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[1].action, DecisionAction::Hold);
+    }
+}
@@ -0,0 +1,394 @@
+use super::{Clock, TradingEngine};
+use crate::analytics::{sharpe_ratio, PerformanceAnalyzer};
+use crate::brokers::{AccountTotal, AccountTotals, ModelAnalytics};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// 默认快照间隔
+pub const DEFAULT_RECORD_INTERVAL: Duration = Duration::from_secs(60);
+
+/// 权益快照持久化接口
+///
+/// `PgEquityStore` 写入 `account_equity_snapshots`, `InMemoryEquityStore` 用于测试与无数据库运行。
+#[async_trait]
+pub trait EquityStore: Send + Sync {
+    /// 追加一条快照
+    async fn append(&self, total: &AccountTotal) -> Result<()>;
+
+    /// 按时间升序读取 `since_inception_hourly_marker >= from_hourly_marker` 的快照
+    async fn load(
+        &self,
+        model_id: Option<&str>,
+        from_hourly_marker: Option<i32>,
+    ) -> Result<Vec<AccountTotal>>;
+
+    /// 模型的第一条快照
+    async fn first(&self, model_id: &str) -> Result<Option<AccountTotal>>;
+
+    /// 每个模型的最新快照
+    async fn latest(&self) -> Result<Vec<AccountTotal>>;
+
+    /// 最早的快照时间, 作为比赛起点
+    async fn inception(&self) -> Result<Option<i64>>;
+}
+
+/// 内存权益快照存储
+#[derive(Default)]
+pub struct InMemoryEquityStore {
+    totals: RwLock<Vec<AccountTotal>>,
+}
+
+impl InMemoryEquityStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl EquityStore for InMemoryEquityStore {
+    async fn append(&self, total: &AccountTotal) -> Result<()> {
+        self.totals.write().await.push(total.clone());
+        Ok(())
+    }
+
+    async fn load(
+        &self,
+        model_id: Option<&str>,
+        from_hourly_marker: Option<i32>,
+    ) -> Result<Vec<AccountTotal>> {
+        Ok(self
+            .totals
+            .read()
+            .await
+            .iter()
+            .filter(|t| model_id.is_none_or(|id| t.model_id == id))
+            .filter(|t| from_hourly_marker.is_none_or(|m| t.since_inception_hourly_marker >= m))
+            .cloned()
+            .collect())
+    }
+
+    async fn first(&self, model_id: &str) -> Result<Option<AccountTotal>> {
+        Ok(self
+            .totals
+            .read()
+            .await
+            .iter()
+            .find(|t| t.model_id == model_id)
+            .cloned())
+    }
+
+    async fn latest(&self) -> Result<Vec<AccountTotal>> {
+        let mut latest: HashMap<&str, &AccountTotal> = HashMap::new();
+        let totals = self.totals.read().await;
+        for total in totals.iter() {
+            latest.insert(&total.model_id, total);
+        }
+        let mut latest: Vec<AccountTotal> = latest.into_values().cloned().collect();
+        latest.sort_by(|a, b| a.model_id.cmp(&b.model_id));
+        Ok(latest)
+    }
+
+    async fn inception(&self) -> Result<Option<i64>> {
+        Ok(self.totals.read().await.iter().map(|t| t.timestamp).min())
+    }
+}
+
+/// 模型的起始净值 (对应 `/api/since-inception-values`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinceInceptionValue {
+    pub id: String,
+    pub model_id: String,
+    /// 模型所用经纪商
+    pub exchange_id: String,
+    pub nav_since_inception: f64,
+    pub inception_date: i64,
+    pub num_invocations: u64,
+}
+
+/// 单个模型的运行状态
+struct ModelSeries {
+    initial: f64,
+    equity: Vec<f64>,
+}
+
+/// 权益曲线记录器
+///
+/// 按固定间隔读取每个已启用 Agent 所绑定经纪商的余额与持仓, 计算权益、已实现/未实现盈亏、
+/// 收益率与夏普比率并持久化。`since_inception_minute_marker` / `hourly_marker` 为距比赛起点
+/// (第一条快照) 的分钟数与小时数, 所有模型共用同一起点, 前端据此增量拉取。
+/// 多个 Agent 共用同一经纪商时读取各自的子账户 (见 [`TradingEngine::account_for`])。
+pub struct EquityRecorder {
+    engine: Arc<TradingEngine>,
+    store: Arc<dyn EquityStore>,
+    interval: Duration,
+    clock: Clock,
+    inception: Mutex<Option<i64>>,
+    series: Mutex<HashMap<String, ModelSeries>>,
+}
+
+impl EquityRecorder {
+    pub fn new(engine: Arc<TradingEngine>, store: Arc<dyn EquityStore>) -> Self {
+        Self {
+            engine,
+            store,
+            interval: DEFAULT_RECORD_INTERVAL,
+            clock: Clock::system(),
+            inception: Mutex::new(None),
+            series: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 按间隔记录快照, 直到 `shutdown` 被取消
+    pub async fn run(self: Arc<Self>, shutdown: CancellationToken) {
+        info!("Equity recorder started (every {:?})", self.interval);
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => {
+                    if let Err(e) = self.record_once().await {
+                        warn!("Failed to record equity: {:#}", e);
+                    }
+                }
+            }
+        }
+        info!("Equity recorder stopped");
+    }
+
+    /// 为所有启用的 Agent 记录一次快照
+    pub async fn record_once(&self) -> Result<Vec<AccountTotal>> {
+        let now = self.clock.now();
+        let inception = {
+            let mut inception = self.inception.lock().await;
+            if inception.is_none() {
+                *inception = Some(self.store.inception().await?.unwrap_or(now));
+            }
+            inception.unwrap_or(now)
+        };
+        let elapsed = (now - inception).max(0);
+
+        let mut recorded = Vec::new();
+        for agent in self.engine.list_agents().await {
+            if !agent.enabled {
+                continue;
+            }
+            let Some(broker) = self.engine.account_for(&agent).await else {
+                continue;
+            };
+
+            let account = async {
+                let balance = broker.get_balance().await?;
+                let positions = broker.get_positions(None).await?;
+                Ok::<_, crate::brokers::BrokerError>((balance, positions))
+            }
+            .await;
+            let (balance, positions) = match account {
+                Ok(account) => account,
+                Err(e) => {
+                    warn!("Skipping equity snapshot for {}: {}", agent.id, e);
+                    continue;
+                }
+            };
+
+            let equity = balance.total_balance;
+            let unrealized: f64 = positions.positions.values().map(|p| p.unrealized_pnl).sum();
+
+            let mut series = self.series.lock().await;
+            if !series.contains_key(&agent.id) {
+                let history = self.store.load(Some(&agent.id), None).await?;
+                let initial = if agent.initial_capital > 0.0 {
+                    agent.initial_capital
+                } else {
+                    history.first().map(|t| t.account_value).unwrap_or(equity)
+                };
+                series.insert(
+                    agent.id.clone(),
+                    ModelSeries {
+                        initial,
+                        equity: history.iter().map(|t| t.dollar_equity).collect(),
+                    },
+                );
+            }
+            let model = series.get_mut(&agent.id).expect("series initialized above");
+            model.equity.push(equity);
+
+            let return_pct = if model.initial > 0.0 {
+                (equity - model.initial) / model.initial * 100.0
+            } else {
+                0.0
+            };
+            let total = AccountTotal {
+                model_id: agent.id.clone(),
+                model_name: agent.name.clone(),
+                strategy: agent.llm_provider.clone(),
+                risk_level: String::new(),
+                broker_id: agent.market.clone(),
+                timestamp: now,
+                account_value: equity,
+                dollar_equity: equity,
+                equity,
+                realized_pnl: equity - model.initial - unrealized,
+                unrealized_pnl: unrealized,
+                total_unrealized_pnl: unrealized,
+                return_pct,
+                cum_pnl_pct: return_pct,
+                sharpe_ratio: sharpe_ratio(&model.equity, self.interval.as_secs() as i64),
+                since_inception_minute_marker: (elapsed / 60) as i32,
+                since_inception_hourly_marker: (elapsed / 3600) as i32,
+            };
+            drop(series);
+
+            self.store
+                .append(&total)
+                .await
+                .with_context(|| format!("Failed to store equity snapshot for {}", agent.id))?;
+            recorded.push(total);
+        }
+        Ok(recorded)
+    }
+
+    /// 账户权益历史, 给定 `last_hourly_marker` 时只返回该小时及之后的快照
+    ///
+    /// 包含 marker 所在小时本身, 使前端以同一 marker 轮询时能拿到该小时内的新点。
+    pub async fn account_totals(&self, last_hourly_marker: Option<i32>) -> Result<AccountTotals> {
+        Ok(AccountTotals {
+            account_totals: self.store.load(None, last_hourly_marker).await?,
+        })
+    }
+
+    /// 每个模型的最新快照
+    pub async fn latest_totals(&self) -> Result<Vec<AccountTotal>> {
+        self.store.latest().await
+    }
+
+    /// 各模型的起始净值与决策次数
+    pub async fn since_inception_values(&self) -> Result<Vec<SinceInceptionValue>> {
+        let mut values = Vec::new();
+        for agent in self.engine.list_agents().await {
+            let Some(first) = self.store.first(&agent.id).await? else {
+                continue;
+            };
+            let nav = if agent.initial_capital > 0.0 {
+                agent.initial_capital
+            } else {
+                first.account_value
+            };
+            values.push(SinceInceptionValue {
+                id: format!("{}_inception", agent.id),
+                model_id: agent.id.clone(),
+                exchange_id: first.broker_id.clone(),
+                nav_since_inception: nav,
+                inception_date: first.timestamp,
+                num_invocations: self.engine.invocation_count(&agent.id).await,
+            });
+        }
+        Ok(values)
+    }
+
+    /// 各模型的绩效分析: 成交取自所绑定的经纪商, 权益曲线取自已记录的快照
+    pub async fn analytics(&self) -> Result<Vec<(String, ModelAnalytics)>> {
+        let mut reports = Vec::new();
+        for agent in self.engine.list_agents().await {
+            let Some(broker) = self.engine.account_for(&agent).await else {
+                continue;
+            };
+            let trades = match broker.get_trades().await {
+                Ok(trades) => trades.trades,
+                Err(e) => {
+                    warn!("Skipping analytics for {}: {}", agent.id, e);
+                    continue;
+                }
+            };
+            let totals = self.store.load(Some(&agent.id), None).await?;
+            let report = PerformanceAnalyzer::new(agent.id.clone())
+                .with_account_totals(&totals)
+                .with_invocations(self.engine.invocation_stats(&agent.id).await)
+                .analyze(&trades, self.clock.now());
+            reports.push((agent.market.clone(), report));
+        }
+        Ok(reports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::paper::PaperConfig;
+    use crate::brokers::{DynBroker, MockBroker, PaperBroker};
+    use crate::engine::Agent;
+    use crate::mcp::McpServer;
+
+    #[tokio::test]
+    async fn test_records_markers_and_serves_incremental_totals() {
+        let engine = Arc::new(TradingEngine::new(Arc::new(McpServer::new())));
+        let feed: Arc<dyn DynBroker> = Arc::new(MockBroker::new());
+        let config = PaperConfig {
+            initial_balance: 10_000.0,
+            ..Default::default()
+        };
+        let paper = PaperBroker::new("paper-a", "Paper A", feed, config);
+        engine
+            .register_broker("paper-a".to_string(), Arc::new(paper))
+            .await;
+
+        let mut agent = Agent::new(
+            "model-a".to_string(),
+            "Model A".to_string(),
+            "scripted".to_string(),
+            "paper-a".to_string(),
+        );
+        agent.initial_capital = 10_000.0;
+        engine.register_agent(agent).await;
+
+        let start = 1_700_000_000;
+        let clock = Clock::simulated(start);
+        let recorder = EquityRecorder::new(engine, Arc::new(InMemoryEquityStore::new()))
+            .with_clock(clock.clone());
+
+        let first = recorder.record_once().await.unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].equity, 10_000.0);
+        assert_eq!(first[0].since_inception_minute_marker, 0);
+
+        clock.set(start + 3600 + 120);
+        let second = recorder.record_once().await.unwrap();
+        assert_eq!(second[0].since_inception_minute_marker, 62);
+        assert_eq!(second[0].since_inception_hourly_marker, 1);
+
+        let all = recorder.account_totals(None).await.unwrap();
+        assert_eq!(all.account_totals.len(), 2);
+        let incremental = recorder.account_totals(Some(1)).await.unwrap();
+        assert_eq!(incremental.account_totals.len(), 1);
+        assert_eq!(incremental.account_totals[0].timestamp, start + 3720);
+
+        let inception = recorder.since_inception_values().await.unwrap();
+        assert_eq!(inception.len(), 1);
+        assert_eq!(inception[0].exchange_id, "paper-a");
+        assert_eq!(inception[0].inception_date, start);
+        assert_eq!(inception[0].nav_since_inception, 10_000.0);
+        assert_eq!(inception[0].num_invocations, 0);
+
+        let analytics = recorder.analytics().await.unwrap();
+        assert_eq!(analytics.len(), 1);
+        assert_eq!(analytics[0].0, "paper-a");
+        assert_eq!(analytics[0].1.model_id, "model-a");
+    }
+}
@@ -0,0 +1,1098 @@
+use super::{Agent, Clock, DecisionAction, ExitPlan, TradeDecision, TradingEngine};
+use crate::brokers::{
+    DynBroker, MarketEvent, MarketEventStream, OrderRequest, OrderSide, OrderStatus, OrderType,
+    Position,
+};
+use crate::risk::{OrderInfo, RiskEvent, RiskEventType, RiskLevel};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// 默认持仓检查间隔
+pub const DEFAULT_EXIT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 内存中保留的退出事件条数
+const MAX_EXIT_EVENTS: usize = 1000;
+
+/// 交易所原生止损单
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NativeStop {
+    pub order_id: String,
+    pub stop_price: f64,
+    pub quantity: f64,
+}
+
+/// Agent 为持仓声明的退出计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedExitPlan {
+    pub agent_id: String,
+    pub symbol: String,
+    pub plan: ExitPlan,
+    /// 已挂出的原生止损单, 为空时由监控合成止损
+    pub native_stop: Option<NativeStop>,
+    /// 经纪商不接受原生止损单, 止损价变化前不再尝试
+    pub synthetic: bool,
+    /// 已就失效条件提醒过 Agent
+    pub invalidation_alerted: bool,
+    pub updated_at: i64,
+}
+
+/// 退出计划簿, 由决策周期写入, `ExitPlanManager` 读取
+#[derive(Default)]
+pub struct ExitPlanBook {
+    plans: RwLock<HashMap<(String, String), TrackedExitPlan>>,
+}
+
+impl ExitPlanBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(agent_id: &str, symbol: &str) -> (String, String) {
+        (agent_id.to_string(), symbol.to_uppercase())
+    }
+
+    /// 应用已接受的决策: 开仓设置退出计划, 调整合并非空字段
+    pub async fn apply_decisions(&self, agent_id: &str, decisions: &[TradeDecision], now: i64) {
+        let mut plans = self.plans.write().await;
+        for decision in decisions {
+            if !matches!(
+                decision.action,
+                DecisionAction::Open | DecisionAction::Adjust
+            ) {
+                continue;
+            }
+            let (Some(symbol), Some(exit_plan)) = (&decision.symbol, &decision.exit_plan) else {
+                continue;
+            };
+            let entry =
+                plans
+                    .entry(Self::key(agent_id, symbol))
+                    .or_insert_with(|| TrackedExitPlan {
+                        agent_id: agent_id.to_string(),
+                        symbol: symbol.to_uppercase(),
+                        plan: ExitPlan::default(),
+                        native_stop: None,
+                        synthetic: false,
+                        invalidation_alerted: false,
+                        updated_at: now,
+                    });
+
+            match decision.action {
+                DecisionAction::Open => entry.plan = exit_plan.clone(),
+                DecisionAction::Adjust => {
+                    let plan = &mut entry.plan;
+                    plan.profit_target = exit_plan.profit_target.or(plan.profit_target);
+                    plan.stop_loss = exit_plan.stop_loss.or(plan.stop_loss);
+                    plan.invalidation_condition = exit_plan
+                        .invalidation_condition
+                        .clone()
+                        .or(plan.invalidation_condition.take());
+                }
+                DecisionAction::Close | DecisionAction::Hold => {}
+            }
+            entry.synthetic = false;
+            entry.invalidation_alerted = false;
+            entry.updated_at = now;
+        }
+    }
+
+    pub async fn get(&self, agent_id: &str, symbol: &str) -> Option<TrackedExitPlan> {
+        self.plans
+            .read()
+            .await
+            .get(&Self::key(agent_id, symbol))
+            .cloned()
+    }
+
+    /// 列出退出计划, 按 Agent 与品种排序
+    pub async fn list(&self, agent_id: Option<&str>) -> Vec<TrackedExitPlan> {
+        let mut plans: Vec<_> = self
+            .plans
+            .read()
+            .await
+            .values()
+            .filter(|p| agent_id.is_none_or(|id| p.agent_id == id))
+            .cloned()
+            .collect();
+        plans.sort_by(|a, b| (&a.agent_id, &a.symbol).cmp(&(&b.agent_id, &b.symbol)));
+        plans
+    }
+
+    async fn update(&self, plan: TrackedExitPlan) {
+        self.plans
+            .write()
+            .await
+            .insert(Self::key(&plan.agent_id, &plan.symbol), plan);
+    }
+
+    async fn remove(&self, agent_id: &str, symbol: &str) {
+        self.plans
+            .write()
+            .await
+            .remove(&Self::key(agent_id, symbol));
+    }
+}
+
+/// 退出触发类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitTrigger {
+    StopLoss,
+    TakeProfit,
+    /// 文本失效条件可能已触发, 已重新询问 Agent
+    Invalidation,
+}
+
+/// 退出事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExitEvent {
+    pub agent_id: String,
+    pub symbol: String,
+    pub trigger: ExitTrigger,
+    /// 触发时的价格
+    pub price: f64,
+    /// 触发的止损/止盈/失效价位
+    pub level: Option<f64>,
+    /// 由交易所原生止损单执行
+    pub native: bool,
+    pub order_id: Option<String>,
+    pub error: Option<String>,
+    pub timestamp: i64,
+}
+
+/// 价位方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelDirection {
+    Below,
+    Above,
+}
+
+/// 指标类词语, 其后的数字不是价格 (如 "RSI > 80")
+const INDICATOR_WORDS: &[&str] = &[
+    "rsi",
+    "macd",
+    "ema",
+    "sma",
+    "atr",
+    "adx",
+    "cci",
+    "stoch",
+    "stochastic",
+    "funding",
+    "volume",
+    "oi",
+    "interest",
+    "ratio",
+    "volatility",
+    "dominance",
+];
+
+/// 分句词, 向前查找条件主语时在此停止
+const CLAUSE_WORDS: &[&str] = &["or", "and", "if", "when", "while", "unless", "then"];
+
+/// 从文本失效条件中提取价位, 如 "closes below 105,000" → `(Below, 105000)`
+///
+/// 主语为指标 (RSI、资金费率等) 的条件与百分比数值不视为价位。
+pub fn invalidation_levels(condition: &str) -> Vec<(LevelDirection, f64)> {
+    let tokens: Vec<(String, bool)> = condition
+        .split_whitespace()
+        .map(|t| {
+            let percent = t
+                .trim_end_matches(|c: char| ",.;)".contains(c))
+                .ends_with('%');
+            let token = t
+                .trim_matches(|c: char| !c.is_alphanumeric() && c != '<' && c != '>')
+                .replace([',', '$'], "")
+                .to_lowercase();
+            (token, percent)
+        })
+        .collect();
+
+    let mut levels = Vec::new();
+    for (i, (token, _)) in tokens.iter().enumerate() {
+        let direction = match token.as_str() {
+            "below" | "under" | "beneath" | "lower" | "<" => LevelDirection::Below,
+            "above" | "over" | "exceeds" | "higher" | ">" => LevelDirection::Above,
+            _ => continue,
+        };
+        let indicator = tokens[..i]
+            .iter()
+            .rev()
+            .take(4)
+            .map(|(t, _)| t.as_str())
+            .take_while(|t| !CLAUSE_WORDS.contains(t))
+            .any(|t| INDICATOR_WORDS.contains(&t));
+        if indicator {
+            continue;
+        }
+        if let Some(level) = tokens[i + 1..]
+            .iter()
+            .take(4)
+            .find_map(|(t, percent)| t.parse::<f64>().ok().filter(|v| *v > 0.0 && !percent))
+        {
+            levels.push((direction, level));
+        }
+    }
+    levels
+}
+
+/// 失效条件中的价位是否已被当前价格触及
+fn invalidation_may_have_fired(condition: &str, price: f64) -> Option<f64> {
+    invalidation_levels(condition)
+        .into_iter()
+        .find(|(direction, level)| match direction {
+            LevelDirection::Below => price <= *level,
+            LevelDirection::Above => price >= *level,
+        })
+        .map(|(_, level)| level)
+}
+
+fn is_short(position: &Position) -> bool {
+    position.direction.as_deref() == Some("short") || position.quantity < 0.0
+}
+
+/// 平仓方向
+fn closing_side(position: &Position) -> OrderSide {
+    if is_short(position) {
+        OrderSide::Buy
+    } else {
+        OrderSide::Sell
+    }
+}
+
+/// 持仓及其所属 Agent
+#[derive(Clone)]
+struct Holding {
+    agent: Agent,
+    position: Position,
+}
+
+/// 持仓退出计划监控
+///
+/// 按经纪商轮询持仓 (也可通过 [`ExitPlanManager::watch`] 订阅推送行情), 执行 Agent 声明的
+/// 止损/止盈: 经纪商接受止损单时挂出原生止损单, 否则在价格触及时以市价平仓 (合成止损)。
+/// 未声明止损的持仓按风控 `stop_loss_ratio` 止损。止损触发记录 `StopLossTriggered` 风险事件;
+/// 文本失效条件中的价位被触及时, 重新询问所属 Agent。
+///
+/// 持仓读取自 Agent 的交易账户 ([`TradingEngine::account_for`]): 多个 Agent 共用经纪商时
+/// 每个 Agent 只处理自己子账户中的持仓与数量, 不属于任何 Agent 的持仓不做处理。
+pub struct ExitPlanManager {
+    engine: Arc<TradingEngine>,
+    interval: Duration,
+    clock: Clock,
+    /// 最近一次轮询的各经纪商持仓, 推送行情据此判断
+    positions: RwLock<HashMap<String, Vec<Holding>>>,
+    events: RwLock<Vec<ExitEvent>>,
+    /// 串行化轮询与推送, 避免重复平仓
+    evaluating: Mutex<()>,
+}
+
+impl ExitPlanManager {
+    pub fn new(engine: Arc<TradingEngine>) -> Self {
+        Self {
+            engine,
+            interval: DEFAULT_EXIT_CHECK_INTERVAL,
+            clock: Clock::system(),
+            positions: RwLock::new(HashMap::new()),
+            events: RwLock::new(Vec::new()),
+            evaluating: Mutex::new(()),
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 最近的退出事件 (新的在后)
+    pub async fn events(&self, limit: usize) -> Vec<ExitEvent> {
+        let events = self.events.read().await;
+        events[events.len().saturating_sub(limit)..].to_vec()
+    }
+
+    /// 按间隔检查持仓, 直到 `shutdown` 被取消
+    pub async fn run(self: Arc<Self>, shutdown: CancellationToken) {
+        info!("Exit plan manager started (every {:?})", self.interval);
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => {
+                    if let Err(e) = self.check_once().await {
+                        warn!("Failed to check exit plans: {:#}", e);
+                    }
+                }
+            }
+        }
+        info!("Exit plan manager stopped");
+    }
+
+    /// 消费经纪商 `market` 的推送行情, ticker 与逐笔成交价即时触发检查
+    pub async fn watch(
+        self: Arc<Self>,
+        market: String,
+        mut stream: MarketEventStream,
+        shutdown: CancellationToken,
+    ) {
+        loop {
+            let event = tokio::select! {
+                _ = shutdown.cancelled() => break,
+                event = stream.recv() => event,
+            };
+            match event {
+                Some(MarketEvent::Ticker(t)) => {
+                    self.on_price(&market, &t.symbol, t.last_price).await;
+                }
+                Some(MarketEvent::Trade(t)) => {
+                    self.on_price(&market, &t.symbol, t.price).await;
+                }
+                Some(_) => {}
+                None => break,
+            }
+        }
+    }
+
+    /// 检查所有经纪商上各 Agent 的持仓: 同步原生止损单, 按持仓标记价判断触发
+    pub async fn check_once(&self) -> Result<Vec<ExitEvent>> {
+        let _guard = self.evaluating.lock().await;
+        let book = self.engine.exit_plans();
+        let mut events = Vec::new();
+
+        for (market, owners) in self.agents_by_market().await {
+            let mut holdings = Vec::new();
+            for agent in owners {
+                let Some(account) = self.engine.account_for(&agent).await else {
+                    continue;
+                };
+                let positions: Vec<Position> = match account.get_positions(None).await {
+                    Ok(positions) => positions
+                        .positions
+                        .into_values()
+                        .filter(|p| p.quantity.abs() > 0.0)
+                        .collect(),
+                    Err(e) => {
+                        warn!("Failed to load positions for {}: {}", agent.id, e);
+                        continue;
+                    }
+                };
+
+                // 已平仓品种: 确认或撤销原生止损单并移除计划
+                for plan in book.list(Some(&agent.id)).await {
+                    if !positions
+                        .iter()
+                        .any(|p| p.symbol.eq_ignore_ascii_case(&plan.symbol))
+                    {
+                        events.extend(self.release(&agent, account.as_ref(), plan).await);
+                    }
+                }
+
+                for position in &positions {
+                    self.sync_native_stop(account.as_ref(), position, &agent)
+                        .await;
+                }
+                holdings.extend(positions.into_iter().map(|position| Holding {
+                    agent: agent.clone(),
+                    position,
+                }));
+            }
+            self.positions
+                .write()
+                .await
+                .insert(market, holdings.clone());
+
+            for holding in &holdings {
+                let price = holding.position.current_price;
+                if price > 0.0 {
+                    events.extend(self.evaluate_holding(holding, price).await);
+                }
+            }
+        }
+
+        self.push_events(&events).await;
+        Ok(events)
+    }
+
+    /// 推送行情: 检查经纪商 `market` 上 `symbol` 的持仓
+    pub async fn on_price(&self, market: &str, symbol: &str, price: f64) -> Vec<ExitEvent> {
+        let _guard = self.evaluating.lock().await;
+        let holdings: Vec<Holding> = self
+            .positions
+            .read()
+            .await
+            .get(market)
+            .map(|holdings| {
+                holdings
+                    .iter()
+                    .filter(|h| h.position.symbol.eq_ignore_ascii_case(symbol))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut events = Vec::new();
+        for holding in &holdings {
+            events.extend(self.evaluate_holding(holding, price).await);
+        }
+
+        self.push_events(&events).await;
+        events
+    }
+
+    /// 按经纪商分组的已启用 Agent
+    async fn agents_by_market(&self) -> BTreeMap<String, Vec<Agent>> {
+        let mut markets: BTreeMap<String, Vec<Agent>> = BTreeMap::new();
+        for agent in self.engine.list_agents().await {
+            if agent.enabled {
+                markets.entry(agent.market.clone()).or_default().push(agent);
+            }
+        }
+        markets
+    }
+
+    /// 在所属 Agent 的交易账户上判断并执行
+    async fn evaluate_holding(&self, holding: &Holding, price: f64) -> Option<ExitEvent> {
+        let account = self.engine.account_for(&holding.agent).await?;
+        self.evaluate(&holding.agent, account.as_ref(), &holding.position, price)
+            .await
+    }
+
+    async fn push_events(&self, new_events: &[ExitEvent]) {
+        if new_events.is_empty() {
+            return;
+        }
+        let mut events = self.events.write().await;
+        events.extend_from_slice(new_events);
+        let len = events.len();
+        if len > MAX_EXIT_EVENTS {
+            events.drain(0..len - MAX_EXIT_EVENTS);
+        }
+    }
+
+    /// 按当前价格判断止损、止盈与失效条件
+    async fn evaluate(
+        &self,
+        agent: &Agent,
+        broker: &dyn DynBroker,
+        position: &Position,
+        price: f64,
+    ) -> Option<ExitEvent> {
+        let book = self.engine.exit_plans();
+        let tracked = book.get(&agent.id, &position.symbol).await;
+        let plan = tracked.as_ref().map(|t| t.plan.clone()).unwrap_or_default();
+        let short = is_short(position);
+
+        let stop = match plan.stop_loss {
+            Some(stop) => Some(stop),
+            None => {
+                let config = self.engine.risk_manager_for(agent).await.get_config().await;
+                let ratio = config.loss_limits.stop_loss_ratio;
+                let offset = if short { ratio } else { -ratio };
+                (config.enabled && ratio > 0.0 && position.entry_price > 0.0)
+                    .then_some(position.entry_price * (1.0 + offset))
+            }
+        };
+        let crossed = |level: f64, against: bool| {
+            if short == against {
+                price >= level
+            } else {
+                price <= level
+            }
+        };
+
+        if let Some(stop) = stop.filter(|s| crossed(*s, true)) {
+            // 原生止损单由交易所触发, 平仓后在 `release` 中确认
+            if tracked.as_ref().is_some_and(|t| t.native_stop.is_some()) {
+                return None;
+            }
+            return self
+                .close(agent, broker, position, ExitTrigger::StopLoss, price, stop)
+                .await;
+        }
+        if let Some(target) = plan.profit_target.filter(|t| crossed(*t, false)) {
+            return self
+                .close(
+                    agent,
+                    broker,
+                    position,
+                    ExitTrigger::TakeProfit,
+                    price,
+                    target,
+                )
+                .await;
+        }
+
+        let mut tracked = tracked?;
+        if tracked.invalidation_alerted {
+            return None;
+        }
+        let condition = tracked.plan.invalidation_condition.clone()?;
+        let level = invalidation_may_have_fired(&condition, price)?;
+        tracked.invalidation_alerted = true;
+        book.update(tracked).await;
+
+        let note = format!(
+            "Exit plan alert for {}: your invalidation condition \"{}\" may have fired \
+             (last price {}). Re-evaluate this position now: close it, adjust its exit plan, \
+             or hold with a justification.",
+            position.symbol, condition, price
+        );
+        let engine = self.engine.clone();
+        let owner = agent.clone();
+        tokio::spawn(async move {
+            if let Err(e) = engine.reassess_agent(&owner, &note).await {
+                warn!("Failed to re-ask {} after invalidation: {:#}", owner.id, e);
+            }
+        });
+        info!(
+            "Invalidation condition for {} {} may have fired at {}, re-asking agent",
+            agent.id, position.symbol, price
+        );
+
+        Some(self.event(agent, position, ExitTrigger::Invalidation, price, level))
+    }
+
+    fn event(
+        &self,
+        agent: &Agent,
+        position: &Position,
+        trigger: ExitTrigger,
+        price: f64,
+        level: f64,
+    ) -> ExitEvent {
+        ExitEvent {
+            agent_id: agent.id.clone(),
+            symbol: position.symbol.clone(),
+            trigger,
+            price,
+            level: Some(level),
+            native: false,
+            order_id: None,
+            error: None,
+            timestamp: self.clock.now(),
+        }
+    }
+
+    /// 以市价只减仓单平仓 (合成止损/止盈), 并撤销未触发的原生止损单
+    ///
+    /// 平仓前重新读取 Agent 账户中的持仓, 已平仓时不下单 (原生止损单由下次轮询确认)。
+    async fn close(
+        &self,
+        agent: &Agent,
+        broker: &dyn DynBroker,
+        cached: &Position,
+        trigger: ExitTrigger,
+        price: f64,
+        level: f64,
+    ) -> Option<ExitEvent> {
+        let mut event = self.event(agent, cached, trigger, price, level);
+        let position = match broker.get_positions(None).await {
+            Ok(positions) => positions
+                .positions
+                .into_values()
+                .find(|p| p.symbol.eq_ignore_ascii_case(&cached.symbol) && p.quantity.abs() > 0.0),
+            Err(e) => {
+                warn!(
+                    "Failed to refresh {} {} before closing: {}",
+                    agent.id, cached.symbol, e
+                );
+                event.error = Some(e.to_string());
+                return Some(event);
+            }
+        };
+        let Some(position) = position else {
+            self.forget_holding(agent, &cached.symbol).await;
+            return None;
+        };
+        let position = &position;
+
+        let book = self.engine.exit_plans();
+        if let Some(native) = book
+            .get(&agent.id, &position.symbol)
+            .await
+            .and_then(|t| t.native_stop)
+        {
+            if let Err(e) = broker.cancel_order(&native.order_id).await {
+                warn!("Failed to cancel stop order {}: {}", native.order_id, e);
+            }
+        }
+
+        let request = OrderRequest {
+            symbol: position.symbol.clone(),
+            side: closing_side(position),
+            order_type: OrderType::Market,
+            quantity: position.quantity.abs(),
+            price: None,
+            time_in_force: None,
+            reduce_only: true,
+        };
+        match broker.place_order(request).await {
+            Ok(response) => {
+                info!(
+                    "{:?} for {} {} at {} (level {})",
+                    trigger, agent.id, position.symbol, price, level
+                );
+                event.order_id = Some(response.order_id);
+                book.remove(&agent.id, &position.symbol).await;
+                self.forget_holding(agent, &position.symbol).await;
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to close {} {} on {:?}: {}",
+                    agent.id, position.symbol, trigger, e
+                );
+                event.error = Some(e.to_string());
+            }
+        }
+
+        if trigger == ExitTrigger::StopLoss {
+            self.record_stop_loss(agent, position, &event).await;
+        }
+        Some(event)
+    }
+
+    /// 从推送行情使用的持仓缓存中移除
+    async fn forget_holding(&self, agent: &Agent, symbol: &str) {
+        if let Some(holdings) = self.positions.write().await.get_mut(&agent.market) {
+            holdings.retain(|h| {
+                h.agent.id != agent.id || !h.position.symbol.eq_ignore_ascii_case(symbol)
+            });
+        }
+    }
+
+    /// 持仓已平: 原生止损单已成交时记录止损, 否则撤销
+    async fn release(
+        &self,
+        agent: &Agent,
+        broker: &dyn DynBroker,
+        tracked: TrackedExitPlan,
+    ) -> Option<ExitEvent> {
+        self.engine
+            .exit_plans()
+            .remove(&agent.id, &tracked.symbol)
+            .await;
+        let native = tracked.native_stop?;
+
+        match broker.get_order(&native.order_id).await {
+            Ok(order) if matches!(order.status, OrderStatus::Filled) => {
+                let position = Position {
+                    symbol: tracked.symbol.clone(),
+                    entry_price: 0.0,
+                    current_price: native.stop_price,
+                    quantity: native.quantity,
+                    unrealized_pnl: 0.0,
+                    direction: None,
+                    leverage: None,
+                    margin: None,
+                    timestamp: self.clock.now(),
+                };
+                let price = order.avg_price.unwrap_or(native.stop_price);
+                let mut event = self.event(
+                    agent,
+                    &position,
+                    ExitTrigger::StopLoss,
+                    price,
+                    native.stop_price,
+                );
+                event.native = true;
+                event.order_id = Some(native.order_id);
+                self.record_stop_loss(agent, &position, &event).await;
+                Some(event)
+            }
+            _ => {
+                if let Err(e) = broker.cancel_order(&native.order_id).await {
+                    warn!("Failed to cancel stop order {}: {}", native.order_id, e);
+                }
+                None
+            }
+        }
+    }
+
+    /// 让原生止损单与退出计划的止损价、持仓数量保持一致
+    async fn sync_native_stop(&self, broker: &dyn DynBroker, position: &Position, agent: &Agent) {
+        let book = self.engine.exit_plans();
+        let Some(mut tracked) = book.get(&agent.id, &position.symbol).await else {
+            return;
+        };
+        let quantity = position.quantity.abs();
+        let stop = tracked.plan.stop_loss;
+        if let (Some(native), Some(stop)) = (&tracked.native_stop, stop) {
+            if native.stop_price == stop && native.quantity == quantity {
+                return;
+            }
+        }
+        if tracked.synthetic && tracked.native_stop.is_none() {
+            return;
+        }
+
+        if let Some(native) = tracked.native_stop.take() {
+            if let Err(e) = broker.cancel_order(&native.order_id).await {
+                warn!("Failed to cancel stop order {}: {}", native.order_id, e);
+            }
+        }
+        if let Some(stop) = stop {
+            let request = OrderRequest {
+                symbol: position.symbol.clone(),
+                side: closing_side(position),
+                order_type: OrderType::Stop,
+                quantity,
+                price: Some(stop),
+                time_in_force: None,
+                reduce_only: false,
+            };
+            match broker.place_order(request).await {
+                Ok(response) => {
+                    tracked.native_stop = Some(NativeStop {
+                        order_id: response.order_id,
+                        stop_price: stop,
+                        quantity,
+                    });
+                }
+                Err(e) => {
+                    info!(
+                        "{} does not accept stop orders for {}, using synthetic stop: {}",
+                        broker.broker_id(),
+                        position.symbol,
+                        e
+                    );
+                    tracked.synthetic = true;
+                }
+            }
+        }
+        book.update(tracked).await;
+    }
+
+    async fn record_stop_loss(&self, agent: &Agent, position: &Position, event: &ExitEvent) {
+        let side = match closing_side(position) {
+            OrderSide::Buy => "buy",
+            OrderSide::Sell => "sell",
+        };
+        let risk_event = RiskEvent {
+            timestamp: chrono::Utc::now(),
+            event_type: RiskEventType::StopLossTriggered,
+            rule_name: "ExitPlan".to_string(),
+            risk_level: RiskLevel::High,
+            description: format!(
+                "{} stop loss {} on {} hit at {} ({})",
+                agent.id,
+                event.level.unwrap_or_default(),
+                position.symbol,
+                event.price,
+                if event.native { "native" } else { "synthetic" }
+            ),
+            order_info: Some(OrderInfo {
+                symbol: position.symbol.clone(),
+                side: side.to_string(),
+                quantity: position.quantity.abs(),
+                price: Some(event.price),
+                order_type: if event.native { "stop" } else { "market" }.to_string(),
+                account_id: agent.id.clone(),
+            }),
+        };
+        let risk_manager = self.engine.risk_manager_for(agent).await;
+        if let Err(e) = risk_manager.record_event(risk_event).await {
+            warn!("Failed to record stop loss event for {}: {}", agent.id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::paper::PaperConfig;
+    use crate::brokers::{MockBroker, PaperBroker, Ticker24h};
+    use crate::llm::{ScriptStep, ScriptedProvider};
+    use crate::mcp::McpServer;
+
+    fn ticker(price: f64) -> MarketEvent {
+        MarketEvent::Ticker(Ticker24h {
+            symbol: "BTCUSDT".to_string(),
+            last_price: price,
+            change_24h: 0.0,
+            high_24h: price,
+            low_24h: price,
+            volume_24h: 0.0,
+            open_interest: None,
+            timestamp: 0,
+        })
+    }
+
+    fn open(plan: ExitPlan) -> TradeDecision {
+        TradeDecision {
+            action: DecisionAction::Open,
+            symbol: Some("BTCUSDT".to_string()),
+            side: Some(super::super::PositionSide::Long),
+            quantity: Some(0.1),
+            risk_usd: None,
+            leverage: None,
+            confidence: 0.6,
+            exit_plan: Some(plan),
+            justification: None,
+        }
+    }
+
+    async fn setup() -> (Arc<TradingEngine>, PaperBroker, Arc<ScriptedProvider>) {
+        let llm = Arc::new(ScriptedProvider::new(vec![ScriptStep {
+            content: "Holding".to_string(),
+            repeat: true,
+            ..Default::default()
+        }]));
+        let engine = Arc::new(TradingEngine::new(Arc::new(McpServer::new())));
+        let paper = PaperBroker::new(
+            "paper",
+            "Paper",
+            Arc::new(MockBroker::new()),
+            PaperConfig::default(),
+        );
+        engine
+            .register_broker("paper".to_string(), Arc::new(paper.clone()))
+            .await;
+        engine
+            .register_llm_provider("scripted".to_string(), llm.clone())
+            .await;
+        let mut agent = Agent::new(
+            "agent-1".to_string(),
+            "Agent".to_string(),
+            "scripted".to_string(),
+            "paper".to_string(),
+        );
+        agent.symbols = vec!["BTCUSDT".to_string()];
+        engine.register_agent(agent).await;
+
+        paper.apply_event(&ticker(50000.0));
+        DynBroker::place_order(
+            &paper,
+            OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                order_type: OrderType::Market,
+                quantity: 0.1,
+                price: None,
+                time_in_force: None,
+                reduce_only: false,
+            },
+        )
+        .await
+        .unwrap();
+        (engine, paper, llm)
+    }
+
+    #[test]
+    fn test_invalidation_levels() {
+        assert_eq!(
+            invalidation_levels("If the price closes below $105,000 on a 3-minute candle"),
+            vec![(LevelDirection::Below, 105000.0)]
+        );
+        assert_eq!(
+            invalidation_levels("BTC breaks above 4,200.5 or 4h RSI > 80"),
+            vec![(LevelDirection::Above, 4200.5)]
+        );
+        assert!(
+            invalidation_levels("funding rate above 0.05% and volume drops below 50%").is_empty()
+        );
+        assert!(invalidation_levels("Funding turns sharply negative").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalidation_and_take_profit() {
+        let (engine, paper, llm) = setup().await;
+        engine
+            .exit_plans()
+            .apply_decisions(
+                "agent-1",
+                &[open(ExitPlan {
+                    profit_target: Some(52000.0),
+                    stop_loss: Some(48000.0),
+                    invalidation_condition: Some("4h close below 49,500".to_string()),
+                })],
+                0,
+            )
+            .await;
+        let manager = ExitPlanManager::new(engine.clone());
+
+        // 止损价以原生止损单挂出
+        assert!(manager.check_once().await.unwrap().is_empty());
+        let plan = engine.exit_plans().get("agent-1", "BTCUSDT").await.unwrap();
+        let native = plan.native_stop.unwrap();
+        assert_eq!(native.stop_price, 48000.0);
+        let order = DynBroker::get_order(&paper, &native.order_id)
+            .await
+            .unwrap();
+        assert!(matches!(order.order_type, OrderType::Stop));
+
+        // 失效价位被触及: 只提醒一次, 重新询问 Agent
+        let events = manager.on_price("paper", "BTCUSDT", 49400.0).await;
+        assert_eq!(events[0].trigger, ExitTrigger::Invalidation);
+        assert!(manager
+            .on_price("paper", "BTCUSDT", 49300.0)
+            .await
+            .is_empty());
+        for _ in 0..100 {
+            if !llm.requests().is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let requests = llm.requests();
+        let alert = requests[0].request.messages.last().unwrap();
+        assert!(alert.content.contains("Exit plan alert for BTCUSDT"));
+
+        // 止盈合成平仓, 并撤销原生止损单
+        let events = manager.on_price("paper", "BTCUSDT", 52100.0).await;
+        assert_eq!(events[0].trigger, ExitTrigger::TakeProfit);
+        assert!(events[0].error.is_none());
+        assert!(DynBroker::get_positions(&paper, None)
+            .await
+            .unwrap()
+            .positions
+            .is_empty());
+        let order = DynBroker::get_order(&paper, &native.order_id)
+            .await
+            .unwrap();
+        assert!(matches!(order.status, OrderStatus::Cancelled));
+        assert!(engine.exit_plans().list(None).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stop_loss_emits_risk_event() {
+        let (engine, paper, _) = setup().await;
+        let manager = ExitPlanManager::new(engine.clone());
+        let agent = engine.get_agent("agent-1").await.unwrap();
+
+        // 未声明止损: 按 stop_loss_ratio (5%) 合成止损
+        assert!(manager.check_once().await.unwrap().is_empty());
+
+        // 轮询后持仓被部分平掉, 止损按重新读取的持仓数量平仓
+        DynBroker::place_order(
+            &paper,
+            OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Sell,
+                order_type: OrderType::Market,
+                quantity: 0.04,
+                price: None,
+                time_in_force: None,
+                reduce_only: false,
+            },
+        )
+        .await
+        .unwrap();
+        let events = manager.on_price("paper", "BTCUSDT", 47000.0).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].trigger, ExitTrigger::StopLoss);
+        assert!(!events[0].native);
+        let order_id = events[0].order_id.as_deref().unwrap();
+        let order = DynBroker::get_order(&paper, order_id).await.unwrap();
+        assert!((order.quantity - 0.06).abs() < 1e-9);
+
+        let history = engine
+            .risk_manager_for(&agent)
+            .await
+            .get_event_history(10)
+            .await;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].event_type, RiskEventType::StopLossTriggered);
+        assert!(DynBroker::get_positions(&paper, None)
+            .await
+            .unwrap()
+            .positions
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_agents_sharing_broker_only_exit_own_positions() {
+        let engine = Arc::new(TradingEngine::new(Arc::new(McpServer::new())));
+        let paper = PaperBroker::new(
+            "paper",
+            "Paper",
+            Arc::new(MockBroker::new()),
+            PaperConfig::default(),
+        );
+        engine
+            .register_broker("paper".to_string(), Arc::new(paper.clone()))
+            .await;
+        for id in ["agent-1", "agent-2"] {
+            let mut agent = Agent::new(
+                id.to_string(),
+                id.to_string(),
+                "scripted".to_string(),
+                "paper".to_string(),
+            );
+            agent.initial_capital = 50_000.0;
+            engine.register_agent(agent).await;
+        }
+        paper.apply_event(&ticker(50000.0));
+
+        // agent-1 多 0.1 并声明止损, agent-2 空 0.3 且没有退出计划; 交易所净持仓为空 0.2
+        for (id, side, quantity) in [
+            ("agent-1", OrderSide::Buy, 0.1),
+            ("agent-2", OrderSide::Sell, 0.3),
+        ] {
+            let agent = engine.get_agent(id).await.unwrap();
+            let account = engine.account_for(&agent).await.unwrap();
+            account
+                .place_order(OrderRequest {
+                    symbol: "BTCUSDT".to_string(),
+                    side,
+                    order_type: OrderType::Market,
+                    quantity,
+                    price: None,
+                    time_in_force: None,
+                    reduce_only: false,
+                })
+                .await
+                .unwrap();
+        }
+        engine
+            .exit_plans()
+            .apply_decisions(
+                "agent-1",
+                &[open(ExitPlan {
+                    stop_loss: Some(48000.0),
+                    ..Default::default()
+                })],
+                0,
+            )
+            .await;
+        let manager = ExitPlanManager::new(engine.clone());
+
+        // 只为 agent-1 自己的数量挂出一张原生止损单
+        assert!(manager.check_once().await.unwrap().is_empty());
+        let stops: Vec<_> = DynBroker::get_orders(&paper, None)
+            .await
+            .unwrap()
+            .orders
+            .into_iter()
+            .filter(|o| matches!(o.order_type, OrderType::Stop))
+            .collect();
+        assert_eq!(stops.len(), 1);
+        assert_eq!(stops[0].quantity, 0.1);
+
+        // 价格上行触及 agent-2 的合成止损, 只平掉 agent-2 的空头
+        let events = manager.on_price("paper", "BTCUSDT", 53000.0).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].agent_id, "agent-2");
+        assert_eq!(events[0].trigger, ExitTrigger::StopLoss);
+        assert!(events[0].error.is_none());
+
+        let net = DynBroker::get_positions(&paper, None).await.unwrap();
+        let btc = &net.positions["BTCUSDT"];
+        assert!((btc.quantity - 0.1).abs() < 1e-9);
+        assert_eq!(btc.direction.as_deref(), Some("long"));
+        assert!(manager
+            .on_price("paper", "BTCUSDT", 53100.0)
+            .await
+            .is_empty());
+    }
+}
@@ -2,14 +2,28 @@
 
 pub mod agent;
 pub mod agent_store;
+pub mod calendar;
+pub mod clock;
+pub mod cron;
+pub mod decision;
+pub mod equity_recorder;
 pub mod executor;
+pub mod exit_plan;
 pub mod scheduler;
 pub mod tool_executor;
 pub mod trading;
+pub mod trading_tools;
 
 pub use agent::*;
 pub use agent_store::*;
+pub use calendar::*;
+pub use clock::*;
+pub use cron::*;
+pub use decision::*;
+pub use equity_recorder::*;
 pub use executor::*;
+pub use exit_plan::*;
 pub use scheduler::*;
 pub use tool_executor::*;
 pub use trading::*;
+pub use trading_tools::*;
@@ -1,27 +1,432 @@
-use tokio::time::{interval, Duration};
-use tracing::info;
+use super::{CronSchedule, TradingCalendar};
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 
-pub struct Scheduler {
-    interval_secs: u64,
+type JobFuture = Pin<Box<dyn Future<Output = Result<(), anyhow::Error>> + Send>>;
+type JobFn = Arc<dyn Fn(CancellationToken) -> JobFuture + Send + Sync>;
+
+/// 任务触发规则
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    /// 固定间隔, 启动后立即执行一次
+    Interval(Duration),
+    /// Cron 表达式
+    Cron(CronSchedule),
 }
 
-impl Scheduler {
-    pub fn new(interval_secs: u64) -> Self {
-        Self { interval_secs }
+impl Schedule {
+    fn first_fire(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Interval(_) => Some(now),
+            Schedule::Cron(cron) => cron.next_after(now),
+        }
+    }
+
+    fn next_after(&self, last: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Interval(interval) => {
+                Some(last + chrono::Duration::from_std(*interval).ok()?)
+            }
+            Schedule::Cron(cron) => cron.next_after(last),
+        }
+    }
+}
+
+/// 上一次执行尚未结束时再次触发的处理策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverlapPolicy {
+    /// 跳过本次触发
+    #[default]
+    Skip,
+    /// 排队, 等上一次结束后执行
+    Queue,
+    /// 取消上一次执行, 立即执行本次
+    Cancel,
+}
+
+/// 任务的手动触发器
+///
+/// 手动触发与定时触发一样经过交易日历与重叠策略, 例如 `Skip` 策略下上一次执行未结束时被跳过。
+#[derive(Clone)]
+pub struct JobTrigger {
+    sender: mpsc::UnboundedSender<()>,
+}
+
+impl JobTrigger {
+    /// 请求执行一次, 调度已停止时返回 false
+    pub fn fire(&self) -> bool {
+        self.sender.send(()).is_ok()
     }
+}
 
-    pub async fn run<F>(&self, mut task: F) -> Result<(), anyhow::Error>
+/// 调度任务
+pub struct Job {
+    name: String,
+    schedule: Schedule,
+    jitter: Duration,
+    overlap: OverlapPolicy,
+    calendar: Option<Arc<TradingCalendar>>,
+    task: JobFn,
+    trigger: Option<(JobTrigger, mpsc::UnboundedReceiver<()>)>,
+}
+
+impl Job {
+    /// 创建任务, 每次执行时传入可用于协作式取消的 token
+    pub fn new<F, Fut>(name: impl Into<String>, schedule: Schedule, task: F) -> Self
     where
-        F: FnMut() -> Result<(), anyhow::Error>,
+        F: Fn(CancellationToken) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
     {
-        let mut ticker = interval(Duration::from_secs(self.interval_secs));
+        Self {
+            name: name.into(),
+            schedule,
+            jitter: Duration::ZERO,
+            overlap: OverlapPolicy::default(),
+            calendar: None,
+            task: Arc::new(move |token| Box::pin(task(token))),
+            trigger: None,
+        }
+    }
+
+    /// 每次触发前随机延迟 `[0, jitter)`
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// 设置重叠策略
+    pub fn with_overlap(mut self, overlap: OverlapPolicy) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// 仅在交易日历的交易时段内执行
+    pub fn with_calendar(mut self, calendar: Arc<TradingCalendar>) -> Self {
+        self.calendar = Some(calendar);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 手动触发器, 在任务加入调度器前获取
+    pub fn trigger(&mut self) -> JobTrigger {
+        let (trigger, _) = self.trigger.get_or_insert_with(|| {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            (JobTrigger { sender }, receiver)
+        });
+        trigger.clone()
+    }
+
+    fn jitter_delay(&self) -> Duration {
+        if self.jitter.is_zero() {
+            return Duration::ZERO;
+        }
+        rand::thread_rng().gen_range(Duration::ZERO..self.jitter)
+    }
+
+    async fn run_loop(mut self, shutdown: CancellationToken) {
+        let gate = Arc::new(Semaphore::new(1));
+        let mut runs = JoinSet::new();
+        let mut current: Option<CancellationToken> = None;
+        let mut next = self.schedule.first_fire(Utc::now());
+        let mut manual = self.trigger.take().map(|(_, receiver)| receiver);
+
+        while let Some(at) = next {
+            let delay = (at - Utc::now()).to_std().unwrap_or_default() + self.jitter_delay();
+            let triggered = async {
+                match manual.as_mut() {
+                    Some(receiver) => receiver.recv().await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = tokio::time::sleep(delay) => {
+                    next = self.schedule.next_after(at.max(Utc::now()));
+                }
+                // 手动触发不推迟下一次定时执行
+                Some(()) = triggered => debug!("Job {} triggered manually", self.name),
+            }
+
+            if let Some(calendar) = &self.calendar {
+                if !calendar.is_open(Utc::now()) {
+                    debug!(
+                        "Job {} skipped: {} market closed",
+                        self.name,
+                        calendar.name()
+                    );
+                    continue;
+                }
+            }
+
+            while runs.try_join_next().is_some() {}
+            if !runs.is_empty() {
+                match self.overlap {
+                    OverlapPolicy::Skip => {
+                        warn!("Job {} is still running, skipping this run", self.name);
+                        continue;
+                    }
+                    OverlapPolicy::Cancel => {
+                        info!(
+                            "Job {} is still running, cancelling previous run",
+                            self.name
+                        );
+                        if let Some(token) = &current {
+                            token.cancel();
+                        }
+                    }
+                    OverlapPolicy::Queue => {
+                        debug!("Job {} is still running, queueing this run", self.name);
+                    }
+                }
+            }
+
+            let token = shutdown.child_token();
+            current = Some(token.clone());
+            let (name, task, gate) = (self.name.clone(), self.task.clone(), gate.clone());
+            runs.spawn(async move {
+                // 同一任务同时只有一次执行
+                let _permit = tokio::select! {
+                    permit = gate.acquire_owned() => permit,
+                    _ = token.cancelled() => return,
+                };
+
+                // 优先轮询任务本身, 让协作式取消的任务有机会自行收尾
+                tokio::select! {
+                    biased;
+                    result = task(token.clone()) => {
+                        if let Err(e) = result {
+                            error!("Job {} failed: {:#}", name, e);
+                        }
+                    }
+                    _ = token.cancelled() => info!("Job {} cancelled", name),
+                }
+            });
+        }
+
+        // 等待执行中的任务结束（关闭时已通过 token 通知取消）
+        while runs.join_next().await.is_some() {}
+        debug!("Job {} stopped", self.name);
+    }
+}
+
+/// 异步任务调度器
+///
+/// 每个任务独立调度, 调用 `shutdown_token().cancel()` 后停止触发新的执行,
+/// 取消执行中的任务并等待其结束后 `run` 返回。
+#[derive(Default)]
+pub struct Scheduler {
+    jobs: Vec<Job>,
+    shutdown: CancellationToken,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 使用外部的关闭 token
+    pub fn with_shutdown_token(mut self, shutdown: CancellationToken) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
+    pub fn add_job(&mut self, job: Job) {
+        info!("Scheduling job: {}", job.name);
+        self.jobs.push(job);
+    }
+
+    /// 关闭 token, 取消后调度器优雅退出
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
 
-        loop {
-            ticker.tick().await;
-            info!("Scheduler tick");
-            if let Err(e) = task() {
-                tracing::error!("Scheduler task error: {}", e);
+    pub fn job_names(&self) -> Vec<&str> {
+        self.jobs.iter().map(Job::name).collect()
+    }
+
+    /// 运行所有任务, 直到关闭或所有任务都不再触发
+    pub async fn run(self) -> Result<(), anyhow::Error> {
+        let mut loops = JoinSet::new();
+        for job in self.jobs {
+            loops.spawn(job.run_loop(self.shutdown.clone()));
+        }
+
+        while let Some(result) = loops.join_next().await {
+            if let Err(e) = result {
+                error!("Scheduler job loop panicked: {}", e);
             }
         }
+
+        info!("Scheduler stopped");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// 统计执行次数、最大并发与被取消次数的测试任务
+    #[derive(Default)]
+    struct Probe {
+        started: AtomicUsize,
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+        cancelled: AtomicUsize,
+    }
+
+    fn probe_job(
+        probe: Arc<Probe>,
+        every: Duration,
+        work: Duration,
+        overlap: OverlapPolicy,
+    ) -> Job {
+        Job::new("probe", Schedule::Interval(every), move |token| {
+            let probe = probe.clone();
+            async move {
+                probe.started.fetch_add(1, Ordering::SeqCst);
+                let now = probe.running.fetch_add(1, Ordering::SeqCst) + 1;
+                probe.max_running.fetch_max(now, Ordering::SeqCst);
+                tokio::select! {
+                    _ = token.cancelled() => {
+                        probe.cancelled.fetch_add(1, Ordering::SeqCst);
+                    }
+                    _ = tokio::time::sleep(work) => {}
+                }
+                probe.running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            }
+        })
+        .with_overlap(overlap)
+    }
+
+    async fn run_for(job: Job, duration: Duration) {
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(job);
+        let shutdown = scheduler.shutdown_token();
+        let handle = tokio::spawn(scheduler.run());
+
+        tokio::time::sleep(duration).await;
+        shutdown.cancel();
+        tokio::time::timeout(Duration::from_secs(2), handle)
+            .await
+            .expect("scheduler should stop after shutdown")
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_interval_job_and_graceful_shutdown() {
+        let probe = Arc::new(Probe::default());
+        let job = probe_job(
+            probe.clone(),
+            Duration::from_millis(20),
+            Duration::from_millis(1),
+            OverlapPolicy::Skip,
+        );
+        run_for(job, Duration::from_millis(150)).await;
+
+        assert!(probe.started.load(Ordering::SeqCst) >= 3);
+        assert_eq!(probe.running.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_overlap_policies() {
+        // 单次执行 100ms, 每 20ms 触发一次
+        let every = Duration::from_millis(20);
+        let work = Duration::from_millis(100);
+
+        let skip = Arc::new(Probe::default());
+        run_for(
+            probe_job(skip.clone(), every, work, OverlapPolicy::Skip),
+            Duration::from_millis(250),
+        )
+        .await;
+        assert_eq!(skip.max_running.load(Ordering::SeqCst), 1);
+        assert!(skip.started.load(Ordering::SeqCst) <= 3);
+
+        let cancel = Arc::new(Probe::default());
+        run_for(
+            probe_job(cancel.clone(), every, work, OverlapPolicy::Cancel),
+            Duration::from_millis(150),
+        )
+        .await;
+        assert_eq!(cancel.max_running.load(Ordering::SeqCst), 1);
+        assert!(cancel.started.load(Ordering::SeqCst) >= 4);
+        assert!(cancel.cancelled.load(Ordering::SeqCst) >= 3);
+
+        let queue = Arc::new(Probe::default());
+        run_for(
+            probe_job(
+                queue.clone(),
+                every,
+                Duration::from_millis(30),
+                OverlapPolicy::Queue,
+            ),
+            Duration::from_millis(200),
+        )
+        .await;
+        assert_eq!(queue.max_running.load(Ordering::SeqCst), 1);
+        assert!(queue.started.load(Ordering::SeqCst) >= 4);
+    }
+
+    #[tokio::test]
+    async fn test_manual_trigger_follows_overlap_policy() {
+        let probe = Arc::new(Probe::default());
+        let mut job = probe_job(
+            probe.clone(),
+            Duration::from_secs(3600),
+            Duration::from_millis(100),
+            OverlapPolicy::Skip,
+        );
+        let trigger = job.trigger();
+        let mut scheduler = Scheduler::new();
+        scheduler.add_job(job);
+        let shutdown = scheduler.shutdown_token();
+        let handle = tokio::spawn(scheduler.run());
+
+        // 首次执行尚未结束时的手动触发被跳过, 空闲后再触发则执行
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(trigger.fire());
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(probe.started.load(Ordering::SeqCst), 1);
+        assert!(trigger.fire());
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert_eq!(probe.started.load(Ordering::SeqCst), 2);
+
+        shutdown.cancel();
+        handle.await.unwrap().unwrap();
+        assert_eq!(probe.max_running.load(Ordering::SeqCst), 1);
+        assert!(!trigger.fire());
+    }
+
+    #[tokio::test]
+    async fn test_calendar_gates_job() {
+        let closed =
+            TradingCalendar::new("closed", chrono::FixedOffset::east_opt(0).unwrap(), vec![]);
+        let probe = Arc::new(Probe::default());
+        let job = probe_job(
+            probe.clone(),
+            Duration::from_millis(10),
+            Duration::from_millis(1),
+            OverlapPolicy::Skip,
+        )
+        .with_calendar(Arc::new(closed))
+        .with_jitter(Duration::from_millis(5));
+        run_for(job, Duration::from_millis(80)).await;
+
+        assert_eq!(probe.started.load(Ordering::SeqCst), 0);
     }
 }
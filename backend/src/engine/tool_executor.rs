@@ -106,6 +106,20 @@ impl ToolExecutor {
                 result.to_string()
             };
 
+            // 工具执行失败以 isError 结果返回, 内容即错误信息
+            if result.get("isError").and_then(|v| v.as_bool()) == Some(true) {
+                warn!(
+                    "Tool execution failed: {} - {}",
+                    tool_call.name, result_text
+                );
+                return ExecutionResult {
+                    tool_call: tool_call.clone(),
+                    result: String::new(),
+                    success: false,
+                    error: Some(result_text),
+                };
+            }
+
             info!("Tool execution succeeded: {}", tool_call.name);
             debug!("Tool result: {}", result_text);
 
@@ -154,26 +168,31 @@ impl ToolExecutor {
     }
 
     /// 将工具执行结果转换为聊天消息
+    ///
+    /// 每条结果生成一条携带 `ToolResult` 块的 `tool` 消息, 通过 `tool_use_id` 与调用配对。
     pub fn execution_results_to_messages(&self, executions: &[ExecutionResult]) -> Vec<Message> {
         executions
             .iter()
             .map(|exec| {
-                let content = if exec.success {
-                    format!(
-                        "Tool '{}' executed successfully:\n{}",
-                        exec.tool_call.name, exec.result
+                let (result, content) = if exec.success {
+                    (
+                        exec.result.clone(),
+                        format!(
+                            "Tool '{}' executed successfully:\n{}",
+                            exec.tool_call.name, exec.result
+                        ),
                     )
                 } else {
-                    format!(
-                        "Tool '{}' execution failed: {}",
-                        exec.tool_call.name,
-                        exec.error.as_ref().unwrap_or(&"Unknown error".to_string())
+                    let error = exec.error.as_deref().unwrap_or("Unknown error");
+                    (
+                        error.to_string(),
+                        format!("Tool '{}' execution failed: {}", exec.tool_call.name, error),
                     )
                 };
 
                 Message {
-                    role: "tool".to_string(),
                     content,
+                    ..Message::tool_result(&exec.tool_call.id, result, !exec.success)
                 }
             })
             .collect()
@@ -227,11 +246,7 @@ impl ToolExecutor {
                 .context("Failed to call LLM")?;
 
             // 将 LLM 的回复添加到历史
-            let assistant_message = Message {
-                role: "assistant".to_string(),
-                content: response.content.clone(),
-            };
-            message_history.push(assistant_message);
+            message_history.push(response.to_message());
 
             // 检查是否有工具调用
             if let Some(tool_calls) = &response.tool_calls {
@@ -289,6 +304,7 @@ impl ToolExecutor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llm::ContentBlock;
 
     #[tokio::test]
     async fn test_tool_executor_creation() {
@@ -303,4 +319,63 @@ mod tests {
         let executor = ToolExecutor::new(mcp_server).with_max_rounds(5);
         assert_eq!(executor.max_rounds, 5);
     }
+
+    #[tokio::test]
+    async fn test_multi_round_dialogue_with_scripted_provider() {
+        use crate::llm::{LlmProvider, ScriptedProvider};
+        use crate::mcp::{GetPriceTool, PlaceOrderTool};
+
+        let mut mcp_server = McpServer::new();
+        mcp_server.register_tool(GetPriceTool::schema(), Box::new(GetPriceTool));
+        mcp_server.register_tool(PlaceOrderTool::schema(), Box::new(PlaceOrderTool));
+        let executor = ToolExecutor::new(Arc::new(mcp_server));
+
+        let provider = Arc::new(
+            ScriptedProvider::from_file(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/fixtures/llm/trading_dialogue.yaml"
+            ))
+            .unwrap(),
+        );
+        let request = ChatRequest {
+            messages: vec![Message::user("Buy some BTC")],
+            temperature: None,
+            max_tokens: None,
+        };
+
+        let result = executor
+            .execute_dialogue(request, vec![json!({"name": "get_price"})], |req, tools| {
+                let provider = provider.clone();
+                async move { provider.chat_with_tools(req, tools).await }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.total_rounds, 4);
+        assert_eq!(result.final_response, "Bought 0.1 BTC.");
+        assert_eq!(result.executions.len(), 3);
+        assert!(result.executions[0].success);
+        // 畸形参数导致工具执行失败, 错误回传给 LLM 后重试成功
+        assert!(!result.executions[1].success);
+        assert!(result.executions[2].success);
+
+        let requests = provider.requests();
+        assert_eq!(requests.len(), 4);
+        assert!(requests.iter().all(|r| r.tools.len() == 1));
+        assert_eq!(requests[3].request.messages.len(), 7);
+
+        // 工具调用与结果通过 id 配对
+        let history = &requests[3].request.messages;
+        let calls = history[3].tool_calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(
+            history[4].blocks,
+            vec![ContentBlock::ToolResult {
+                tool_use_id: calls[0].id.clone(),
+                content: result.executions[1].error.clone().unwrap(),
+                is_error: true,
+            }]
+        );
+        assert_eq!(provider.remaining(), 0);
+    }
 }
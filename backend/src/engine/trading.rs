@@ -1,16 +1,123 @@
-use crate::llm::{ChatRequest, LlmProvider, Message};
+use super::{
+    extract_json, repair_prompt, AccountStateTool, Agent, Clock, ConversationStore, CronSchedule,
+    DecisionLog, DecisionValidator, ExitPlanBook, Job, JobTrigger, MarketSnapshotTool, OrderLog,
+    OrderOutcome, OverlapPolicy, RiskCheckedOrderTool, Schedule, Scheduler, SubmitDecisionsTool,
+    ToolExecutor, TradeDecision, TradingCalendar, MAX_DECISION_REPAIRS,
+};
+use crate::analytics::InvocationStats;
+use crate::brokers::{Balance, DynBroker, Position, SubAccountBroker, Ticker24h};
+use crate::config::AgentsConfig;
+use crate::llm::{build_provider, ChatRequest, LlmProvider, Message};
 use crate::markets::MarketAdapter;
-use crate::mcp::McpServer;
+use crate::mcp::{McpServer, McpTool, ToolHandler};
+use crate::prompt::{PromptContextBuilder, PromptLibrary, DEFAULT_PROMPT_TOKEN_BUDGET};
+use crate::risk::{PositionInfo, RiskConfig, RiskManager};
+use crate::usage::{MeteredProvider, UsageTracker};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
-use tracing::info;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+/// 默认决策间隔
+pub const DEFAULT_DECISION_INTERVAL: Duration = Duration::from_secs(600);
+
+/// 默认采样温度
+const DEFAULT_TEMPERATURE: f32 = 0.7;
+
+/// 默认最大生成 token 数
+const DEFAULT_MAX_TOKENS: u32 = 1000;
+
+/// Agent 决策前采集的行情与账户快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentSnapshot {
+    pub tickers: Vec<Ticker24h>,
+    pub balance: Balance,
+    pub positions: Vec<Position>,
+    pub timestamp: i64,
+}
+
+impl AgentSnapshot {
+    /// 从经纪商采集指定品种的行情与账户快照
+    pub async fn collect(
+        broker: &dyn DynBroker,
+        symbols: &[String],
+        timestamp: i64,
+    ) -> Result<Self, anyhow::Error> {
+        let mut tickers = Vec::with_capacity(symbols.len());
+        for symbol in symbols {
+            tickers.push(broker.get_ticker_24h(symbol).await?);
+        }
+
+        let balance = broker.get_balance().await?;
+        let mut positions: Vec<Position> = broker
+            .get_positions(None)
+            .await?
+            .positions
+            .into_values()
+            .collect();
+        positions.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        Ok(Self {
+            tickers,
+            balance,
+            positions,
+            timestamp,
+        })
+    }
+}
+
+/// 单个 Agent 一次决策周期的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentCycleReport {
+    pub agent_id: String,
+    /// 决策周期 ID (`{agent_id}-{开始时间}`), LLM 调用记录按此归集
+    pub cycle_id: String,
+    /// 使用的 Prompt 模板版本 (`id@version`)
+    pub prompt_version: String,
+    /// 持久化的会话 ID（未配置存储或写入失败时为 None）
+    pub session_id: Option<i64>,
+    pub final_response: String,
+    pub total_rounds: usize,
+    pub tool_calls: usize,
+    pub orders: Vec<OrderOutcome>,
+    /// 已接受的结构化交易决策
+    pub decisions: Vec<TradeDecision>,
+}
 
 pub struct TradingEngine {
     mcp_server: Arc<McpServer>,
     llm_providers: Arc<RwLock<HashMap<String, Arc<dyn LlmProvider>>>>,
     markets: Arc<RwLock<HashMap<String, Arc<dyn MarketAdapter>>>>,
-    // agents: Arc<RwLock<HashMap<String, Agent>>>,
+    brokers: Arc<RwLock<HashMap<String, Arc<dyn DynBroker>>>>,
+    /// 共用经纪商的 Agent 各自的子账户
+    sub_accounts: Arc<RwLock<HashMap<String, Arc<SubAccountBroker>>>>,
+    /// 子账户订单归属的持久化, 未设置时子账户账本只保存在内存中
+    ledger_pool: Option<Arc<PgPool>>,
+    agents: Arc<RwLock<HashMap<String, Agent>>>,
+    risk_manager: Arc<RiskManager>,
+    /// 配置了风控覆盖的 Agent 各自独立的风险管理器
+    agent_risk_managers: Arc<RwLock<HashMap<String, Arc<RiskManager>>>>,
+    store: Option<Arc<dyn ConversationStore>>,
+    /// 各 Agent 的决策调用统计
+    invocations: Arc<RwLock<HashMap<String, InvocationStats>>>,
+    /// LLM 用量统计与日预算, 未设置时不计量
+    usage: Option<Arc<UsageTracker>>,
+    prompts: Arc<PromptLibrary>,
+    /// Agent 声明的持仓退出计划, 由 `ExitPlanManager` 执行
+    exit_plans: Arc<ExitPlanBook>,
+    /// 按经纪商绑定的交易日历, 未绑定的市场 7x24 运行
+    calendars: Arc<RwLock<HashMap<String, Arc<TradingCalendar>>>>,
+    /// 调度中各 Agent 任务的手动触发器
+    triggers: Arc<RwLock<HashMap<String, JobTrigger>>>,
+    /// 待下一轮决策附加的提醒
+    pending_notes: Arc<RwLock<HashMap<String, String>>>,
+    decision_interval: Duration,
+    jitter: Duration,
+    clock: Clock,
 }
 
 impl TradingEngine {
@@ -19,10 +126,88 @@ impl TradingEngine {
             mcp_server,
             llm_providers: Arc::new(RwLock::new(HashMap::new())),
             markets: Arc::new(RwLock::new(HashMap::new())),
-            // agents: Arc::new(RwLock::new(HashMap::new())),
+            brokers: Arc::new(RwLock::new(HashMap::new())),
+            sub_accounts: Arc::new(RwLock::new(HashMap::new())),
+            ledger_pool: None,
+            agents: Arc::new(RwLock::new(HashMap::new())),
+            risk_manager: Arc::new(RiskManager::new(RiskConfig::default())),
+            agent_risk_managers: Arc::new(RwLock::new(HashMap::new())),
+            store: None,
+            invocations: Arc::new(RwLock::new(HashMap::new())),
+            usage: None,
+            prompts: Arc::new(PromptLibrary::builtin()),
+            exit_plans: Arc::new(ExitPlanBook::new()),
+            calendars: Arc::new(RwLock::new(HashMap::new())),
+            triggers: Arc::new(RwLock::new(HashMap::new())),
+            pending_notes: Arc::new(RwLock::new(HashMap::new())),
+            decision_interval: DEFAULT_DECISION_INTERVAL,
+            jitter: Duration::ZERO,
+            clock: Clock::system(),
         }
     }
 
+    /// 设置风险管理器
+    pub fn with_risk_manager(mut self, risk_manager: Arc<RiskManager>) -> Self {
+        self.risk_manager = risk_manager;
+        self
+    }
+
+    /// 设置对话存储
+    pub fn with_store(mut self, store: Arc<dyn ConversationStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// 设置子账户账本的持久化, 重启后从数据库中的订单与成交重建
+    pub fn with_ledger_persistence(mut self, pool: Arc<PgPool>) -> Self {
+        self.ledger_pool = Some(pool);
+        self
+    }
+
+    /// 设置 LLM 用量统计, 超出日预算的 Agent 当天暂停调度
+    pub fn with_usage_tracker(mut self, tracker: Arc<UsageTracker>) -> Self {
+        self.usage = Some(tracker);
+        self
+    }
+
+    /// 设置 Prompt 模板库
+    pub fn with_prompt_library(mut self, prompts: Arc<PromptLibrary>) -> Self {
+        self.prompts = prompts;
+        self
+    }
+
+    /// 设置决策间隔
+    pub fn with_decision_interval(mut self, interval: Duration) -> Self {
+        self.decision_interval = interval;
+        self
+    }
+
+    /// 设置调度随机延迟, 避免多个 Agent 同时请求 LLM
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// 设置时钟, 回测时使用模拟时钟
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn usage_tracker(&self) -> Option<Arc<UsageTracker>> {
+        self.usage.clone()
+    }
+
+    /// Agent 声明的退出计划簿
+    pub fn exit_plans(&self) -> Arc<ExitPlanBook> {
+        self.exit_plans.clone()
+    }
+
+    /// 共享的 MCP Server
+    pub fn mcp_server(&self) -> Arc<McpServer> {
+        self.mcp_server.clone()
+    }
+
     pub async fn register_llm_provider(&self, name: String, provider: Arc<dyn LlmProvider>) {
         info!("Registering LLM provider: {}", name);
         self.llm_providers.write().await.insert(name, provider);
@@ -33,6 +218,56 @@ impl TradingEngine {
         self.markets.write().await.insert(name, adapter);
     }
 
+    /// 注册经纪商, `Agent::market` 按名称引用
+    pub async fn register_broker(&self, name: String, broker: Arc<dyn DynBroker>) {
+        info!("Registering broker: {}", name);
+        self.brokers.write().await.insert(name, broker);
+    }
+
+    /// 为经纪商绑定交易日历, 该经纪商的 Agent 仅在交易时段内执行
+    pub async fn register_calendar(&self, market: String, calendar: Arc<TradingCalendar>) {
+        info!(
+            "Registering trading calendar {} for {}",
+            calendar.name(),
+            market
+        );
+        self.calendars.write().await.insert(market, calendar);
+    }
+
+    pub async fn register_agent(&self, agent: Agent) {
+        info!("Registering agent: {} ({})", agent.id, agent.name);
+        // 重新注册时丢弃旧的风控状态
+        self.agent_risk_managers.write().await.remove(&agent.id);
+        self.agents.write().await.insert(agent.id.clone(), agent);
+    }
+
+    /// 从 agents.yaml 配置加载 Agent
+    ///
+    /// 为启用的 Agent 创建 LLM Provider（相同 provider 与模型共享实例）。
+    /// Provider 创建失败（如缺少 API Key）的 Agent 会被跳过, 返回成功加载的数量。
+    pub async fn load_agents(&self, config: &AgentsConfig) -> usize {
+        let mut loaded = 0;
+
+        for agent_config in config.enabled_agents() {
+            let key = agent_config.llm.registry_key();
+            if self.get_llm_provider(&key).await.is_none() {
+                match build_provider(&agent_config.llm) {
+                    Ok(provider) => self.register_llm_provider(key, provider).await,
+                    Err(e) => {
+                        warn!("Skipping agent {}: {:#}", agent_config.id, e);
+                        continue;
+                    }
+                }
+            }
+
+            self.register_agent(Agent::from_config(&agent_config)).await;
+            loaded += 1;
+        }
+
+        info!("Loaded {} agents from config", loaded);
+        loaded
+    }
+
     pub async fn get_llm_provider(&self, name: &str) -> Option<Arc<dyn LlmProvider>> {
         self.llm_providers.read().await.get(name).cloned()
     }
@@ -41,6 +276,53 @@ impl TradingEngine {
         self.markets.read().await.get(name).cloned()
     }
 
+    pub async fn get_broker(&self, name: &str) -> Option<Arc<dyn DynBroker>> {
+        self.brokers.read().await.get(name).cloned()
+    }
+
+    /// Agent 的交易账户
+    ///
+    /// 经纪商只绑定了一个启用的 Agent 时直接返回经纪商; 多个 Agent 共用时返回该 Agent 的
+    /// [`SubAccountBroker`], 持仓、余额与成交只统计经由它提交的订单。
+    pub async fn account_for(&self, agent: &Agent) -> Option<Arc<dyn DynBroker>> {
+        let broker = self.get_broker(&agent.market).await?;
+        let sharing = self
+            .agents
+            .read()
+            .await
+            .values()
+            .filter(|a| a.enabled && a.market == agent.market)
+            .count();
+        if sharing <= 1 {
+            return Some(broker);
+        }
+
+        let mut accounts = self.sub_accounts.write().await;
+        match accounts.get(&agent.id) {
+            // 经纪商重新注册后旧子账户随之失效
+            Some(account) if Arc::ptr_eq(account.inner(), &broker) => Some(account.clone()),
+            _ => {
+                let mut account =
+                    SubAccountBroker::new(broker, agent.id.clone(), agent.initial_capital);
+                if let Some(pool) = &self.ledger_pool {
+                    account = account.with_persistence(pool.clone());
+                    match account.restore().await {
+                        Ok(0) => {}
+                        Ok(n) => info!("Restored {} fills into sub-account {}", n, agent.id),
+                        Err(e) => warn!("Failed to restore sub-account {}: {}", agent.id, e),
+                    }
+                }
+                let account = Arc::new(account);
+                accounts.insert(agent.id.clone(), account.clone());
+                Some(account)
+            }
+        }
+    }
+
+    pub async fn get_agent(&self, id: &str) -> Option<Agent> {
+        self.agents.read().await.get(id).cloned()
+    }
+
     pub async fn list_llm_providers(&self) -> Vec<String> {
         self.llm_providers.read().await.keys().cloned().collect()
     }
@@ -49,25 +331,185 @@ impl TradingEngine {
         self.markets.read().await.keys().cloned().collect()
     }
 
-    pub async fn run(&self) -> Result<(), anyhow::Error> {
+    pub async fn list_brokers(&self) -> Vec<String> {
+        self.brokers.read().await.keys().cloned().collect()
+    }
+
+    /// Agent 的决策调用次数
+    pub async fn invocation_count(&self, agent_id: &str) -> u64 {
+        self.invocation_stats(agent_id).await.count
+    }
+
+    /// Agent 的决策调用次数与间隔统计
+    pub async fn invocation_stats(&self, agent_id: &str) -> InvocationStats {
+        self.invocations
+            .read()
+            .await
+            .get(agent_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Agent 是否因超出 LLM 日预算而暂停
+    pub async fn is_paused(&self, agent_id: &str) -> bool {
+        match &self.usage {
+            Some(tracker) => tracker.is_over_budget(agent_id).await,
+            None => false,
+        }
+    }
+
+    /// 列出所有 Agent（按 ID 排序）
+    pub async fn list_agents(&self) -> Vec<Agent> {
+        let mut agents: Vec<Agent> = self.agents.read().await.values().cloned().collect();
+        agents.sort_by(|a, b| a.id.cmp(&b.id));
+        agents
+    }
+
+    /// 运行决策主循环, 直到 `shutdown` 被取消
+    pub async fn run(self: Arc<Self>, shutdown: CancellationToken) -> Result<(), anyhow::Error> {
         info!("Trading engine started");
 
         // 列出已注册的 Providers
         let providers = self.list_llm_providers().await;
         info!("Registered LLM providers: {:?}", providers);
 
-        let markets = self.list_markets().await;
-        info!("Registered markets: {:?}", markets);
+        let brokers = self.list_brokers().await;
+        info!("Registered brokers: {:?}", brokers);
+
+        let scheduler = self.scheduler().await?.with_shutdown_token(shutdown);
+        scheduler.run().await
+    }
 
-        // TODO: 主循环
-        // 1. 启动 MCP Server
-        // 2. 启动调度器
-        // 3. 启动价格订阅
-        // 4. 执行 Agent 决策
+    /// 为每个启用的 Agent 创建调度任务
+    ///
+    /// 配置了 `schedule` 的 Agent 按 cron 执行（使用交易日历时区）, 否则按决策间隔执行;
+    /// 绑定了交易日历的市场仅在交易时段内执行。上一轮未结束时跳过本次触发
+    /// ([`TradingEngine::reassess_agent`] 的手动触发同样如此)。
+    pub async fn scheduler(self: &Arc<Self>) -> Result<Scheduler, anyhow::Error> {
+        let mut scheduler = Scheduler::new();
 
+        for agent in self.list_agents().await.into_iter().filter(|a| a.enabled) {
+            let calendar = self.calendars.read().await.get(&agent.market).cloned();
+
+            let schedule = match &agent.schedule {
+                Some(expr) => {
+                    let mut cron = CronSchedule::parse(expr)?;
+                    if let Some(calendar) = &calendar {
+                        cron = cron.with_offset(calendar.offset());
+                    }
+                    Schedule::Cron(cron)
+                }
+                None => Schedule::Interval(self.interval_for(&agent)),
+            };
+
+            let engine = self.clone();
+            let agent_id = agent.id.clone();
+            let mut job = Job::new(agent.id.clone(), schedule, move |_token| {
+                let engine = engine.clone();
+                let agent_id = agent_id.clone();
+                async move { engine.run_agent(&agent_id).await }
+            })
+            .with_jitter(self.jitter)
+            .with_overlap(OverlapPolicy::Skip);
+            if let Some(calendar) = calendar {
+                job = job.with_calendar(calendar);
+            }
+            self.triggers
+                .write()
+                .await
+                .insert(agent.id.clone(), job.trigger());
+
+            scheduler.add_job(job);
+        }
+
+        Ok(scheduler)
+    }
+
+    /// 按 ID 执行单个 Agent（读取最新注册的配置）
+    async fn run_agent(&self, agent_id: &str) -> Result<(), anyhow::Error> {
+        let agent = self
+            .get_agent(agent_id)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Agent not found: {}", agent_id))?;
+        if !agent.enabled {
+            return Ok(());
+        }
+        if self.is_paused(&agent.id).await {
+            warn!("Agent {} paused: daily LLM budget exceeded", agent.id);
+            return Ok(());
+        }
+
+        let note = self.pending_notes.write().await.remove(agent_id);
+        let report = self.execute_cycle(&agent, note.as_deref()).await?;
+        info!(
+            "Agent {} finished in {} rounds with {} orders",
+            agent.id,
+            report.total_rounds,
+            report.orders.len()
+        );
         Ok(())
     }
 
+    /// 对所有启用的 Agent 执行一轮决策
+    ///
+    /// 单个 Agent 失败只记录日志, 不影响其他 Agent。
+    pub async fn run_once(&self) -> Vec<AgentCycleReport> {
+        let agents = self
+            .list_agents()
+            .await
+            .into_iter()
+            .filter(|a| a.enabled)
+            .collect();
+        self.run_agents(agents).await
+    }
+
+    async fn run_agents(&self, agents: Vec<Agent>) -> Vec<AgentCycleReport> {
+        let mut reports = Vec::new();
+
+        for agent in agents {
+            if self.is_paused(&agent.id).await {
+                warn!("Agent {} paused: daily LLM budget exceeded", agent.id);
+                continue;
+            }
+            match self.execute_agent(&agent).await {
+                Ok(report) => {
+                    info!(
+                        "Agent {} finished in {} rounds with {} orders",
+                        agent.id,
+                        report.total_rounds,
+                        report.orders.len()
+                    );
+                    reports.push(report);
+                }
+                Err(e) => error!("Agent {} cycle failed: {:#}", agent.id, e),
+            }
+        }
+
+        reports
+    }
+
+    /// Agent 的决策间隔
+    pub fn interval_for(&self, agent: &Agent) -> Duration {
+        agent
+            .decision_interval_secs
+            .map(Duration::from_secs)
+            .unwrap_or(self.decision_interval)
+    }
+
+    /// Agent 使用的风险管理器, 配置了风控覆盖时为该 Agent 独立创建
+    pub async fn risk_manager_for(&self, agent: &Agent) -> Arc<RiskManager> {
+        let Some(config) = &agent.risk else {
+            return self.risk_manager.clone();
+        };
+
+        self.agent_risk_managers
+            .write()
+            .await
+            .entry(agent.id.clone())
+            .or_insert_with(|| Arc::new(agent_risk_manager(agent, config.clone())))
+            .clone()
+    }
+
     /// 执行简单的 LLM 查询（用于测试和演示）
     pub async fn simple_chat(
         &self,
@@ -80,10 +522,7 @@ impl TradingEngine {
             .ok_or_else(|| anyhow::anyhow!("Provider not found: {}", provider_name))?;
 
         let request = ChatRequest {
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: message.to_string(),
-            }],
+            messages: vec![Message::user(message)],
             temperature: Some(0.7),
             max_tokens: Some(500),
         };
@@ -114,14 +553,674 @@ impl TradingEngine {
         Ok(response)
     }
 
-    async fn execute_agent(&self /* agent: &Agent */) -> Result<(), anyhow::Error> {
-        // TODO: 执行单个 Agent
-        // 1. 获取市场数据
-        // 2. 构建 Prompt
-        // 3. 调用 LLM
-        // 4. 解析决策
-        // 5. 执行交易
-        // 6. 记录结果
+    /// 执行单个 Agent 的一次决策周期
+    ///
+    /// 1. 从经纪商采集行情与账户快照, 同步到风控指标
+    /// 2. 构建 Prompt
+    /// 3. 通过 `ToolExecutor` 与 LLM 多轮对话, 下单工具逐笔经过风控后提交经纪商;
+    ///    要求结构化决策时, 缺失或不合法的决策反馈给 LLM 修正
+    /// 4. 持久化对话
+    pub async fn execute_agent(&self, agent: &Agent) -> Result<AgentCycleReport, anyhow::Error> {
+        self.execute_cycle(agent, None).await
+    }
+
+    /// 在常规 Prompt 后附加提醒, 立即重新执行 Agent 的决策周期 (如退出计划的失效条件被触及)
+    ///
+    /// 调度运行时经由该 Agent 的调度任务触发, 遵循其重叠策略: 上一轮未结束时本次被跳过,
+    /// 提醒留到下一轮决策。未启动调度时直接执行。
+    pub async fn reassess_agent(&self, agent: &Agent, note: &str) -> Result<(), anyhow::Error> {
+        if self.is_paused(&agent.id).await {
+            anyhow::bail!("Agent {} is paused (LLM budget exceeded)", agent.id);
+        }
+
+        let trigger = self.triggers.read().await.get(&agent.id).cloned();
+        if let Some(trigger) = trigger {
+            self.pending_notes
+                .write()
+                .await
+                .insert(agent.id.clone(), note.to_string());
+            if trigger.fire() {
+                return Ok(());
+            }
+            self.pending_notes.write().await.remove(&agent.id);
+        }
+        self.execute_cycle(agent, Some(note)).await?;
         Ok(())
     }
+
+    async fn execute_cycle(
+        &self,
+        agent: &Agent,
+        note: Option<&str>,
+    ) -> Result<AgentCycleReport, anyhow::Error> {
+        let provider = self
+            .get_llm_provider(&agent.llm_provider)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Provider not found: {}", agent.llm_provider))?;
+        let broker = self
+            .account_for(agent)
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Broker not found: {}", agent.market))?;
+
+        let now = self.clock.now();
+        let cycle = {
+            let mut invocations = self.invocations.write().await;
+            let stats = invocations.entry(agent.id.clone()).or_default();
+            stats.record(now);
+            stats.count
+        };
+        let cycle_id = format!("{}-{}", agent.id, now);
+        let provider: Arc<dyn LlmProvider> = match &self.usage {
+            Some(tracker) => Arc::new(
+                MeteredProvider::new(provider, tracker.clone(), agent.id.clone())
+                    .with_cycle_id(cycle_id.clone()),
+            ),
+            None => provider,
+        };
+
+        // 1. 获取市场数据与账户状态
+        let snapshot =
+            AgentSnapshot::collect(broker.as_ref(), &agent.symbols, self.clock.now()).await?;
+        let risk_manager = self.risk_manager_for(agent).await;
+        sync_risk_metrics(&risk_manager, &snapshot).await;
+
+        // 2. 构建 Prompt 与工具
+        let context = PromptContextBuilder::new(agent, &snapshot)
+            .with_broker(broker.as_ref())
+            .with_risk_manager(&risk_manager)
+            .with_cycle(cycle)
+            .build()
+            .await;
+        let template = self.prompts.select(&agent.prompts, cycle)?;
+        let prompt = self.prompts.render(
+            template,
+            &context,
+            agent.system_prompt.as_deref(),
+            agent
+                .prompt_token_budget
+                .unwrap_or(DEFAULT_PROMPT_TOKEN_BUDGET),
+        )?;
+        if prompt.truncated {
+            warn!(
+                "Prompt for {} truncated to ~{} tokens",
+                agent.id, prompt.estimated_tokens
+            );
+        }
+
+        let log = OrderLog::default();
+        let decisions = DecisionLog::default();
+        let validator =
+            DecisionValidator::new(agent.symbols.clone(), risk_manager.get_config().await);
+        let order_tool = RiskCheckedOrderTool::new(
+            broker.clone(),
+            risk_manager,
+            agent.id.clone(),
+            agent.symbols.clone(),
+            log.clone(),
+        );
+        let decision_tool = SubmitDecisionsTool::new(
+            broker.clone(),
+            order_tool.clone(),
+            validator,
+            decisions.clone(),
+        );
+        let (mcp_server, schemas) = agent_tools(agent, broker, order_tool, decision_tool.clone());
+        let tools = tool_definitions(&schemas);
+        let mut messages = prompt.messages;
+        if let Some(note) = note {
+            messages.push(Message::user(note));
+        }
+        let request = ChatRequest {
+            messages,
+            temperature: Some(agent.temperature.unwrap_or(DEFAULT_TEMPERATURE)),
+            max_tokens: Some(agent.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)),
+        };
+
+        // 3. 调用 LLM 并执行工具
+        let executor = ToolExecutor::new(Arc::new(mcp_server));
+        let chat = |req: ChatRequest, tools: Vec<serde_json::Value>| {
+            let provider = provider.clone();
+            async move { provider.chat_with_tools(req, tools).await }
+        };
+        let mut dialogue = executor
+            .execute_dialogue(request.clone(), tools.clone(), &chat)
+            .await?;
+        let mut total_rounds = dialogue.total_rounds;
+        let mut tool_calls = dialogue.executions.len();
+
+        // 未通过工具提交决策时, 尝试解析最终回复中的 JSON, 失败则追加修正轮
+        let mut repairs = 0;
+        while agent.require_decision && !decision_tool.submitted() {
+            let error = match extract_json(&dialogue.final_response) {
+                Some(value) => match decision_tool.execute(value).await {
+                    Ok(_) => break,
+                    Err(e) => e.to_string(),
+                },
+                None => "No trade decision found in the response".to_string(),
+            };
+            if repairs == MAX_DECISION_REPAIRS {
+                warn!(
+                    "Agent {} produced no valid decision after {} repairs: {}",
+                    agent.id, repairs, error
+                );
+                break;
+            }
+            repairs += 1;
+
+            let mut messages = dialogue.message_history;
+            messages.push(Message::user(repair_prompt(&error)));
+            dialogue = executor
+                .execute_dialogue(
+                    ChatRequest {
+                        messages,
+                        ..request.clone()
+                    },
+                    tools.clone(),
+                    &chat,
+                )
+                .await?;
+            total_rounds += dialogue.total_rounds;
+            tool_calls += dialogue.executions.len();
+        }
+
+        // 4. 记录结果
+        let session_id = match &self.store {
+            Some(store) => {
+                match persist(
+                    store.as_ref(),
+                    &agent.id,
+                    &prompt.version_id,
+                    &dialogue.message_history,
+                )
+                .await
+                {
+                    Ok(id) => Some(id),
+                    Err(e) => {
+                        warn!("Failed to persist conversation for {}: {:#}", agent.id, e);
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let orders = std::mem::take(&mut *log.lock().await);
+        let decisions = std::mem::take(&mut *decisions.lock().await);
+        self.exit_plans
+            .apply_decisions(&agent.id, &decisions, self.clock.now())
+            .await;
+        Ok(AgentCycleReport {
+            agent_id: agent.id.clone(),
+            cycle_id,
+            prompt_version: prompt.version_id,
+            session_id,
+            final_response: dialogue.final_response,
+            total_rounds,
+            tool_calls,
+            orders,
+            decisions,
+        })
+    }
+}
+
+/// 为 Agent 创建独立的风险管理器, 以初始资金作为回撤基准
+pub fn agent_risk_manager(agent: &Agent, config: RiskConfig) -> RiskManager {
+    let manager = RiskManager::new(config);
+    if agent.initial_capital > 0.0 {
+        manager.with_initial_balance(agent.initial_capital)
+    } else {
+        manager
+    }
+}
+
+/// 将快照同步到风控指标, 使订单校验基于该 Agent 的账户
+pub async fn sync_risk_metrics(risk_manager: &RiskManager, snapshot: &AgentSnapshot) {
+    let unrealized: f64 = snapshot.positions.iter().map(|p| p.unrealized_pnl).sum();
+    risk_manager
+        .update_balance(
+            snapshot.balance.total_balance,
+            snapshot.balance.total_balance + unrealized,
+        )
+        .await;
+
+    let prices = snapshot
+        .tickers
+        .iter()
+        .map(|t| (t.symbol.clone(), t.last_price))
+        .collect();
+    risk_manager.update_prices(prices).await;
+
+    for p in &snapshot.positions {
+        let quantity = if p.direction.as_deref() == Some("short") {
+            -p.quantity.abs()
+        } else {
+            p.quantity
+        };
+        risk_manager
+            .update_position(
+                p.symbol.clone(),
+                PositionInfo::new(quantity, p.entry_price, p.current_price),
+            )
+            .await;
+    }
+}
+
+/// 构建绑定到 Agent 经纪商的工具集, 按 `allowed_tools` 过滤
+fn agent_tools(
+    agent: &Agent,
+    broker: Arc<dyn DynBroker>,
+    order_tool: RiskCheckedOrderTool,
+    decision_tool: SubmitDecisionsTool,
+) -> (McpServer, Vec<McpTool>) {
+    let mut server = McpServer::new();
+    let mut schemas = Vec::new();
+
+    let mut register = |schema: McpTool, handler: Box<dyn ToolHandler>| {
+        if agent.allows_tool(&schema.name) {
+            schemas.push(schema.clone());
+            server.register_tool(schema, handler);
+        }
+    };
+
+    register(
+        MarketSnapshotTool::schema(),
+        Box::new(MarketSnapshotTool::new(
+            broker.clone(),
+            agent.symbols.clone(),
+        )),
+    );
+    register(
+        AccountStateTool::schema(),
+        Box::new(AccountStateTool::new(broker.clone())),
+    );
+    register(RiskCheckedOrderTool::schema(), Box::new(order_tool));
+    register(SubmitDecisionsTool::schema(), Box::new(decision_tool));
+
+    (server, schemas)
+}
+
+/// 将 MCP 工具描述转换为 function calling 格式
+fn tool_definitions(schemas: &[McpTool]) -> Vec<serde_json::Value> {
+    schemas
+        .iter()
+        .map(|tool| {
+            serde_json::json!({
+                "type": "function",
+                "function": {
+                    "name": tool.name,
+                    "description": tool.description,
+                    "parameters": tool.input_schema,
+                }
+            })
+        })
+        .collect()
+}
+
+async fn persist(
+    store: &dyn ConversationStore,
+    agent_id: &str,
+    prompt_version: &str,
+    messages: &[Message],
+) -> Result<i64, anyhow::Error> {
+    let session = store.create_session(agent_id, Some(prompt_version)).await?;
+    store.add_messages(session.id, messages).await?;
+    Ok(session.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::MockBroker;
+    use crate::engine::InMemoryAgentStore;
+    use crate::llm::{ScriptStep, ScriptedProvider, ScriptedToolCall};
+    use crate::usage::{InMemoryUsageStore, UsageConfig};
+
+    fn reply(content: &str, calls: Vec<(&str, serde_json::Value)>) -> ScriptStep {
+        ScriptStep {
+            content: content.to_string(),
+            tool_calls: calls
+                .into_iter()
+                .map(|(name, arguments)| ScriptedToolCall {
+                    name: name.to_string(),
+                    arguments,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    async fn engine_with(
+        llm: Arc<ScriptedProvider>,
+        store: Arc<InMemoryAgentStore>,
+    ) -> TradingEngine {
+        let engine = TradingEngine::new(Arc::new(McpServer::new())).with_store(store);
+        engine
+            .register_llm_provider("scripted".to_string(), llm)
+            .await;
+        engine
+            .register_broker("mock".to_string(), Arc::new(MockBroker::new()))
+            .await;
+        engine
+    }
+
+    #[tokio::test]
+    async fn test_agent_cycle_end_to_end() {
+        let llm = Arc::new(ScriptedProvider::new(vec![
+            reply(
+                "Checking the market",
+                vec![
+                    ("get_market_snapshot", serde_json::json!({})),
+                    ("get_account_state", serde_json::json!({})),
+                ],
+            ),
+            reply(
+                "Opening a small long and an oversized one",
+                vec![
+                    (
+                        "place_order",
+                        serde_json::json!({"symbol": "BTCUSDT", "side": "buy", "quantity": 0.1}),
+                    ),
+                    (
+                        "place_order",
+                        serde_json::json!({"symbol": "BTCUSDT", "side": "buy", "quantity": 5.0}),
+                    ),
+                ],
+            ),
+            reply("Bought 0.1 BTC, the larger order was rejected", vec![]),
+        ]));
+        let store = Arc::new(InMemoryAgentStore::new());
+        let engine = engine_with(llm.clone(), store.clone()).await;
+
+        let mut agent = Agent::new(
+            "agent-1".to_string(),
+            "Scripted Agent".to_string(),
+            "scripted".to_string(),
+            "mock".to_string(),
+        );
+        agent.symbols = vec!["BTCUSDT".to_string()];
+        engine.register_agent(agent).await;
+
+        let mut disabled = Agent::new(
+            "agent-2".to_string(),
+            "Disabled".to_string(),
+            "scripted".to_string(),
+            "mock".to_string(),
+        );
+        disabled.enabled = false;
+        engine.register_agent(disabled).await;
+
+        let reports = engine.run_once().await;
+        assert_eq!(reports.len(), 1);
+
+        let report = &reports[0];
+        assert_eq!(report.agent_id, "agent-1");
+        assert_eq!(report.total_rounds, 3);
+        assert_eq!(report.tool_calls, 4);
+        assert_eq!(report.orders.len(), 2);
+        assert!(report.orders[0].is_executed());
+        assert!(!report.orders[1].approved);
+        assert!(report.orders[1].response.is_none());
+
+        // Prompt 包含行情与账户快照
+        let requests = llm.requests();
+        let prompt = &requests[0].request.messages[1].content;
+        assert!(prompt.contains("BTCUSDT"));
+        assert!(prompt.contains("total=105000.00"));
+        // 工具结果回传给 LLM
+        assert!(requests[2]
+            .request
+            .messages
+            .iter()
+            .any(|m| m.role == "tool" && m.content.contains("rejected")));
+
+        // 对话已持久化
+        let session_id = report.session_id.unwrap();
+        let sessions = store.sessions("agent-1").await;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(report.prompt_version, "trading@v2");
+        assert_eq!(sessions[0].prompt_version.as_deref(), Some("trading@v2"));
+        let messages = store.get_messages(session_id).await;
+        assert_eq!(messages.first().unwrap().role, "system");
+        assert_eq!(
+            messages.last().unwrap().content,
+            "Bought 0.1 BTC, the larger order was rejected"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_agent_failure_does_not_stop_cycle() {
+        let llm = Arc::new(ScriptedProvider::new(vec![reply("Holding", vec![])]));
+        let store = Arc::new(InMemoryAgentStore::new());
+        let engine = engine_with(llm, store).await;
+
+        engine
+            .register_agent(Agent::new(
+                "a-missing-broker".to_string(),
+                "Broken".to_string(),
+                "scripted".to_string(),
+                "unknown".to_string(),
+            ))
+            .await;
+        engine
+            .register_agent(Agent::new(
+                "b-ok".to_string(),
+                "Ok".to_string(),
+                "scripted".to_string(),
+                "mock".to_string(),
+            ))
+            .await;
+
+        let reports = engine.run_once().await;
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].agent_id, "b-ok");
+        assert_eq!(reports[0].final_response, "Holding");
+        assert!(reports[0].orders.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_required_decision_is_repaired() {
+        let open = |stop_loss: f64| {
+            serde_json::json!({"decisions": [{
+                "action": "open", "symbol": "BTCUSDT", "side": "long", "quantity": 0.1,
+                "confidence": 0.65,
+                "exit_plan": {"stop_loss": stop_loss, "profit_target": 53000,
+                              "invalidation_condition": "4h close below 48000"}
+            }]})
+        };
+        let llm = Arc::new(ScriptedProvider::new(vec![
+            reply("BTC looks strong, I would go long", vec![]),
+            reply("", vec![("submit_decisions", open(51000.0))]),
+            reply("", vec![("submit_decisions", open(49000.0))]),
+            reply("Long 0.1 BTC, stop 49000", vec![]),
+        ]));
+        let engine = engine_with(llm.clone(), Arc::new(InMemoryAgentStore::new())).await;
+        let mut agent = Agent::new(
+            "agent-1".to_string(),
+            "Decider".to_string(),
+            "scripted".to_string(),
+            "mock".to_string(),
+        );
+        agent.symbols = vec!["BTCUSDT".to_string()];
+        agent.require_decision = true;
+
+        let report = engine.execute_agent(&agent).await.unwrap();
+        assert_eq!(report.total_rounds, 4);
+        assert_eq!(report.decisions.len(), 1);
+        assert_eq!(
+            report.decisions[0].exit_plan.as_ref().unwrap().stop_loss,
+            Some(49000.0)
+        );
+        assert_eq!(report.orders.len(), 1);
+        assert!(report.orders[0].is_executed());
+
+        // 缺失决策时追加修正提示, 不合法的决策以工具错误返回
+        let requests = llm.requests();
+        let repair = requests[1].request.messages.last().unwrap();
+        assert_eq!(repair.role, "user");
+        assert!(repair.content.contains("No trade decision found"));
+        assert!(requests[2]
+            .request
+            .messages
+            .iter()
+            .any(|m| m.role == "tool" && m.content.contains("wrong side")));
+    }
+
+    #[tokio::test]
+    async fn test_llm_budget_pauses_agent() {
+        let step = ScriptStep {
+            usage: Some(crate::llm::TokenUsage {
+                prompt_tokens: 1_000_000,
+                completion_tokens: 0,
+                total_tokens: 1_000_000,
+            }),
+            repeat: true,
+            ..reply("Holding", vec![])
+        };
+        let llm = Arc::new(ScriptedProvider::new(vec![step]).with_model("gpt-5"));
+        let config = UsageConfig::from_yaml_str(
+            "prices:\n  - { model: gpt-5, input_per_million: 1.25, output_per_million: 10 }\ndefault_daily_budget_usd: 1.0\n",
+        )
+        .unwrap();
+        let tracker = Arc::new(UsageTracker::new(
+            Arc::new(InMemoryUsageStore::new()),
+            config,
+        ));
+        let engine = engine_with(llm, Arc::new(InMemoryAgentStore::new()))
+            .await
+            .with_usage_tracker(tracker.clone());
+        engine
+            .register_agent(Agent::new(
+                "agent-1".to_string(),
+                "Metered".to_string(),
+                "scripted".to_string(),
+                "mock".to_string(),
+            ))
+            .await;
+
+        let reports = engine.run_once().await;
+        assert_eq!(reports.len(), 1);
+        let usage = tracker.summary(Some("agent-1"), 0, i64::MAX).await.unwrap();
+        assert_eq!(usage[0].calls, 1);
+        assert!((usage[0].cost_usd - 1.25).abs() < 1e-9);
+        assert!(usage[0].paused);
+
+        // 超出日预算后不再调度
+        assert!(engine.is_paused("agent-1").await);
+        assert!(engine.run_once().await.is_empty());
+        assert_eq!(engine.invocation_count("agent-1").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_agent_config_overrides() {
+        let config = AgentsConfig::from_yaml_str(
+            r#"
+agents:
+  - id: tight
+    name: Tight Risk
+    llm: { provider: scripted, model: offline, temperature: 0.2, max_tokens: 256 }
+    broker: mock
+    symbols: [BTCUSDT]
+    initial_capital: 5000
+    decision_interval_secs: 60
+    system_prompt: "You are {{name}} ({{id}}) on {{broker}} with {{initial_capital}}, trading {{symbols}}."
+    allowed_tools: [place_order]
+    risk:
+      order_size_limits:
+        max_order_value: 1000
+"#,
+        )
+        .unwrap();
+        let agent = Agent::from_config(&config.agents[0]);
+        assert_eq!(agent.llm_provider, "scripted:offline");
+
+        // 0.1 * 50000 = 5000 USD: 默认风控通过, 但超过该 Agent 的 1000 上限
+        let llm = Arc::new(ScriptedProvider::new(vec![
+            reply(
+                "Buying",
+                vec![(
+                    "place_order",
+                    serde_json::json!({"symbol": "BTCUSDT", "side": "buy", "quantity": 0.1}),
+                )],
+            ),
+            reply("Rejected, holding", vec![]),
+        ]));
+        let engine = engine_with(llm.clone(), Arc::new(InMemoryAgentStore::new())).await;
+        engine
+            .register_llm_provider(agent.llm_provider.clone(), llm.clone())
+            .await;
+        assert_eq!(engine.interval_for(&agent), Duration::from_secs(60));
+
+        let report = engine.execute_agent(&agent).await.unwrap();
+        assert_eq!(report.orders.len(), 1);
+        assert!(!report.orders[0].approved);
+
+        let requests = llm.requests();
+        let first = &requests[0];
+        assert_eq!(first.request.temperature, Some(0.2));
+        assert_eq!(first.request.max_tokens, Some(256));
+        assert_eq!(
+            first.request.messages[0].content,
+            "You are Tight Risk (tight) on mock with 5000, trading BTCUSDT."
+        );
+        assert_eq!(first.tools.len(), 1);
+        assert_eq!(first.tools[0]["function"]["name"], "place_order");
+
+        // 共享风控不受影响
+        let default_agent = Agent::new(
+            "other".to_string(),
+            "Other".to_string(),
+            "scripted".to_string(),
+            "mock".to_string(),
+        );
+        assert!(Arc::ptr_eq(
+            &engine.risk_manager_for(&default_agent).await,
+            &engine.risk_manager
+        ));
+        assert_eq!(
+            engine.interval_for(&default_agent),
+            DEFAULT_DECISION_INTERVAL
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_agents_skips_unavailable_providers() {
+        let yaml = format!(
+            r#"
+agents:
+  - id: offline
+    name: Offline
+    llm: {{ provider: scripted, model: v1, script: "{}/fixtures/llm/trading_dialogue.yaml" }}
+    broker: mock
+    symbols: [BTCUSDT]
+    initial_capital: 10000
+  - id: no_key
+    name: No Key
+    llm: {{ provider: deepseek, model: deepseek-chat, api_key_env: NOF0_TEST_UNSET_API_KEY }}
+    broker: mock
+    symbols: [BTCUSDT]
+    initial_capital: 10000
+  - id: disabled
+    name: Disabled
+    enabled: false
+    llm: {{ provider: unknown, model: x }}
+    broker: mock
+    symbols: [BTCUSDT]
+    initial_capital: 10000
+"#,
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let config = AgentsConfig::from_yaml_str(&yaml).unwrap();
+
+        let engine = TradingEngine::new(Arc::new(McpServer::new()));
+        assert_eq!(engine.load_agents(&config).await, 1);
+
+        let agents = engine.list_agents().await;
+        assert_eq!(agents.len(), 1);
+        assert_eq!(agents[0].id, "offline");
+        assert!(engine
+            .get_llm_provider(&agents[0].llm_provider)
+            .await
+            .is_some());
+    }
 }
@@ -0,0 +1,428 @@
+use super::{sync_risk_metrics, AgentSnapshot, Clock};
+use crate::brokers::{DynBroker, OrderRequest, OrderResponse, OrderSide, OrderType};
+use crate::mcp::{McpTool, ToolHandler};
+use crate::risk::{OrderInfo, RiskManager};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// 单笔订单在决策周期中的处理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderOutcome {
+    /// LLM 提交的订单
+    pub request: OrderRequest,
+    /// 是否通过风控
+    pub approved: bool,
+    /// 风控警告与拒绝原因
+    pub risk_messages: Vec<String>,
+    /// 经纪商回报（通过风控且下单成功时）
+    pub response: Option<OrderResponse>,
+    /// 下单失败原因
+    pub error: Option<String>,
+}
+
+impl OrderOutcome {
+    /// 是否已被经纪商接受
+    pub fn is_executed(&self) -> bool {
+        self.response.is_some()
+    }
+}
+
+/// 决策周期内的订单记录（在工具与引擎之间共享）
+pub type OrderLog = Arc<Mutex<Vec<OrderOutcome>>>;
+
+/// 工具: 获取 Agent 交易品种的行情快照
+pub struct MarketSnapshotTool {
+    broker: Arc<dyn DynBroker>,
+    symbols: Vec<String>,
+}
+
+impl MarketSnapshotTool {
+    pub fn new(broker: Arc<dyn DynBroker>, symbols: Vec<String>) -> Self {
+        Self { broker, symbols }
+    }
+
+    pub fn schema() -> McpTool {
+        McpTool {
+            name: "get_market_snapshot".to_string(),
+            description: "Get 24h ticker for the given symbols (defaults to the agent's universe)"
+                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "symbols": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Trading symbols, e.g. [\"BTCUSDT\"]"
+                    }
+                }
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for MarketSnapshotTool {
+    async fn execute(&self, input: serde_json::Value) -> Result<serde_json::Value, anyhow::Error> {
+        let symbols: Vec<String> = match input.get("symbols").and_then(|v| v.as_array()) {
+            Some(list) => list
+                .iter()
+                .filter_map(|s| s.as_str().map(String::from))
+                .collect(),
+            None => self.symbols.clone(),
+        };
+
+        let mut tickers = Vec::with_capacity(symbols.len());
+        for symbol in &symbols {
+            tickers.push(self.broker.get_ticker_24h(symbol).await?);
+        }
+
+        Ok(serde_json::json!({ "tickers": tickers }))
+    }
+}
+
+/// 工具: 获取账户余额与持仓
+pub struct AccountStateTool {
+    broker: Arc<dyn DynBroker>,
+}
+
+impl AccountStateTool {
+    pub fn new(broker: Arc<dyn DynBroker>) -> Self {
+        Self { broker }
+    }
+
+    pub fn schema() -> McpTool {
+        McpTool {
+            name: "get_account_state".to_string(),
+            description: "Get account balance and open positions".to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {}
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolHandler for AccountStateTool {
+    async fn execute(&self, _input: serde_json::Value) -> Result<serde_json::Value, anyhow::Error> {
+        let balance = self.broker.get_balance().await?;
+        let positions = self.broker.get_positions(None).await?;
+
+        Ok(serde_json::json!({
+            "balance": balance,
+            "positions": positions.positions,
+        }))
+    }
+}
+
+/// 工具: 下单
+///
+/// 每笔订单先经 `RiskManager::validate_order` 校验, 通过后才提交给经纪商;
+/// 风控拒绝不会作为工具错误返回, 而是把原因回传给 LLM, 便于其调整决策。
+/// 只接受 `allowed_symbols` 中的品种, 未配置品种时拒绝所有订单。
+#[derive(Clone)]
+pub struct RiskCheckedOrderTool {
+    broker: Arc<dyn DynBroker>,
+    risk_manager: Arc<RiskManager>,
+    account_id: String,
+    allowed_symbols: Vec<String>,
+    log: OrderLog,
+    sync_metrics: bool,
+}
+
+impl RiskCheckedOrderTool {
+    pub fn new(
+        broker: Arc<dyn DynBroker>,
+        risk_manager: Arc<RiskManager>,
+        account_id: String,
+        allowed_symbols: Vec<String>,
+        log: OrderLog,
+    ) -> Self {
+        Self {
+            broker,
+            risk_manager,
+            account_id,
+            allowed_symbols,
+            log,
+            sync_metrics: false,
+        }
+    }
+
+    /// 每笔订单校验前从经纪商同步余额、行情与持仓到风控指标 (在决策周期之外使用时)
+    pub fn with_metrics_sync(mut self) -> Self {
+        self.sync_metrics = true;
+        self
+    }
+
+    pub fn schema() -> McpTool {
+        McpTool {
+            name: "place_order".to_string(),
+            description: "Place an order; it is checked by risk control before execution"
+                .to_string(),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "symbol": {
+                        "type": "string",
+                        "description": "Trading symbol"
+                    },
+                    "side": {
+                        "type": "string",
+                        "enum": ["buy", "sell"],
+                        "description": "Order side"
+                    },
+                    "quantity": {
+                        "type": "number",
+                        "description": "Order quantity"
+                    },
+                    "order_type": {
+                        "type": "string",
+                        "enum": ["market", "limit"],
+                        "description": "Order type, defaults to market"
+                    },
+                    "price": {
+                        "type": "number",
+                        "description": "Limit price (required for limit orders)"
+                    }
+                },
+                "required": ["symbol", "side", "quantity"]
+            }),
+        }
+    }
+
+    fn parse_order(&self, input: &serde_json::Value) -> Result<OrderRequest, anyhow::Error> {
+        let symbol = input
+            .get("symbol")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Missing symbol parameter"))?;
+
+        if !self
+            .allowed_symbols
+            .iter()
+            .any(|s| s.eq_ignore_ascii_case(symbol))
+        {
+            anyhow::bail!("Symbol {} is not in the agent's universe", symbol);
+        }
+
+        let side = match input.get("side").and_then(|v| v.as_str()) {
+            Some(s) if s.eq_ignore_ascii_case("buy") => OrderSide::Buy,
+            Some(s) if s.eq_ignore_ascii_case("sell") => OrderSide::Sell,
+            Some(s) => anyhow::bail!("Invalid side: {}", s),
+            None => anyhow::bail!("Missing side parameter"),
+        };
+
+        let quantity = input
+            .get("quantity")
+            .and_then(|v| v.as_f64())
+            .filter(|q| *q > 0.0)
+            .ok_or_else(|| anyhow::anyhow!("Missing or non-positive quantity parameter"))?;
+
+        let price = input.get("price").and_then(|v| v.as_f64());
+        let order_type = match input.get("order_type").and_then(|v| v.as_str()) {
+            None | Some("market") => OrderType::Market,
+            Some("limit") if price.is_some() => OrderType::Limit,
+            Some("limit") => anyhow::bail!("Limit order requires price"),
+            Some(t) => anyhow::bail!("Unsupported order type: {}", t),
+        };
+
+        Ok(OrderRequest {
+            symbol: symbol.to_string(),
+            side,
+            order_type,
+            quantity,
+            price,
+            time_in_force: None,
+            reduce_only: false,
+        })
+    }
+
+    /// 经风控校验后提交订单, 结果写入订单记录
+    pub async fn submit(&self, request: OrderRequest) -> Result<OrderOutcome, anyhow::Error> {
+        // 市价单使用最新成交价估算订单金额
+        let reference_price = match request.price {
+            Some(price) => price,
+            None => {
+                self.broker
+                    .get_ticker_24h(&request.symbol)
+                    .await?
+                    .last_price
+            }
+        };
+        self.submit_at(request, reference_price).await
+    }
+
+    /// 以给定参考价估算订单金额, 经风控校验后提交订单
+    pub async fn submit_at(
+        &self,
+        request: OrderRequest,
+        reference_price: f64,
+    ) -> Result<OrderOutcome, anyhow::Error> {
+        if self.sync_metrics {
+            let snapshot = AgentSnapshot::collect(
+                self.broker.as_ref(),
+                &self.allowed_symbols,
+                Clock::system().now(),
+            )
+            .await?;
+            sync_risk_metrics(&self.risk_manager, &snapshot).await;
+        }
+
+        let order_info = OrderInfo {
+            symbol: request.symbol.clone(),
+            side: match request.side {
+                OrderSide::Buy => "buy".to_string(),
+                OrderSide::Sell => "sell".to_string(),
+            },
+            quantity: request.quantity,
+            price: Some(reference_price),
+            order_type: match request.order_type {
+                OrderType::Limit => "limit".to_string(),
+                _ => "market".to_string(),
+            },
+            account_id: self.account_id.clone(),
+        };
+
+        let validation = self
+            .risk_manager
+            .validate_order(&order_info)
+            .await
+            .map_err(|e| anyhow::anyhow!("Risk validation failed: {}", e))?;
+        let risk_messages = validation.all_messages();
+
+        let outcome = if !validation.approved {
+            warn!(
+                "Order rejected by risk control: {} {:?}",
+                request.symbol, risk_messages
+            );
+            OrderOutcome {
+                request,
+                approved: false,
+                risk_messages,
+                response: None,
+                error: None,
+            }
+        } else {
+            match self.broker.place_order(request.clone()).await {
+                Ok(response) => {
+                    info!(
+                        "Order {} placed on {}: {} x {}",
+                        response.order_id,
+                        self.broker.broker_id(),
+                        request.symbol,
+                        request.quantity
+                    );
+                    OrderOutcome {
+                        request,
+                        approved: true,
+                        risk_messages,
+                        response: Some(response),
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    warn!("Order placement failed: {}", e);
+                    OrderOutcome {
+                        request,
+                        approved: true,
+                        risk_messages,
+                        response: None,
+                        error: Some(e.to_string()),
+                    }
+                }
+            }
+        };
+
+        self.log.lock().await.push(outcome.clone());
+        Ok(outcome)
+    }
+}
+
+/// 订单处理结果回传给 LLM 的 JSON
+pub fn outcome_json(outcome: &OrderOutcome) -> serde_json::Value {
+    serde_json::json!({
+        "status": if outcome.is_executed() {
+            "executed"
+        } else if outcome.approved {
+            "failed"
+        } else {
+            "rejected"
+        },
+        "order": outcome.request,
+        "response": outcome.response,
+        "risk_messages": outcome.risk_messages,
+        "error": outcome.error,
+    })
+}
+
+#[async_trait]
+impl ToolHandler for RiskCheckedOrderTool {
+    async fn execute(&self, input: serde_json::Value) -> Result<serde_json::Value, anyhow::Error> {
+        let request = self.parse_order(&input)?;
+        let outcome = self.submit(request).await?;
+        Ok(outcome_json(&outcome))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::MockBroker;
+    use crate::risk::RiskConfig;
+
+    fn order_tool(log: OrderLog) -> RiskCheckedOrderTool {
+        RiskCheckedOrderTool::new(
+            Arc::new(MockBroker::new()),
+            Arc::new(RiskManager::new(RiskConfig::default())),
+            "agent-1".to_string(),
+            vec!["BTCUSDT".to_string()],
+            log,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_order_tool_validates_before_execution() {
+        let log = OrderLog::default();
+        let tool = order_tool(log.clone());
+
+        // 0.1 * 50000 = 5000 USD, 在默认限额内
+        let ok = tool
+            .execute(serde_json::json!({"symbol": "BTCUSDT", "side": "buy", "quantity": 0.1}))
+            .await
+            .unwrap();
+        assert_eq!(ok["status"], "executed");
+
+        // 1 * 50000 = 50000 USD, 超过单笔上限
+        let rejected = tool
+            .execute(serde_json::json!({"symbol": "BTCUSDT", "side": "buy", "quantity": 1.0}))
+            .await
+            .unwrap();
+        assert_eq!(rejected["status"], "rejected");
+        assert!(!rejected["risk_messages"].as_array().unwrap().is_empty());
+
+        let log = log.lock().await;
+        assert_eq!(log.len(), 2);
+        assert!(log[0].is_executed());
+        assert!(!log[1].approved);
+    }
+
+    #[tokio::test]
+    async fn test_order_tool_rejects_invalid_input() {
+        let log = OrderLog::default();
+        let tool = order_tool(log.clone());
+
+        let err = tool
+            .execute(serde_json::json!({"symbol": "ETHUSDT", "side": "buy", "quantity": 1.0}))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("universe"));
+
+        assert!(tool
+            .execute(serde_json::json!({"symbol": "BTCUSDT", "side": "hold", "quantity": 1.0}))
+            .await
+            .is_err());
+        assert!(log.lock().await.is_empty());
+    }
+}
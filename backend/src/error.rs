@@ -1,11 +1,13 @@
 use axum::{
-    http::StatusCode,
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::json;
 use thiserror::Error;
 
+use crate::brokers::BrokerError;
+
 #[derive(Error, Debug)]
 pub enum AppError {
     // 暂时注释掉 sqlx，等需要时再启用
@@ -23,6 +25,9 @@ pub enum AppError {
     #[error("Market adapter error: {0}")]
     MarketAdapter(String),
 
+    #[error("Broker error: {0}")]
+    Broker(#[from] BrokerError),
+
     #[error("MCP protocol error: {0}")]
     McpProtocol(String),
 
@@ -46,6 +51,7 @@ impl IntoResponse for AppError {
             AppError::Redis(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             AppError::LlmProvider(_) => (StatusCode::BAD_GATEWAY, self.to_string()),
             AppError::MarketAdapter(_) => (StatusCode::BAD_GATEWAY, self.to_string()),
+            AppError::Broker(e) => (broker_status(e), self.to_string()),
             AppError::McpProtocol(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             AppError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             AppError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
@@ -60,6 +66,53 @@ impl IntoResponse for AppError {
             "error": message,
         }));
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if let AppError::Broker(e) = &self {
+            if let Some(retry_after) = e.retry_after() {
+                // Retry-After 以秒为单位, 不足 1 秒按 1 秒计
+                let secs = retry_after.as_secs().max(1);
+                if let Ok(value) = secs.to_string().parse() {
+                    response.headers_mut().insert(header::RETRY_AFTER, value);
+                }
+            }
+        }
+        response
+    }
+}
+
+/// 经纪商错误对应的 HTTP 状态码
+fn broker_status(e: &BrokerError) -> StatusCode {
+    match e {
+        BrokerError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        BrokerError::InsufficientFunds(_) | BrokerError::OrderRejected { .. } => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        BrokerError::InvalidSymbol(_) => StatusCode::BAD_REQUEST,
+        BrokerError::NotSupported(_) => StatusCode::NOT_IMPLEMENTED,
+        BrokerError::Network(_) => StatusCode::SERVICE_UNAVAILABLE,
+        // 交易所认证失败属于上游配置问题, 不是调用方未授权
+        BrokerError::Auth(_) | BrokerError::Exchange { .. } => StatusCode::BAD_GATEWAY,
+        BrokerError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_broker_error_response() {
+        let response = AppError::from(BrokerError::RateLimited {
+            retry_after: Some(Duration::from_millis(2500)),
+        })
+        .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+
+        let response =
+            AppError::from(BrokerError::InsufficientFunds("margin".to_string())).into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(response.headers().get(header::RETRY_AFTER).is_none());
     }
 }
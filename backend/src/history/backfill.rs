@@ -0,0 +1,280 @@
+use super::resample::find_gaps;
+use super::store::MarketDataStore;
+use crate::backtest::interval_secs;
+use crate::brokers::binance::BinanceRestClient;
+use crate::brokers::okex::OkexRestClient;
+use crate::brokers::Kline;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// 可分页拉取历史K线的行情源
+#[async_trait]
+pub trait KlineSource: Send + Sync {
+    /// 交易所标识, 作为存储中的 venue
+    fn venue(&self) -> &str;
+
+    /// 单次请求最多返回的K线数量
+    fn max_page(&self) -> i32;
+
+    /// 拉取开盘时间在 `[start, end)` 内的K线（升序, 最多 `limit` 根）
+    async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+        limit: i32,
+    ) -> Result<Vec<Kline>>;
+}
+
+#[async_trait]
+impl KlineSource for BinanceRestClient {
+    fn venue(&self) -> &str {
+        "binance"
+    }
+
+    fn max_page(&self) -> i32 {
+        1000
+    }
+
+    async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+        limit: i32,
+    ) -> Result<Vec<Kline>> {
+        Ok(self
+            .get_klines_range(symbol, interval, start, end, limit)
+            .await?
+            .klines)
+    }
+}
+
+#[async_trait]
+impl KlineSource for OkexRestClient {
+    fn venue(&self) -> &str {
+        "okex"
+    }
+
+    fn max_page(&self) -> i32 {
+        100
+    }
+
+    async fn fetch_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+        limit: i32,
+    ) -> Result<Vec<Kline>> {
+        Ok(self
+            .get_klines_range(symbol, interval, start, end, limit)
+            .await?
+            .klines)
+    }
+}
+
+/// 回补结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BackfillReport {
+    /// 回补前发现的缺口数
+    pub gaps_found: usize,
+    /// 从交易所拉取的K线数
+    pub fetched: usize,
+    /// 回补后仍然缺失的区间（交易所无数据, 如停牌或上市前）
+    pub remaining_gaps: Vec<(i64, i64)>,
+}
+
+/// 历史K线回补
+///
+/// 对比存储中已有的K线找出缺口, 按交易所单页上限逐页拉取并写入存储,
+/// 只回补已收盘的K线。重复运行是幂等的, 只会拉取仍然缺失的区间。
+pub struct Backfiller {
+    source: Arc<dyn KlineSource>,
+    store: Arc<dyn MarketDataStore>,
+    /// 两次分页请求之间的间隔, 避免触发限频
+    page_delay: Duration,
+}
+
+impl Backfiller {
+    pub fn new(source: Arc<dyn KlineSource>, store: Arc<dyn MarketDataStore>) -> Self {
+        Self {
+            source,
+            store,
+            page_delay: Duration::from_millis(100),
+        }
+    }
+
+    pub fn with_page_delay(mut self, delay: Duration) -> Self {
+        self.page_delay = delay;
+        self
+    }
+
+    /// 回补 `[start, end)` 内的K线
+    pub async fn backfill(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<BackfillReport> {
+        let bar_secs =
+            interval_secs(interval).ok_or_else(|| anyhow!("Invalid interval: {}", interval))?;
+        let venue = self.source.venue().to_string();
+
+        // 不回补尚未收盘的K线
+        let now = chrono::Utc::now().timestamp();
+        let end = end.min(now - now.rem_euclid(bar_secs));
+        let start = start - start.rem_euclid(bar_secs);
+        if start >= end {
+            return Ok(BackfillReport::default());
+        }
+
+        let existing = self
+            .store
+            .load_klines(&venue, symbol, interval, start, end)
+            .await?;
+        let gaps = find_gaps(&existing, bar_secs, start, end);
+        let mut report = BackfillReport {
+            gaps_found: gaps.len(),
+            ..Default::default()
+        };
+
+        let limit = self.source.max_page();
+        let mut pages = 0usize;
+        for (gap_start, gap_end) in gaps {
+            let mut cursor = gap_start;
+            while cursor < gap_end {
+                if pages > 0 && !self.page_delay.is_zero() {
+                    tokio::time::sleep(self.page_delay).await;
+                }
+                pages += 1;
+
+                let mut klines = self
+                    .source
+                    .fetch_klines(symbol, interval, cursor, gap_end, limit)
+                    .await?;
+                klines.retain(|k| k.timestamp >= cursor && k.timestamp < gap_end);
+                klines.sort_by_key(|k| k.timestamp);
+                let Some(last) = klines.last().map(|k| k.timestamp) else {
+                    warn!(
+                        "{} has no {} {} klines in [{}, {})",
+                        venue, symbol, interval, cursor, gap_end
+                    );
+                    break;
+                };
+
+                self.store
+                    .upsert_klines(&venue, symbol, interval, &klines)
+                    .await?;
+                report.fetched += klines.len();
+                cursor = last + bar_secs;
+            }
+        }
+
+        let stored = self
+            .store
+            .load_klines(&venue, symbol, interval, start, end)
+            .await?;
+        report.remaining_gaps = find_gaps(&stored, bar_secs, start, end);
+        info!(
+            "Backfilled {} {} {}: {} gaps, {} klines fetched, {} gaps remaining",
+            venue,
+            symbol,
+            interval,
+            report.gaps_found,
+            report.fetched,
+            report.remaining_gaps.len()
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::CsvMarketDataStore;
+    use std::sync::Mutex;
+
+    /// 模拟交易所: 只有 [0, 3000) 的 1m K线, 单页 10 根
+    struct FakeSource {
+        requests: Mutex<Vec<(i64, i64)>>,
+    }
+
+    #[async_trait]
+    impl KlineSource for FakeSource {
+        fn venue(&self) -> &str {
+            "fake"
+        }
+
+        fn max_page(&self) -> i32 {
+            10
+        }
+
+        async fn fetch_klines(
+            &self,
+            _symbol: &str,
+            _interval: &str,
+            start: i64,
+            end: i64,
+            limit: i32,
+        ) -> Result<Vec<Kline>> {
+            self.requests.lock().unwrap().push((start, end));
+            Ok((start.max(0)..end.min(3000))
+                .step_by(60)
+                .take(limit as usize)
+                .map(|t| Kline {
+                    timestamp: t,
+                    open: 1.0,
+                    high: 1.0,
+                    low: 1.0,
+                    close: 1.0,
+                    volume: 1.0,
+                    open_interest: None,
+                })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_backfill_pages_through_gaps() {
+        let root = std::env::temp_dir().join(format!("nof0-backfill-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let store = Arc::new(CsvMarketDataStore::new(&root));
+        let source = Arc::new(FakeSource {
+            requests: Mutex::new(Vec::new()),
+        });
+
+        // 预先存在 [600, 1200) 的数据
+        let cached: Vec<Kline> = source.fetch_klines("X", "1m", 600, 1200, 10).await.unwrap();
+        store
+            .upsert_klines("fake", "X", "1m", &cached)
+            .await
+            .unwrap();
+        source.requests.lock().unwrap().clear();
+
+        let backfiller =
+            Backfiller::new(source.clone(), store.clone()).with_page_delay(Duration::ZERO);
+        let report = backfiller.backfill("X", "1m", 0, 3600).await.unwrap();
+        assert_eq!(report.gaps_found, 2);
+        assert_eq!(report.fetched, 40);
+        assert_eq!(report.remaining_gaps, vec![(3000, 3600)]);
+        // [0,600) 一页, [1200,3600) 三页后遇到空页
+        assert_eq!(source.requests.lock().unwrap().len(), 5);
+
+        // 再次运行只请求仍然缺失的区间
+        source.requests.lock().unwrap().clear();
+        let report = backfiller.backfill("X", "1m", 0, 3600).await.unwrap();
+        assert_eq!(report.fetched, 0);
+        assert_eq!(*source.requests.lock().unwrap(), vec![(3000, 3600)]);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,340 @@
+use super::resample::resample;
+use super::store::MarketDataStore;
+use crate::backtest::interval_secs;
+use crate::brokers::*;
+use crate::engine::Clock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+/// 带本地K线缓存的经纪商包装
+///
+/// `get_klines` 先从存储读取最近 `limit` 根已收盘K线, 不完整时尝试由缓存的 1m K线
+/// 重采样, 仍不足才请求交易所, 并把返回的已收盘K线写回存储。其余接口直接转发。
+/// 缓存只包含已收盘的K线, 命中时不返回正在形成的当前K线。
+pub struct CachedBroker {
+    inner: Arc<dyn DynBroker>,
+    store: Arc<dyn MarketDataStore>,
+    venue: String,
+    clock: Clock,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl CachedBroker {
+    /// `venue` 为存储中的交易所标识, 与回补时使用的一致
+    pub fn new(
+        inner: Arc<dyn DynBroker>,
+        store: Arc<dyn MarketDataStore>,
+        venue: impl Into<String>,
+    ) -> Self {
+        Self {
+            inner,
+            store,
+            venue: venue.into(),
+            clock: Clock::system(),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// 缓存命中次数
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    /// 缓存未命中次数
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::SeqCst)
+    }
+
+    /// 从缓存读取最近 `count` 根已收盘K线, 不完整时返回 None
+    async fn cached_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        bar_secs: i64,
+        count: usize,
+    ) -> Option<Vec<Kline>> {
+        let now = self.clock.now();
+        let end = now - now.rem_euclid(bar_secs);
+        let start = end - bar_secs * count as i64;
+
+        match self
+            .store
+            .load_klines(&self.venue, symbol, interval, start, end)
+            .await
+        {
+            Ok(klines) if klines.len() >= count => return Some(klines),
+            Ok(_) => {}
+            Err(e) => {
+                warn!("Failed to read kline cache for {}: {}", symbol, e);
+                return None;
+            }
+        }
+
+        // 由 1m K线重采样
+        if interval == "1m" || bar_secs % 60 != 0 {
+            return None;
+        }
+        let minutes = self
+            .store
+            .load_klines(&self.venue, symbol, "1m", start, end)
+            .await
+            .ok()?;
+        let klines = resample(&minutes, 60, bar_secs).ok()?;
+        (klines.len() >= count).then_some(klines)
+    }
+
+    async fn klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: Option<i32>,
+    ) -> BrokerResult<Klines> {
+        let Some(bar_secs) = interval_secs(interval) else {
+            return self.inner.get_klines(symbol, interval, limit).await;
+        };
+
+        let count = limit.unwrap_or(100).max(1) as usize;
+        if let Some(klines) = self.cached_klines(symbol, interval, bar_secs, count).await {
+            debug!("Kline cache hit: {} {} {}", self.venue, symbol, interval);
+            self.hits.fetch_add(1, Ordering::SeqCst);
+            return Ok(Klines {
+                symbol: symbol.to_string(),
+                interval: interval.to_string(),
+                klines,
+            });
+        }
+
+        self.misses.fetch_add(1, Ordering::SeqCst);
+        let klines = self.inner.get_klines(symbol, interval, limit).await?;
+        let now = self.clock.now();
+        let closed: Vec<Kline> = klines
+            .klines
+            .iter()
+            .filter(|k| k.timestamp + bar_secs <= now)
+            .cloned()
+            .collect();
+        if let Err(e) = self
+            .store
+            .upsert_klines(&self.venue, symbol, interval, &closed)
+            .await
+        {
+            warn!("Failed to write kline cache for {}: {}", symbol, e);
+        }
+        Ok(klines)
+    }
+}
+
+impl Broker for CachedBroker {
+    fn broker_id(&self) -> &str {
+        self.inner.broker_id()
+    }
+
+    fn broker_name(&self) -> &str {
+        self.inner.broker_name()
+    }
+}
+
+impl MarketData for CachedBroker {
+    fn get_prices(&self) -> impl std::future::Future<Output = Result<Prices, BrokerError>> + Send {
+        self.inner.get_prices()
+    }
+
+    fn get_orderbook(
+        &self,
+        symbol: &str,
+    ) -> impl std::future::Future<Output = Result<Orderbook, BrokerError>> + Send {
+        let symbol = symbol.to_string();
+        async move { self.inner.get_orderbook(&symbol).await }
+    }
+
+    fn get_klines(
+        &self,
+        symbol: &str,
+        interval: &str,
+        limit: Option<i32>,
+    ) -> impl std::future::Future<Output = Result<Klines, BrokerError>> + Send {
+        let (symbol, interval) = (symbol.to_string(), interval.to_string());
+        async move { self.klines(&symbol, &interval, limit).await }
+    }
+
+    fn get_ticker_24h(
+        &self,
+        symbol: &str,
+    ) -> impl std::future::Future<Output = Result<Ticker24h, BrokerError>> + Send {
+        let symbol = symbol.to_string();
+        async move { self.inner.get_ticker_24h(&symbol).await }
+    }
+}
+
+impl Trading for CachedBroker {
+    fn place_order(
+        &self,
+        order: OrderRequest,
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send {
+        self.inner.place_order(order)
+    }
+
+    fn cancel_order(
+        &self,
+        order_id: &str,
+    ) -> impl std::future::Future<Output = Result<OrderResponse, BrokerError>> + Send {
+        let order_id = order_id.to_string();
+        async move { self.inner.cancel_order(&order_id).await }
+    }
+
+    fn get_order(
+        &self,
+        order_id: &str,
+    ) -> impl std::future::Future<Output = Result<Order, BrokerError>> + Send {
+        let order_id = order_id.to_string();
+        async move { self.inner.get_order(&order_id).await }
+    }
+
+    fn get_orders(
+        &self,
+        symbol: Option<&str>,
+    ) -> impl std::future::Future<Output = Result<Orders, BrokerError>> + Send {
+        let symbol = symbol.map(str::to_string);
+        async move { self.inner.get_orders(symbol.as_deref()).await }
+    }
+
+    fn get_trades(&self) -> impl std::future::Future<Output = Result<Trades, BrokerError>> + Send {
+        self.inner.get_trades()
+    }
+
+    fn set_leverage(
+        &self,
+        symbol: &str,
+        leverage: f64,
+    ) -> impl std::future::Future<Output = Result<(), BrokerError>> + Send {
+        let symbol = symbol.to_string();
+        async move { self.inner.set_leverage(&symbol, leverage).await }
+    }
+}
+
+impl AccountManagement for CachedBroker {
+    fn get_account_totals(
+        &self,
+        last_marker: Option<i32>,
+    ) -> impl std::future::Future<Output = Result<AccountTotals, BrokerError>> + Send {
+        self.inner.get_account_totals(last_marker)
+    }
+
+    fn get_model_accounts(
+        &self,
+    ) -> impl std::future::Future<Output = Result<ModelAccounts, BrokerError>> + Send {
+        self.inner.get_model_accounts()
+    }
+
+    fn get_positions(
+        &self,
+        limit: Option<i32>,
+    ) -> impl std::future::Future<Output = Result<Positions, BrokerError>> + Send {
+        self.inner.get_positions(limit)
+    }
+
+    fn get_balance(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Balance, BrokerError>> + Send {
+        self.inner.get_balance()
+    }
+
+    fn get_broker_account(
+        &self,
+    ) -> impl std::future::Future<Output = Result<BrokerAccount, BrokerError>> + Send {
+        self.inner.get_broker_account()
+    }
+}
+
+impl Analytics for CachedBroker {
+    fn get_analytics(
+        &self,
+    ) -> impl std::future::Future<Output = Result<AnalyticsData, BrokerError>> + Send {
+        self.inner.get_analytics()
+    }
+
+    fn get_leaderboard(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Leaderboard, BrokerError>> + Send {
+        self.inner.get_leaderboard()
+    }
+
+    fn get_since_inception_values(
+        &self,
+    ) -> impl std::future::Future<Output = Result<SinceInceptionValues, BrokerError>> + Send {
+        self.inner.get_since_inception_values()
+    }
+
+    fn get_conversations(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Conversations, BrokerError>> + Send {
+        self.inner.get_conversations()
+    }
+
+    fn get_models_list(
+        &self,
+    ) -> impl std::future::Future<Output = Result<Models, BrokerError>> + Send {
+        self.inner.get_models_list()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::{HistoricalData, HistoricalFeed};
+    use crate::history::CsvMarketDataStore;
+
+    #[tokio::test]
+    async fn test_klines_served_from_cache_and_resampled() {
+        let minutes: Vec<Kline> = (0..20)
+            .map(|i| Kline {
+                timestamp: i * 60,
+                open: 100.0 + i as f64,
+                high: 101.0 + i as f64,
+                low: 99.0 + i as f64,
+                close: 100.5 + i as f64,
+                volume: 1.0,
+                open_interest: None,
+            })
+            .collect();
+        let data = HistoricalData::new("1m").with_klines("BTCUSDT", minutes);
+        let clock = Clock::simulated(1200);
+        let feed: Arc<dyn DynBroker> =
+            Arc::new(HistoricalFeed::new(Arc::new(data), clock.clone()).unwrap());
+
+        let root = std::env::temp_dir().join(format!("nof0-kline-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let broker = CachedBroker::new(feed, Arc::new(CsvMarketDataStore::new(&root)), "hist")
+            .with_clock(clock);
+
+        let first = MarketData::get_klines(&broker, "BTCUSDT", "1m", Some(20))
+            .await
+            .unwrap();
+        let second = MarketData::get_klines(&broker, "BTCUSDT", "1m", Some(10))
+            .await
+            .unwrap();
+        assert_eq!((broker.hits(), broker.misses()), (1, 1));
+        assert_eq!(second.klines.len(), 10);
+        assert_eq!(second.klines[9].close, first.klines[19].close);
+
+        // 行情源不支持 5m, 由缓存的 1m 重采样
+        let five = MarketData::get_klines(&broker, "BTCUSDT", "5m", Some(4))
+            .await
+            .unwrap();
+        assert_eq!(broker.hits(), 2);
+        assert_eq!(five.klines.len(), 4);
+        assert_eq!(five.klines[3].timestamp, 900);
+        assert_eq!(five.klines[3].open, 115.0);
+        assert_eq!(five.klines[3].close, 119.5);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
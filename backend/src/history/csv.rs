@@ -0,0 +1,345 @@
+use super::store::MarketDataStore;
+use crate::brokers::stream::MarketTrade;
+use crate::brokers::{Kline, OrderSide};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::Mutex;
+
+const KLINE_HEADER: &str = "open_time,open,high,low,close,volume,open_interest";
+const TRADE_HEADER: &str = "timestamp,trade_id,price,quantity,side";
+
+/// 本地 CSV 行情缓存
+///
+/// 目录结构为 `<root>/<venue>/<symbol>/<interval>.csv` 与
+/// `<root>/<venue>/<symbol>/trades.csv`, 写入时按时间合并去重后整体重写。
+pub struct CsvMarketDataStore {
+    root: PathBuf,
+    /// 串行化读-合并-写, 避免并发写入丢数据
+    write_lock: Mutex<()>,
+}
+
+impl CsvMarketDataStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn kline_path(&self, venue: &str, symbol: &str, interval: &str) -> PathBuf {
+        self.root
+            .join(venue)
+            .join(symbol)
+            .join(format!("{}.csv", interval))
+    }
+
+    fn trade_path(&self, venue: &str, symbol: &str) -> PathBuf {
+        self.root.join(venue).join(symbol).join("trades.csv")
+    }
+}
+
+async fn read_lines(path: &Path) -> Result<Vec<String>> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(content) => Ok(content
+            .lines()
+            .skip(1)
+            .filter(|line| !line.trim().is_empty())
+            .map(str::to_string)
+            .collect()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+async fn write_lines(path: &Path, header: &str, lines: impl Iterator<Item = String>) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        tokio::fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let mut content = String::from(header);
+    content.push('\n');
+    for line in lines {
+        content.push_str(&line);
+        content.push('\n');
+    }
+
+    // 先写临时文件再改名, 中断时不会留下半个文件
+    let tmp = path.with_extension("csv.tmp");
+    tokio::fs::write(&tmp, content)
+        .await
+        .with_context(|| format!("Failed to write {}", tmp.display()))?;
+    tokio::fs::rename(&tmp, path)
+        .await
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+async fn read_klines(path: &Path) -> Result<BTreeMap<i64, Kline>> {
+    read_lines(path)
+        .await?
+        .iter()
+        .map(|line| {
+            let kline = parse_kline(line)
+                .ok_or_else(|| anyhow!("Invalid kline row in {}: {}", path.display(), line))?;
+            Ok((kline.timestamp, kline))
+        })
+        .collect()
+}
+
+fn parse_kline(line: &str) -> Option<Kline> {
+    let fields: Vec<&str> = line.split(',').collect();
+    if fields.len() != 7 {
+        return None;
+    }
+    Some(Kline {
+        timestamp: fields[0].parse().ok()?,
+        open: fields[1].parse().ok()?,
+        high: fields[2].parse().ok()?,
+        low: fields[3].parse().ok()?,
+        close: fields[4].parse().ok()?,
+        volume: fields[5].parse().ok()?,
+        open_interest: match fields[6] {
+            "" => None,
+            oi => Some(oi.parse().ok()?),
+        },
+    })
+}
+
+fn format_kline(k: &Kline) -> String {
+    format!(
+        "{},{},{},{},{},{},{}",
+        k.timestamp,
+        k.open,
+        k.high,
+        k.low,
+        k.close,
+        k.volume,
+        k.open_interest.map(|oi| oi.to_string()).unwrap_or_default()
+    )
+}
+
+fn parse_trade(symbol: &str, line: &str) -> Option<MarketTrade> {
+    let fields: Vec<&str> = line.split(',').collect();
+    if fields.len() != 5 {
+        return None;
+    }
+    Some(MarketTrade {
+        symbol: symbol.to_string(),
+        timestamp: fields[0].parse().ok()?,
+        trade_id: fields[1].to_string(),
+        price: fields[2].parse().ok()?,
+        quantity: fields[3].parse().ok()?,
+        side: match fields[4] {
+            "buy" => OrderSide::Buy,
+            "sell" => OrderSide::Sell,
+            _ => return None,
+        },
+    })
+}
+
+fn format_trade(t: &MarketTrade) -> String {
+    format!(
+        "{},{},{},{},{}",
+        t.timestamp,
+        t.trade_id.replace(',', "_"),
+        t.price,
+        t.quantity,
+        match t.side {
+            OrderSide::Buy => "buy",
+            OrderSide::Sell => "sell",
+        }
+    )
+}
+
+async fn read_trades(path: &Path, symbol: &str) -> Result<Vec<MarketTrade>> {
+    read_lines(path)
+        .await?
+        .iter()
+        .map(|line| {
+            parse_trade(symbol, line)
+                .ok_or_else(|| anyhow!("Invalid trade row in {}: {}", path.display(), line))
+        })
+        .collect()
+}
+
+#[async_trait]
+impl MarketDataStore for CsvMarketDataStore {
+    async fn upsert_klines(
+        &self,
+        venue: &str,
+        symbol: &str,
+        interval: &str,
+        klines: &[Kline],
+    ) -> Result<usize> {
+        if klines.is_empty() {
+            return Ok(0);
+        }
+        let _guard = self.write_lock.lock().await;
+        let path = self.kline_path(venue, symbol, interval);
+        let mut merged = read_klines(&path).await?;
+        for kline in klines {
+            merged.insert(kline.timestamp, kline.clone());
+        }
+        write_lines(&path, KLINE_HEADER, merged.values().map(format_kline)).await?;
+        Ok(klines.len())
+    }
+
+    async fn load_klines(
+        &self,
+        venue: &str,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Kline>> {
+        let klines = read_klines(&self.kline_path(venue, symbol, interval)).await?;
+        Ok(klines
+            .range(start..end.max(start))
+            .map(|(_, k)| k.clone())
+            .collect())
+    }
+
+    async fn latest_kline_time(
+        &self,
+        venue: &str,
+        symbol: &str,
+        interval: &str,
+    ) -> Result<Option<i64>> {
+        let klines = read_klines(&self.kline_path(venue, symbol, interval)).await?;
+        Ok(klines.keys().next_back().copied())
+    }
+
+    async fn upsert_trades(&self, venue: &str, trades: &[MarketTrade]) -> Result<usize> {
+        let _guard = self.write_lock.lock().await;
+        let mut by_symbol: BTreeMap<&str, Vec<&MarketTrade>> = BTreeMap::new();
+        for trade in trades {
+            by_symbol.entry(&trade.symbol).or_default().push(trade);
+        }
+
+        for (symbol, new_trades) in by_symbol {
+            let path = self.trade_path(venue, symbol);
+            let mut merged: BTreeMap<(i64, String), MarketTrade> = read_trades(&path, symbol)
+                .await?
+                .into_iter()
+                .map(|t| ((t.timestamp, t.trade_id.clone()), t))
+                .collect();
+            // 同一 trade_id 只保留一条
+            merged.retain(|(_, id), _| !new_trades.iter().any(|t| &t.trade_id == id));
+            for trade in new_trades {
+                merged.insert((trade.timestamp, trade.trade_id.clone()), trade.clone());
+            }
+            write_lines(&path, TRADE_HEADER, merged.values().map(format_trade)).await?;
+        }
+        Ok(trades.len())
+    }
+
+    async fn load_trades(
+        &self,
+        venue: &str,
+        symbol: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<MarketTrade>> {
+        let trades = read_trades(&self.trade_path(venue, symbol), symbol).await?;
+        Ok(trades
+            .into_iter()
+            .filter(|t| (start..end).contains(&t.timestamp))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(timestamp: i64, close: f64) -> Kline {
+        Kline {
+            timestamp,
+            open: close,
+            high: close + 1.0,
+            low: close - 1.0,
+            close,
+            volume: 2.5,
+            open_interest: (timestamp == 0).then_some(42),
+        }
+    }
+
+    fn trade(id: &str, timestamp: i64, side: OrderSide) -> MarketTrade {
+        MarketTrade {
+            symbol: "BTCUSDT".to_string(),
+            trade_id: id.to_string(),
+            price: 100.5,
+            quantity: 0.1,
+            side,
+            timestamp,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_csv_store_merges_and_reloads() {
+        let root = std::env::temp_dir().join(format!("nof0-csv-store-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let store = CsvMarketDataStore::new(&root);
+
+        store
+            .upsert_klines("binance", "BTCUSDT", "1m", &[bar(60, 2.0), bar(0, 1.0)])
+            .await
+            .unwrap();
+        // 重叠写入覆盖旧值
+        store
+            .upsert_klines("binance", "BTCUSDT", "1m", &[bar(60, 3.0), bar(120, 4.0)])
+            .await
+            .unwrap();
+
+        let klines = store
+            .load_klines("binance", "BTCUSDT", "1m", 0, 180)
+            .await
+            .unwrap();
+        let closes: Vec<f64> = klines.iter().map(|k| k.close).collect();
+        assert_eq!(closes, vec![1.0, 3.0, 4.0]);
+        assert_eq!(klines[0].open_interest, Some(42));
+        assert_eq!(klines[1].open_interest, None);
+        assert_eq!(
+            store
+                .latest_kline_time("binance", "BTCUSDT", "1m")
+                .await
+                .unwrap(),
+            Some(120)
+        );
+        assert!(store
+            .load_klines("okex", "BTCUSDT", "1m", 0, 180)
+            .await
+            .unwrap()
+            .is_empty());
+
+        store
+            .upsert_trades(
+                "binance",
+                &[
+                    trade("2", 20, OrderSide::Sell),
+                    trade("1", 10, OrderSide::Buy),
+                ],
+            )
+            .await
+            .unwrap();
+        store
+            .upsert_trades("binance", &[trade("2", 20, OrderSide::Sell)])
+            .await
+            .unwrap();
+        let trades = store
+            .load_trades("binance", "BTCUSDT", 0, 30)
+            .await
+            .unwrap();
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].trade_id, "1");
+        assert!(matches!(trades[1].side, OrderSide::Sell));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
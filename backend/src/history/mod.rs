@@ -0,0 +1,15 @@
+// Historical market data store
+
+pub mod backfill;
+pub mod cached;
+pub mod csv;
+pub mod postgres;
+pub mod resample;
+pub mod store;
+
+pub use backfill::*;
+pub use cached::CachedBroker;
+pub use csv::CsvMarketDataStore;
+pub use postgres::PgMarketDataStore;
+pub use resample::*;
+pub use store::*;
@@ -0,0 +1,129 @@
+use crate::brokers::Kline;
+use anyhow::{anyhow, Result};
+
+/// 将K线从 `from_secs` 周期聚合为 `to_secs` 周期
+///
+/// 目标周期必须是源周期的整数倍, 按 `to_secs` 对齐分桶（日线按 UTC 零点）,
+/// 不完整的桶（缺少源K线）会被丢弃, 避免生成错误的高低点。
+pub fn resample(klines: &[Kline], from_secs: i64, to_secs: i64) -> Result<Vec<Kline>> {
+    if from_secs <= 0 || to_secs < from_secs || to_secs % from_secs != 0 {
+        return Err(anyhow!(
+            "Cannot resample {}s klines into {}s",
+            from_secs,
+            to_secs
+        ));
+    }
+    let per_bucket = (to_secs / from_secs) as usize;
+
+    let mut sorted: Vec<&Kline> = klines.iter().collect();
+    sorted.sort_by_key(|k| k.timestamp);
+    sorted.dedup_by_key(|k| k.timestamp);
+
+    let mut result = Vec::new();
+    let mut bucket: Vec<&Kline> = Vec::with_capacity(per_bucket);
+    let mut bucket_start = None;
+    for kline in sorted {
+        let start = kline.timestamp - kline.timestamp.rem_euclid(to_secs);
+        if bucket_start != Some(start) {
+            if bucket.len() == per_bucket {
+                result.push(merge(bucket_start.unwrap_or_default(), &bucket));
+            }
+            bucket.clear();
+            bucket_start = Some(start);
+        }
+        bucket.push(kline);
+    }
+    if bucket.len() == per_bucket {
+        result.push(merge(bucket_start.unwrap_or_default(), &bucket));
+    }
+    Ok(result)
+}
+
+fn merge(timestamp: i64, bars: &[&Kline]) -> Kline {
+    Kline {
+        timestamp,
+        open: bars[0].open,
+        high: bars.iter().map(|k| k.high).fold(f64::MIN, f64::max),
+        low: bars.iter().map(|k| k.low).fold(f64::MAX, f64::min),
+        close: bars[bars.len() - 1].close,
+        volume: bars.iter().map(|k| k.volume).sum(),
+        open_interest: bars[bars.len() - 1].open_interest,
+    }
+}
+
+/// 查找 `[start, end)` 内缺失的K线区间
+///
+/// 返回缺口的 `[gap_start, gap_end)` 列表, 边界按 `bar_secs` 对齐。
+pub fn find_gaps(klines: &[Kline], bar_secs: i64, start: i64, end: i64) -> Vec<(i64, i64)> {
+    let mut gaps = Vec::new();
+    if bar_secs <= 0 {
+        return gaps;
+    }
+
+    let mut expected = start + (bar_secs - start.rem_euclid(bar_secs)) % bar_secs;
+    let mut times: Vec<i64> = klines
+        .iter()
+        .map(|k| k.timestamp)
+        .filter(|t| (start..end).contains(t))
+        .collect();
+    times.sort_unstable();
+    times.dedup();
+
+    for time in times {
+        if time > expected {
+            gaps.push((expected, time));
+        }
+        expected = expected.max(time + bar_secs);
+    }
+    if expected < end {
+        gaps.push((expected, end));
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(timestamp: i64, close: f64) -> Kline {
+        Kline {
+            timestamp,
+            open: close - 0.5,
+            high: close + 1.0,
+            low: close - 1.0,
+            close,
+            volume: 1.0,
+            open_interest: None,
+        }
+    }
+
+    #[test]
+    fn test_resample_drops_incomplete_buckets() {
+        // 0..300 完整, 300..600 缺一根
+        let mut klines: Vec<Kline> = (0..5).map(|i| bar(i * 60, 100.0 + i as f64)).collect();
+        klines.extend((6..10).map(|i| bar(i * 60, 100.0 + i as f64)));
+
+        let bars = resample(&klines, 60, 300).unwrap();
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].timestamp, 0);
+        assert_eq!(bars[0].open, 99.5);
+        assert_eq!(bars[0].close, 104.0);
+        assert_eq!(bars[0].high, 105.0);
+        assert_eq!(bars[0].low, 99.0);
+        assert_eq!(bars[0].volume, 5.0);
+
+        assert!(resample(&klines, 60, 90).is_err());
+        assert!(resample(&klines, 300, 60).is_err());
+    }
+
+    #[test]
+    fn test_find_gaps() {
+        let klines = vec![bar(60, 1.0), bar(120, 1.0), bar(300, 1.0)];
+        assert_eq!(
+            find_gaps(&klines, 60, 0, 480),
+            vec![(0, 60), (180, 300), (360, 480)]
+        );
+        assert_eq!(find_gaps(&klines, 60, 60, 180), vec![]);
+        assert_eq!(find_gaps(&[], 60, 30, 200), vec![(60, 200)]);
+    }
+}
@@ -0,0 +1,50 @@
+use crate::brokers::stream::MarketTrade;
+use crate::brokers::Kline;
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// 历史行情存储
+///
+/// K线以 (venue, symbol, interval, 开盘时间) 唯一, 逐笔成交以 (venue, symbol, trade_id) 唯一,
+/// 重复写入覆盖旧值。时间均为秒级时间戳, 查询区间为 `[start, end)`。
+#[async_trait]
+pub trait MarketDataStore: Send + Sync {
+    /// 写入K线, 返回写入条数
+    async fn upsert_klines(
+        &self,
+        venue: &str,
+        symbol: &str,
+        interval: &str,
+        klines: &[Kline],
+    ) -> Result<usize>;
+
+    /// 读取开盘时间在 `[start, end)` 内的K线（按时间升序）
+    async fn load_klines(
+        &self,
+        venue: &str,
+        symbol: &str,
+        interval: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<Kline>>;
+
+    /// 最新一根K线的开盘时间
+    async fn latest_kline_time(
+        &self,
+        venue: &str,
+        symbol: &str,
+        interval: &str,
+    ) -> Result<Option<i64>>;
+
+    /// 写入逐笔成交, 返回写入条数
+    async fn upsert_trades(&self, venue: &str, trades: &[MarketTrade]) -> Result<usize>;
+
+    /// 读取 `[start, end)` 内的逐笔成交（按时间升序）
+    async fn load_trades(
+        &self,
+        venue: &str,
+        symbol: &str,
+        start: i64,
+        end: i64,
+    ) -> Result<Vec<MarketTrade>>;
+}
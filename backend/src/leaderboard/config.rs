@@ -0,0 +1,90 @@
+use super::fx::FxRates;
+use super::ranking::RankingPolicy;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// 排行榜配置 (config/leaderboard.yaml)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardConfig {
+    #[serde(default)]
+    pub ranking: RankingPolicy,
+    #[serde(default)]
+    pub fx: FxRates,
+    /// 快照间隔 (秒), 同一天内的快照覆盖当天记录
+    #[serde(default = "default_snapshot_interval_secs")]
+    pub snapshot_interval_secs: u64,
+}
+
+fn default_snapshot_interval_secs() -> u64 {
+    3600
+}
+
+impl Default for LeaderboardConfig {
+    fn default() -> Self {
+        Self {
+            ranking: RankingPolicy::default(),
+            fx: FxRates::default(),
+            snapshot_interval_secs: default_snapshot_interval_secs(),
+        }
+    }
+}
+
+impl LeaderboardConfig {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::from_yaml_str(&content)
+    }
+
+    /// 从 YAML 字符串加载并校验
+    pub fn from_yaml_str(yaml: &str) -> Result<Self> {
+        let config: LeaderboardConfig = serde_yaml::from_str(yaml)?;
+        if config.snapshot_interval_secs == 0 {
+            bail!("snapshot_interval_secs must be positive");
+        }
+        if let Some((currency, rate)) = config.fx.rates.iter().find(|(_, r)| **r <= 0.0) {
+            bail!("Invalid FX rate for {}: {}", currency, rate);
+        }
+        Ok(config)
+    }
+
+    /// 从默认路径加载配置, 找不到文件时使用默认配置
+    pub fn load_default() -> Result<Self> {
+        let possible_paths = [
+            "config/leaderboard.yaml",
+            "backend/config/leaderboard.yaml",
+            "../config/leaderboard.yaml",
+        ];
+
+        for path in possible_paths {
+            if Path::new(path).exists() {
+                return Self::from_file(path);
+            }
+        }
+
+        tracing::warn!("No leaderboard config file found, using defaults");
+        Ok(Self::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leaderboard::RankingMetric;
+
+    #[test]
+    fn test_bundled_config_parses() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/leaderboard.yaml");
+        let config = LeaderboardConfig::from_file(path).unwrap();
+        assert_eq!(config.ranking.metric, RankingMetric::Return);
+        assert_eq!(config.fx.convert(100.0, "usdt").unwrap(), 100.0);
+        assert!(config.fx.rate("CNY").unwrap() < 1.0);
+        assert!(config.fx.rate("JPY").is_err());
+
+        assert!(LeaderboardConfig::from_yaml_str(
+            "fx:\n  reporting_currency: USD\n  rates:\n    CNY: 0\n"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,64 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 报告币种折算汇率
+///
+/// `rates` 为 1 单位外币折合的报告币种数量, 例如报告币种为 USD 时 `CNY: 0.14`,
+/// 稳定币 (USDT/USDC) 需显式配置为 1.0。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FxRates {
+    pub reporting_currency: String,
+    #[serde(default)]
+    pub rates: HashMap<String, f64>,
+}
+
+impl Default for FxRates {
+    fn default() -> Self {
+        Self {
+            reporting_currency: "USD".to_string(),
+            rates: HashMap::from([
+                ("USDT".to_string(), 1.0),
+                ("USDC".to_string(), 1.0),
+                ("CNY".to_string(), 0.14),
+            ]),
+        }
+    }
+}
+
+impl FxRates {
+    pub fn new(reporting_currency: impl Into<String>) -> Self {
+        Self {
+            reporting_currency: reporting_currency.into(),
+            rates: HashMap::new(),
+        }
+    }
+
+    pub fn with_rate(mut self, currency: impl Into<String>, rate: f64) -> Self {
+        self.rates.insert(currency.into(), rate);
+        self
+    }
+
+    /// 1 单位 `currency` 折合的报告币种数量 (币种不区分大小写)
+    pub fn rate(&self, currency: &str) -> Result<f64> {
+        if currency.eq_ignore_ascii_case(&self.reporting_currency) {
+            return Ok(1.0);
+        }
+        self.rates
+            .iter()
+            .find(|(c, _)| c.eq_ignore_ascii_case(currency))
+            .map(|(_, rate)| *rate)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No FX rate from {} to {}",
+                    currency,
+                    self.reporting_currency
+                )
+            })
+    }
+
+    /// 折算为报告币种
+    pub fn convert(&self, amount: f64, currency: &str) -> Result<f64> {
+        Ok(amount * self.rate(currency)?)
+    }
+}
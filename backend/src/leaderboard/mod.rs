@@ -0,0 +1,13 @@
+// Cross-broker leaderboard: ranking policies, currency normalization and daily snapshots
+
+pub mod config;
+pub mod fx;
+pub mod ranking;
+pub mod service;
+pub mod store;
+
+pub use config::LeaderboardConfig;
+pub use fx::FxRates;
+pub use ranking::*;
+pub use service::LeaderboardService;
+pub use store::*;
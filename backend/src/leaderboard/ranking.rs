@@ -0,0 +1,129 @@
+use crate::brokers::LeaderboardEntry;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// 排名指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankingMetric {
+    /// 收益率
+    Return,
+    /// 折算后的权益
+    Equity,
+    /// 夏普比率
+    Sharpe,
+    /// 风险调整收益: 收益率 / 最大回撤 (回撤低于 1% 按 1% 计)
+    RiskAdjusted,
+    /// 胜率
+    WinRate,
+    /// 交易次数
+    Trades,
+}
+
+impl RankingMetric {
+    /// 指标值, 越大排名越靠前
+    pub fn value(&self, entry: &LeaderboardEntry) -> f64 {
+        match self {
+            RankingMetric::Return => entry.return_pct,
+            RankingMetric::Equity => entry.equity,
+            RankingMetric::Sharpe => entry.sharpe_ratio,
+            RankingMetric::RiskAdjusted => entry.return_pct / entry.max_drawdown_pct.max(1.0),
+            RankingMetric::WinRate => entry.win_rate,
+            RankingMetric::Trades => entry.total_trades as f64,
+        }
+    }
+}
+
+/// 排名规则: 按主指标降序, 相同时依次比较 `tie_breakers`, 仍相同时按模型 ID 升序
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankingPolicy {
+    pub metric: RankingMetric,
+    #[serde(default)]
+    pub tie_breakers: Vec<RankingMetric>,
+}
+
+impl Default for RankingPolicy {
+    fn default() -> Self {
+        Self {
+            metric: RankingMetric::Return,
+            tie_breakers: vec![RankingMetric::Sharpe, RankingMetric::Equity],
+        }
+    }
+}
+
+impl RankingPolicy {
+    pub fn new(metric: RankingMetric) -> Self {
+        Self {
+            metric,
+            tie_breakers: Vec::new(),
+        }
+    }
+
+    pub fn with_tie_breakers(mut self, tie_breakers: Vec<RankingMetric>) -> Self {
+        self.tie_breakers = tie_breakers;
+        self
+    }
+
+    fn compare(&self, a: &LeaderboardEntry, b: &LeaderboardEntry) -> Ordering {
+        std::iter::once(&self.metric)
+            .chain(&self.tie_breakers)
+            .map(|metric| metric.value(b).total_cmp(&metric.value(a)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.model_id.cmp(&b.model_id))
+    }
+
+    /// 排序并写入名次, 主指标与所有次级指标都相同的条目名次并列
+    pub fn rank(&self, entries: &mut [LeaderboardEntry]) {
+        entries.sort_by(|a, b| self.compare(a, b));
+        for i in 0..entries.len() {
+            let tied = i > 0
+                && std::iter::once(&self.metric)
+                    .chain(&self.tie_breakers)
+                    .all(|m| m.value(&entries[i]) == m.value(&entries[i - 1]));
+            entries[i].rank = if tied {
+                entries[i - 1].rank
+            } else {
+                i as i32 + 1
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: &str, return_pct: f64, sharpe: f64, drawdown: f64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            model_id: id.to_string(),
+            return_pct,
+            sharpe_ratio: sharpe,
+            max_drawdown_pct: drawdown,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_policies_and_tie_breaking() {
+        let mut entries = vec![
+            entry("a", 10.0, 1.0, 20.0),
+            entry("b", 10.0, 2.0, 5.0),
+            entry("c", 6.0, 3.0, 0.5),
+            entry("d", 6.0, 3.0, 0.5),
+        ];
+
+        RankingPolicy::new(RankingMetric::Return)
+            .with_tie_breakers(vec![RankingMetric::Sharpe])
+            .rank(&mut entries);
+        let order: Vec<(&str, i32)> = entries
+            .iter()
+            .map(|e| (e.model_id.as_str(), e.rank))
+            .collect();
+        assert_eq!(order, vec![("b", 1), ("a", 2), ("c", 3), ("d", 3)]);
+
+        // 10/20 = 0.5, 10/5 = 2, 6/1 = 6
+        RankingPolicy::new(RankingMetric::RiskAdjusted).rank(&mut entries);
+        let ids: Vec<&str> = entries.iter().map(|e| e.model_id.as_str()).collect();
+        assert_eq!(ids, vec!["c", "d", "b", "a"]);
+    }
+}
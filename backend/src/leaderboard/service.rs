@@ -0,0 +1,272 @@
+use super::config::LeaderboardConfig;
+use super::store::{LeaderboardSnapshot, LeaderboardStore, RankHistoryPoint};
+use crate::brokers::{Leaderboard, LeaderboardEntry};
+use crate::engine::{Clock, EquityRecorder, TradingEngine};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, NaiveDate};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// 跨经纪商排行榜
+///
+/// 以权益记录器的最新快照与绩效分析为数据源, 按经纪商账户币种将权益折算为报告币种后
+/// 按配置的排名规则排序, 并按天保存快照用于绘制名次历史。
+pub struct LeaderboardService {
+    engine: Arc<TradingEngine>,
+    recorder: Arc<EquityRecorder>,
+    store: Arc<dyn LeaderboardStore>,
+    config: LeaderboardConfig,
+    clock: Clock,
+}
+
+impl LeaderboardService {
+    pub fn new(
+        engine: Arc<TradingEngine>,
+        recorder: Arc<EquityRecorder>,
+        store: Arc<dyn LeaderboardStore>,
+        config: LeaderboardConfig,
+    ) -> Self {
+        Self {
+            engine,
+            recorder,
+            store,
+            config,
+            clock: Clock::system(),
+        }
+    }
+
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn config(&self) -> &LeaderboardConfig {
+        &self.config
+    }
+
+    /// 按快照间隔保存当天排行榜, 直到 `shutdown` 被取消
+    pub async fn run(self: Arc<Self>, shutdown: CancellationToken) {
+        let interval = Duration::from_secs(self.config.snapshot_interval_secs);
+        info!("Leaderboard snapshots started (every {:?})", interval);
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = ticker.tick() => {
+                    if let Err(e) = self.snapshot_once().await {
+                        warn!("Failed to snapshot leaderboard: {:#}", e);
+                    }
+                }
+            }
+        }
+        info!("Leaderboard snapshots stopped");
+    }
+
+    /// 当前排行榜
+    pub async fn current(&self) -> Result<Leaderboard> {
+        let analytics: HashMap<String, _> = self
+            .recorder
+            .analytics()
+            .await?
+            .into_iter()
+            .map(|(_, a)| (a.model_id.clone(), a))
+            .collect();
+
+        // 经纪商账户币种, 每个经纪商只查询一次
+        let mut currencies: HashMap<String, Result<String, String>> = HashMap::new();
+        let mut entries = Vec::new();
+        for total in self.recorder.latest_totals().await? {
+            if !currencies.contains_key(&total.broker_id) {
+                let currency = self
+                    .broker_currency(&total.broker_id)
+                    .await
+                    .map_err(|e| format!("{:#}", e));
+                currencies.insert(total.broker_id.clone(), currency);
+            }
+            let rate = currencies[&total.broker_id]
+                .clone()
+                .map_err(anyhow::Error::msg)
+                .and_then(|currency| self.config.fx.rate(&currency));
+            let rate = match rate {
+                Ok(rate) => rate,
+                Err(e) => {
+                    warn!("Skipping {} on leaderboard: {:#}", total.model_id, e);
+                    continue;
+                }
+            };
+
+            let mut entry = LeaderboardEntry {
+                model_id: total.model_id.clone(),
+                model_name: total.model_name.clone(),
+                broker_id: total.broker_id.clone(),
+                equity: total.equity * rate,
+                currency: self.config.fx.reporting_currency.clone(),
+                return_pct: total.return_pct,
+                sharpe_ratio: total.sharpe_ratio,
+                ..Default::default()
+            };
+            if let Some(a) = analytics.get(&total.model_id) {
+                let trades = a.overall_trades_overview_table.total_trades;
+                let wl = &a.winners_losers_breakdown_table;
+                let wins = (wl.win_rate / 100.0 * trades as f64).round() as i32;
+                let losses = trades as i32 - wins;
+                entry.max_drawdown_pct = a.performance_table.max_drawdown_pct;
+                entry.total_trades = trades as i32;
+                entry.num_wins = wins;
+                entry.num_losses = losses;
+                entry.win_dollars = wl.avg_winners_net_pnl * wins as f64 * rate;
+                entry.lose_dollars = wl.avg_losers_net_pnl * losses as f64 * rate;
+                entry.win_rate = wl.win_rate / 100.0;
+            }
+            entries.push(entry);
+        }
+
+        self.config.ranking.rank(&mut entries);
+        Ok(Leaderboard {
+            leaderboard: entries,
+        })
+    }
+
+    /// 保存 (覆盖) 当天的排行榜快照
+    pub async fn snapshot_once(&self) -> Result<LeaderboardSnapshot> {
+        let now = self.clock.now();
+        let date = DateTime::from_timestamp(now, 0)
+            .ok_or_else(|| anyhow!("Invalid timestamp {}", now))?
+            .date_naive();
+        let snapshot = LeaderboardSnapshot {
+            date,
+            taken_at: now,
+            currency: self.config.fx.reporting_currency.clone(),
+            entries: self.current().await?.leaderboard,
+        };
+        self.store.save(&snapshot).await?;
+        Ok(snapshot)
+    }
+
+    /// `[from, to]` 内每天的名次, 可按模型过滤
+    pub async fn rank_history(
+        &self,
+        model_id: Option<&str>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<RankHistoryPoint>> {
+        Ok(self
+            .store
+            .load(from, to)
+            .await?
+            .into_iter()
+            .flat_map(|snapshot| {
+                snapshot
+                    .entries
+                    .into_iter()
+                    .filter(|e| model_id.is_none_or(|id| e.model_id == id))
+                    .map(move |e| RankHistoryPoint {
+                        date: snapshot.date,
+                        model_id: e.model_id,
+                        rank: e.rank,
+                        equity: e.equity,
+                        return_pct: e.return_pct,
+                    })
+            })
+            .collect())
+    }
+
+    async fn broker_currency(&self, broker_id: &str) -> Result<String> {
+        let broker = self
+            .engine
+            .get_broker(broker_id)
+            .await
+            .ok_or_else(|| anyhow!("Broker not found: {}", broker_id))?;
+        Ok(broker.get_balance().await?.currency)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::paper::PaperConfig;
+    use crate::brokers::{DynBroker, MockBroker, PaperBroker};
+    use crate::engine::{Agent, InMemoryEquityStore};
+    use crate::leaderboard::{InMemoryLeaderboardStore, RankingMetric, RankingPolicy};
+    use crate::mcp::McpServer;
+
+    async fn register(engine: &TradingEngine, id: &str, currency: &str, balance: f64) {
+        let feed: Arc<dyn DynBroker> = Arc::new(MockBroker::new());
+        let config = PaperConfig {
+            initial_balance: balance,
+            currency: currency.to_string(),
+            ..Default::default()
+        };
+        let broker_id = format!("{}-broker", id);
+        engine
+            .register_broker(
+                broker_id.clone(),
+                Arc::new(PaperBroker::new(broker_id.clone(), id, feed, config)),
+            )
+            .await;
+        let mut agent = Agent::new(
+            id.to_string(),
+            id.to_string(),
+            "scripted".to_string(),
+            broker_id,
+        );
+        agent.initial_capital = balance;
+        engine.register_agent(agent).await;
+    }
+
+    #[tokio::test]
+    async fn test_normalizes_currencies_and_snapshots_daily() {
+        let engine = Arc::new(TradingEngine::new(Arc::new(McpServer::new())));
+        register(&engine, "crypto", "USDT", 10_000.0).await;
+        register(&engine, "futures", "CNY", 1_000_000.0).await;
+        register(&engine, "unknown", "JPY", 1_000_000.0).await;
+
+        let start = 1_700_000_000;
+        let clock = Clock::simulated(start);
+        let recorder = Arc::new(
+            EquityRecorder::new(engine.clone(), Arc::new(InMemoryEquityStore::new()))
+                .with_clock(clock.clone()),
+        );
+        let config = LeaderboardConfig {
+            ranking: RankingPolicy::new(RankingMetric::Equity),
+            ..Default::default()
+        };
+        let service = LeaderboardService::new(
+            engine,
+            recorder.clone(),
+            Arc::new(InMemoryLeaderboardStore::new()),
+            config,
+        )
+        .with_clock(clock.clone());
+
+        recorder.record_once().await.unwrap();
+        let board = service.current().await.unwrap().leaderboard;
+        // 没有 JPY 汇率的模型不参与排名
+        let ranked: Vec<(&str, i32)> = board
+            .iter()
+            .map(|e| (e.model_id.as_str(), e.rank))
+            .collect();
+        assert_eq!(ranked, vec![("futures", 1), ("crypto", 2)]);
+        assert!((board[0].equity - 140_000.0).abs() < 1e-6);
+        assert_eq!(board[0].currency, "USD");
+
+        service.snapshot_once().await.unwrap();
+        clock.set(start + 86400);
+        recorder.record_once().await.unwrap();
+        service.snapshot_once().await.unwrap();
+        // 同一天再次快照覆盖当天记录
+        service.snapshot_once().await.unwrap();
+
+        let day = |ts: i64| DateTime::from_timestamp(ts, 0).unwrap().date_naive();
+        let history = service
+            .rank_history(Some("crypto"), day(start), day(start + 86400))
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history.iter().all(|p| p.rank == 2));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::brokers::LeaderboardEntry;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tokio::sync::RwLock;
+
+/// 某一天的排行榜快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardSnapshot {
+    pub date: NaiveDate,
+    /// 快照时间 (秒)
+    pub taken_at: i64,
+    pub currency: String,
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+/// 模型某一天的名次
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankHistoryPoint {
+    pub date: NaiveDate,
+    pub model_id: String,
+    pub rank: i32,
+    pub equity: f64,
+    pub return_pct: f64,
+}
+
+/// 排行榜快照持久化接口, 每天一份, 重复写入覆盖当天快照
+#[async_trait]
+pub trait LeaderboardStore: Send + Sync {
+    async fn save(&self, snapshot: &LeaderboardSnapshot) -> Result<()>;
+
+    /// `[from, to]` 日期范围内的快照, 按日期升序
+    async fn load(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<LeaderboardSnapshot>>;
+}
+
+/// 内存排行榜快照存储
+#[derive(Default)]
+pub struct InMemoryLeaderboardStore {
+    snapshots: RwLock<BTreeMap<NaiveDate, LeaderboardSnapshot>>,
+}
+
+impl InMemoryLeaderboardStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl LeaderboardStore for InMemoryLeaderboardStore {
+    async fn save(&self, snapshot: &LeaderboardSnapshot) -> Result<()> {
+        self.snapshots
+            .write()
+            .await
+            .insert(snapshot.date, snapshot.clone());
+        Ok(())
+    }
+
+    async fn load(&self, from: NaiveDate, to: NaiveDate) -> Result<Vec<LeaderboardSnapshot>> {
+        if from > to {
+            return Ok(Vec::new());
+        }
+        Ok(self
+            .snapshots
+            .read()
+            .await
+            .range(from..=to)
+            .map(|(_, s)| s.clone())
+            .collect())
+    }
+}
@@ -1,11 +1,18 @@
 // Library exports for examples and tests
 
+pub mod analytics;
+pub mod backtest;
 pub mod brokers;
 pub mod config;
+pub mod db;
 pub mod engine;
 pub mod error;
+pub mod history;
+pub mod leaderboard;
 pub mod llm;
 pub mod markets;
 pub mod mcp;
 pub mod mock_data;
+pub mod prompt;
 pub mod risk;
+pub mod usage;
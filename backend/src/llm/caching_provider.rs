@@ -0,0 +1,213 @@
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+use super::provider::{ChatRequest, ChatResponse, LlmProvider};
+
+/// 缓存 LLM 回复的 Provider 包装
+///
+/// 以 provider、模型、请求与工具定义的 SHA-256 作为键, 相同输入直接返回缓存的回复,
+/// 使回测重跑结果确定且不产生调用费用。配置目录后缓存以 `<key>.json` 持久化。
+pub struct CachingProvider {
+    inner: Arc<dyn LlmProvider>,
+    dir: Option<PathBuf>,
+    /// 只回放缓存, 未命中时返回错误而不调用底层 Provider
+    replay_only: bool,
+    memory: Mutex<HashMap<String, ChatResponse>>,
+    hits: AtomicUsize,
+    misses: AtomicUsize,
+}
+
+impl CachingProvider {
+    /// 仅内存缓存
+    pub fn new(inner: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            inner,
+            dir: None,
+            replay_only: false,
+            memory: Mutex::new(HashMap::new()),
+            hits: AtomicUsize::new(0),
+            misses: AtomicUsize::new(0),
+        }
+    }
+
+    /// 将缓存持久化到目录
+    pub fn with_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = Some(dir.into());
+        self
+    }
+
+    /// 只回放缓存（离线重跑）
+    pub fn replay_only(mut self) -> Self {
+        self.replay_only = true;
+        self
+    }
+
+    /// 缓存命中次数
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    /// 缓存未命中次数
+    pub fn misses(&self) -> usize {
+        self.misses.load(Ordering::SeqCst)
+    }
+
+    /// 计算缓存键
+    pub fn cache_key(&self, req: &ChatRequest, tools: &[serde_json::Value]) -> String {
+        let payload = serde_json::json!({
+            "provider": self.inner.name(),
+            "model": self.inner.model(),
+            "request": req,
+            "tools": tools,
+        });
+        hex::encode(Sha256::digest(payload.to_string().as_bytes()))
+    }
+
+    fn path_for(&self, key: &str) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", key)))
+    }
+
+    fn lookup(&self, key: &str) -> Result<Option<ChatResponse>> {
+        if let Some(response) = self.memory.lock().unwrap().get(key) {
+            return Ok(Some(response.clone()));
+        }
+
+        let Some(path) = self.path_for(key).filter(|p| p.exists()) else {
+            return Ok(None);
+        };
+        let response: ChatResponse = read_json(&path)?;
+        self.memory
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), response.clone());
+        Ok(Some(response))
+    }
+
+    fn store(&self, key: &str, response: &ChatResponse) -> Result<()> {
+        self.memory
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), response.clone());
+
+        if let Some(path) = self.path_for(key) {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("Failed to create cache dir {}", dir.display()))?;
+            }
+            std::fs::write(&path, serde_json::to_vec_pretty(response)?)
+                .with_context(|| format!("Failed to write cache {}", path.display()))?;
+        }
+        Ok(())
+    }
+
+    async fn cached(
+        &self,
+        req: ChatRequest,
+        tools: Option<Vec<serde_json::Value>>,
+    ) -> Result<ChatResponse> {
+        let key = self.cache_key(&req, tools.as_deref().unwrap_or_default());
+        if let Some(response) = self.lookup(&key)? {
+            debug!("LLM cache hit: {}", key);
+            self.hits.fetch_add(1, Ordering::SeqCst);
+            return Ok(response);
+        }
+
+        self.misses.fetch_add(1, Ordering::SeqCst);
+        if self.replay_only {
+            return Err(anyhow!("LLM cache miss in replay-only mode: {}", key));
+        }
+
+        let response = match tools {
+            Some(tools) => self.inner.chat_with_tools(req, tools).await?,
+            None => self.inner.chat(req).await?,
+        };
+        self.store(&key, &response)?;
+        Ok(response)
+    }
+}
+
+fn read_json(path: &Path) -> Result<ChatResponse> {
+    let content =
+        std::fs::read(path).with_context(|| format!("Failed to read cache {}", path.display()))?;
+    serde_json::from_slice(&content)
+        .with_context(|| format!("Invalid cache entry {}", path.display()))
+}
+
+#[async_trait]
+impl LlmProvider for CachingProvider {
+    async fn chat(&self, req: ChatRequest) -> Result<ChatResponse> {
+        self.cached(req, None).await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatResponse> {
+        self.cached(req, Some(tools)).await
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{Message, ScriptStep, ScriptedProvider};
+
+    fn request(content: &str) -> ChatRequest {
+        ChatRequest {
+            messages: vec![Message::user(content)],
+            temperature: Some(0.0),
+            max_tokens: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cache_hits_and_persists() {
+        let dir = std::env::temp_dir().join(format!("nof0-llm-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let steps = vec![
+            ScriptStep {
+                content: "first".to_string(),
+                ..Default::default()
+            },
+            ScriptStep {
+                content: "second".to_string(),
+                ..Default::default()
+            },
+        ];
+        let inner = Arc::new(ScriptedProvider::new(steps));
+        let cache = CachingProvider::new(inner.clone()).with_dir(&dir);
+
+        assert_eq!(cache.chat(request("a")).await.unwrap().content, "first");
+        assert_eq!(cache.chat(request("a")).await.unwrap().content, "first");
+        assert_eq!(cache.chat(request("b")).await.unwrap().content, "second");
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+        assert_eq!(inner.requests().len(), 2);
+
+        // 新实例从磁盘回放, 不再调用底层 Provider
+        let replay = CachingProvider::new(Arc::new(ScriptedProvider::new(vec![])))
+            .with_dir(&dir)
+            .replay_only();
+        assert_eq!(replay.chat(request("b")).await.unwrap().content, "second");
+        assert!(replay.chat(request("c")).await.is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
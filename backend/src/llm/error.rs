@@ -0,0 +1,56 @@
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// LLM 接口返回的非 2xx 响应
+#[derive(Debug, Clone, Error)]
+#[error("{provider} API error ({status}): {body}")]
+pub struct LlmHttpError {
+    pub provider: String,
+    pub status: u16,
+    /// 服务端要求的重试等待 (`Retry-After` / `retry-after-ms`)
+    pub retry_after: Option<Duration>,
+    pub body: String,
+}
+
+impl LlmHttpError {
+    /// 限流、超时与服务端错误可重试; 其余 4xx 为请求本身的问题
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.status, 408 | 409 | 429) || self.status >= 500
+    }
+}
+
+/// 检查响应状态, 非 2xx 时读取响应体与重试等待并返回 [`LlmHttpError`]
+pub(crate) async fn check_status(
+    provider: &str,
+    response: reqwest::Response,
+) -> anyhow::Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status().as_u16();
+    let retry_after = retry_after(response.headers());
+    let body = response.text().await?;
+    Err(LlmHttpError {
+        provider: provider.to_string(),
+        status,
+        retry_after,
+        body,
+    }
+    .into())
+}
+
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.trim().parse::<f64>().ok()) {
+        return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
+    }
+    let value = header("retry-after")?.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return Some(Duration::from_secs_f64(secs.max(0.0)));
+    }
+    // HTTP-date 格式
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or_default())
+}
@@ -0,0 +1,175 @@
+use super::{
+    find_preset, AnthropicProvider, GeminiProvider, LlmProvider, OpenAICompatibleProvider,
+    ProviderKind, ProviderPreset, ResilientProvider, RigOpenAIProvider, ScriptedProvider,
+};
+use crate::config::{AgentLlmConfig, LlmProviderConfig};
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+
+/// 根据 Agent 的 LLM 配置创建 Provider
+pub fn build_provider(config: &AgentLlmConfig) -> Result<Arc<dyn LlmProvider>> {
+    let model = config.model.as_str();
+
+    if config.provider == "scripted" {
+        let script = config
+            .script
+            .as_ref()
+            .ok_or_else(|| anyhow!("Scripted provider requires `script`"))?;
+        return Ok(Arc::new(
+            ScriptedProvider::from_file(script)?.with_model(model),
+        ));
+    }
+
+    let preset = preset(&config.provider)?;
+    let api_key = api_key(preset, config.api_key_env.as_deref())?;
+    let provider = create(preset, api_key, model, config.base_url.clone())?;
+
+    if config.resilience.is_none() && config.fallback.is_none() {
+        return Ok(provider);
+    }
+    let mut resilient =
+        ResilientProvider::new(provider, config.resilience.clone().unwrap_or_default());
+    if let Some(fallback) = &config.fallback {
+        resilient = resilient.with_fallback(build_provider(fallback)?);
+    }
+    Ok(Arc::new(resilient))
+}
+
+/// 根据全局 `llm_providers` 配置创建 Provider, `name` 为预设名或别名
+///
+/// `api_key` 为空时从预设的默认环境变量读取。
+pub fn build_provider_from_config(config: &LlmProviderConfig) -> Result<Arc<dyn LlmProvider>> {
+    let preset = preset(&config.name)?;
+    let api_key = if config.api_key.is_empty() {
+        api_key(preset, None)?
+    } else {
+        config.api_key.clone()
+    };
+    create(preset, api_key, &config.model, config.base_url.clone())
+}
+
+fn preset(name: &str) -> Result<&'static ProviderPreset> {
+    find_preset(name).ok_or_else(|| anyhow!("Unsupported LLM provider: {}", name))
+}
+
+fn create(
+    preset: &ProviderPreset,
+    api_key: String,
+    model: &str,
+    base_url: Option<String>,
+) -> Result<Arc<dyn LlmProvider>> {
+    let provider: Arc<dyn LlmProvider> = match preset.kind {
+        ProviderKind::OpenAI => {
+            let provider = RigOpenAIProvider::new(api_key, model)?;
+            match base_url {
+                Some(url) => Arc::new(provider.with_base_url(url)),
+                None => Arc::new(provider),
+            }
+        }
+        ProviderKind::Anthropic => Arc::new(AnthropicProvider::new(api_key, model)?),
+        ProviderKind::Gemini => {
+            let provider = GeminiProvider::new(api_key, model)?;
+            match base_url {
+                Some(url) => Arc::new(provider.with_base_url(url)),
+                None => Arc::new(provider),
+            }
+        }
+        ProviderKind::OpenAICompatible => {
+            let provider = OpenAICompatibleProvider::from_preset(preset, api_key, model)?;
+            match base_url {
+                Some(url) => Arc::new(provider.with_base_url(url)),
+                None if preset.base_url.is_empty() => {
+                    return Err(anyhow!("{} provider requires `base_url`", preset.name))
+                }
+                None => Arc::new(provider),
+            }
+        }
+    };
+
+    Ok(provider)
+}
+
+/// 读取 API Key, 未指定环境变量时使用预设默认值; 预设无需 Key 时返回空串
+fn api_key(preset: &ProviderPreset, env: Option<&str>) -> Result<String> {
+    let Some(env) = env.or(preset.api_key_env) else {
+        return Ok(String::new());
+    };
+    std::env::var(env).map_err(|_| anyhow!("{} not set for provider {}", env, preset.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn llm_config(provider: &str) -> AgentLlmConfig {
+        AgentLlmConfig {
+            provider: provider.to_string(),
+            model: "test-model".to_string(),
+            temperature: None,
+            max_tokens: None,
+            base_url: None,
+            api_key_env: Some("NOF0_TEST_UNSET_API_KEY".to_string()),
+            script: None,
+            resilience: None,
+            fallback: None,
+        }
+    }
+
+    #[test]
+    fn test_build_provider_errors() {
+        let err = build_provider(&llm_config("unknown")).err().unwrap();
+        assert!(err.to_string().contains("Unsupported"));
+
+        let err = build_provider(&llm_config("deepseek")).err().unwrap();
+        assert!(err.to_string().contains("NOF0_TEST_UNSET_API_KEY"));
+
+        assert!(build_provider(&llm_config("scripted")).is_err());
+
+        let mut config = llm_config("openai_compatible");
+        config.api_key_env = Some("NOF0_TEST_API_KEY".to_string());
+        std::env::set_var("NOF0_TEST_API_KEY", "test-key");
+        let err = build_provider(&config).err().unwrap();
+        assert!(err.to_string().contains("base_url"));
+    }
+
+    #[test]
+    fn test_build_presets() {
+        let mut config = llm_config("ollama");
+        config.api_key_env = None;
+        let provider = build_provider(&config).unwrap();
+        assert_eq!(provider.name(), "ollama-test-model");
+
+        let provider = build_provider_from_config(&LlmProviderConfig {
+            name: "grok".to_string(),
+            model: "grok-4".to_string(),
+            api_key: "xai-key".to_string(),
+            base_url: None,
+        })
+        .unwrap();
+        assert_eq!(provider.name(), "xai-grok-4");
+
+        let provider = build_provider_from_config(&LlmProviderConfig {
+            name: "gemini".to_string(),
+            model: "gemini-2.5-pro".to_string(),
+            api_key: "g-key".to_string(),
+            base_url: None,
+        })
+        .unwrap();
+        assert_eq!(provider.model(), "gemini-2.5-pro");
+    }
+
+    #[test]
+    fn test_build_scripted_provider() {
+        let mut config = llm_config("scripted");
+        config.script = Some(
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/fixtures/llm/trading_dialogue.yaml"
+            )
+            .to_string(),
+        );
+
+        let provider = build_provider(&config).unwrap();
+        assert_eq!(provider.model(), "test-model");
+    }
+}
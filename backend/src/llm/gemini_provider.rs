@@ -0,0 +1,509 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tracing::info;
+
+use super::error::check_status;
+use super::presets::GEMINI;
+use super::provider::{
+    ChatRequest, ChatResponse, ContentBlock, LlmProvider, Message, TokenUsage, ToolCall,
+};
+use super::rig_provider::function_declaration;
+use super::stream::{sse_stream, ChatStream, SseEvent, StreamDecoder, StreamDelta};
+
+/// Google Gemini Provider (原生 `generateContent` 接口)
+pub struct GeminiProvider {
+    name: String,
+    client: Client,
+    api_key: String,
+    model_name: String,
+    base_url: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system_instruction: Option<GeminiContent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<Value>>,
+    generation_config: GeminiGenerationConfig,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiGenerationConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GeminiContent {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    role: String,
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPart {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    /// 思考摘要 (thinking 模型)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    thought: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    inline_data: Option<GeminiBlob>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_call: Option<GeminiFunctionCall>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    function_response: Option<GeminiFunctionResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiBlob {
+    mime_type: String,
+    data: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiFunctionCall {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    args: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiFunctionResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    name: String,
+    response: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    #[serde(default)]
+    usage_metadata: Option<GeminiUsage>,
+    #[serde(default)]
+    prompt_feedback: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCandidate {
+    #[serde(default)]
+    content: GeminiContent,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsage {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+    #[serde(default)]
+    thoughts_token_count: u32,
+    #[serde(default)]
+    total_token_count: u32,
+}
+
+impl GeminiUsage {
+    fn token_usage(&self) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_token_count,
+            completion_tokens: self.candidates_token_count + self.thoughts_token_count,
+            total_tokens: self.total_token_count,
+        }
+    }
+}
+
+impl GeminiProvider {
+    pub fn new(api_key: String, model: &str) -> Result<Self> {
+        Ok(Self {
+            name: format!("gemini-{}", model),
+            client: Client::new(),
+            api_key,
+            model_name: model.to_string(),
+            base_url: GEMINI.base_url.to_string(),
+        })
+    }
+
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    fn request(&self, req: ChatRequest, tools: Vec<Value>) -> GeminiRequest {
+        let (system_instruction, contents) = to_gemini_contents(req.messages);
+        GeminiRequest {
+            contents,
+            system_instruction,
+            tools: (!tools.is_empty()).then(|| to_gemini_tools(&tools)),
+            generation_config: GeminiGenerationConfig {
+                temperature: req.temperature,
+                max_output_tokens: req.max_tokens,
+            },
+        }
+    }
+
+    async fn generate(&self, req: ChatRequest, tools: Vec<Value>) -> Result<ChatResponse> {
+        let gemini_req = self.request(req, tools);
+
+        let response = self
+            .client
+            .post(format!(
+                "{}/models/{}:generateContent",
+                self.base_url, self.model_name
+            ))
+            .header("x-goog-api-key", &self.api_key)
+            .header("Content-Type", "application/json")
+            .json(&gemini_req)
+            .send()
+            .await?;
+
+        let response = check_status("Gemini", response).await?;
+
+        let gemini_resp: GeminiResponse = response.json().await?;
+        parse_response(gemini_resp)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for GeminiProvider {
+    async fn chat(&self, req: ChatRequest) -> Result<ChatResponse> {
+        info!("Sending chat request to {}", self.name);
+        self.generate(req, Vec::new()).await
+    }
+
+    async fn chat_with_tools(&self, req: ChatRequest, tools: Vec<Value>) -> Result<ChatResponse> {
+        info!("Sending chat request with tools to {}", self.name);
+        self.generate(req, tools).await
+    }
+
+    async fn chat_stream(&self, req: ChatRequest, tools: Vec<Value>) -> Result<ChatStream> {
+        info!("Sending streaming chat request to {}", self.name);
+        let gemini_req = self.request(req, tools);
+
+        let response = self
+            .client
+            .post(format!(
+                "{}/models/{}:streamGenerateContent?alt=sse",
+                self.base_url, self.model_name
+            ))
+            .header("x-goog-api-key", &self.api_key)
+            .header("Content-Type", "application/json")
+            .json(&gemini_req)
+            .send()
+            .await?;
+
+        let response = check_status("Gemini", response).await?;
+
+        Ok(sse_stream(response, GeminiStreamDecoder::default()))
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn model(&self) -> &str {
+        &self.model_name
+    }
+}
+
+/// 将消息翻译为 Gemini 格式, 返回 (systemInstruction, contents)
+///
+/// assistant 对应 `model` 角色; `ToolResult` 转为 `functionResponse`, 其 `name` 由
+/// 历史中的 `ToolUse` 按 id 查得; 相邻同角色内容合并。
+fn to_gemini_contents(messages: Vec<Message>) -> (Option<GeminiContent>, Vec<GeminiContent>) {
+    let mut system = Vec::new();
+    let mut contents: Vec<GeminiContent> = Vec::new();
+    let mut call_names: HashMap<String, String> = HashMap::new();
+
+    for m in messages {
+        if m.role == "system" {
+            system.push(GeminiPart {
+                text: Some(m.content),
+                ..Default::default()
+            });
+            continue;
+        }
+        let role = if m.role == "assistant" {
+            "model"
+        } else {
+            "user"
+        };
+        let blocks = if m.blocks.is_empty() {
+            vec![ContentBlock::Text { text: m.content }]
+        } else {
+            m.blocks
+        };
+
+        let parts: Vec<GeminiPart> = blocks
+            .into_iter()
+            .filter_map(|block| match block {
+                ContentBlock::Text { text } if text.is_empty() => None,
+                ContentBlock::Text { text } => Some(GeminiPart {
+                    text: Some(text),
+                    ..Default::default()
+                }),
+                ContentBlock::Image { media_type, data } => Some(GeminiPart {
+                    inline_data: Some(GeminiBlob {
+                        mime_type: media_type,
+                        data,
+                    }),
+                    ..Default::default()
+                }),
+                ContentBlock::ToolUse { id, name, input } => {
+                    call_names.insert(id.clone(), name.clone());
+                    Some(GeminiPart {
+                        function_call: Some(GeminiFunctionCall {
+                            id: Some(id),
+                            name,
+                            // args 必须为对象
+                            args: if input.is_object() {
+                                input
+                            } else {
+                                json!({ "input": input })
+                            },
+                        }),
+                        ..Default::default()
+                    })
+                }
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    is_error,
+                } => Some(GeminiPart {
+                    function_response: Some(GeminiFunctionResponse {
+                        name: call_names
+                            .get(&tool_use_id)
+                            .cloned()
+                            .unwrap_or_else(|| tool_use_id.clone()),
+                        id: Some(tool_use_id),
+                        response: if is_error {
+                            json!({ "error": content })
+                        } else {
+                            json!({ "result": content })
+                        },
+                    }),
+                    ..Default::default()
+                }),
+            })
+            .collect();
+        if parts.is_empty() {
+            continue;
+        }
+
+        match contents.last_mut() {
+            Some(last) if last.role == role => last.parts.extend(parts),
+            _ => contents.push(GeminiContent {
+                role: role.to_string(),
+                parts,
+            }),
+        }
+    }
+
+    let system = (!system.is_empty()).then_some(GeminiContent {
+        role: String::new(),
+        parts: system,
+    });
+    (system, contents)
+}
+
+/// 转换为 `functionDeclarations`, 去掉 Gemini 不支持的 JSON Schema 字段
+fn to_gemini_tools(tools: &[Value]) -> Vec<Value> {
+    let declarations: Vec<Value> = tools
+        .iter()
+        .filter_map(function_declaration)
+        .map(|(name, description, mut parameters)| {
+            strip_unsupported_schema(&mut parameters);
+            json!({ "name": name, "description": description, "parameters": parameters })
+        })
+        .collect();
+    vec![json!({ "functionDeclarations": declarations })]
+}
+
+fn strip_unsupported_schema(schema: &mut Value) {
+    match schema {
+        Value::Object(map) => {
+            for key in ["$schema", "$id", "additionalProperties", "examples"] {
+                map.remove(key);
+            }
+            map.values_mut().for_each(strip_unsupported_schema);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_unsupported_schema),
+        _ => {}
+    }
+}
+
+fn parse_response(resp: GeminiResponse) -> Result<ChatResponse> {
+    let usage = resp
+        .usage_metadata
+        .as_ref()
+        .map(GeminiUsage::token_usage)
+        .unwrap_or_default();
+    let candidate = resp.candidates.into_iter().next().ok_or_else(|| {
+        anyhow!(
+            "No response from Gemini: {}",
+            resp.prompt_feedback.unwrap_or(Value::Null)
+        )
+    })?;
+
+    let mut content = Vec::new();
+    let mut reasoning = Vec::new();
+    let mut tool_calls = Vec::new();
+    for part in candidate.content.parts {
+        if let Some(call) = part.function_call {
+            tool_calls.push(ToolCall {
+                // Gemini 可能不返回 id
+                id: call
+                    .id
+                    .unwrap_or_else(|| format!("call_{}", tool_calls.len())),
+                name: call.name,
+                arguments: call.args,
+            });
+        } else if let Some(text) = part.text {
+            if part.thought {
+                reasoning.push(text);
+            } else {
+                content.push(text);
+            }
+        }
+    }
+
+    Ok(ChatResponse {
+        content: content.join(""),
+        reasoning: (!reasoning.is_empty()).then(|| reasoning.join("")),
+        tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+        usage,
+    })
+}
+
+/// `streamGenerateContent?alt=sse` 的事件解码, 每个事件是一个完整的响应分块
+#[derive(Debug, Default)]
+pub struct GeminiStreamDecoder {
+    tool_calls: usize,
+}
+
+impl StreamDecoder for GeminiStreamDecoder {
+    fn decode(&mut self, event: &SseEvent) -> Result<Vec<StreamDelta>> {
+        let chunk: GeminiResponse = serde_json::from_str(&event.data)
+            .map_err(|e| anyhow!("Invalid stream chunk: {} ({})", e, event.data))?;
+
+        let mut deltas = Vec::new();
+        if let Some(candidate) = chunk.candidates.into_iter().next() {
+            for part in candidate.content.parts {
+                if let Some(call) = part.function_call {
+                    // 函数调用在单个分块中完整给出
+                    let index = self.tool_calls;
+                    self.tool_calls += 1;
+                    deltas.push(StreamDelta::ToolCall {
+                        index,
+                        id: Some(call.id.unwrap_or_else(|| format!("call_{}", index))),
+                        name: Some(call.name),
+                        arguments: call.args.to_string(),
+                    });
+                } else if let Some(text) = part.text.filter(|t| !t.is_empty()) {
+                    deltas.push(if part.thought {
+                        StreamDelta::Reasoning { text }
+                    } else {
+                        StreamDelta::Text { text }
+                    });
+                }
+            }
+            if let Some(reason) = candidate.finish_reason {
+                deltas.push(StreamDelta::Stop {
+                    reason: Some(reason),
+                });
+            }
+        }
+        if let Some(usage) = &chunk.usage_metadata {
+            deltas.push(StreamDelta::Usage(usage.token_usage()));
+        }
+        Ok(deltas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gemini_function_calling_round_trip() {
+        let call = ToolCall {
+            id: "call_0".to_string(),
+            name: "get_price".to_string(),
+            arguments: json!({"symbol": "BTC"}),
+        };
+        let (system, contents) = to_gemini_contents(vec![
+            Message::system("You are a trader"),
+            Message::user("Check BTC"),
+            Message::assistant_with_tool_calls("", &[call]),
+            Message::tool_result("call_0", "50000", false),
+        ]);
+        assert_eq!(
+            system.unwrap().parts[0].text.as_deref(),
+            Some("You are a trader")
+        );
+        let body = serde_json::to_value(&contents).unwrap();
+        assert_eq!(body[1]["role"], "model");
+        assert_eq!(body[1]["parts"][0]["functionCall"]["name"], "get_price");
+        assert_eq!(body[2]["role"], "user");
+        assert_eq!(body[2]["parts"][0]["functionResponse"]["name"], "get_price");
+        assert_eq!(
+            body[2]["parts"][0]["functionResponse"]["response"]["result"],
+            "50000"
+        );
+
+        let tools = to_gemini_tools(&[json!({
+            "type": "function",
+            "function": {
+                "name": "get_price",
+                "description": "price",
+                "parameters": {"type": "object", "additionalProperties": false, "properties": {}},
+            }
+        })]);
+        let declaration = &tools[0]["functionDeclarations"][0];
+        assert_eq!(declaration["name"], "get_price");
+        assert!(declaration["parameters"]
+            .get("additionalProperties")
+            .is_none());
+
+        let resp: GeminiResponse = serde_json::from_value(json!({
+            "candidates": [{
+                "content": {"role": "model", "parts": [
+                    {"text": "weighing momentum", "thought": true},
+                    {"text": "Buying."},
+                    {"functionCall": {"name": "place_order", "args": {"side": "buy"}}}
+                ]},
+                "finishReason": "STOP"
+            }],
+            "usageMetadata": {"promptTokenCount": 10, "candidatesTokenCount": 4, "thoughtsTokenCount": 3, "totalTokenCount": 17}
+        }))
+        .unwrap();
+        let response = parse_response(resp).unwrap();
+        assert_eq!(response.content, "Buying.");
+        assert_eq!(response.reasoning.as_deref(), Some("weighing momentum"));
+        let calls = response.tool_calls.unwrap();
+        assert_eq!(calls[0].id, "call_0");
+        assert_eq!(calls[0].arguments["side"], "buy");
+        assert_eq!(response.usage.completion_tokens, 7);
+    }
+}
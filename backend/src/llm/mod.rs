@@ -1,5 +1,27 @@
+mod caching_provider;
+mod error;
+mod factory;
+mod gemini_provider;
+mod presets;
 mod provider;
+mod resilient_provider;
 mod rig_provider;
+mod scripted_provider;
+mod stream;
 
+pub use caching_provider::CachingProvider;
+pub use error::LlmHttpError;
+pub use factory::{build_provider, build_provider_from_config};
+pub use gemini_provider::{GeminiProvider, GeminiStreamDecoder};
+pub use presets::{find_preset, ProviderKind, ProviderPreset, PRESETS};
 pub use provider::*;
+pub use resilient_provider::{CircuitState, ResilienceConfig, ResilientProvider, TokenBucket};
 pub use rig_provider::{AnthropicProvider, OpenAICompatibleProvider, RigOpenAIProvider};
+pub use scripted_provider::{
+    MessageMatcher, RecordedRequest, Script, ScriptStep, ScriptedError, ScriptedProvider,
+    ScriptedToolCall,
+};
+pub use stream::{
+    collect_stream, response_stream, sse_stream, AnthropicStreamDecoder, ChatStream,
+    OpenAIStreamDecoder, SseEvent, SseParser, StreamAccumulator, StreamDecoder, StreamDelta,
+};
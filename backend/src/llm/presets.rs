@@ -0,0 +1,108 @@
+// LLM Provider 预设
+// Named provider presets
+
+/// 预设对应的协议实现
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProviderKind {
+    OpenAI,
+    Anthropic,
+    Gemini,
+    /// 基于 `OpenAICompatibleProvider`
+    OpenAICompatible,
+}
+
+/// Provider 预设: agents.yaml 中 `llm.provider` 与全局 `llm_providers[].name` 均按预设名查找
+#[derive(Debug, Clone, Copy)]
+pub struct ProviderPreset {
+    pub name: &'static str,
+    /// 别名, 如 `grok` -> `xai`
+    pub aliases: &'static [&'static str],
+    pub kind: ProviderKind,
+    /// 默认 API 地址, 为空时必须在配置中指定 `base_url`
+    pub base_url: &'static str,
+    /// 默认读取 API Key 的环境变量, `None` 表示无需 Key
+    pub api_key_env: Option<&'static str>,
+}
+
+pub const OPENAI: ProviderPreset = ProviderPreset {
+    name: "openai",
+    aliases: &["gpt"],
+    kind: ProviderKind::OpenAI,
+    base_url: "https://api.openai.com/v1",
+    api_key_env: Some("OPENAI_API_KEY"),
+};
+
+pub const ANTHROPIC: ProviderPreset = ProviderPreset {
+    name: "anthropic",
+    aliases: &["claude"],
+    kind: ProviderKind::Anthropic,
+    base_url: "https://api.anthropic.com/v1",
+    api_key_env: Some("ANTHROPIC_API_KEY"),
+};
+
+pub const GEMINI: ProviderPreset = ProviderPreset {
+    name: "gemini",
+    aliases: &["google"],
+    kind: ProviderKind::Gemini,
+    base_url: "https://generativelanguage.googleapis.com/v1beta",
+    api_key_env: Some("GEMINI_API_KEY"),
+};
+
+pub const DEEPSEEK: ProviderPreset = ProviderPreset {
+    name: "deepseek",
+    aliases: &[],
+    kind: ProviderKind::OpenAICompatible,
+    base_url: "https://api.deepseek.com/v1",
+    api_key_env: Some("DEEPSEEK_API_KEY"),
+};
+
+pub const QWEN: ProviderPreset = ProviderPreset {
+    name: "qwen",
+    aliases: &["dashscope"],
+    kind: ProviderKind::OpenAICompatible,
+    base_url: "https://dashscope.aliyuncs.com/compatible-mode/v1",
+    api_key_env: Some("DASHSCOPE_API_KEY"),
+};
+
+pub const XAI: ProviderPreset = ProviderPreset {
+    name: "xai",
+    aliases: &["grok"],
+    kind: ProviderKind::OpenAICompatible,
+    base_url: "https://api.x.ai/v1",
+    api_key_env: Some("XAI_API_KEY"),
+};
+
+pub const OLLAMA: ProviderPreset = ProviderPreset {
+    name: "ollama",
+    aliases: &[],
+    kind: ProviderKind::OpenAICompatible,
+    base_url: "http://localhost:11434/v1",
+    api_key_env: None,
+};
+
+pub const OPENAI_COMPATIBLE: ProviderPreset = ProviderPreset {
+    name: "openai_compatible",
+    aliases: &[],
+    kind: ProviderKind::OpenAICompatible,
+    base_url: "",
+    api_key_env: Some("OPENAI_API_KEY"),
+};
+
+/// 全部预设
+pub const PRESETS: &[ProviderPreset] = &[
+    OPENAI,
+    ANTHROPIC,
+    GEMINI,
+    DEEPSEEK,
+    QWEN,
+    XAI,
+    OLLAMA,
+    OPENAI_COMPATIBLE,
+];
+
+/// 按名称或别名查找预设
+pub fn find_preset(name: &str) -> Option<&'static ProviderPreset> {
+    PRESETS.iter().find(|p| {
+        p.name.eq_ignore_ascii_case(name) || p.aliases.iter().any(|a| a.eq_ignore_ascii_case(name))
+    })
+}
@@ -1,6 +1,8 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
+use super::stream::{response_stream, ChatStream};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatRequest {
     pub messages: Vec<Message>,
@@ -10,20 +12,158 @@ pub struct ChatRequest {
     pub max_tokens: Option<u32>,
 }
 
+/// 对话消息
+///
+/// `content` 为消息的纯文本形式; `blocks` 非空时为结构化内容, 由各 Provider 按协议翻译:
+/// assistant 消息携带 `ToolUse`, `tool` 角色消息携带 `ToolResult`。
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: String,
     pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blocks: Vec<ContentBlock>,
+}
+
+/// 结构化内容块
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    /// 模型发起的工具调用
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    /// 工具执行结果, `tool_use_id` 对应发起调用的 `ToolUse::id`
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        #[serde(default)]
+        is_error: bool,
+    },
+    /// Base64 编码的图片
+    Image {
+        media_type: String,
+        data: String,
+    },
+}
+
+impl Message {
+    pub fn new(role: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            role: role.into(),
+            content: content.into(),
+            blocks: Vec::new(),
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new("system", content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new("user", content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new("assistant", content)
+    }
+
+    /// 带工具调用的 assistant 消息
+    pub fn assistant_with_tool_calls(content: impl Into<String>, tool_calls: &[ToolCall]) -> Self {
+        let content = content.into();
+        let mut blocks = Vec::with_capacity(tool_calls.len() + 1);
+        if !content.is_empty() {
+            blocks.push(ContentBlock::Text {
+                text: content.clone(),
+            });
+        }
+        blocks.extend(tool_calls.iter().map(|call| ContentBlock::ToolUse {
+            id: call.id.clone(),
+            name: call.name.clone(),
+            input: call.arguments.clone(),
+        }));
+        Self {
+            role: "assistant".to_string(),
+            content,
+            blocks,
+        }
+    }
+
+    /// 工具结果消息
+    pub fn tool_result(
+        tool_use_id: impl Into<String>,
+        result: impl Into<String>,
+        is_error: bool,
+    ) -> Self {
+        let result = result.into();
+        Self {
+            role: "tool".to_string(),
+            content: result.clone(),
+            blocks: vec![ContentBlock::ToolResult {
+                tool_use_id: tool_use_id.into(),
+                content: result,
+                is_error,
+            }],
+        }
+    }
+
+    /// 追加一张图片
+    pub fn with_image(mut self, media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        if self.blocks.is_empty() && !self.content.is_empty() {
+            self.blocks.push(ContentBlock::Text {
+                text: self.content.clone(),
+            });
+        }
+        self.blocks.push(ContentBlock::Image {
+            media_type: media_type.into(),
+            data: data.into(),
+        });
+        self
+    }
+
+    /// 消息中的工具调用
+    pub fn tool_calls(&self) -> Vec<ToolCall> {
+        self.blocks
+            .iter()
+            .filter_map(|block| match block {
+                ContentBlock::ToolUse { id, name, input } => Some(ToolCall {
+                    id: id.clone(),
+                    name: name.clone(),
+                    arguments: input.clone(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatResponse {
     pub content: String,
+    /// 推理模型的思考过程 (DeepSeek `reasoning_content`、Gemini thought 等), 仅供展示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
     pub usage: TokenUsage,
 }
 
+impl ChatResponse {
+    /// 转为写回对话历史的 assistant 消息, 保留工具调用
+    pub fn to_message(&self) -> Message {
+        match &self.tool_calls {
+            Some(calls) if !calls.is_empty() => {
+                Message::assistant_with_tool_calls(self.content.clone(), calls)
+            }
+            _ => Message::assistant(self.content.clone()),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolCall {
     pub id: String,
@@ -31,7 +171,7 @@ pub struct ToolCall {
     pub arguments: serde_json::Value,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct TokenUsage {
     pub prompt_tokens: u32,
     pub completion_tokens: u32,
@@ -48,6 +188,22 @@ pub trait LlmProvider: Send + Sync {
         tools: Vec<serde_json::Value>,
     ) -> Result<ChatResponse, anyhow::Error>;
 
+    /// 流式对话, `tools` 为空时不启用工具; 丢弃返回的流即中止生成
+    ///
+    /// 默认实现等待完整回复后拆分为增量。
+    async fn chat_stream(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatStream, anyhow::Error> {
+        let response = if tools.is_empty() {
+            self.chat(req).await?
+        } else {
+            self.chat_with_tools(req, tools).await?
+        };
+        Ok(response_stream(response))
+    }
+
     fn name(&self) -> &str;
     fn model(&self) -> &str;
 }
@@ -0,0 +1,606 @@
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::time::Instant;
+use tracing::warn;
+
+use super::error::LlmHttpError;
+use super::provider::{ChatRequest, ChatResponse, LlmProvider};
+use super::stream::ChatStream;
+
+/// 容错配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResilienceConfig {
+    /// 单次请求超时（秒）
+    pub timeout_secs: f64,
+    /// 最大重试次数（不含首次请求）
+    pub max_retries: u32,
+    /// 首次退避（毫秒）, 之后按 2 倍递增
+    pub initial_backoff_ms: u64,
+    /// 退避上限（毫秒）
+    pub max_backoff_ms: u64,
+    /// 每分钟请求数上限
+    pub requests_per_minute: Option<u32>,
+    /// 每分钟 token 数上限
+    pub tokens_per_minute: Option<u32>,
+    /// 连续失败多少次后熔断
+    pub failure_threshold: u32,
+    /// 熔断持续时间（秒）, 到期后放行一次试探请求
+    pub cooldown_secs: f64,
+}
+
+impl Default for ResilienceConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 120.0,
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            requests_per_minute: None,
+            tokens_per_minute: None,
+            failure_threshold: 5,
+            cooldown_secs: 30.0,
+        }
+    }
+}
+
+/// 令牌桶, 按分钟配额匀速补充
+#[derive(Debug)]
+pub struct TokenBucket {
+    capacity: f64,
+    available: f64,
+    per_sec: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    pub fn per_minute(limit: u32) -> Self {
+        let capacity = limit.max(1) as f64;
+        Self {
+            capacity,
+            available: capacity,
+            per_sec: capacity / 60.0,
+            updated: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = (self.available + elapsed * self.per_sec).min(self.capacity);
+        self.updated = now;
+    }
+
+    /// 尝试取出 `amount`, 成功返回 `None`, 否则返回需等待的时长
+    ///
+    /// 超过容量的请求在桶满时放行, 余额记为负数由后续请求偿还。
+    pub fn try_acquire(&mut self, amount: f64, now: Instant) -> Option<Duration> {
+        self.refill(now);
+        let needed = amount.min(self.capacity);
+        if self.available >= needed {
+            self.available -= amount;
+            None
+        } else {
+            Some(Duration::from_secs_f64(
+                (needed - self.available) / self.per_sec,
+            ))
+        }
+    }
+
+    /// 按实际用量修正 (正数补扣, 负数退还)
+    pub fn adjust(&mut self, delta: f64) {
+        self.available = (self.available - delta).min(self.capacity);
+    }
+}
+
+/// 熔断器状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircuitState {
+    Closed,
+    /// 熔断中, 到期时间之前请求直接失败
+    Open {
+        until: Instant,
+    },
+    /// 放行一次试探请求, 试探完成前其他请求直接失败
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: CircuitState,
+    failures: u32,
+    /// 半开状态下试探请求的开始时间
+    probe_started: Option<Instant>,
+}
+
+/// 为 Provider 增加超时、重试、限流、熔断与降级的包装
+///
+/// 限流与服务端错误 (429/5xx/超时) 按指数退避重试, 优先使用服务端给出的 `Retry-After`;
+/// 其他 4xx 直接返回, 不降级。重试耗尽或熔断时转交降级 Provider (如有)。
+pub struct ResilientProvider {
+    inner: Arc<dyn LlmProvider>,
+    fallback: Option<Arc<dyn LlmProvider>>,
+    config: ResilienceConfig,
+    requests: Option<Mutex<TokenBucket>>,
+    tokens: Option<Mutex<TokenBucket>>,
+    breaker: Mutex<Breaker>,
+}
+
+impl ResilientProvider {
+    pub fn new(inner: Arc<dyn LlmProvider>, config: ResilienceConfig) -> Self {
+        Self {
+            inner,
+            fallback: None,
+            requests: config
+                .requests_per_minute
+                .map(|n| Mutex::new(TokenBucket::per_minute(n))),
+            tokens: config
+                .tokens_per_minute
+                .map(|n| Mutex::new(TokenBucket::per_minute(n))),
+            breaker: Mutex::new(Breaker {
+                state: CircuitState::Closed,
+                failures: 0,
+                probe_started: None,
+            }),
+            config,
+        }
+    }
+
+    /// 主 Provider 不可用时使用的降级 Provider
+    pub fn with_fallback(mut self, fallback: Arc<dyn LlmProvider>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// 当前熔断状态
+    pub fn circuit_state(&self) -> CircuitState {
+        self.breaker.lock().unwrap().state
+    }
+
+    async fn acquire(&self, estimated_tokens: f64) {
+        for (bucket, amount) in [(&self.requests, 1.0), (&self.tokens, estimated_tokens)] {
+            let Some(bucket) = bucket else { continue };
+            loop {
+                let wait = bucket.lock().unwrap().try_acquire(amount, Instant::now());
+                match wait {
+                    Some(wait) => tokio::time::sleep(wait).await,
+                    None => break,
+                }
+            }
+        }
+    }
+
+    /// 熔断中返回 `false`; 冷却到期后转为半开, 只放行一个试探请求
+    ///
+    /// 试探请求超过单次超时仍未结束 (如调用方已取消) 时再放行一个。
+    fn allow(&self) -> bool {
+        let mut breaker = self.breaker.lock().unwrap();
+        let now = Instant::now();
+        match breaker.state {
+            CircuitState::Closed => true,
+            CircuitState::Open { until } if now < until => false,
+            CircuitState::Open { .. } => {
+                breaker.state = CircuitState::HalfOpen;
+                breaker.probe_started = Some(now);
+                true
+            }
+            CircuitState::HalfOpen => {
+                let timeout = Duration::from_secs_f64(self.config.timeout_secs);
+                match breaker.probe_started {
+                    Some(started) if now.duration_since(started) < timeout => false,
+                    _ => {
+                        breaker.probe_started = Some(now);
+                        true
+                    }
+                }
+            }
+        }
+    }
+
+    fn record(&self, success: bool) {
+        let mut breaker = self.breaker.lock().unwrap();
+        breaker.probe_started = None;
+        if success {
+            breaker.failures = 0;
+            breaker.state = CircuitState::Closed;
+            return;
+        }
+        breaker.failures += 1;
+        if breaker.state == CircuitState::HalfOpen
+            || breaker.failures >= self.config.failure_threshold
+        {
+            warn!(
+                "Circuit opened for {} after {} failures",
+                self.inner.name(),
+                breaker.failures
+            );
+            breaker.state = CircuitState::Open {
+                until: Instant::now() + Duration::from_secs_f64(self.config.cooldown_secs),
+            };
+        }
+    }
+
+    /// 第 `attempt` 次重试前的等待
+    fn backoff(&self, attempt: u32, err: &anyhow::Error) -> Duration {
+        if let Some(wait) = err
+            .downcast_ref::<LlmHttpError>()
+            .and_then(|e| e.retry_after)
+        {
+            return wait.min(Duration::from_millis(self.config.max_backoff_ms));
+        }
+        let base = self
+            .config
+            .initial_backoff_ms
+            .saturating_mul(1u64 << attempt.min(20))
+            .min(self.config.max_backoff_ms);
+        // 0.5x - 1x 抖动, 避免多个 Agent 同时重试
+        let jitter = rand::thread_rng().gen_range(0.5..=1.0);
+        Duration::from_millis((base as f64 * jitter) as u64)
+    }
+
+    async fn call<T, F, Fut>(
+        &self,
+        req: &ChatRequest,
+        usage: fn(&T) -> Option<u32>,
+        f: F,
+    ) -> Result<T>
+    where
+        F: Fn(Arc<dyn LlmProvider>, ChatRequest) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let estimated = estimate_tokens(req);
+        let timeout = Duration::from_secs_f64(self.config.timeout_secs);
+        let mut attempt = 0;
+
+        let err = loop {
+            if !self.allow() {
+                break anyhow!("Circuit open for {}", self.inner.name());
+            }
+            self.acquire(estimated).await;
+
+            let result =
+                match tokio::time::timeout(timeout, f(self.inner.clone(), req.clone())).await {
+                    Ok(result) => result,
+                    Err(_) => Err(TimeoutError {
+                        provider: self.inner.name().to_string(),
+                        timeout,
+                    }
+                    .into()),
+                };
+
+            match result {
+                Ok(value) => {
+                    self.record(true);
+                    if let (Some(tokens), Some(actual)) = (&self.tokens, usage(&value)) {
+                        tokens.lock().unwrap().adjust(actual as f64 - estimated);
+                    }
+                    return Ok(value);
+                }
+                Err(e) if is_retryable(&e) => {
+                    self.record(false);
+                    if attempt >= self.config.max_retries {
+                        break e;
+                    }
+                    let wait = self.backoff(attempt, &e);
+                    warn!(
+                        "{} failed (attempt {}), retrying in {:?}: {:#}",
+                        self.inner.name(),
+                        attempt + 1,
+                        wait,
+                        e
+                    );
+                    attempt += 1;
+                    tokio::time::sleep(wait).await;
+                }
+                Err(e) => {
+                    // 服务端已正常响应, 错误在请求本身, 不计入熔断也不降级
+                    self.record(true);
+                    return Err(e);
+                }
+            }
+        };
+
+        match &self.fallback {
+            Some(fallback) => {
+                warn!(
+                    "{} unavailable, falling back to {}: {:#}",
+                    self.inner.name(),
+                    fallback.name(),
+                    err
+                );
+                f(fallback.clone(), req.clone()).await
+            }
+            None => Err(err),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{provider} request timed out after {timeout:?}")]
+struct TimeoutError {
+    provider: String,
+    timeout: Duration,
+}
+
+/// 限流、超时、连接失败与服务端错误可重试
+fn is_retryable(err: &anyhow::Error) -> bool {
+    if let Some(e) = err.downcast_ref::<LlmHttpError>() {
+        return e.is_retryable();
+    }
+    if let Some(e) = err.downcast_ref::<reqwest::Error>() {
+        return e.is_timeout() || e.is_connect() || e.is_request();
+    }
+    err.is::<TimeoutError>()
+}
+
+/// 估算请求消耗的 token (约 4 字符一个 token, 加上最大生成数)
+fn estimate_tokens(req: &ChatRequest) -> f64 {
+    let chars: usize = req.messages.iter().map(|m| m.content.chars().count()).sum();
+    (chars / 4) as f64 + req.max_tokens.unwrap_or(0) as f64
+}
+
+#[async_trait]
+impl LlmProvider for ResilientProvider {
+    async fn chat(&self, req: ChatRequest) -> Result<ChatResponse> {
+        self.call(
+            &req,
+            |resp: &ChatResponse| Some(resp.usage.total_tokens),
+            |provider, req| async move { provider.chat(req).await },
+        )
+        .await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatResponse> {
+        self.call(
+            &req,
+            |resp: &ChatResponse| Some(resp.usage.total_tokens),
+            |provider, req| {
+                let tools = tools.clone();
+                async move { provider.chat_with_tools(req, tools).await }
+            },
+        )
+        .await
+    }
+
+    /// 重试与超时仅作用于建立流, 流开始后的错误由调用方处理
+    async fn chat_stream(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatStream> {
+        self.call(
+            &req,
+            |_: &ChatStream| None,
+            |provider, req| {
+                let tools = tools.clone();
+                async move { provider.chat_stream(req, tools).await }
+            },
+        )
+        .await
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{Message, OpenAICompatibleProvider, ScriptStep, ScriptedProvider};
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::{extract::State, routing::post, Json, Router};
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// (状态码, Retry-After, 响应延迟)
+    type Reply = (u16, Option<&'static str>, Duration);
+
+    /// 依次返回预设响应的本地 mock 服务, 用尽后重复最后一个
+    #[derive(Clone)]
+    struct MockServer {
+        replies: Arc<Mutex<VecDeque<Reply>>>,
+        hits: Arc<AtomicUsize>,
+    }
+
+    async fn handle(
+        State(server): State<MockServer>,
+    ) -> (StatusCode, HeaderMap, Json<serde_json::Value>) {
+        server.hits.fetch_add(1, Ordering::SeqCst);
+        let (status, retry_after, delay) = {
+            let mut replies = server.replies.lock().unwrap();
+            if replies.len() > 1 {
+                replies.pop_front().unwrap()
+            } else {
+                replies[0]
+            }
+        };
+        tokio::time::sleep(delay).await;
+
+        let mut headers = HeaderMap::new();
+        if let Some(value) = retry_after {
+            headers.insert("retry-after", value.parse().unwrap());
+        }
+        let body = if status == 200 {
+            serde_json::json!({
+                "choices": [{"message": {"role": "assistant", "content": "ok"}}],
+                "usage": {"prompt_tokens": 3, "completion_tokens": 1, "total_tokens": 4}
+            })
+        } else {
+            serde_json::json!({"error": {"message": "mock error"}})
+        };
+        (StatusCode::from_u16(status).unwrap(), headers, Json(body))
+    }
+
+    async fn mock_provider(replies: Vec<Reply>) -> (Arc<dyn LlmProvider>, Arc<AtomicUsize>) {
+        let server = MockServer {
+            replies: Arc::new(Mutex::new(replies.into())),
+            hits: Arc::new(AtomicUsize::new(0)),
+        };
+        let hits = server.hits.clone();
+        let app = Router::new()
+            .route("/v1/chat/completions", post(handle))
+            .with_state(server);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let provider = OpenAICompatibleProvider::custom(
+            "test-key".to_string(),
+            "mock-model",
+            format!("http://{}/v1", addr),
+            "mock".to_string(),
+        )
+        .unwrap();
+        (Arc::new(provider), hits)
+    }
+
+    fn request() -> ChatRequest {
+        ChatRequest {
+            messages: vec![Message::user("hello")],
+            temperature: None,
+            max_tokens: Some(10),
+        }
+    }
+
+    fn fast_config() -> ResilienceConfig {
+        ResilienceConfig {
+            timeout_secs: 0.2,
+            max_retries: 3,
+            initial_backoff_ms: 5,
+            max_backoff_ms: 50,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_rate_limits_and_server_errors() {
+        let ms = Duration::ZERO;
+        let (inner, hits) = mock_provider(vec![
+            (429, Some("0"), ms),
+            (503, None, ms),
+            (200, None, Duration::from_millis(500)), // 超时
+            (200, None, ms),
+            (400, None, ms),
+        ])
+        .await;
+        let provider = ResilientProvider::new(inner, fast_config());
+
+        let resp = provider.chat(request()).await.unwrap();
+        assert_eq!(resp.content, "ok");
+        assert_eq!(hits.load(Ordering::SeqCst), 4);
+        assert_eq!(provider.circuit_state(), CircuitState::Closed);
+
+        // 400 不重试
+        let err = provider.chat(request()).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<LlmHttpError>().unwrap().status, 400);
+        assert_eq!(hits.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_and_fallback() {
+        let (inner, hits) = mock_provider(vec![(503, Some("0"), Duration::ZERO)]).await;
+        let fallback = Arc::new(ScriptedProvider::new(vec![ScriptStep {
+            content: "fallback".to_string(),
+            repeat: true,
+            ..Default::default()
+        }]));
+        let provider = ResilientProvider::new(
+            inner,
+            ResilienceConfig {
+                max_retries: 1,
+                failure_threshold: 2,
+                cooldown_secs: 60.0,
+                ..fast_config()
+            },
+        )
+        .with_fallback(fallback);
+
+        // 重试耗尽后降级, 连续失败达到阈值熔断
+        let resp = provider.chat(request()).await.unwrap();
+        assert_eq!(resp.content, "fallback");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert!(matches!(
+            provider.circuit_state(),
+            CircuitState::Open { .. }
+        ));
+
+        // 熔断期间不再请求主 Provider
+        let resp = provider.chat(request()).await.unwrap();
+        assert_eq!(resp.content, "fallback");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_probe_and_client_errors() {
+        let (inner, hits) = mock_provider(vec![
+            (503, None, Duration::ZERO),
+            (200, None, Duration::from_millis(50)),
+            (400, None, Duration::ZERO),
+        ])
+        .await;
+        let fallback = Arc::new(ScriptedProvider::new(vec![ScriptStep {
+            content: "fallback".to_string(),
+            repeat: true,
+            ..Default::default()
+        }]));
+        let provider = ResilientProvider::new(
+            inner,
+            ResilienceConfig {
+                max_retries: 0,
+                failure_threshold: 1,
+                cooldown_secs: 0.05,
+                ..fast_config()
+            },
+        )
+        .with_fallback(fallback);
+
+        provider.chat(request()).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+
+        // 半开时并发请求只有一个试探主 Provider, 其余降级
+        let (a, b) = tokio::join!(provider.chat(request()), provider.chat(request()));
+        let mut contents = [a.unwrap().content, b.unwrap().content];
+        contents.sort();
+        assert_eq!(contents, ["fallback", "ok"]);
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(provider.circuit_state(), CircuitState::Closed);
+
+        // 400 直接返回, 不降级
+        let err = provider.chat(request()).await.unwrap_err();
+        assert_eq!(err.downcast_ref::<LlmHttpError>().unwrap().status, 400);
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::per_minute(60);
+        assert!(bucket.try_acquire(60.0, start).is_none());
+        let wait = bucket.try_acquire(2.0, start).unwrap();
+        assert_eq!(wait, Duration::from_secs(2));
+        assert!(bucket
+            .try_acquire(2.0, start + Duration::from_secs(2))
+            .is_none());
+
+        // 实际用量少于预估时退还
+        bucket.adjust(-10.0);
+        assert!(bucket
+            .try_acquire(10.0, start + Duration::from_secs(2))
+            .is_none());
+    }
+}
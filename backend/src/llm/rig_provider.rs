@@ -2,9 +2,15 @@ use anyhow::Result;
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tracing::info;
 
-use super::provider::{ChatRequest, ChatResponse, LlmProvider, Message, TokenUsage, ToolCall};
+use super::error::check_status;
+use super::presets::{self, ProviderPreset};
+use super::provider::{
+    ChatRequest, ChatResponse, ContentBlock, LlmProvider, Message, TokenUsage, ToolCall,
+};
+use super::stream::{sse_stream, AnthropicStreamDecoder, ChatStream, OpenAIStreamDecoder};
 
 /// 直接使用 HTTP 请求的 OpenAI Provider
 pub struct RigOpenAIProvider {
@@ -27,20 +33,31 @@ struct OpenAIRequest {
     tools: Option<Vec<serde_json::Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_choice: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct OpenAIMessage {
     role: String,
-    content: String,
+    /// 文本或多段内容数组; 仅含工具调用的 assistant 回复中为 null
+    #[serde(default)]
+    content: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_calls: Option<Vec<OpenAIToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_call_id: Option<String>,
+    /// DeepSeek / Qwen / Grok 推理模型返回的思考过程, 不回传给模型
+    #[serde(default, skip_serializing)]
+    reasoning_content: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct OpenAIToolCall {
+    /// 部分服务 (Ollama 等) 不返回 id
+    #[serde(default)]
     id: String,
     #[serde(rename = "type")]
     call_type: String,
@@ -50,12 +67,26 @@ struct OpenAIToolCall {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct OpenAIFunction {
     name: String,
+    /// 标准格式为 JSON 字符串, Ollama 可能直接返回对象
+    #[serde(default, deserialize_with = "string_or_json")]
     arguments: String,
 }
 
+fn string_or_json<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::String(s) => s,
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    })
+}
+
 #[derive(Debug, Deserialize)]
 struct OpenAIResponse {
     choices: Vec<OpenAIChoice>,
+    #[serde(default)]
     usage: OpenAIUsage,
 }
 
@@ -64,13 +95,157 @@ struct OpenAIChoice {
     message: OpenAIMessage,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct OpenAIUsage {
     prompt_tokens: u32,
     completion_tokens: u32,
     total_tokens: u32,
 }
 
+impl OpenAIMessage {
+    fn new(role: &str, content: Option<serde_json::Value>) -> Self {
+        Self {
+            role: role.to_string(),
+            content,
+            tool_calls: None,
+            tool_call_id: None,
+            reasoning_content: None,
+        }
+    }
+
+    fn text(&self) -> String {
+        match &self.content {
+            Some(serde_json::Value::String(text)) => text.clone(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// 将消息翻译为 OpenAI 格式
+///
+/// `ToolUse` 块转为 assistant 的 `tool_calls`, 每个 `ToolResult` 块转为一条带
+/// `tool_call_id` 的 `tool` 消息; 图片转为 data URL 的 `image_url` 段。
+fn to_openai_messages(messages: Vec<Message>) -> Vec<OpenAIMessage> {
+    let mut out = Vec::with_capacity(messages.len());
+    for m in messages {
+        // 无结构化内容的 tool 消息缺少配对 id, 退化为 user 文本
+        let role = if m.role == "tool" {
+            "user"
+        } else {
+            m.role.as_str()
+        };
+        if m.blocks.is_empty() {
+            out.push(OpenAIMessage::new(
+                role,
+                Some(serde_json::Value::String(m.content)),
+            ));
+            continue;
+        }
+
+        let mut parts = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in m.blocks {
+            match block {
+                ContentBlock::Text { text } => parts.push(json!({"type": "text", "text": text})),
+                ContentBlock::Image { media_type, data } => parts.push(json!({
+                    "type": "image_url",
+                    "image_url": {"url": format!("data:{};base64,{}", media_type, data)},
+                })),
+                ContentBlock::ToolUse { id, name, input } => tool_calls.push(OpenAIToolCall {
+                    id,
+                    call_type: "function".to_string(),
+                    function: OpenAIFunction {
+                        name,
+                        arguments: input.to_string(),
+                    },
+                }),
+                ContentBlock::ToolResult {
+                    tool_use_id,
+                    content,
+                    ..
+                } => out.push(OpenAIMessage {
+                    tool_call_id: Some(tool_use_id),
+                    ..OpenAIMessage::new("tool", Some(serde_json::Value::String(content)))
+                }),
+            }
+        }
+
+        let content = match parts.as_slice() {
+            [] => None,
+            // 纯文本保持字符串形式, 兼容不支持内容数组的服务
+            [part] if part["type"] == "text" => Some(part["text"].clone()),
+            _ => Some(serde_json::Value::Array(parts)),
+        };
+        if content.is_some() || !tool_calls.is_empty() {
+            out.push(OpenAIMessage {
+                tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+                ..OpenAIMessage::new(role, content)
+            });
+        }
+    }
+    out
+}
+
+/// 转换回复中的工具调用
+///
+/// 缺失或重复的 id 按序号补全为 `call_{i}`, 保证后续 `tool_call_id` 可配对。
+fn openai_tool_calls(calls: Vec<OpenAIToolCall>) -> Vec<ToolCall> {
+    let mut seen = std::collections::HashSet::new();
+    calls
+        .into_iter()
+        .enumerate()
+        .map(|(i, tc)| {
+            let id = if tc.id.is_empty() || !seen.insert(tc.id.clone()) {
+                format!("call_{}", i)
+            } else {
+                tc.id
+            };
+            ToolCall {
+                id,
+                name: tc.function.name,
+                arguments: serde_json::from_str(&tc.function.arguments).unwrap_or(json!({})),
+            }
+        })
+        .collect()
+}
+
+/// 发送流式 `chat/completions` 请求
+async fn openai_chat_stream(
+    client: &Client,
+    base_url: &str,
+    api_key: &str,
+    name: &str,
+    model: &str,
+    req: ChatRequest,
+    tools: Vec<serde_json::Value>,
+) -> Result<ChatStream> {
+    info!("Sending streaming chat request to {}", name);
+
+    let with_tools = !tools.is_empty();
+    let openai_req = OpenAIRequest {
+        model: model.to_string(),
+        messages: to_openai_messages(req.messages),
+        temperature: req.temperature,
+        max_tokens: req.max_tokens,
+        tools: with_tools.then_some(tools),
+        tool_choice: with_tools.then(|| "auto".to_string()),
+        stream: true,
+        stream_options: Some(json!({"include_usage": true})),
+    };
+
+    let response = client
+        .post(format!("{}/chat/completions", base_url))
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .json(&openai_req)
+        .send()
+        .await?;
+
+    let response = check_status(name, response).await?;
+
+    Ok(sse_stream(response, OpenAIStreamDecoder))
+}
+
 impl RigOpenAIProvider {
     pub fn new(api_key: String, model: &str) -> Result<Self> {
         Ok(Self {
@@ -95,20 +270,13 @@ impl LlmProvider for RigOpenAIProvider {
 
         let openai_req = OpenAIRequest {
             model: self.model_name.clone(),
-            messages: req
-                .messages
-                .into_iter()
-                .map(|m| OpenAIMessage {
-                    role: m.role,
-                    content: m.content,
-                    tool_calls: None,
-                    tool_call_id: None,
-                })
-                .collect(),
+            messages: to_openai_messages(req.messages),
             temperature: req.temperature,
             max_tokens: req.max_tokens,
             tools: None,
             tool_choice: None,
+            stream: false,
+            stream_options: None,
         };
 
         let response = self
@@ -120,11 +288,7 @@ impl LlmProvider for RigOpenAIProvider {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            anyhow::bail!("OpenAI API error ({}): {}", status, error_text);
-        }
+        let response = check_status("OpenAI", response).await?;
 
         let openai_resp: OpenAIResponse = response.json().await?;
 
@@ -135,7 +299,8 @@ impl LlmProvider for RigOpenAIProvider {
             .ok_or_else(|| anyhow::anyhow!("No response from OpenAI"))?;
 
         Ok(ChatResponse {
-            content: choice.message.content,
+            content: choice.message.text(),
+            reasoning: choice.message.reasoning_content,
             tool_calls: None,
             usage: TokenUsage {
                 prompt_tokens: openai_resp.usage.prompt_tokens,
@@ -154,20 +319,13 @@ impl LlmProvider for RigOpenAIProvider {
 
         let openai_req = OpenAIRequest {
             model: self.model_name.clone(),
-            messages: req
-                .messages
-                .into_iter()
-                .map(|m| OpenAIMessage {
-                    role: m.role,
-                    content: m.content,
-                    tool_calls: None,
-                    tool_call_id: None,
-                })
-                .collect(),
+            messages: to_openai_messages(req.messages),
             temperature: req.temperature,
             max_tokens: req.max_tokens,
             tools: Some(tools),
             tool_choice: Some("auto".to_string()),
+            stream: false,
+            stream_options: None,
         };
 
         let response = self
@@ -179,11 +337,7 @@ impl LlmProvider for RigOpenAIProvider {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            anyhow::bail!("OpenAI API error ({}): {}", status, error_text);
-        }
+        let response = check_status("OpenAI", response).await?;
 
         let openai_resp: OpenAIResponse = response.json().await?;
 
@@ -192,24 +346,18 @@ impl LlmProvider for RigOpenAIProvider {
             .choices
             .first()
             .and_then(|choice| choice.message.tool_calls.clone())
-            .map(|calls| {
-                calls
-                    .into_iter()
-                    .map(|tc| ToolCall {
-                        id: tc.id,
-                        name: tc.function.name,
-                        arguments: serde_json::from_str(&tc.function.arguments)
-                            .unwrap_or(serde_json::json!({})),
-                    })
-                    .collect()
-            });
+            .map(openai_tool_calls);
 
         Ok(ChatResponse {
             content: openai_resp
                 .choices
                 .first()
-                .map(|c| c.message.content.clone())
+                .map(|c| c.message.text())
                 .unwrap_or_default(),
+            reasoning: openai_resp
+                .choices
+                .first()
+                .and_then(|c| c.message.reasoning_content.clone()),
             tool_calls,
             usage: TokenUsage {
                 prompt_tokens: openai_resp.usage.prompt_tokens,
@@ -219,6 +367,23 @@ impl LlmProvider for RigOpenAIProvider {
         })
     }
 
+    async fn chat_stream(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatStream> {
+        openai_chat_stream(
+            &self.client,
+            &self.base_url,
+            &self.api_key,
+            &self.name,
+            &self.model_name,
+            req,
+            tools,
+        )
+        .await
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -247,12 +412,14 @@ struct AnthropicRequest {
     system: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize)]
 struct AnthropicMessage {
     role: String,
-    content: String,
+    content: Vec<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -280,6 +447,106 @@ struct AnthropicUsage {
     output_tokens: u32,
 }
 
+/// 将 function calling 格式的工具定义转换为 Anthropic 的 `input_schema` 格式
+fn to_anthropic_tools(tools: Vec<serde_json::Value>) -> Vec<serde_json::Value> {
+    tools
+        .into_iter()
+        .map(|tool| match function_declaration(&tool) {
+            Some((name, description, parameters)) => json!({
+                "name": name,
+                "description": description,
+                "input_schema": parameters,
+            }),
+            None => tool,
+        })
+        .collect()
+}
+
+/// 从 `{"type": "function", "function": {...}}`、扁平的 `{name, description, parameters}`
+/// 或 Anthropic 格式中提取 (name, description, parameters)
+pub(crate) fn function_declaration(
+    tool: &serde_json::Value,
+) -> Option<(String, String, serde_json::Value)> {
+    let function = tool.get("function").unwrap_or(tool);
+    let name = function.get("name")?.as_str()?.to_string();
+    let description = function["description"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let parameters = function
+        .get("parameters")
+        .or_else(|| function.get("input_schema"))
+        .cloned()
+        .unwrap_or_else(|| json!({"type": "object", "properties": {}}));
+    Some((name, description, parameters))
+}
+
+/// 将消息翻译为 Anthropic 格式, 返回 (system, messages)
+///
+/// 内容统一使用块数组: assistant 携带 `tool_use`, 工具结果作为 user 消息中的
+/// `tool_result` 块; 相邻同角色消息合并, 满足 user/assistant 交替的要求。
+fn to_anthropic_messages(messages: Vec<Message>) -> (Option<String>, Vec<AnthropicMessage>) {
+    let mut system = Vec::new();
+    let mut out: Vec<AnthropicMessage> = Vec::new();
+    for m in messages {
+        if m.role == "system" {
+            system.push(m.content);
+            continue;
+        }
+        let role = if m.role == "assistant" {
+            "assistant"
+        } else {
+            "user"
+        };
+        let content: Vec<serde_json::Value> = if m.blocks.is_empty() {
+            vec![json!({"type": "text", "text": m.content})]
+        } else {
+            m.blocks
+                .into_iter()
+                .map(|block| match block {
+                    ContentBlock::Text { text } => json!({"type": "text", "text": text}),
+                    ContentBlock::ToolUse { id, name, input } => {
+                        json!({"type": "tool_use", "id": id, "name": name, "input": input})
+                    }
+                    ContentBlock::ToolResult {
+                        tool_use_id,
+                        content,
+                        is_error,
+                    } => json!({
+                        "type": "tool_result",
+                        "tool_use_id": tool_use_id,
+                        "content": content,
+                        "is_error": is_error,
+                    }),
+                    ContentBlock::Image { media_type, data } => json!({
+                        "type": "image",
+                        "source": {"type": "base64", "media_type": media_type, "data": data},
+                    }),
+                })
+                .collect()
+        };
+        // Anthropic 拒绝空文本块
+        let content: Vec<_> = content
+            .into_iter()
+            .filter(|block| block["type"] != "text" || block["text"] != "")
+            .collect();
+        if content.is_empty() {
+            continue;
+        }
+
+        match out.last_mut() {
+            Some(last) if last.role == role => last.content.extend(content),
+            _ => out.push(AnthropicMessage {
+                role: role.to_string(),
+                content,
+            }),
+        }
+    }
+
+    let system = (!system.is_empty()).then(|| system.join("\n\n"));
+    (system, out)
+}
+
 impl AnthropicProvider {
     pub fn new(api_key: String, model: &str) -> Result<Self> {
         Ok(Self {
@@ -296,28 +563,17 @@ impl LlmProvider for AnthropicProvider {
     async fn chat(&self, req: ChatRequest) -> Result<ChatResponse> {
         info!("Sending chat request to {}", self.name);
 
-        // 提取 system message
-        let system_msg = req
-            .messages
-            .iter()
-            .find(|m| m.role == "system")
-            .map(|m| m.content.clone());
+        // Anthropic 单独处理 system
+        let (system_msg, messages) = to_anthropic_messages(req.messages);
 
         let anthropic_req = AnthropicRequest {
             model: self.model_name.clone(),
-            messages: req
-                .messages
-                .into_iter()
-                .filter(|m| m.role != "system") // Anthropic 单独处理 system
-                .map(|m| AnthropicMessage {
-                    role: m.role,
-                    content: m.content,
-                })
-                .collect(),
+            messages,
             max_tokens: req.max_tokens.unwrap_or(4096),
             temperature: req.temperature,
             system: system_msg,
             tools: None,
+            stream: false,
         };
 
         let response = self
@@ -330,11 +586,7 @@ impl LlmProvider for AnthropicProvider {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            anyhow::bail!("Anthropic API error ({}): {}", status, error_text);
-        }
+        let response = check_status("Anthropic", response).await?;
 
         let anthropic_resp: AnthropicResponse = response.json().await?;
 
@@ -351,6 +603,7 @@ impl LlmProvider for AnthropicProvider {
 
         Ok(ChatResponse {
             content,
+            reasoning: None,
             tool_calls: None,
             usage: TokenUsage {
                 prompt_tokens: anthropic_resp.usage.input_tokens,
@@ -368,28 +621,17 @@ impl LlmProvider for AnthropicProvider {
     ) -> Result<ChatResponse> {
         info!("Sending chat request with tools to {}", self.name);
 
-        // 提取 system message
-        let system_msg = req
-            .messages
-            .iter()
-            .find(|m| m.role == "system")
-            .map(|m| m.content.clone());
+        // Anthropic 单独处理 system
+        let (system_msg, messages) = to_anthropic_messages(req.messages);
 
         let anthropic_req = AnthropicRequest {
             model: self.model_name.clone(),
-            messages: req
-                .messages
-                .into_iter()
-                .filter(|m| m.role != "system")
-                .map(|m| AnthropicMessage {
-                    role: m.role,
-                    content: m.content,
-                })
-                .collect(),
+            messages,
             max_tokens: req.max_tokens.unwrap_or(4096),
             temperature: req.temperature,
             system: system_msg,
-            tools: Some(tools),
+            tools: Some(to_anthropic_tools(tools)),
+            stream: false,
         };
 
         let response = self
@@ -402,11 +644,7 @@ impl LlmProvider for AnthropicProvider {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            anyhow::bail!("Anthropic API error ({}): {}", status, error_text);
-        }
+        let response = check_status("Anthropic", response).await?;
 
         let anthropic_resp: AnthropicResponse = response.json().await?;
 
@@ -431,6 +669,7 @@ impl LlmProvider for AnthropicProvider {
 
         Ok(ChatResponse {
             content: content_texts.join("\n"),
+            reasoning: None,
             tool_calls: if tool_calls.is_empty() {
                 None
             } else {
@@ -445,6 +684,39 @@ impl LlmProvider for AnthropicProvider {
         })
     }
 
+    async fn chat_stream(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatStream> {
+        info!("Sending streaming chat request to {}", self.name);
+
+        let (system_msg, messages) = to_anthropic_messages(req.messages);
+        let anthropic_req = AnthropicRequest {
+            model: self.model_name.clone(),
+            messages,
+            max_tokens: req.max_tokens.unwrap_or(4096),
+            temperature: req.temperature,
+            system: system_msg,
+            tools: (!tools.is_empty()).then(|| to_anthropic_tools(tools)),
+            stream: true,
+        };
+
+        let response = self
+            .client
+            .post("https://api.anthropic.com/v1/messages")
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("Content-Type", "application/json")
+            .json(&anthropic_req)
+            .send()
+            .await?;
+
+        let response = check_status("Anthropic", response).await?;
+
+        Ok(sse_stream(response, AnthropicStreamDecoder))
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -454,7 +726,7 @@ impl LlmProvider for AnthropicProvider {
     }
 }
 
-/// 通用的 OpenAI 兼容 Provider (支持 DeepSeek, Qwen, etc.)
+/// 通用的 OpenAI 兼容 Provider (支持 DeepSeek, Qwen, Grok, Ollama 等)
 pub struct OpenAICompatibleProvider {
     name: String,
     client: Client,
@@ -464,24 +736,34 @@ pub struct OpenAICompatibleProvider {
 }
 
 impl OpenAICompatibleProvider {
-    pub fn deepseek(api_key: String, model: &str) -> Result<Self> {
+    /// 按预设创建, 预设见 [`super::PRESETS`]
+    pub fn from_preset(preset: &ProviderPreset, api_key: String, model: &str) -> Result<Self> {
         Ok(Self {
-            name: format!("deepseek-{}", model),
+            name: format!("{}-{}", preset.name, model),
             client: Client::new(),
             api_key,
             model_name: model.to_string(),
-            base_url: "https://api.deepseek.com/v1".to_string(),
+            base_url: preset.base_url.to_string(),
         })
     }
 
+    pub fn deepseek(api_key: String, model: &str) -> Result<Self> {
+        Self::from_preset(&presets::DEEPSEEK, api_key, model)
+    }
+
+    /// 阿里云 DashScope 兼容模式
     pub fn qwen(api_key: String, model: &str) -> Result<Self> {
-        Ok(Self {
-            name: format!("qwen-{}", model),
-            client: Client::new(),
-            api_key,
-            model_name: model.to_string(),
-            base_url: "https://dashscope.aliyuncs.com/compatible-mode/v1".to_string(),
-        })
+        Self::from_preset(&presets::QWEN, api_key, model)
+    }
+
+    /// xAI Grok
+    pub fn xai(api_key: String, model: &str) -> Result<Self> {
+        Self::from_preset(&presets::XAI, api_key, model)
+    }
+
+    /// 本地 Ollama, 无需 API Key
+    pub fn ollama(model: &str) -> Result<Self> {
+        Self::from_preset(&presets::OLLAMA, String::new(), model)
     }
 
     pub fn custom(api_key: String, model: &str, base_url: String, name: String) -> Result<Self> {
@@ -493,6 +775,11 @@ impl OpenAICompatibleProvider {
             base_url,
         })
     }
+
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
 }
 
 #[async_trait]
@@ -502,20 +789,13 @@ impl LlmProvider for OpenAICompatibleProvider {
 
         let openai_req = OpenAIRequest {
             model: self.model_name.clone(),
-            messages: req
-                .messages
-                .into_iter()
-                .map(|m| OpenAIMessage {
-                    role: m.role,
-                    content: m.content,
-                    tool_calls: None,
-                    tool_call_id: None,
-                })
-                .collect(),
+            messages: to_openai_messages(req.messages),
             temperature: req.temperature,
             max_tokens: req.max_tokens,
             tools: None,
             tool_choice: None,
+            stream: false,
+            stream_options: None,
         };
 
         let response = self
@@ -527,11 +807,7 @@ impl LlmProvider for OpenAICompatibleProvider {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            anyhow::bail!("{} API error ({}): {}", self.name, status, error_text);
-        }
+        let response = check_status(&self.name, response).await?;
 
         let openai_resp: OpenAIResponse = response.json().await?;
 
@@ -542,7 +818,8 @@ impl LlmProvider for OpenAICompatibleProvider {
             .ok_or_else(|| anyhow::anyhow!("No response from {}", self.name))?;
 
         Ok(ChatResponse {
-            content: choice.message.content,
+            content: choice.message.text(),
+            reasoning: choice.message.reasoning_content,
             tool_calls: None,
             usage: TokenUsage {
                 prompt_tokens: openai_resp.usage.prompt_tokens,
@@ -561,20 +838,13 @@ impl LlmProvider for OpenAICompatibleProvider {
 
         let openai_req = OpenAIRequest {
             model: self.model_name.clone(),
-            messages: req
-                .messages
-                .into_iter()
-                .map(|m| OpenAIMessage {
-                    role: m.role,
-                    content: m.content,
-                    tool_calls: None,
-                    tool_call_id: None,
-                })
-                .collect(),
+            messages: to_openai_messages(req.messages),
             temperature: req.temperature,
             max_tokens: req.max_tokens,
             tools: Some(tools),
             tool_choice: Some("auto".to_string()),
+            stream: false,
+            stream_options: None,
         };
 
         let response = self
@@ -586,11 +856,7 @@ impl LlmProvider for OpenAICompatibleProvider {
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await?;
-            anyhow::bail!("{} API error ({}): {}", self.name, status, error_text);
-        }
+        let response = check_status(&self.name, response).await?;
 
         let openai_resp: OpenAIResponse = response.json().await?;
 
@@ -599,24 +865,18 @@ impl LlmProvider for OpenAICompatibleProvider {
             .choices
             .first()
             .and_then(|choice| choice.message.tool_calls.clone())
-            .map(|calls| {
-                calls
-                    .into_iter()
-                    .map(|tc| ToolCall {
-                        id: tc.id,
-                        name: tc.function.name,
-                        arguments: serde_json::from_str(&tc.function.arguments)
-                            .unwrap_or(serde_json::json!({})),
-                    })
-                    .collect()
-            });
+            .map(openai_tool_calls);
 
         Ok(ChatResponse {
             content: openai_resp
                 .choices
                 .first()
-                .map(|c| c.message.content.clone())
+                .map(|c| c.message.text())
                 .unwrap_or_default(),
+            reasoning: openai_resp
+                .choices
+                .first()
+                .and_then(|c| c.message.reasoning_content.clone()),
             tool_calls,
             usage: TokenUsage {
                 prompt_tokens: openai_resp.usage.prompt_tokens,
@@ -626,6 +886,23 @@ impl LlmProvider for OpenAICompatibleProvider {
         })
     }
 
+    async fn chat_stream(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatStream> {
+        openai_chat_stream(
+            &self.client,
+            &self.base_url,
+            &self.api_key,
+            &self.name,
+            &self.model_name,
+            req,
+            tools,
+        )
+        .await
+    }
+
     fn name(&self) -> &str {
         &self.name
     }
@@ -646,10 +923,7 @@ mod tests {
         let provider = RigOpenAIProvider::new(api_key, "gpt-4").unwrap();
 
         let request = ChatRequest {
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: "Say hello!".to_string(),
-            }],
+            messages: vec![Message::user("Say hello!")],
             temperature: Some(0.7),
             max_tokens: Some(100),
         };
@@ -657,4 +931,95 @@ mod tests {
         let response = provider.chat(request).await.unwrap();
         assert!(!response.content.is_empty());
     }
+
+    fn tool_dialogue() -> Vec<Message> {
+        let call = ToolCall {
+            id: "call_1".to_string(),
+            name: "get_price".to_string(),
+            arguments: json!({"symbol": "BTCUSDT"}),
+        };
+        vec![
+            Message::system("You are a trader"),
+            Message::user("Check BTC").with_image("image/png", "aGVsbG8="),
+            Message::assistant_with_tool_calls("", &[call]),
+            Message::tool_result("call_1", "price: 50000", false),
+            Message::user("Now decide"),
+        ]
+    }
+
+    #[test]
+    fn test_anthropic_tool_round_trip_blocks() {
+        let (system, messages) = to_anthropic_messages(tool_dialogue());
+        assert_eq!(system.as_deref(), Some("You are a trader"));
+        // tool 结果与随后的 user 文本合并为同一条 user 消息
+        let roles: Vec<_> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["user", "assistant", "user"]);
+        assert_eq!(messages[0].content[1]["source"]["media_type"], "image/png");
+        assert_eq!(
+            messages[1].content,
+            vec![
+                json!({"type": "tool_use", "id": "call_1", "name": "get_price", "input": {"symbol": "BTCUSDT"}})
+            ]
+        );
+        assert_eq!(messages[2].content[0]["type"], "tool_result");
+        assert_eq!(messages[2].content[0]["tool_use_id"], "call_1");
+        assert_eq!(messages[2].content[1]["text"], "Now decide");
+    }
+
+    #[test]
+    fn test_openai_tool_call_pairs() {
+        let messages = to_openai_messages(tool_dialogue());
+        let roles: Vec<_> = messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, vec!["system", "user", "assistant", "tool", "user"]);
+        assert_eq!(
+            messages[1].content.as_ref().unwrap()[1]["image_url"]["url"],
+            "data:image/png;base64,aGVsbG8="
+        );
+        assert!(messages[2].content.is_none());
+        let calls = messages[2].tool_calls.as_ref().unwrap();
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(calls[0].function.arguments, r#"{"symbol":"BTCUSDT"}"#);
+        assert_eq!(messages[3].tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(messages[3].text(), "price: 50000");
+
+        // 仅含工具调用的回复 content 为 null
+        let reply: OpenAIMessage = serde_json::from_value(json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{"id": "call_2", "type": "function", "function": {"name": "get_price", "arguments": "{}"}}],
+        }))
+        .unwrap();
+        assert_eq!(reply.text(), "");
+    }
+
+    #[test]
+    fn test_openai_compatible_quirks() {
+        // DeepSeek reasoning_content 与 Ollama 无 id / 对象参数的工具调用
+        let resp: OpenAIResponse = serde_json::from_value(json!({
+            "choices": [{"message": {
+                "role": "assistant",
+                "content": "",
+                "reasoning_content": "BTC looks strong",
+                "tool_calls": [
+                    {"type": "function", "function": {"name": "get_price", "arguments": {"symbol": "BTC"}}},
+                    {"id": "", "type": "function", "function": {"name": "get_price", "arguments": "{\"symbol\":\"ETH\"}"}}
+                ]
+            }}]
+        }))
+        .unwrap();
+        let message = &resp.choices[0].message;
+        assert_eq!(
+            message.reasoning_content.as_deref(),
+            Some("BTC looks strong")
+        );
+        let calls = openai_tool_calls(message.tool_calls.clone().unwrap());
+        assert_eq!(calls[0].id, "call_0");
+        assert_eq!(calls[0].arguments["symbol"], "BTC");
+        assert_eq!(calls[1].id, "call_1");
+        assert_eq!(calls[1].arguments["symbol"], "ETH");
+
+        // 思考过程不回传
+        let sent = serde_json::to_value(message).unwrap();
+        assert!(sent.get("reasoning_content").is_none());
+    }
 }
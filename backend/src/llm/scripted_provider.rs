@@ -0,0 +1,429 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, info};
+
+use super::provider::{ChatRequest, ChatResponse, LlmProvider, Message, TokenUsage, ToolCall};
+
+/// 脚本文件（YAML 格式）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Script {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    /// 默认延迟（毫秒）
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    pub steps: Vec<ScriptStep>,
+}
+
+/// 脚本中的一步回复
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptStep {
+    /// 匹配条件, 为空时匹配任意请求
+    #[serde(default, rename = "match")]
+    pub matcher: Option<MessageMatcher>,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub tool_calls: Vec<ScriptedToolCall>,
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
+    /// 本步延迟（毫秒）, 覆盖脚本默认值
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    /// 模拟 HTTP 错误
+    #[serde(default)]
+    pub error: Option<ScriptedError>,
+    /// 是否可重复使用（不被消耗）
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+/// 消息匹配条件
+///
+/// 检查最近一条消息（指定 `role` 时为该角色的最近一条）是否包含 `contains`。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageMatcher {
+    pub contains: String,
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+impl MessageMatcher {
+    pub fn matches(&self, messages: &[Message]) -> bool {
+        messages
+            .iter()
+            .rev()
+            .find(|m| self.role.as_ref().is_none_or(|r| &m.role == r))
+            .is_some_and(|m| m.content.contains(&self.contains))
+    }
+}
+
+/// 脚本中的工具调用
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScriptedToolCall {
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+    /// 原始参数字符串; 无法解析为 JSON 时原样作为字符串传给工具, 用于模拟畸形参数
+    #[serde(default)]
+    pub raw_arguments: Option<String>,
+}
+
+/// 模拟的 HTTP 错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptedError {
+    pub status: u16,
+    #[serde(default)]
+    pub message: String,
+}
+
+/// Provider 收到的一次请求
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub request: ChatRequest,
+    pub tools: Vec<serde_json::Value>,
+    /// 命中的脚本步骤序号
+    pub step: Option<usize>,
+}
+
+struct ScriptState {
+    consumed: Vec<bool>,
+    requests: Vec<RecordedRequest>,
+}
+
+/// 按脚本回放回复的 Provider, 用于离线测试与演示
+///
+/// 每次请求选择第一个未消耗且匹配的步骤; 没有步骤匹配时返回错误。
+pub struct ScriptedProvider {
+    name: String,
+    model_name: String,
+    latency: Duration,
+    steps: Vec<ScriptStep>,
+    state: Mutex<ScriptState>,
+}
+
+impl ScriptedProvider {
+    pub fn new(steps: Vec<ScriptStep>) -> Self {
+        Self::from_script(Script {
+            steps,
+            ..Default::default()
+        })
+    }
+
+    pub fn from_script(script: Script) -> Self {
+        Self {
+            name: script.name.unwrap_or_else(|| "scripted".to_string()),
+            model_name: script.model.unwrap_or_else(|| "scripted".to_string()),
+            latency: Duration::from_millis(script.latency_ms.unwrap_or(0)),
+            state: Mutex::new(ScriptState {
+                consumed: vec![false; script.steps.len()],
+                requests: Vec::new(),
+            }),
+            steps: script.steps,
+        }
+    }
+
+    /// 从 YAML 脚本加载
+    pub fn from_yaml_str(yaml: &str) -> Result<Self> {
+        let script: Script = serde_yaml::from_str(yaml).context("Invalid YAML script")?;
+        Ok(Self::from_script(script))
+    }
+
+    /// 从 JSONL 脚本加载, 每行一个步骤
+    pub fn from_jsonl_str(jsonl: &str) -> Result<Self> {
+        let steps = jsonl
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| format!("Invalid script line {}", i + 1))
+            })
+            .collect::<Result<Vec<ScriptStep>>>()?;
+        Ok(Self::new(steps))
+    }
+
+    /// 按扩展名加载脚本文件（.jsonl 或 .yaml/.yml）
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read script {}", path.display()))?;
+
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("jsonl") => Self::from_jsonl_str(&content),
+            _ => Self::from_yaml_str(&content),
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model_name = model.into();
+        self
+    }
+
+    /// 设置默认延迟
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// 已收到的所有请求
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// 尚未消耗的一次性步骤数
+    pub fn remaining(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        self.steps
+            .iter()
+            .zip(&state.consumed)
+            .filter(|(step, consumed)| !step.repeat && !**consumed)
+            .count()
+    }
+
+    /// 选择匹配的步骤并记录请求, 返回步骤序号与请求编号
+    fn select_step(
+        &self,
+        req: &ChatRequest,
+        tools: &[serde_json::Value],
+    ) -> (Option<usize>, usize) {
+        let mut state = self.state.lock().unwrap();
+
+        let index = self.steps.iter().enumerate().position(|(i, step)| {
+            !state.consumed[i]
+                && step
+                    .matcher
+                    .as_ref()
+                    .is_none_or(|m| m.matches(&req.messages))
+        });
+
+        if let Some(i) = index {
+            if !self.steps[i].repeat {
+                state.consumed[i] = true;
+            }
+        }
+
+        state.requests.push(RecordedRequest {
+            request: req.clone(),
+            tools: tools.to_vec(),
+            step: index,
+        });
+
+        (index, state.requests.len())
+    }
+
+    async fn respond(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatResponse> {
+        let (index, request_no) = self.select_step(&req, &tools);
+
+        let Some(index) = index else {
+            anyhow::bail!(
+                "{} script has no response matching request #{}",
+                self.name,
+                request_no
+            );
+        };
+        let step = &self.steps[index];
+        debug!(
+            "{} replaying step {} for request #{}",
+            self.name, index, request_no
+        );
+
+        let latency = step
+            .latency_ms
+            .map(Duration::from_millis)
+            .unwrap_or(self.latency);
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+
+        if let Some(error) = &step.error {
+            anyhow::bail!(
+                "{} API error ({}): {}",
+                self.name,
+                error.status,
+                error.message
+            );
+        }
+
+        let tool_calls = (!step.tool_calls.is_empty()).then(|| {
+            step.tool_calls
+                .iter()
+                .enumerate()
+                .map(|(i, call)| ToolCall {
+                    id: call
+                        .id
+                        .clone()
+                        .unwrap_or_else(|| format!("call_{}_{}", request_no, i)),
+                    name: call.name.clone(),
+                    arguments: match &call.raw_arguments {
+                        Some(raw) => serde_json::from_str(raw)
+                            .unwrap_or_else(|_| serde_json::Value::String(raw.clone())),
+                        None => call.arguments.clone(),
+                    },
+                })
+                .collect()
+        });
+
+        let usage = step.usage.clone().unwrap_or_else(|| {
+            let prompt_tokens = estimate_tokens(req.messages.iter().map(|m| m.content.as_str()));
+            let completion_tokens = estimate_tokens(std::iter::once(step.content.as_str()));
+            TokenUsage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            }
+        });
+
+        Ok(ChatResponse {
+            content: step.content.clone(),
+            reasoning: None,
+            tool_calls,
+            usage,
+        })
+    }
+}
+
+/// 粗略估算 token 数（约 4 字符一个 token）
+fn estimate_tokens<'a>(texts: impl Iterator<Item = &'a str>) -> u32 {
+    let chars: usize = texts.map(|t| t.chars().count()).sum();
+    chars.div_ceil(4) as u32
+}
+
+#[async_trait]
+impl LlmProvider for ScriptedProvider {
+    async fn chat(&self, req: ChatRequest) -> Result<ChatResponse> {
+        info!("Sending chat request to {}", self.name);
+        self.respond(req, Vec::new()).await
+    }
+
+    async fn chat_with_tools(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatResponse> {
+        info!("Sending chat request with tools to {}", self.name);
+        self.respond(req, tools).await
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn model(&self) -> &str {
+        &self.model_name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(messages: &[(&str, &str)]) -> ChatRequest {
+        ChatRequest {
+            messages: messages
+                .iter()
+                .map(|(role, content)| Message::new(*role, *content))
+                .collect(),
+            temperature: None,
+            max_tokens: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replays_steps_by_pattern() {
+        let provider = ScriptedProvider::from_yaml_str(
+            r#"
+name: fake
+model: fake-1
+steps:
+  - match: { contains: "rejected", role: tool }
+    content: "Order was rejected, holding"
+  - content: "Buying"
+    tool_calls:
+      - name: place_order
+        arguments: { symbol: BTCUSDT, side: buy, quantity: 0.1 }
+  - content: "fallback"
+    repeat: true
+"#,
+        )
+        .unwrap();
+        assert_eq!(provider.name(), "fake");
+        assert_eq!(provider.model(), "fake-1");
+
+        let first = provider
+            .chat_with_tools(request(&[("user", "trade")]), vec![serde_json::json!({})])
+            .await
+            .unwrap();
+        assert_eq!(first.content, "Buying");
+        let calls = first.tool_calls.unwrap();
+        assert_eq!(calls[0].name, "place_order");
+        assert_eq!(calls[0].arguments["symbol"], "BTCUSDT");
+
+        let second = provider
+            .chat(request(&[("user", "trade"), ("tool", "status: rejected")]))
+            .await
+            .unwrap();
+        assert_eq!(second.content, "Order was rejected, holding");
+        assert!(second.tool_calls.is_none());
+
+        // 一次性步骤耗尽后命中可重复步骤
+        for _ in 0..2 {
+            let resp = provider.chat(request(&[("user", "again")])).await.unwrap();
+            assert_eq!(resp.content, "fallback");
+        }
+        assert_eq!(provider.remaining(), 0);
+
+        let requests = provider.requests();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0].step, Some(1));
+        assert_eq!(requests[0].tools.len(), 1);
+        assert_eq!(requests[1].step, Some(0));
+        assert_eq!(requests[1].request.messages.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_simulated_failures() {
+        let provider = ScriptedProvider::from_jsonl_str(
+            r#"{"error": {"status": 429, "message": "rate limited"}}
+
+{"tool_calls": [{"name": "place_order", "raw_arguments": "{\"symbol\": \"BTC"}]}
+{"content": "slow", "latency_ms": 30}
+"#,
+        )
+        .unwrap();
+
+        let err = provider.chat(request(&[("user", "hi")])).await.unwrap_err();
+        assert!(err.to_string().contains("(429)"));
+
+        let resp = provider.chat(request(&[("user", "hi")])).await.unwrap();
+        let args = &resp.tool_calls.unwrap()[0].arguments;
+        assert_eq!(args, &serde_json::json!("{\"symbol\": \"BTC"));
+
+        let started = std::time::Instant::now();
+        let resp = provider.chat(request(&[("user", "hi")])).await.unwrap();
+        assert_eq!(resp.content, "slow");
+        assert!(started.elapsed() >= Duration::from_millis(30));
+        assert!(resp.usage.total_tokens > 0);
+
+        // 脚本耗尽
+        assert!(provider.chat(request(&[("user", "hi")])).await.is_err());
+        assert_eq!(provider.requests().len(), 4);
+        assert_eq!(provider.requests()[3].step, None);
+    }
+}
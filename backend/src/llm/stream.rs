@@ -0,0 +1,498 @@
+// 流式对话: SSE 解析与增量聚合
+// Streaming chat responses
+
+use std::collections::BTreeMap;
+use std::pin::Pin;
+
+use anyhow::{anyhow, Result};
+use futures_util::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio_util::sync::CancellationToken;
+
+use super::provider::{ChatResponse, TokenUsage, ToolCall};
+
+/// 流式回复中的一个增量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamDelta {
+    /// 文本片段
+    Text { text: String },
+    /// 思考过程片段
+    Reasoning { text: String },
+    /// 工具调用增量; `id`/`name` 仅在该调用的首个增量中出现, `arguments` 为 JSON 参数片段
+    ToolCall {
+        index: usize,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        arguments: String,
+    },
+    /// Token 用量, 字段为 0 表示本次事件未提供该项
+    Usage(TokenUsage),
+    /// 生成结束
+    Stop {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+}
+
+/// 增量流; 丢弃即中止生成并关闭底层连接
+pub type ChatStream = Pin<Box<dyn Stream<Item = Result<StreamDelta>> + Send>>;
+
+// ============================================================================
+// SSE
+// ============================================================================
+
+/// 一条 SSE 事件
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub data: String,
+}
+
+/// 增量 SSE 解析器, 可跨网络分块喂入字节
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 喂入一段字节, 返回已完整的事件
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(SseEvent {
+                        event: self.event.take(),
+                        data: self.data.join("\n"),
+                    });
+                    self.data.clear();
+                }
+                self.event = None;
+                continue;
+            }
+            if line.starts_with(':') {
+                continue; // 注释 / 心跳
+            }
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+/// 将 SSE 事件解码为增量
+pub trait StreamDecoder: Send + 'static {
+    fn decode(&mut self, event: &SseEvent) -> Result<Vec<StreamDelta>>;
+}
+
+/// OpenAI 兼容接口 (`chat/completions` + `stream: true`) 的事件解码
+#[derive(Debug, Default)]
+pub struct OpenAIStreamDecoder;
+
+impl StreamDecoder for OpenAIStreamDecoder {
+    fn decode(&mut self, event: &SseEvent) -> Result<Vec<StreamDelta>> {
+        if event.data.trim() == "[DONE]" {
+            return Ok(Vec::new());
+        }
+        let chunk: Value = serde_json::from_str(&event.data)
+            .map_err(|e| anyhow!("Invalid stream chunk: {} ({})", e, event.data))?;
+        if let Some(error) = chunk.get("error") {
+            return Err(anyhow!("Stream error: {}", error));
+        }
+
+        let mut deltas = Vec::new();
+        if let Some(choice) = chunk["choices"].get(0) {
+            let delta = &choice["delta"];
+            if let Some(text) = delta["reasoning_content"]
+                .as_str()
+                .filter(|t| !t.is_empty())
+            {
+                deltas.push(StreamDelta::Reasoning {
+                    text: text.to_string(),
+                });
+            }
+            if let Some(text) = delta["content"].as_str().filter(|t| !t.is_empty()) {
+                deltas.push(StreamDelta::Text {
+                    text: text.to_string(),
+                });
+            }
+            for call in delta["tool_calls"].as_array().into_iter().flatten() {
+                deltas.push(StreamDelta::ToolCall {
+                    index: call["index"].as_u64().unwrap_or(0) as usize,
+                    id: call["id"].as_str().map(String::from),
+                    name: call["function"]["name"].as_str().map(String::from),
+                    arguments: call["function"]["arguments"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                });
+            }
+            if let Some(reason) = choice["finish_reason"].as_str() {
+                deltas.push(StreamDelta::Stop {
+                    reason: Some(reason.to_string()),
+                });
+            }
+        }
+        // stream_options.include_usage 时最后一个分块携带用量
+        if let Some(usage) = chunk.get("usage").filter(|u| u.is_object()) {
+            deltas.push(StreamDelta::Usage(TokenUsage {
+                prompt_tokens: usage["prompt_tokens"].as_u64().unwrap_or(0) as u32,
+                completion_tokens: usage["completion_tokens"].as_u64().unwrap_or(0) as u32,
+                total_tokens: usage["total_tokens"].as_u64().unwrap_or(0) as u32,
+            }));
+        }
+        Ok(deltas)
+    }
+}
+
+/// Anthropic Messages API 的事件解码
+///
+/// 内容块按 `index` 编号; 工具调用的 `index` 取其内容块序号。
+#[derive(Debug, Default)]
+pub struct AnthropicStreamDecoder;
+
+impl StreamDecoder for AnthropicStreamDecoder {
+    fn decode(&mut self, event: &SseEvent) -> Result<Vec<StreamDelta>> {
+        let data: Value = serde_json::from_str(&event.data)
+            .map_err(|e| anyhow!("Invalid stream event: {} ({})", e, event.data))?;
+        let kind = event
+            .event
+            .as_deref()
+            .or_else(|| data["type"].as_str())
+            .unwrap_or_default();
+        let index = data["index"].as_u64().unwrap_or(0) as usize;
+
+        let delta = match kind {
+            "message_start" => {
+                let input = data["message"]["usage"]["input_tokens"]
+                    .as_u64()
+                    .unwrap_or(0) as u32;
+                StreamDelta::Usage(TokenUsage {
+                    prompt_tokens: input,
+                    completion_tokens: 0,
+                    total_tokens: input,
+                })
+            }
+            "content_block_start" => {
+                let block = &data["content_block"];
+                match block["type"].as_str() {
+                    Some("tool_use") => StreamDelta::ToolCall {
+                        index,
+                        id: block["id"].as_str().map(String::from),
+                        name: block["name"].as_str().map(String::from),
+                        arguments: String::new(),
+                    },
+                    Some("text") => match block["text"].as_str().filter(|t| !t.is_empty()) {
+                        Some(text) => StreamDelta::Text {
+                            text: text.to_string(),
+                        },
+                        None => return Ok(Vec::new()),
+                    },
+                    _ => return Ok(Vec::new()),
+                }
+            }
+            "content_block_delta" => {
+                let delta = &data["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => StreamDelta::Text {
+                        text: delta["text"].as_str().unwrap_or_default().to_string(),
+                    },
+                    Some("input_json_delta") => StreamDelta::ToolCall {
+                        index,
+                        id: None,
+                        name: None,
+                        arguments: delta["partial_json"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                    },
+                    _ => return Ok(Vec::new()),
+                }
+            }
+            "message_delta" => {
+                let output = data["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32;
+                return Ok(vec![
+                    StreamDelta::Usage(TokenUsage {
+                        prompt_tokens: 0,
+                        completion_tokens: output,
+                        total_tokens: output,
+                    }),
+                    StreamDelta::Stop {
+                        reason: data["delta"]["stop_reason"].as_str().map(String::from),
+                    },
+                ]);
+            }
+            "error" => return Err(anyhow!("Stream error: {}", data["error"])),
+            // ping / content_block_stop / message_stop
+            _ => return Ok(Vec::new()),
+        };
+        Ok(vec![delta])
+    }
+}
+
+/// 将 SSE 响应体转换为增量流
+pub fn sse_stream(response: reqwest::Response, decoder: impl StreamDecoder) -> ChatStream {
+    let state = (response.bytes_stream(), SseParser::new(), decoder);
+    let stream = futures_util::stream::unfold(Some(state), |state| async move {
+        let (mut body, mut parser, mut decoder) = state?;
+        let chunk = match body.next().await? {
+            Ok(chunk) => chunk,
+            Err(e) => return Some((vec![Err(anyhow!(e))], None)),
+        };
+        let mut items = Vec::new();
+        for event in parser.push(&chunk) {
+            match decoder.decode(&event) {
+                Ok(deltas) => items.extend(deltas.into_iter().map(Ok)),
+                Err(e) => {
+                    items.push(Err(e));
+                    return Some((items, None));
+                }
+            }
+        }
+        Some((items, Some((body, parser, decoder))))
+    });
+    Box::pin(stream.flat_map(futures_util::stream::iter))
+}
+
+/// 将完整回复拆为增量流, 用于不支持流式的 Provider
+pub fn response_stream(response: ChatResponse) -> ChatStream {
+    let mut deltas = Vec::new();
+    if let Some(text) = response.reasoning {
+        deltas.push(StreamDelta::Reasoning { text });
+    }
+    if !response.content.is_empty() {
+        deltas.push(StreamDelta::Text {
+            text: response.content,
+        });
+    }
+    for (index, call) in response.tool_calls.into_iter().flatten().enumerate() {
+        deltas.push(StreamDelta::ToolCall {
+            index,
+            id: Some(call.id),
+            name: Some(call.name),
+            arguments: call.arguments.to_string(),
+        });
+    }
+    deltas.push(StreamDelta::Usage(response.usage));
+    deltas.push(StreamDelta::Stop { reason: None });
+    Box::pin(futures_util::stream::iter(deltas.into_iter().map(Ok)))
+}
+
+// ============================================================================
+// 聚合
+// ============================================================================
+
+#[derive(Debug, Default)]
+struct PartialToolCall {
+    id: String,
+    name: String,
+    arguments: String,
+}
+
+/// 将增量聚合为完整回复
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    content: String,
+    reasoning: String,
+    tool_calls: BTreeMap<usize, PartialToolCall>,
+    usage: TokenUsage,
+    stop_reason: Option<String>,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, delta: &StreamDelta) {
+        match delta {
+            StreamDelta::Text { text } => self.content.push_str(text),
+            StreamDelta::Reasoning { text } => self.reasoning.push_str(text),
+            StreamDelta::ToolCall {
+                index,
+                id,
+                name,
+                arguments,
+            } => {
+                let call = self.tool_calls.entry(*index).or_default();
+                if let Some(id) = id {
+                    call.id = id.clone();
+                }
+                if let Some(name) = name {
+                    call.name = name.clone();
+                }
+                call.arguments.push_str(arguments);
+            }
+            StreamDelta::Usage(usage) => {
+                if usage.prompt_tokens > 0 {
+                    self.usage.prompt_tokens = usage.prompt_tokens;
+                }
+                if usage.completion_tokens > 0 {
+                    self.usage.completion_tokens = usage.completion_tokens;
+                }
+                self.usage.total_tokens = self.usage.prompt_tokens + self.usage.completion_tokens;
+            }
+            StreamDelta::Stop { reason } => {
+                if reason.is_some() {
+                    self.stop_reason = reason.clone();
+                }
+            }
+        }
+    }
+
+    /// 结束原因 (`stop` / `tool_calls` / `end_turn` / `max_tokens` ...)
+    pub fn stop_reason(&self) -> Option<&str> {
+        self.stop_reason.as_deref()
+    }
+
+    /// 已聚合的文本
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    pub fn finish(self) -> ChatResponse {
+        let tool_calls: Vec<ToolCall> = self
+            .tool_calls
+            .into_iter()
+            .map(|(index, call)| ToolCall {
+                // 部分服务流式返回时不带 id
+                id: if call.id.is_empty() {
+                    format!("call_{}", index)
+                } else {
+                    call.id
+                },
+                name: call.name,
+                arguments: if call.arguments.trim().is_empty() {
+                    serde_json::json!({})
+                } else {
+                    serde_json::from_str(&call.arguments).unwrap_or(Value::String(call.arguments))
+                },
+            })
+            .collect();
+        ChatResponse {
+            content: self.content,
+            reasoning: (!self.reasoning.is_empty()).then_some(self.reasoning),
+            tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+            usage: self.usage,
+        }
+    }
+}
+
+/// 消费增量流直到结束或被取消, 取消时返回已生成的部分
+pub async fn collect_stream(
+    mut stream: ChatStream,
+    cancel: &CancellationToken,
+) -> Result<ChatResponse> {
+    let mut acc = StreamAccumulator::new();
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            delta = stream.next() => match delta {
+                Some(delta) => acc.push(&delta?),
+                None => break,
+            },
+        }
+    }
+    Ok(acc.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_all(decoder: &mut impl StreamDecoder, raw: &str) -> ChatResponse {
+        let mut parser = SseParser::new();
+        let mut acc = StreamAccumulator::new();
+        // 按小块喂入, 覆盖跨分块的行
+        for chunk in raw.as_bytes().chunks(7) {
+            for event in parser.push(chunk) {
+                for delta in decoder.decode(&event).unwrap() {
+                    acc.push(&delta);
+                }
+            }
+        }
+        acc.finish()
+    }
+
+    #[test]
+    fn test_openai_stream_with_tool_call() {
+        let raw = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\",\"content\":\"Checking\"}}]}\n\n",
+            ": keep-alive\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\" price\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"type\":\"function\",\"function\":{\"name\":\"get_price\",\"arguments\":\"\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"{\\\"symbol\\\":\"}}]}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"\\\"BTC\\\"}\"}}]},\"finish_reason\":\"tool_calls\"}]}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":12,\"completion_tokens\":5,\"total_tokens\":17}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let response = decode_all(&mut OpenAIStreamDecoder, raw);
+        assert_eq!(response.content, "Checking price");
+        let calls = response.tool_calls.unwrap();
+        assert_eq!(calls[0].id, "call_1");
+        assert_eq!(calls[0].name, "get_price");
+        assert_eq!(calls[0].arguments["symbol"], "BTC");
+        assert_eq!(response.usage.total_tokens, 17);
+    }
+
+    #[test]
+    fn test_anthropic_stream_with_tool_use() {
+        let raw = concat!(
+            "event: message_start\r\n",
+            "data: {\"type\":\"message_start\",\"message\":{\"usage\":{\"input_tokens\":20,\"output_tokens\":1}}}\r\n\r\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Buying\"}}\n\n",
+            "event: ping\n",
+            "data: {\"type\":\"ping\"}\n\n",
+            "event: content_block_start\n",
+            "data: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"toolu_1\",\"name\":\"place_order\",\"input\":{}}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"side\\\": \\\"buy\\\"\"}}\n\n",
+            "event: content_block_delta\n",
+            "data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\", \\\"quantity\\\": 0.1}\"}}\n\n",
+            "event: message_delta\n",
+            "data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":30}}\n\n",
+            "event: message_stop\n",
+            "data: {\"type\":\"message_stop\"}\n\n",
+        );
+        let response = decode_all(&mut AnthropicStreamDecoder, raw);
+        assert_eq!(response.content, "Buying");
+        let calls = response.tool_calls.unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].id, "toolu_1");
+        assert_eq!(calls[0].arguments["quantity"], 0.1);
+        assert_eq!(response.usage.prompt_tokens, 20);
+        assert_eq!(response.usage.completion_tokens, 30);
+        assert_eq!(response.usage.total_tokens, 50);
+
+        let error = SseEvent {
+            event: Some("error".to_string()),
+            data: r#"{"type":"error","error":{"type":"overloaded_error"}}"#.to_string(),
+        };
+        assert!(AnthropicStreamDecoder.decode(&error).is_err());
+    }
+}
@@ -4,16 +4,24 @@
     windows_subsystem = "windows"
 )]
 
+mod analytics;
+mod backtest;
 mod brokers;
 mod config;
+mod db;
 mod engine;
 mod error;
+mod history;
+mod leaderboard;
 mod llm;
 mod markets;
 mod mcp;
 mod mock_data;
+mod prompt;
+mod risk;
 mod server;
 mod tray;
+mod usage;
 
 use std::net::SocketAddr;
 
@@ -25,6 +33,12 @@ use crate::tray::run_system_tray;
 fn main() -> anyhow::Result<()> {
     init_tracing();
 
+    // MCP stdio 模式: 由 MCP 客户端以子进程方式启动
+    if std::env::args().any(|arg| arg == "--mcp-stdio") {
+        let runtime = tokio::runtime::Runtime::new()?;
+        return runtime.block_on(crate::server::run_mcp_stdio());
+    }
+
     let addr: SocketAddr = std::env::var("PORT")
         .ok()
         .and_then(|p| p.parse().ok())
@@ -52,5 +66,7 @@ fn init_tracing() {
         .with_env_filter(filter)
         .with_target(false)
         .with_max_level(Level::INFO)
+        // stdout 留给 MCP stdio 传输
+        .with_writer(std::io::stderr)
         .try_init();
 }
@@ -5,5 +5,5 @@ mod types;
 
 pub use server::{McpServer, ToolHandler};
 pub use tools::*;
-pub use transport::Transport;
+pub use transport::{http_router, serve_lines, serve_stdio, MCP_HTTP_PATH};
 pub use types::*;
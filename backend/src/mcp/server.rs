@@ -1,4 +1,4 @@
-use super::types::{McpMessage, McpTool};
+use super::types::*;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use tracing::{debug, error, info};
@@ -8,9 +8,17 @@ pub trait ToolHandler: Send + Sync {
     async fn execute(&self, input: serde_json::Value) -> Result<serde_json::Value, anyhow::Error>;
 }
 
+/// MCP 服务端
+///
+/// 实现 `initialize` 握手与协议版本协商、`ping`、工具列表与调用;
+/// 通知 (不带 ID 的消息) 不回复, 支持 JSON-RPC 批量请求。
+/// 传输层见 [`super::transport`] (stdio 与 Streamable HTTP)。
 pub struct McpServer {
     tools: HashMap<String, Box<dyn ToolHandler>>,
     tool_schemas: HashMap<String, McpTool>,
+    name: String,
+    version: String,
+    instructions: Option<String>,
 }
 
 impl McpServer {
@@ -18,9 +26,25 @@ impl McpServer {
         Self {
             tools: HashMap::new(),
             tool_schemas: HashMap::new(),
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            instructions: None,
         }
     }
 
+    /// 设置 `initialize` 返回的服务端名称与版本
+    pub fn with_server_info(mut self, name: impl Into<String>, version: impl Into<String>) -> Self {
+        self.name = name.into();
+        self.version = version.into();
+        self
+    }
+
+    /// 设置 `initialize` 返回的使用说明
+    pub fn with_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = Some(instructions.into());
+        self
+    }
+
     pub fn register_tool(&mut self, schema: McpTool, handler: Box<dyn ToolHandler>) {
         let name = schema.name.clone();
         self.tool_schemas.insert(name.clone(), schema);
@@ -28,6 +52,74 @@ impl McpServer {
         info!("Registered MCP tool: {}", name);
     }
 
+    /// 处理一条 JSON-RPC 文本消息 (单条或批量), 无需回复时返回 None
+    pub async fn handle_str(&self, raw: &str) -> Option<String> {
+        let response = match serde_json::from_str(raw) {
+            Ok(value) => self.handle_value(value).await?,
+            Err(e) => serde_json::to_value(McpMessage::error(
+                None,
+                PARSE_ERROR,
+                format!("Parse error: {}", e),
+            ))
+            .ok()?,
+        };
+        Some(response.to_string())
+    }
+
+    /// 处理一条 JSON-RPC 消息或批量请求, 无需回复时返回 None
+    pub async fn handle_value(&self, value: serde_json::Value) -> Option<serde_json::Value> {
+        let serde_json::Value::Array(batch) = value else {
+            return self.handle_single(value).await;
+        };
+        if batch.is_empty() {
+            return self
+                .handle_single(serde_json::Value::Array(Vec::new()))
+                .await;
+        }
+
+        let mut responses = Vec::new();
+        for item in batch {
+            responses.extend(self.handle_single(item).await);
+        }
+        (!responses.is_empty()).then_some(serde_json::Value::Array(responses))
+    }
+
+    async fn handle_single(&self, value: serde_json::Value) -> Option<serde_json::Value> {
+        let id = value.get("id").cloned();
+        let response = match serde_json::from_value::<McpMessage>(value) {
+            Ok(msg) if msg.jsonrpc != "2.0" => McpMessage::error(
+                msg.id,
+                INVALID_REQUEST,
+                "Invalid Request: jsonrpc must be \"2.0\"".to_string(),
+            ),
+            Ok(msg) => self.handle_message(msg).await?,
+            Err(e) => McpMessage::error(id, INVALID_REQUEST, format!("Invalid Request: {}", e)),
+        };
+        serde_json::to_value(response).ok()
+    }
+
+    /// 处理已解析的消息: 请求返回响应, 通知与客户端响应返回 None
+    pub async fn handle_message(&self, msg: McpMessage) -> Option<McpMessage> {
+        if msg.is_notification() {
+            self.handle_notification(&msg);
+            return None;
+        }
+        if msg.method.is_none() && (msg.result.is_some() || msg.error.is_some()) {
+            // 服务端不发起请求, 忽略客户端的响应
+            debug!("Ignoring MCP response: {:?}", msg.id);
+            return None;
+        }
+        Some(self.handle_request(msg).await)
+    }
+
+    fn handle_notification(&self, msg: &McpMessage) {
+        match msg.method.as_deref() {
+            Some("notifications/initialized") => info!("MCP client initialized"),
+            Some("notifications/cancelled") => debug!("MCP request cancelled: {:?}", msg.params),
+            method => debug!("Ignoring MCP notification: {:?}", method),
+        }
+    }
+
     pub async fn handle_request(&self, msg: McpMessage) -> McpMessage {
         debug!("Handling MCP request: {:?}", msg);
 
@@ -35,27 +127,91 @@ impl McpServer {
         let method = match &msg.method {
             Some(m) => m,
             None => {
-                return McpMessage::error(id, -32600, "Invalid Request: missing method".to_string())
+                return McpMessage::error(
+                    id,
+                    INVALID_REQUEST,
+                    "Invalid Request: missing method".to_string(),
+                )
             }
         };
 
         match method.as_str() {
+            "initialize" => self.handle_initialize(id, msg.params),
+            "ping" => {
+                McpMessage::success(id.unwrap_or(serde_json::Value::Null), serde_json::json!({}))
+            }
             "tools/list" => self.handle_tools_list(id),
             "tools/call" => self.handle_tools_call(id, msg.params).await,
             "resources/list" => self.handle_resources_list(id),
             "prompts/list" => self.handle_prompts_list(id),
-            _ => McpMessage::error(id, -32601, format!("Method not found: {}", method)),
+            _ => McpMessage::error(
+                id,
+                METHOD_NOT_FOUND,
+                format!("Method not found: {}", method),
+            ),
         }
     }
 
+    /// 版本协商: 支持客户端请求的版本时使用该版本, 否则返回服务端最新版本由客户端决定是否断开
+    fn handle_initialize(
+        &self,
+        id: Option<serde_json::Value>,
+        params: Option<serde_json::Value>,
+    ) -> McpMessage {
+        let Some(requested) = params
+            .as_ref()
+            .and_then(|p| p.get("protocolVersion"))
+            .and_then(|v| v.as_str())
+        else {
+            return McpMessage::error(
+                id,
+                INVALID_PARAMS,
+                "Invalid params: missing protocolVersion".to_string(),
+            );
+        };
+        let version = SUPPORTED_PROTOCOL_VERSIONS
+            .iter()
+            .find(|v| **v == requested)
+            .copied()
+            .unwrap_or(LATEST_PROTOCOL_VERSION);
+
+        let client = params
+            .as_ref()
+            .and_then(|p| p.get("clientInfo"))
+            .and_then(|c| c.get("name"))
+            .and_then(|n| n.as_str())
+            .unwrap_or("unknown");
+        info!(
+            "MCP initialize from {} (requested {}, using {})",
+            client, requested, version
+        );
+
+        let mut result = serde_json::json!({
+            "protocolVersion": version,
+            "capabilities": {
+                "tools": { "listChanged": false }
+            },
+            "serverInfo": {
+                "name": self.name,
+                "version": self.version,
+            },
+        });
+        if let Some(instructions) = &self.instructions {
+            result["instructions"] = serde_json::json!(instructions);
+        }
+        McpMessage::success(id.unwrap_or(serde_json::Value::Null), result)
+    }
+
     fn handle_tools_list(&self, id: Option<serde_json::Value>) -> McpMessage {
-        let tools: Vec<&McpTool> = self.tool_schemas.values().collect();
+        let mut tools: Vec<&McpTool> = self.tool_schemas.values().collect();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
         McpMessage::success(
             id.unwrap_or(serde_json::Value::Null),
             serde_json::json!({ "tools": tools }),
         )
     }
 
+    /// 工具执行失败以 `isError` 结果返回 (而非协议错误), 便于模型据此修正
     async fn handle_tools_call(
         &self,
         id: Option<serde_json::Value>,
@@ -66,7 +222,7 @@ impl McpServer {
             None => {
                 return McpMessage::error(
                     id,
-                    -32602,
+                    INVALID_PARAMS,
                     "Invalid params: missing parameters".to_string(),
                 )
             }
@@ -77,7 +233,7 @@ impl McpServer {
             None => {
                 return McpMessage::error(
                     id,
-                    -32602,
+                    INVALID_PARAMS,
                     "Invalid params: missing tool name".to_string(),
                 )
             }
@@ -90,19 +246,29 @@ impl McpServer {
 
         let handler = match self.tools.get(tool_name) {
             Some(h) => h,
-            None => return McpMessage::error(id, -32602, format!("Tool not found: {}", tool_name)),
+            None => {
+                return McpMessage::error(
+                    id,
+                    INVALID_PARAMS,
+                    format!("Tool not found: {}", tool_name),
+                )
+            }
         };
 
-        match handler.execute(input).await {
-            Ok(result) => McpMessage::success(
-                id.unwrap_or(serde_json::Value::Null),
-                serde_json::json!({ "content": [{ "type": "text", "text": result.to_string() }] }),
-            ),
+        let (text, is_error) = match handler.execute(input).await {
+            Ok(result) => (result.to_string(), false),
             Err(e) => {
                 error!("Tool execution error: {}", e);
-                McpMessage::error(id, -32603, format!("Tool execution failed: {}", e))
+                (format!("Tool execution failed: {}", e), true)
             }
-        }
+        };
+        McpMessage::success(
+            id.unwrap_or(serde_json::Value::Null),
+            serde_json::json!({
+                "content": [{ "type": "text", "text": text }],
+                "isError": is_error,
+            }),
+        )
     }
 
     fn handle_resources_list(&self, id: Option<serde_json::Value>) -> McpMessage {
@@ -125,3 +291,107 @@ impl Default for McpServer {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::{GetPriceTool, PlaceOrderTool};
+    use serde_json::json;
+
+    fn server() -> McpServer {
+        let mut server = McpServer::new().with_instructions("Trading tools");
+        server.register_tool(GetPriceTool::schema(), Box::new(GetPriceTool));
+        server.register_tool(PlaceOrderTool::schema(), Box::new(PlaceOrderTool));
+        server
+    }
+
+    #[tokio::test]
+    async fn test_initialize_negotiates_version() {
+        let server = server();
+        let init = |version: &str| {
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "initialize",
+                "params": {
+                    "protocolVersion": version,
+                    "capabilities": {},
+                    "clientInfo": { "name": "claude-desktop", "version": "1.0" }
+                }
+            })
+        };
+
+        let response = server.handle_value(init("2024-11-05")).await.unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["result"]["protocolVersion"], "2024-11-05");
+        assert_eq!(
+            response["result"]["capabilities"]["tools"]["listChanged"],
+            false
+        );
+        assert_eq!(response["result"]["serverInfo"]["name"], "nof0-backend");
+        assert_eq!(response["result"]["instructions"], "Trading tools");
+
+        // 不支持的版本: 返回服务端最新版本
+        let response = server.handle_value(init("1999-01-01")).await.unwrap();
+        assert_eq!(
+            response["result"]["protocolVersion"],
+            LATEST_PROTOCOL_VERSION
+        );
+
+        let response = server
+            .handle_value(json!({"jsonrpc": "2.0", "id": "p", "method": "ping"}))
+            .await
+            .unwrap();
+        assert_eq!(response["id"], "p");
+        assert_eq!(response["result"], json!({}));
+    }
+
+    #[tokio::test]
+    async fn test_notifications_and_batches() {
+        let server = server();
+
+        // 通知不回复
+        let initialized = json!({"jsonrpc": "2.0", "method": "notifications/initialized"});
+        assert!(server.handle_value(initialized.clone()).await.is_none());
+        assert!(server
+            .handle_value(json!([initialized.clone()]))
+            .await
+            .is_none());
+
+        let batch = json!([
+            {"jsonrpc": "2.0", "id": 1, "method": "tools/list"},
+            initialized,
+            {"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+             "params": {"name": "get_price", "arguments": {}}},
+            {"jsonrpc": "2.0", "id": 3, "method": "unknown"},
+            {"jsonrpc": "1.0", "id": 4, "method": "ping"}
+        ]);
+        let responses = server.handle_value(batch).await.unwrap();
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 4);
+
+        let tools = responses[0]["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools[0]["name"], "get_price");
+        assert!(tools[0]["inputSchema"].is_object());
+
+        // 工具执行失败作为 isError 结果返回
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(responses[1]["result"]["isError"], true);
+        assert!(responses[1]["result"]["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Missing symbol"));
+
+        assert_eq!(responses[2]["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(responses[3]["error"]["code"], INVALID_REQUEST);
+
+        let empty = server.handle_value(json!([])).await.unwrap();
+        assert_eq!(empty["error"]["code"], INVALID_REQUEST);
+        assert!(empty["id"].is_null());
+
+        let parse_error: serde_json::Value =
+            serde_json::from_str(&server.handle_str("{not json").await.unwrap()).unwrap();
+        assert_eq!(parse_error["error"]["code"], PARSE_ERROR);
+        assert!(parse_error["id"].is_null());
+    }
+}
@@ -1,20 +1,146 @@
-use super::types::McpMessage;
+use super::server::McpServer;
+use super::types::SUPPORTED_PROTOCOL_VERSIONS;
 use anyhow::Result;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::Router;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+use tracing::{info, warn};
 
-pub enum Transport {
-    Stdio,
-    Sse,
-    WebSocket,
+/// Streamable HTTP 传输的端点路径
+pub const MCP_HTTP_PATH: &str = "/mcp";
+
+/// 按行读取 JSON-RPC 消息并逐行写回响应 (stdio 传输的消息格式)
+pub async fn serve_lines<R, W>(server: &McpServer, reader: R, mut writer: W) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle_str(&line).await {
+            writer.write_all(response.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+            writer.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// stdio 传输: 供 Claude Desktop 等客户端以子进程方式启动, 日志须输出到 stderr
+pub async fn serve_stdio(server: Arc<McpServer>) -> Result<()> {
+    info!("Serving MCP over stdio");
+    let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+    serve_lines(&server, stdin, tokio::io::stdout()).await
 }
 
-impl Transport {
-    pub async fn send(&self, _msg: &McpMessage) -> Result<()> {
-        // TODO: 实现不同传输方式
-        Ok(())
+/// Streamable HTTP 传输: `POST /mcp` 收发 JSON-RPC, 不提供服务端推送流
+pub fn http_router(server: Arc<McpServer>) -> Router {
+    Router::new()
+        .route(
+            MCP_HTTP_PATH,
+            post(handle_http).get(|| async { StatusCode::METHOD_NOT_ALLOWED }),
+        )
+        .with_state(server)
+}
+
+async fn handle_http(
+    State(server): State<Arc<McpServer>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    // 防止 DNS 重绑定: 仅接受本机来源的浏览器请求
+    if let Some(origin) = headers.get(header::ORIGIN) {
+        if !origin.to_str().is_ok_and(is_local_origin) {
+            warn!("Rejected MCP request from origin {:?}", origin);
+            return StatusCode::FORBIDDEN.into_response();
+        }
+    }
+    if let Some(version) = headers.get("mcp-protocol-version") {
+        let supported = version
+            .to_str()
+            .is_ok_and(|v| SUPPORTED_PROTOCOL_VERSIONS.contains(&v));
+        if !supported {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Unsupported MCP-Protocol-Version: {:?}", version),
+            )
+                .into_response();
+        }
+    }
+
+    let value = match serde_json::from_slice(&body) {
+        Ok(value) => value,
+        Err(_) => {
+            let response = server.handle_str(&String::from_utf8_lossy(&body)).await;
+            return (
+                StatusCode::BAD_REQUEST,
+                [(header::CONTENT_TYPE, "application/json")],
+                response.unwrap_or_default(),
+            )
+                .into_response();
+        }
+    };
+    match server.handle_value(value).await {
+        Some(response) => axum::Json(response).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
     }
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let host = origin
+        .split_once("://")
+        .map_or(origin, |(_, rest)| rest)
+        .trim_end_matches('/');
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::GetPriceTool;
+
+    #[tokio::test]
+    async fn test_serve_lines() {
+        let mut server = McpServer::new();
+        server.register_tool(GetPriceTool::schema(), Box::new(GetPriceTool));
+
+        let input = concat!(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{}}}"#,
+            "\n",
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "\n\n",
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"get_price","arguments":{"symbol":"BTC/USDT"}}}"#,
+            "\n",
+        );
+        let mut output = Vec::new();
+        serve_lines(&server, input.as_bytes(), &mut output)
+            .await
+            .unwrap();
+
+        let responses: Vec<serde_json::Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["result"]["protocolVersion"], "2025-06-18");
+        assert_eq!(responses[1]["id"], 2);
+        assert_eq!(responses[1]["result"]["isError"], false);
 
-    pub async fn receive(&self) -> Result<McpMessage> {
-        // TODO: 实现不同传输方式
-        todo!()
+        assert!(is_local_origin("http://localhost:8788"));
+        assert!(is_local_origin("http://[::1]:8788"));
+        assert!(!is_local_origin("https://evil.example"));
     }
 }
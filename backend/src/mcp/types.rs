@@ -1,5 +1,18 @@
 use serde::{Deserialize, Serialize};
 
+/// 最新支持的 MCP 协议版本
+pub const LATEST_PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// 支持的 MCP 协议版本 (新的在前)
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// JSON-RPC 错误码
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const INTERNAL_ERROR: i32 = -32603;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct McpMessage {
     pub jsonrpc: String,
@@ -27,6 +40,7 @@ pub struct McpError {
 pub struct McpTool {
     pub name: String,
     pub description: String,
+    #[serde(rename = "inputSchema", alias = "input_schema")]
     pub input_schema: serde_json::Value,
 }
 
@@ -52,10 +66,11 @@ impl McpMessage {
         }
     }
 
+    /// 错误响应, 无法确定请求 ID 时 `id` 为 null
     pub fn error(id: Option<serde_json::Value>, code: i32, message: String) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
-            id,
+            id: Some(id.unwrap_or(serde_json::Value::Null)),
             method: None,
             params: None,
             result: None,
@@ -66,4 +81,21 @@ impl McpMessage {
             }),
         }
     }
+
+    /// 通知 (不带 ID, 接收方不回复)
+    pub fn notification(method: impl Into<String>, params: Option<serde_json::Value>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: None,
+            method: Some(method.into()),
+            params,
+            result: None,
+            error: None,
+        }
+    }
+
+    /// 是否为通知
+    pub fn is_notification(&self) -> bool {
+        self.method.is_some() && self.id.is_none()
+    }
 }
@@ -0,0 +1,316 @@
+use super::indicators::Indicators;
+use crate::brokers::{DynBroker, Order, OrderStatus, Position, Trade};
+use crate::engine::{Agent, AgentSnapshot};
+use crate::risk::RiskManager;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// 默认 K 线周期
+pub const DEFAULT_KLINE_INTERVAL: &str = "15m";
+
+/// 默认 K 线数量 (满足 EMA50 / MACD)
+pub const DEFAULT_KLINE_LIMIT: i32 = 100;
+
+/// 默认最近成交条数
+pub const DEFAULT_TRADE_LIMIT: usize = 20;
+
+/// 模板中的 Agent 信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentContext {
+    pub id: String,
+    pub name: String,
+    pub broker: String,
+    pub symbols: Vec<String>,
+    pub initial_capital: f64,
+}
+
+/// 账户状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountContext {
+    pub currency: String,
+    pub total_balance: f64,
+    pub available: f64,
+    pub margin_used: Option<f64>,
+    pub unrealized_pnl: f64,
+    pub equity: f64,
+    /// 相对初始资金的收益率 (%), 未配置初始资金时为 None
+    pub return_pct: Option<f64>,
+}
+
+/// 单个品种的行情与指标
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketContext {
+    pub symbol: String,
+    pub price: f64,
+    pub change_24h: f64,
+    pub high_24h: f64,
+    pub low_24h: f64,
+    pub volume_24h: f64,
+    pub indicators: Option<Indicators>,
+}
+
+/// 风控状态摘要
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskContext {
+    pub risk_score: f64,
+    pub risk_level: String,
+    pub drawdown_pct: f64,
+    pub leverage: f64,
+    pub margin_usage_pct: f64,
+    pub daily_pnl: f64,
+    pub daily_order_count: u32,
+    pub recent_events: Vec<String>,
+}
+
+/// Prompt 模板上下文
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptContext {
+    pub agent: AgentContext,
+    /// 决策时间 (RFC 3339)
+    pub time: String,
+    pub timestamp: i64,
+    /// 第几次决策
+    pub cycle: u64,
+    pub account: AccountContext,
+    pub markets: Vec<MarketContext>,
+    pub positions: Vec<Position>,
+    pub open_orders: Vec<Order>,
+    /// 最近成交, 按时间升序
+    pub recent_trades: Vec<Trade>,
+    pub risk: Option<RiskContext>,
+    /// 为满足 token 预算裁剪过内容
+    pub truncated: bool,
+}
+
+impl PromptContext {
+    /// 裁剪一项最不重要的内容, 没有可裁剪内容时返回 false
+    ///
+    /// 顺序: 最早的成交 -> 挂单 -> 风险事件 -> 无持仓品种的指标 -> 无持仓品种 -> 其余指标。
+    /// 持仓与账户信息始终保留。
+    pub fn shrink(&mut self) -> bool {
+        let held =
+            |symbol: &str, positions: &[Position]| positions.iter().any(|p| p.symbol == symbol);
+        let shrunk = if !self.recent_trades.is_empty() {
+            self.recent_trades.remove(0);
+            true
+        } else if self.open_orders.pop().is_some() {
+            true
+        } else if let Some(risk) = self.risk.as_mut().filter(|r| !r.recent_events.is_empty()) {
+            risk.recent_events.pop();
+            true
+        } else if let Some(m) = self
+            .markets
+            .iter_mut()
+            .rev()
+            .find(|m| m.indicators.is_some() && !held(&m.symbol, &self.positions))
+        {
+            m.indicators = None;
+            true
+        } else if let Some(i) = self
+            .markets
+            .iter()
+            .rposition(|m| !held(&m.symbol, &self.positions))
+        {
+            self.markets.remove(i);
+            true
+        } else if let Some(m) = self
+            .markets
+            .iter_mut()
+            .rev()
+            .find(|m| m.indicators.is_some())
+        {
+            m.indicators = None;
+            true
+        } else {
+            false
+        };
+        self.truncated |= shrunk;
+        shrunk
+    }
+}
+
+/// 从决策快照构建 Prompt 上下文, 可选从经纪商补充 K 线指标、挂单与成交
+pub struct PromptContextBuilder<'a> {
+    agent: &'a Agent,
+    snapshot: &'a AgentSnapshot,
+    broker: Option<&'a dyn DynBroker>,
+    risk_manager: Option<&'a RiskManager>,
+    kline_interval: String,
+    kline_limit: i32,
+    trade_limit: usize,
+    cycle: u64,
+}
+
+impl<'a> PromptContextBuilder<'a> {
+    pub fn new(agent: &'a Agent, snapshot: &'a AgentSnapshot) -> Self {
+        Self {
+            agent,
+            snapshot,
+            broker: None,
+            risk_manager: None,
+            kline_interval: DEFAULT_KLINE_INTERVAL.to_string(),
+            kline_limit: DEFAULT_KLINE_LIMIT,
+            trade_limit: DEFAULT_TRADE_LIMIT,
+            cycle: 0,
+        }
+    }
+
+    pub fn with_broker(mut self, broker: &'a dyn DynBroker) -> Self {
+        self.broker = Some(broker);
+        self
+    }
+
+    pub fn with_risk_manager(mut self, risk_manager: &'a RiskManager) -> Self {
+        self.risk_manager = Some(risk_manager);
+        self
+    }
+
+    /// 设置计算指标的 K 线周期与数量
+    pub fn with_klines(mut self, interval: impl Into<String>, limit: i32) -> Self {
+        self.kline_interval = interval.into();
+        self.kline_limit = limit;
+        self
+    }
+
+    pub fn with_trade_limit(mut self, limit: usize) -> Self {
+        self.trade_limit = limit;
+        self
+    }
+
+    pub fn with_cycle(mut self, cycle: u64) -> Self {
+        self.cycle = cycle;
+        self
+    }
+
+    /// 构建上下文; 经纪商补充数据获取失败时只告警并跳过
+    pub async fn build(self) -> PromptContext {
+        let agent = self.agent;
+        let snapshot = self.snapshot;
+
+        let mut markets = Vec::with_capacity(snapshot.tickers.len());
+        for t in &snapshot.tickers {
+            let indicators = match self.broker {
+                Some(broker) => match broker
+                    .get_klines(&t.symbol, &self.kline_interval, Some(self.kline_limit))
+                    .await
+                {
+                    Ok(k) => Some(Indicators::from_klines(&self.kline_interval, &k.klines)),
+                    Err(e) => {
+                        warn!("Skipping indicators for {}: {}", t.symbol, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+            markets.push(MarketContext {
+                symbol: t.symbol.clone(),
+                price: t.last_price,
+                change_24h: t.change_24h,
+                high_24h: t.high_24h,
+                low_24h: t.low_24h,
+                volume_24h: t.volume_24h,
+                indicators,
+            });
+        }
+
+        let (open_orders, recent_trades) = match self.broker {
+            Some(broker) => (
+                open_orders(broker, agent).await,
+                recent_trades(broker, agent, self.trade_limit).await,
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let risk = match self.risk_manager {
+            Some(manager) => {
+                let report = manager.generate_risk_report().await;
+                Some(RiskContext {
+                    risk_score: report.risk_score,
+                    risk_level: report.risk_level,
+                    drawdown_pct: report.current_drawdown * 100.0,
+                    leverage: report.leverage,
+                    margin_usage_pct: report.margin_usage * 100.0,
+                    daily_pnl: report.daily_pnl,
+                    daily_order_count: report.daily_order_count,
+                    recent_events: report
+                        .recent_events
+                        .iter()
+                        .map(|e| format!("{}: {}", e.rule_name, e.description))
+                        .collect(),
+                })
+            }
+            None => None,
+        };
+
+        let balance = &snapshot.balance;
+        let unrealized_pnl: f64 = snapshot.positions.iter().map(|p| p.unrealized_pnl).sum();
+        let equity = balance.total_balance + unrealized_pnl;
+        PromptContext {
+            agent: AgentContext {
+                id: agent.id.clone(),
+                name: agent.name.clone(),
+                broker: agent.market.clone(),
+                symbols: agent.symbols.clone(),
+                initial_capital: agent.initial_capital,
+            },
+            time: chrono::DateTime::from_timestamp(snapshot.timestamp, 0)
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+            timestamp: snapshot.timestamp,
+            cycle: self.cycle,
+            account: AccountContext {
+                currency: balance.currency.clone(),
+                total_balance: balance.total_balance,
+                available: balance.available,
+                margin_used: balance.margin_used,
+                unrealized_pnl,
+                equity,
+                return_pct: (agent.initial_capital > 0.0)
+                    .then(|| (equity / agent.initial_capital - 1.0) * 100.0),
+            },
+            markets,
+            positions: snapshot.positions.clone(),
+            open_orders,
+            recent_trades,
+            risk,
+            truncated: false,
+        }
+    }
+}
+
+/// Agent 交易品种上未完成的订单
+async fn open_orders(broker: &dyn DynBroker, agent: &Agent) -> Vec<Order> {
+    match broker.get_orders(None).await {
+        Ok(orders) => orders
+            .orders
+            .into_iter()
+            .filter(|o| {
+                matches!(
+                    o.status,
+                    OrderStatus::Pending | OrderStatus::Accepted | OrderStatus::PartiallyFilled
+                )
+            })
+            .filter(|o| agent.symbols.is_empty() || agent.symbols.contains(&o.symbol))
+            .collect(),
+        Err(e) => {
+            warn!("Skipping open orders for {}: {}", agent.id, e);
+            Vec::new()
+        }
+    }
+}
+
+/// 最近 `limit` 笔成交, 按时间升序
+async fn recent_trades(broker: &dyn DynBroker, agent: &Agent, limit: usize) -> Vec<Trade> {
+    match broker.get_trades().await {
+        Ok(trades) => {
+            let mut trades = trades.trades;
+            trades.sort_by_key(|t| t.timestamp);
+            let skip = trades.len().saturating_sub(limit);
+            trades.split_off(skip)
+        }
+        Err(e) => {
+            warn!("Skipping recent trades for {}: {}", agent.id, e);
+            Vec::new()
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::brokers::Kline;
+use serde::{Deserialize, Serialize};
+
+/// 由 K 线计算的技术指标 (数据不足的指标为 None)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Indicators {
+    pub interval: String,
+    pub ema20: Option<f64>,
+    pub ema50: Option<f64>,
+    pub rsi7: Option<f64>,
+    pub rsi14: Option<f64>,
+    /// MACD(12, 26, 9)
+    pub macd: Option<f64>,
+    pub macd_signal: Option<f64>,
+    pub atr14: Option<f64>,
+    /// 最近一根 K 线成交量
+    pub volume: Option<f64>,
+    /// 同期平均成交量
+    pub avg_volume: Option<f64>,
+}
+
+impl Indicators {
+    /// K 线按时间升序
+    pub fn from_klines(interval: &str, klines: &[Kline]) -> Self {
+        let closes: Vec<f64> = klines.iter().map(|k| k.close).collect();
+        let (macd, macd_signal) = match macd(&closes, 12, 26, 9) {
+            Some((m, s)) => (Some(m), Some(s)),
+            None => (None, None),
+        };
+        Self {
+            interval: interval.to_string(),
+            ema20: ema(&closes, 20).last().copied(),
+            ema50: ema(&closes, 50).last().copied(),
+            rsi7: rsi(&closes, 7),
+            rsi14: rsi(&closes, 14),
+            macd,
+            macd_signal,
+            atr14: atr(klines, 14),
+            volume: klines.last().map(|k| k.volume),
+            avg_volume: (!klines.is_empty())
+                .then(|| klines.iter().map(|k| k.volume).sum::<f64>() / klines.len() as f64),
+        }
+    }
+}
+
+/// 指数移动平均序列, 以前 `period` 个值的简单平均为起点; 数据不足时为空
+pub fn ema(values: &[f64], period: usize) -> Vec<f64> {
+    if period == 0 || values.len() < period {
+        return Vec::new();
+    }
+    let k = 2.0 / (period as f64 + 1.0);
+    let seed = values[..period].iter().sum::<f64>() / period as f64;
+    let mut out = Vec::with_capacity(values.len() - period + 1);
+    out.push(seed);
+    for v in &values[period..] {
+        let prev = *out.last().unwrap();
+        out.push(prev + k * (v - prev));
+    }
+    out
+}
+
+/// Wilder RSI
+pub fn rsi(closes: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || closes.len() <= period {
+        return None;
+    }
+    let changes: Vec<f64> = closes.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut gain = changes[..period].iter().map(|c| c.max(0.0)).sum::<f64>() / period as f64;
+    let mut loss = changes[..period].iter().map(|c| (-c).max(0.0)).sum::<f64>() / period as f64;
+    for c in &changes[period..] {
+        gain = (gain * (period - 1) as f64 + c.max(0.0)) / period as f64;
+        loss = (loss * (period - 1) as f64 + (-c).max(0.0)) / period as f64;
+    }
+    if loss == 0.0 {
+        return Some(if gain == 0.0 { 50.0 } else { 100.0 });
+    }
+    Some(100.0 - 100.0 / (1.0 + gain / loss))
+}
+
+/// MACD 线与信号线
+pub fn macd(closes: &[f64], fast: usize, slow: usize, signal: usize) -> Option<(f64, f64)> {
+    let fast = ema(closes, fast);
+    let slow = ema(closes, slow);
+    if slow.is_empty() {
+        return None;
+    }
+    // 两条 EMA 按末尾对齐
+    let offset = fast.len() - slow.len();
+    let line: Vec<f64> = slow
+        .iter()
+        .enumerate()
+        .map(|(i, s)| fast[i + offset] - s)
+        .collect();
+    let signal = ema(&line, signal);
+    Some((*line.last()?, *signal.last()?))
+}
+
+/// Wilder ATR
+pub fn atr(klines: &[Kline], period: usize) -> Option<f64> {
+    if period == 0 || klines.len() <= period {
+        return None;
+    }
+    let ranges: Vec<f64> = klines
+        .windows(2)
+        .map(|w| {
+            let (prev, k) = (&w[0], &w[1]);
+            (k.high - k.low)
+                .max((k.high - prev.close).abs())
+                .max((k.low - prev.close).abs())
+        })
+        .collect();
+    let mut atr = ranges[..period].iter().sum::<f64>() / period as f64;
+    for r in &ranges[period..] {
+        atr = (atr * (period - 1) as f64 + r) / period as f64;
+    }
+    Some(atr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kline(close: f64) -> Kline {
+        Kline {
+            timestamp: 0,
+            open: close,
+            high: close + 1.0,
+            low: close - 1.0,
+            close,
+            volume: 10.0,
+            open_interest: None,
+        }
+    }
+
+    #[test]
+    fn test_indicators_on_trend() {
+        let klines: Vec<Kline> = (0..60).map(|i| kline(100.0 + i as f64)).collect();
+        let ind = Indicators::from_klines("15m", &klines);
+        assert_eq!(ind.rsi14, Some(100.0));
+        assert!(ind.ema20.unwrap() > ind.ema50.unwrap());
+        assert!(ind.macd.unwrap() > 0.0);
+        // 单根振幅 2, 相邻收盘差 1
+        assert!((ind.atr14.unwrap() - 2.0).abs() < 1e-9);
+        assert_eq!(ind.avg_volume, Some(10.0));
+
+        let short = Indicators::from_klines("15m", &klines[..10]);
+        assert_eq!(short.ema20, None);
+        assert_eq!(short.macd, None);
+        assert!(short.rsi7.is_some());
+    }
+}
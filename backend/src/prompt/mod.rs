@@ -0,0 +1,9 @@
+// Prompt construction: versioned templates, typed context and token-budget truncation
+
+pub mod context;
+pub mod indicators;
+pub mod template;
+
+pub use context::*;
+pub use indicators::Indicators;
+pub use template::*;
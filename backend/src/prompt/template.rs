@@ -0,0 +1,322 @@
+use super::context::PromptContext;
+use crate::llm::Message;
+use anyhow::{anyhow, bail, Context, Result};
+use minijinja::{context, Environment, Value};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// 默认 Prompt token 预算
+pub const DEFAULT_PROMPT_TOKEN_BUDGET: usize = 6000;
+
+/// 内置模板 (config/prompts.yaml)
+const BUILTIN_PROMPTS: &str = include_str!("../../config/prompts.yaml");
+
+/// 一个版本的 System + User Prompt 模板
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    pub system: String,
+    pub user: String,
+}
+
+impl PromptTemplate {
+    /// 版本标识 `id@version`, 随会话一起保存
+    pub fn version_id(&self) -> String {
+        format!("{}@{}", self.id, self.version)
+    }
+}
+
+/// 渲染结果
+#[derive(Debug, Clone)]
+pub struct RenderedPrompt {
+    pub version_id: String,
+    pub messages: Vec<Message>,
+    pub estimated_tokens: usize,
+    /// 是否为满足预算裁剪过上下文
+    pub truncated: bool,
+}
+
+/// 版本化 Prompt 模板库 (config/prompts.yaml)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptLibrary {
+    /// 未指定模板时使用的模板 (`id` 或 `id@version`)
+    pub default: String,
+    pub templates: Vec<PromptTemplate>,
+}
+
+impl Default for PromptLibrary {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl PromptLibrary {
+    /// 内置模板库
+    pub fn builtin() -> Self {
+        Self::from_yaml_str(BUILTIN_PROMPTS).expect("built-in prompts are valid")
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::from_yaml_str(&content)
+    }
+
+    /// 从 YAML 字符串加载并校验 (版本唯一、默认模板存在、模板可编译)
+    pub fn from_yaml_str(yaml: &str) -> Result<Self> {
+        let library: PromptLibrary = serde_yaml::from_str(yaml)?;
+        let env = environment();
+        let mut seen = HashSet::new();
+        for template in &library.templates {
+            let version_id = template.version_id();
+            if !seen.insert(version_id.clone()) {
+                bail!("Duplicate prompt template: {}", version_id);
+            }
+            for source in [&template.system, &template.user] {
+                env.template_from_str(source)
+                    .with_context(|| format!("Invalid prompt template {}", version_id))?;
+            }
+        }
+        if library.get(&library.default).is_none() {
+            bail!("Default prompt template not found: {}", library.default);
+        }
+        Ok(library)
+    }
+
+    /// 从默认路径加载配置, 找不到文件时使用内置模板
+    pub fn load_default() -> Result<Self> {
+        let possible_paths = [
+            "config/prompts.yaml",
+            "backend/config/prompts.yaml",
+            "../config/prompts.yaml",
+        ];
+
+        for path in possible_paths {
+            if Path::new(path).exists() {
+                return Self::from_file(path);
+            }
+        }
+
+        tracing::warn!("No prompts config file found, using built-in templates");
+        Ok(Self::builtin())
+    }
+
+    /// 按 `id@version` 查找; 只给 `id` 时返回该 id 最后定义的版本
+    pub fn get(&self, spec: &str) -> Option<&PromptTemplate> {
+        match spec.split_once('@') {
+            Some((id, version)) => self
+                .templates
+                .iter()
+                .find(|t| t.id == id && t.version == version),
+            None => self.templates.iter().rev().find(|t| t.id == spec),
+        }
+    }
+
+    /// 选择本周期的模板: 多个候选时按周期轮换 (A/B), 为空时使用默认模板
+    pub fn select(&self, specs: &[String], cycle: u64) -> Result<&PromptTemplate> {
+        let spec = match specs.len() {
+            0 => &self.default,
+            n => &specs[(cycle.saturating_sub(1) % n as u64) as usize],
+        };
+        self.get(spec)
+            .ok_or_else(|| anyhow!("Prompt template not found: {}", spec))
+    }
+
+    /// 渲染模板, 超出 token 预算时逐项裁剪上下文后重新渲染
+    ///
+    /// `system_override` 为 Agent 自定义的 System Prompt 模板, 除上下文变量外还支持
+    /// `{{id}}` `{{name}}` `{{broker}}` `{{symbols}}` `{{initial_capital}}`。
+    pub fn render(
+        &self,
+        template: &PromptTemplate,
+        context: &PromptContext,
+        system_override: Option<&str>,
+        token_budget: usize,
+    ) -> Result<RenderedPrompt> {
+        let env = environment();
+        let system_source = system_override.unwrap_or(&template.system);
+        let mut context = context.clone();
+        loop {
+            let vars = template_vars(&context);
+            let system = env.render_str(system_source, &vars).with_context(|| {
+                format!("Failed to render system prompt {}", template.version_id())
+            })?;
+            let user = env.render_str(&template.user, &vars).with_context(|| {
+                format!("Failed to render user prompt {}", template.version_id())
+            })?;
+            let estimated_tokens = estimate_tokens(&system) + estimate_tokens(&user);
+            if estimated_tokens <= token_budget || !context.shrink() {
+                return Ok(RenderedPrompt {
+                    version_id: template.version_id(),
+                    messages: vec![
+                        Message::system(system.trim_end()),
+                        Message::user(user.trim_end()),
+                    ],
+                    estimated_tokens,
+                    truncated: context.truncated,
+                });
+            }
+        }
+    }
+}
+
+/// 粗略估算 token 数 (约 4 字符 / token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+fn environment() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_trim_blocks(true);
+    env.set_lstrip_blocks(true);
+    env.add_filter("fixed", |value: f64, precision: Option<usize>| {
+        format!("{:.*}", precision.unwrap_or(2), value)
+    });
+    env
+}
+
+/// 上下文变量, 附带旧版 System Prompt 占位符
+fn template_vars(ctx: &PromptContext) -> Value {
+    let agent = &ctx.agent;
+    context! {
+        id => agent.id,
+        name => agent.name,
+        broker => agent.broker,
+        symbols => agent.symbols.join(", "),
+        initial_capital => agent.initial_capital.to_string(),
+        ..Value::from_serialize(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::{OrderSide, Trade};
+    use crate::prompt::{AccountContext, AgentContext, Indicators, MarketContext};
+
+    fn context() -> PromptContext {
+        PromptContext {
+            agent: AgentContext {
+                id: "agent-1".to_string(),
+                name: "Alpha".to_string(),
+                broker: "mock".to_string(),
+                symbols: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+                initial_capital: 1000.0,
+            },
+            time: "2025-10-28T11:00:05+00:00".to_string(),
+            timestamp: 1_761_649_205,
+            cycle: 3,
+            account: AccountContext {
+                currency: "USDT".to_string(),
+                total_balance: 1064.01,
+                available: 1064.01,
+                margin_used: None,
+                unrealized_pnl: 0.0,
+                equity: 1064.01,
+                return_pct: Some(6.401),
+            },
+            markets: ["BTCUSDT", "ETHUSDT"]
+                .iter()
+                .map(|symbol| MarketContext {
+                    symbol: symbol.to_string(),
+                    price: 113880.0,
+                    change_24h: -0.74,
+                    high_24h: 114039.5,
+                    low_24h: 113709.0,
+                    volume_24h: 292.0,
+                    indicators: Some(Indicators {
+                        interval: "15m".to_string(),
+                        rsi14: Some(42.77),
+                        ..Default::default()
+                    }),
+                })
+                .collect(),
+            positions: Vec::new(),
+            open_orders: Vec::new(),
+            recent_trades: (0..50)
+                .map(|i| Trade {
+                    trade_id: format!("t{}", i),
+                    order_id: format!("o{}", i),
+                    symbol: "BTCUSDT".to_string(),
+                    side: OrderSide::Buy,
+                    price: 113000.0,
+                    quantity: 0.01,
+                    fee: 0.1,
+                    timestamp: i,
+                })
+                .collect(),
+            risk: None,
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn test_builtin_library_selects_and_renders() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/prompts.yaml");
+        let library = PromptLibrary::from_file(path).unwrap();
+        assert_eq!(library.select(&[], 1).unwrap().version_id(), "trading@v2");
+        let ab = vec!["trading@v1".to_string(), "trading@v2".to_string()];
+        assert_eq!(library.select(&ab, 1).unwrap().version, "v1");
+        assert_eq!(library.select(&ab, 2).unwrap().version, "v2");
+        assert!(library.select(&["missing".to_string()], 1).is_err());
+
+        let ctx = context();
+        let template = library.get("trading").unwrap();
+        let rendered = library
+            .render(template, &ctx, None, DEFAULT_PROMPT_TOKEN_BUDGET)
+            .unwrap();
+        assert_eq!(rendered.version_id, "trading@v2");
+        assert!(!rendered.truncated);
+        let user = &rendered.messages[1].content;
+        assert!(user.contains("Cycle #3"));
+        assert!(user.contains("return 6.40% since inception"));
+        assert!(user.contains("15m: rsi14=42.77"));
+        assert!(user.contains("fee=0.1000"));
+
+        let v1 = library.get("trading@v1").unwrap();
+        let rendered = library.render(v1, &ctx, None, 6000).unwrap();
+        assert!(rendered.messages[1]
+            .content
+            .contains("Market:\n- BTCUSDT: last=113880.0 change_24h=-0.74%"));
+        assert!(rendered.messages[1]
+            .content
+            .ends_with("Account: total=1064.01 available=1064.01 USDT\nPositions: none"));
+
+        // 旧版占位符
+        let legacy = library
+            .render(
+                template,
+                &ctx,
+                Some("{{name}} on {{broker}}: {{symbols}}"),
+                6000,
+            )
+            .unwrap();
+        assert_eq!(
+            legacy.messages[0].content,
+            "Alpha on mock: BTCUSDT, ETHUSDT"
+        );
+    }
+
+    #[test]
+    fn test_render_truncates_to_budget() {
+        let library = PromptLibrary::builtin();
+        let template = library.get("trading@v2").unwrap();
+        let full = library
+            .render(template, &context(), None, usize::MAX)
+            .unwrap();
+        let budget = full.estimated_tokens / 2;
+
+        let rendered = library.render(template, &context(), None, budget).unwrap();
+        assert!(rendered.truncated);
+        assert!(rendered.estimated_tokens <= budget);
+        let user = &rendered.messages[1].content;
+        // 最早的成交先被裁剪, 最新的保留
+        assert!(user.contains("omitted"));
+        assert!(user.contains("BTCUSDT: last="));
+        assert!(user.matches("- buy BTCUSDT").count() < 50);
+    }
+}
@@ -52,6 +52,14 @@ impl RiskManager {
         }
     }
 
+    /// 设置初始账户余额（作为回撤计算的初始峰值）
+    pub fn with_initial_balance(self, balance: f64) -> Self {
+        Self {
+            metrics: Arc::new(RwLock::new(RiskMetrics::new(balance))),
+            ..self
+        }
+    }
+
     /// 设置数据库连接池
     pub fn with_pool(mut self, pool: Arc<PgPool>) -> Self {
         self.pool = Some(pool);
@@ -206,7 +214,7 @@ impl RiskManager {
     }
 
     /// 记录风险事件
-    async fn record_event(&self, event: RiskEvent) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn record_event(&self, event: RiskEvent) -> Result<(), Box<dyn std::error::Error>> {
         // 添加到内存缓存
         let mut history = self.event_history.write().await;
         history.push(event.clone());
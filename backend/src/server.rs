@@ -1,9 +1,14 @@
-use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::Context;
 use axum::{
     body::Body,
-    extract::{OriginalUri, Path, State},
+    extract::{OriginalUri, Path, Query, State},
     http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
@@ -14,15 +19,28 @@ use http::Uri;
 use reqwest::Client;
 use rust_embed::RustEmbed;
 use tokio::signal;
+use tokio_util::sync::CancellationToken;
 use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
 };
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::config::BrokersConfig;
-use crate::engine::TradingEngine;
-use crate::mcp::{GetPriceTool, McpServer, PlaceOrderTool};
+use crate::brokers::{BrokerFactory, DynBroker, MarketChannel, StreamSubscription};
+use crate::config::{AgentsConfig, BrokersConfig};
+use crate::engine::{
+    AccountStateTool, AgentStore, EquityRecorder, EquityStore, ExitPlanManager,
+    InMemoryEquityStore, MarketSnapshotTool, OrderLog, RiskCheckedOrderTool, TradingCalendar,
+    TradingEngine,
+};
+use crate::history::{CsvMarketDataStore, MarketDataStore, PgMarketDataStore};
+use crate::leaderboard::{
+    InMemoryLeaderboardStore, LeaderboardConfig, LeaderboardService, LeaderboardStore,
+};
+use crate::mcp::{McpServer, McpTool, ToolHandler};
+use crate::prompt::PromptLibrary;
+use crate::risk::{RiskConfig, RiskManager};
+use crate::usage::{InMemoryUsageStore, UsageConfig, UsageStore, UsageTracker};
 
 #[derive(RustEmbed)]
 #[folder = "../web/dist"]
@@ -32,36 +50,278 @@ struct Assets;
 pub struct AppState {
     upstream: Arc<String>,
     client: Client,
+    recorder: Arc<EquityRecorder>,
+    leaderboard: Arc<LeaderboardService>,
+    usage: Arc<UsageTracker>,
 }
 
-/// 运行 HTTP 服务器（在独立的 Tokio 运行时中）
-pub async fn run_http_server(addr: SocketAddr, url: String) -> anyhow::Result<()> {
-    // 初始化 MCP Server
-    let mut mcp_server = McpServer::new();
-    mcp_server.register_tool(GetPriceTool::schema(), Box::new(GetPriceTool));
-    mcp_server.register_tool(PlaceOrderTool::schema(), Box::new(PlaceOrderTool));
-    let mcp_server = Arc::new(mcp_server);
+/// 构建 MCP Server 并注册交易工具, HTTP 与 stdio 模式共用
+///
+/// 每个经纪商注册行情与账户工具, 工具名以经纪商 ID 为前缀 (如 `binance_get_account_state`)。
+/// 下单工具需在 brokers.yaml 中以 `mcp_order_symbols` 显式开放, 且经纪商未绑定启用的 Agent,
+/// 以免 MCP 订单混入 Agent 的持仓; 订单校验前同步该经纪商的余额、行情与持仓到其独立的风控。
+pub fn mcp_server(
+    brokers: &[Arc<dyn DynBroker>],
+    brokers_config: &BrokersConfig,
+    agents: &AgentsConfig,
+) -> McpServer {
+    let mut server = McpServer::new()
+        .with_server_info("nof0", env!("CARGO_PKG_VERSION"))
+        .with_instructions(
+            "nof0 AI trading tools: market snapshots, account state and, where enabled, \
+             risk-checked orders. Tool names are prefixed with the broker id, \
+             e.g. binance_get_market_snapshot.",
+        );
 
-    // 初始化 Trading Engine
-    let _trading_engine = TradingEngine::new(mcp_server.clone());
+    for broker in brokers {
+        let id = broker.broker_id();
+        let order_symbols = brokers_config
+            .get_broker(id)
+            .map(|c| c.mcp_order_symbols.clone())
+            .unwrap_or_default();
+        let traders: Vec<_> = agents
+            .agents
+            .iter()
+            .filter(|a| a.enabled && a.broker == id)
+            .collect();
+        let symbols: Vec<String> = traders
+            .iter()
+            .flat_map(|a| a.symbols.iter())
+            .chain(&order_symbols)
+            .cloned()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let mut tools: Vec<(McpTool, Box<dyn ToolHandler>)> = vec![
+            (
+                MarketSnapshotTool::schema(),
+                Box::new(MarketSnapshotTool::new(broker.clone(), symbols)),
+            ),
+            (
+                AccountStateTool::schema(),
+                Box::new(AccountStateTool::new(broker.clone())),
+            ),
+        ];
+        if !order_symbols.is_empty() {
+            if traders.is_empty() {
+                // 回撤以首次同步的权益为峰值
+                let risk_manager =
+                    Arc::new(RiskManager::new(RiskConfig::default()).with_initial_balance(0.0));
+                let order_tool = RiskCheckedOrderTool::new(
+                    broker.clone(),
+                    risk_manager,
+                    format!("mcp-{}", id),
+                    order_symbols,
+                    OrderLog::default(),
+                )
+                .with_metrics_sync();
+                tools.push((RiskCheckedOrderTool::schema(), Box::new(order_tool)));
+            } else {
+                warn!(
+                    "Broker {} is traded by agents, not exposing MCP order tool",
+                    id
+                );
+            }
+        }
+
+        for (mut schema, handler) in tools {
+            schema.name = format!("{}_{}", id, schema.name);
+            schema.description = format!("[{}] {}", broker.broker_name(), schema.description);
+            server.register_tool(schema, handler);
+        }
+    }
+    server
+}
 
-    // TODO: 注册 LLM Providers 和 Market Adapters
-    // trading_engine.register_llm_provider("openai".to_string(), Box::new(OpenAiProvider::new()));
-    // trading_engine.register_market("binance".to_string(), Box::new(BinanceAdapter::new()));
+/// 以 stdio 模式运行 MCP Server, 由 MCP 客户端以子进程方式启动
+pub async fn run_mcp_stdio() -> anyhow::Result<()> {
+    let brokers_config = BrokersConfig::load_default()?;
+    let agents_config = AgentsConfig::load_default()?;
+    let built = BrokerFactory::new(&agents_config).build(&brokers_config);
 
-    info!("Initialized MCP Server and Trading Engine");
+    let shutdown = CancellationToken::new();
+    let paper_interval = Duration::from_secs(brokers_config.settings.refresh_interval.max(1));
+    for paper in built.paper {
+        tokio::spawn(paper.run_sync(paper_interval, shutdown.clone()));
+    }
+
+    let server = Arc::new(mcp_server(&built.brokers, &brokers_config, &agents_config));
+    let result = crate::mcp::serve_stdio(server).await;
+    shutdown.cancel();
+    result
+}
+
+/// 运行 HTTP 服务器（在独立的 Tokio 运行时中）
+pub async fn run_http_server(addr: SocketAddr, url: String) -> anyhow::Result<()> {
+    // 连接数据库并执行迁移 (未设置 DATABASE_URL 时不持久化)
+    let mut equity_store: Arc<dyn EquityStore> = Arc::new(InMemoryEquityStore::new());
+    let mut leaderboard_store: Arc<dyn LeaderboardStore> =
+        Arc::new(InMemoryLeaderboardStore::new());
+    let mut usage_store: Arc<dyn UsageStore> = Arc::new(InMemoryUsageStore::new());
+    let pool = match crate::db::connect_from_env().await {
+        Ok(pool) => pool,
+        Err(e) => {
+            error!("Database unavailable, running without persistence: {:#}", e);
+            None
+        }
+    };
+    let shutdown = CancellationToken::new();
 
     // 加载经纪商配置
     let brokers_config = match BrokersConfig::load_default() {
         Ok(config) => {
             info!("Loaded {} brokers from config", config.brokers.len());
-            Arc::new(config)
+            config
         }
         Err(e) => {
             error!("Failed to load brokers config: {}, using default", e);
-            Arc::new(BrokersConfig::default())
+            BrokersConfig::default()
+        }
+    };
+    let agents_config = AgentsConfig::load_default().unwrap_or_else(|e| {
+        error!("Failed to load agents config: {:#}, no agents loaded", e);
+        AgentsConfig::default()
+    });
+
+    // 创建经纪商, 模型列表来自 agents.yaml, K线经本地缓存读取
+    let kline_cache: Arc<dyn MarketDataStore> = match &pool {
+        Some(pool) => Arc::new(PgMarketDataStore::new(pool.clone())),
+        None => Arc::new(CsvMarketDataStore::new("data/market")),
+    };
+    let mut factory = BrokerFactory::new(&agents_config).with_kline_cache(kline_cache);
+    if let Some(pool) = &pool {
+        // 登记 Agent 并持久化交易历史
+        let store = AgentStore::new(pool.clone());
+        for agent in &agents_config.agents {
+            if let Err(e) = store.upsert_agent(agent).await {
+                error!("{:#}", e);
+            }
+        }
+        factory = factory.with_persistence(pool.clone());
+    }
+    let built = factory.build(&brokers_config);
+
+    // MCP Server 暴露各经纪商的行情、账户与 (显式开放的) 风控下单工具
+    let mcp_server = Arc::new(mcp_server(&built.brokers, &brokers_config, &agents_config));
+
+    // 初始化 Trading Engine
+    let mut trading_engine = TradingEngine::new(mcp_server.clone());
+    match &pool {
+        Some(pool) => {
+            trading_engine = trading_engine
+                .with_store(Arc::new(AgentStore::new(pool.clone())))
+                .with_ledger_persistence(pool.clone());
+            equity_store = Arc::new(crate::db::PgEquityStore::new(pool.clone()));
+            leaderboard_store = Arc::new(crate::db::PgLeaderboardStore::new(pool.clone()));
+            usage_store = Arc::new(crate::db::PgUsageStore::new(pool.clone()));
+        }
+        None => info!("DATABASE_URL not set, running without persistence"),
+    }
+
+    // LLM 用量计费与每日预算
+    let usage_config = UsageConfig::load_default().unwrap_or_else(|e| {
+        error!("Failed to load LLM usage config: {:#}, using default", e);
+        UsageConfig::default()
+    });
+    let usage = Arc::new(UsageTracker::new(usage_store, usage_config));
+    let trading_engine = trading_engine.with_usage_tracker(usage.clone());
+
+    // 版本化 Prompt 模板
+    let trading_engine = match PromptLibrary::load_default() {
+        Ok(prompts) => trading_engine.with_prompt_library(Arc::new(prompts)),
+        Err(e) => {
+            error!(
+                "Failed to load prompts config: {:#}, using built-in templates",
+                e
+            );
+            trading_engine
         }
     };
+    let trading_engine = Arc::new(trading_engine);
+
+    // 注册经纪商, 定期撮合模拟盘挂单
+    for broker in built.brokers {
+        trading_engine
+            .register_broker(broker.broker_id().to_string(), broker)
+            .await;
+    }
+    let paper_interval = Duration::from_secs(brokers_config.settings.refresh_interval.max(1));
+    for paper in built.paper {
+        tokio::spawn(paper.run_sync(paper_interval, shutdown.clone()));
+    }
+
+    // 交易日历 (按日历名称绑定到同名经纪商), 须在调度启动前注册
+    match TradingCalendar::load_default() {
+        Ok(Some(calendar)) => {
+            let market = calendar.name().to_string();
+            trading_engine
+                .register_calendar(market, Arc::new(calendar))
+                .await;
+        }
+        Ok(None) => {}
+        Err(e) => error!("Failed to load trading calendar: {:#}", e),
+    }
+
+    // 注册 LLM Providers 并加载 Agent, 启动决策调度
+    trading_engine.load_agents(&agents_config).await;
+    let engine = trading_engine.clone();
+    let engine_shutdown = shutdown.clone();
+    tokio::spawn(async move {
+        if let Err(e) = engine.run(engine_shutdown).await {
+            error!("Trading engine stopped: {:#}", e);
+        }
+    });
+
+    // 权益曲线记录 (account-totals / since-inception-values 的数据来源)
+    let recorder = Arc::new(EquityRecorder::new(trading_engine.clone(), equity_store));
+    tokio::spawn(recorder.clone().run(shutdown.clone()));
+
+    // 持仓退出计划 (止损/止盈/失效条件) 监控
+    let exit_plans = Arc::new(ExitPlanManager::new(trading_engine.clone()));
+    tokio::spawn(exit_plans.clone().run(shutdown.clone()));
+
+    // 有推送行情的经纪商订阅其 Agent 的品种, 价格变动即时检查退出计划
+    for (broker_id, source) in built.streams {
+        let symbols: BTreeSet<String> = agents_config
+            .agents
+            .iter()
+            .filter(|a| a.enabled && a.broker == broker_id)
+            .flat_map(|a| a.symbols.iter().cloned())
+            .collect();
+        if symbols.is_empty() {
+            continue;
+        }
+        let subscription = StreamSubscription::new(symbols).with_channel(MarketChannel::Ticker);
+        match source.subscribe_market(subscription).await {
+            Ok(stream) => {
+                tokio::spawn(
+                    exit_plans
+                        .clone()
+                        .watch(broker_id, stream, shutdown.clone()),
+                );
+            }
+            Err(e) => warn!(
+                "No market stream for {}, exit plans are polled: {}",
+                broker_id, e
+            ),
+        }
+    }
+
+    // 跨经纪商排行榜与每日快照
+    let leaderboard_config = LeaderboardConfig::load_default().unwrap_or_else(|e| {
+        error!("Failed to load leaderboard config: {:#}, using default", e);
+        LeaderboardConfig::default()
+    });
+    let leaderboard = Arc::new(LeaderboardService::new(
+        trading_engine.clone(),
+        recorder.clone(),
+        leaderboard_store,
+        leaderboard_config,
+    ));
+    tokio::spawn(leaderboard.clone().run(shutdown.clone()));
+
+    info!("Initialized MCP Server and Trading Engine");
 
     let upstream =
         std::env::var("NOF1_API_BASE_URL").unwrap_or_else(|_| "https://nof1.ai/api".to_string());
@@ -76,6 +336,9 @@ pub async fn run_http_server(addr: SocketAddr, url: String) -> anyhow::Result<()
     let state = AppState {
         upstream: Arc::new(upstream),
         client,
+        recorder,
+        leaderboard,
+        usage,
     };
 
     let cors = CorsLayer::new()
@@ -102,12 +365,18 @@ pub async fn run_http_server(addr: SocketAddr, url: String) -> anyhow::Result<()
             }),
         )
         .route("/api/config/exchanges", get(get_exchanges_config))
+        .route("/api/leaderboard", get(get_leaderboard))
+        .route("/api/leaderboard/history", get(get_leaderboard_history))
+        .route("/api/usage", get(get_usage))
         .route("/health", get(health))
         .fallback(static_handler)
         .with_state(state)
         .layer(cors)
         .layer(TraceLayer::new_for_http());
 
+    // MCP Streamable HTTP 端点, 供外部 MCP 客户端连接
+    app = app.merge(crate::mcp::http_router(mcp_server));
+
     // 挂载 CTP 路由
     #[cfg(feature = "ctp-real")]
     {
@@ -120,6 +389,7 @@ pub async fn run_http_server(addr: SocketAddr, url: String) -> anyhow::Result<()
         .with_graceful_shutdown(shutdown_signal())
         .await
         .context("server error")?;
+    shutdown.cancel();
 
     Ok(())
 }
@@ -142,11 +412,12 @@ async fn proxy(
 
     let exchange_id = query_params.get("exchange_id");
 
-    // 如果有 exchange_id,返回模拟数据
+    // 如果有 exchange_id, 优先返回记录的真实权益数据, 否则返回模拟数据
     if let Some(exchange_id) = exchange_id {
         use crate::mock_data::generate_mock_data;
 
-        if let Some(mock_data) = generate_mock_data(&path, exchange_id) {
+        let recorded = recorded_data(&state, &path, exchange_id, &query_params).await;
+        if let Some(mock_data) = recorded.or_else(|| generate_mock_data(&path, exchange_id)) {
             let json_body =
                 serde_json::to_string(&mock_data).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
@@ -316,6 +587,92 @@ fn looks_like_static_asset(path: &str) -> bool {
         .is_some()
 }
 
+/// 跨经纪商汇总的排行榜
+async fn get_leaderboard(State(state): State<AppState>) -> Result<Response, StatusCode> {
+    match state.leaderboard.current().await {
+        Ok(leaderboard) => Ok(Json(leaderboard).into_response()),
+        Err(e) => {
+            error!("Failed to compute leaderboard: {:#}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct LeaderboardHistoryQuery {
+    model_id: Option<String>,
+    from: Option<chrono::NaiveDate>,
+    to: Option<chrono::NaiveDate>,
+}
+
+/// 每日名次历史, 默认最近 30 天
+async fn get_leaderboard_history(
+    State(state): State<AppState>,
+    Query(query): Query<LeaderboardHistoryQuery>,
+) -> Result<Response, StatusCode> {
+    let to = query.to.unwrap_or_else(|| chrono::Utc::now().date_naive());
+    let from = query.from.unwrap_or(to - chrono::Duration::days(30));
+    match state
+        .leaderboard
+        .rank_history(query.model_id.as_deref(), from, to)
+        .await
+    {
+        Ok(history) => Ok(Json(serde_json::json!({ "history": history })).into_response()),
+        Err(e) => {
+            error!("Failed to load leaderboard history: {:#}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct UsageQuery {
+    model_id: Option<String>,
+    from: Option<chrono::NaiveDate>,
+    to: Option<chrono::NaiveDate>,
+}
+
+/// 各模型 LLM 用量、单笔交易推理成本与扣除推理成本后的盈亏, 默认自开始以来
+async fn get_usage(
+    State(state): State<AppState>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Response, StatusCode> {
+    let from = query
+        .from
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map_or(0, |t| t.and_utc().timestamp());
+    let to = query
+        .to
+        .and_then(|d| d.and_hms_opt(23, 59, 59))
+        .map_or_else(
+            || chrono::Utc::now().timestamp(),
+            |t| t.and_utc().timestamp(),
+        );
+    let usage = state
+        .usage
+        .summary(query.model_id.as_deref(), from, to)
+        .await
+        .map_err(|e| {
+            error!("Failed to load LLM usage: {:#}", e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let analytics = state.recorder.analytics().await.map_err(|e| {
+        error!("Failed to compute analytics: {:#}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    let usage: Vec<_> = usage
+        .into_iter()
+        .map(
+            |u| match analytics.iter().find(|(_, a)| a.model_id == u.model_id) {
+                Some((_, a)) => u.with_performance(a),
+                None => u,
+            },
+        )
+        .collect();
+    Ok(Json(serde_json::json!({ "usage": usage })).into_response())
+}
+
 async fn health() -> impl IntoResponse {
     Json(HashMap::from([("status", "ok")]))
 }
@@ -408,6 +765,78 @@ fn build_target(base: &str, path: &str, uri: &Uri) -> String {
     target
 }
 
+/// 记录器中该交易所的权益数据, 该交易所尚无记录时返回 None
+async fn recorded_data(
+    state: &AppState,
+    path: &str,
+    exchange_id: &str,
+    query_params: &HashMap<String, String>,
+) -> Option<serde_json::Value> {
+    let mut segments = path.split('/').filter(|s| !s.is_empty());
+    let endpoint = segments.next().unwrap_or("");
+    if !matches!(
+        endpoint,
+        "account-totals" | "since-inception-values" | "analytics" | "leaderboard"
+    ) {
+        return None;
+    }
+
+    let recorder = &state.recorder;
+    let mut values = recorder
+        .since_inception_values()
+        .await
+        .map_err(|e| error!("Failed to load since-inception values: {:#}", e))
+        .ok()?;
+    values.retain(|v| v.exchange_id == exchange_id);
+    if values.is_empty() {
+        return None;
+    }
+
+    match endpoint {
+        "since-inception-values" => Some(serde_json::json!({ "sinceInceptionValues": values })),
+        "analytics" => {
+            // `/analytics/{model_id}` 只返回该模型
+            let model_id = segments.next();
+            let analytics: Vec<_> = recorder
+                .analytics()
+                .await
+                .map_err(|e| error!("Failed to compute analytics: {:#}", e))
+                .ok()?
+                .into_iter()
+                .filter(|(broker_id, a)| {
+                    broker_id == exchange_id && model_id.is_none_or(|id| a.model_id == id)
+                })
+                .map(|(_, a)| a)
+                .collect();
+            serde_json::to_value(crate::brokers::AnalyticsData { analytics }).ok()
+        }
+        "leaderboard" => {
+            let mut leaderboard = state
+                .leaderboard
+                .current()
+                .await
+                .map_err(|e| error!("Failed to compute leaderboard: {:#}", e))
+                .ok()?;
+            leaderboard
+                .leaderboard
+                .retain(|e| e.broker_id == exchange_id);
+            serde_json::to_value(leaderboard).ok()
+        }
+        _ => {
+            let marker = query_params
+                .get("lastHourlyMarker")
+                .and_then(|m| m.parse::<i32>().ok());
+            let mut totals = recorder
+                .account_totals(marker)
+                .await
+                .map_err(|e| error!("Failed to load account totals: {:#}", e))
+                .ok()?;
+            totals.account_totals.retain(|t| t.broker_id == exchange_id);
+            serde_json::to_value(totals).ok()
+        }
+    }
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
@@ -443,3 +872,184 @@ async fn shutdown_signal() {
 
     info!("shutdown signal completed");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::brokers::paper::PaperConfig;
+    use crate::brokers::{DynBroker, MockBroker, OrderRequest, OrderSide, OrderType, PaperBroker};
+    use crate::engine::Agent;
+
+    #[tokio::test]
+    async fn test_proxy_serves_recorded_equity_per_agent() {
+        // 两个 Agent 共用一个经纪商账户, 各自按起始资金记账
+        let engine = Arc::new(TradingEngine::new(Arc::new(McpServer::new())));
+        let feed: Arc<dyn DynBroker> = Arc::new(MockBroker::new());
+        let config = PaperConfig {
+            initial_balance: 100_000.0,
+            ..Default::default()
+        };
+        let paper = PaperBroker::new("binance", "Binance", feed, config);
+        engine
+            .register_broker("binance".to_string(), Arc::new(paper))
+            .await;
+        for (id, capital) in [("agent-a", 30_000.0), ("agent-b", 70_000.0)] {
+            let mut agent = Agent::new(
+                id.to_string(),
+                id.to_string(),
+                "scripted".to_string(),
+                "binance".to_string(),
+            );
+            agent.initial_capital = capital;
+            engine.register_agent(agent).await;
+        }
+
+        let agent_a = engine.get_agent("agent-a").await.unwrap();
+        let account = engine.account_for(&agent_a).await.unwrap();
+        account
+            .place_order(OrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                order_type: OrderType::Market,
+                quantity: 0.1,
+                price: None,
+                time_in_force: None,
+                reduce_only: false,
+            })
+            .await
+            .unwrap();
+
+        let recorder = Arc::new(EquityRecorder::new(
+            engine.clone(),
+            Arc::new(InMemoryEquityStore::new()),
+        ));
+        recorder.record_once().await.unwrap();
+        let leaderboard = Arc::new(LeaderboardService::new(
+            engine,
+            recorder.clone(),
+            Arc::new(InMemoryLeaderboardStore::new()),
+            LeaderboardConfig::default(),
+        ));
+        let state = AppState {
+            // 未命中记录时会请求不可达的上游而失败
+            upstream: Arc::new("http://127.0.0.1:1".to_string()),
+            client: Client::new(),
+            recorder,
+            leaderboard,
+            usage: Arc::new(UsageTracker::new(
+                Arc::new(InMemoryUsageStore::new()),
+                UsageConfig::default(),
+            )),
+        };
+        let app = Router::new()
+            .route("/api/nof1/{*path}", get(proxy))
+            .with_state(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}/api/nof1", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+
+        let totals: serde_json::Value =
+            reqwest::get(format!("{}/account-totals?exchange_id=binance", base))
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+        let totals = totals["accountTotals"].as_array().unwrap();
+        assert_eq!(totals.len(), 2);
+        let value_of = |id: &str| {
+            totals.iter().find(|t| t["model_id"] == id).unwrap()["account_value"]
+                .as_f64()
+                .unwrap()
+        };
+        assert!((value_of("agent-a") - 30_000.0).abs() < 1_000.0);
+        assert_eq!(value_of("agent-b"), 70_000.0);
+
+        let inception: serde_json::Value = reqwest::get(format!(
+            "{}/since-inception-values?exchange_id=binance",
+            base
+        ))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+        let values = inception["sinceInceptionValues"].as_array().unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0]["model_id"], "agent-a");
+        assert_eq!(values[0]["nav_since_inception"], 30_000.0);
+    }
+
+    #[tokio::test]
+    async fn test_mcp_order_tools_are_opt_in_and_risk_synced() {
+        // paper 显式开放 BTCUSDT 下单; binance 由 Agent 交易, 只开放行情与账户
+        let paper_broker = |id: &str| {
+            let feed: Arc<dyn DynBroker> = Arc::new(MockBroker::new());
+            let config = PaperConfig {
+                initial_balance: 10_000.0,
+                ..Default::default()
+            };
+            Arc::new(PaperBroker::new(id, id, feed, config))
+        };
+        let paper = paper_broker("paper");
+        let brokers: Vec<Arc<dyn DynBroker>> = vec![paper.clone(), paper_broker("binance")];
+        let mut brokers_config = BrokersConfig::default();
+        for id in ["paper", "binance"] {
+            let mut config = brokers_config.brokers[0].clone();
+            config.id = id.to_string();
+            config.mcp_order_symbols = vec!["BTCUSDT".to_string()];
+            brokers_config.brokers.push(config);
+        }
+        let agents = AgentsConfig::from_yaml_str(
+            r#"
+agents:
+  - id: trend
+    name: Trend
+    llm: { provider: scripted, model: offline }
+    broker: binance
+    symbols: [BTCUSDT]
+    initial_capital: 10000
+"#,
+        )
+        .unwrap();
+        let server = mcp_server(&brokers, &brokers_config, &agents);
+
+        let order = |quantity: f64, symbol: &str| {
+            serde_json::json!({"jsonrpc": "2.0", "id": 2, "method": "tools/call",
+                "params": {"name": "paper_place_order",
+                           "arguments": {"symbol": symbol, "side": "buy", "quantity": quantity}}})
+        };
+        let list = serde_json::json!({"jsonrpc": "2.0", "id": 1, "method": "tools/list"});
+        let tools = server.handle_value(list).await.unwrap();
+        let names: Vec<&str> = tools["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains(&"paper_place_order"));
+        assert!(names.contains(&"binance_get_account_state"));
+        assert!(!names.contains(&"binance_place_order"));
+
+        let text = |response: Option<serde_json::Value>| {
+            response.unwrap()["result"]["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        // 0.05 * 50000 = 2500 USD, 占权益 25%
+        let first = text(server.handle_value(order(0.05, "BTCUSDT")).await);
+        assert!(first.contains("executed"), "{}", first);
+        // 风控同步了已有持仓, 加仓后占比超过 30% 被拒绝
+        let second = text(server.handle_value(order(0.05, "BTCUSDT")).await);
+        assert!(second.contains("rejected"), "{}", second);
+        // 未开放的品种直接拒绝
+        let other = text(server.handle_value(order(0.01, "ETHUSDT")).await);
+        assert!(other.contains("universe"), "{}", other);
+
+        let trades = DynBroker::get_trades(paper.as_ref()).await.unwrap();
+        assert_eq!(trades.trades.len(), 1);
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// 模型单价 (美元 / 百万 token)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// 模型名前缀, 多条匹配时取最长前缀
+    pub model: String,
+    /// 仅匹配该 provider, 为空时匹配任意 provider
+    #[serde(default)]
+    pub provider: Option<String>,
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl ModelPrice {
+    pub fn cost(&self, prompt_tokens: u32, completion_tokens: u32) -> f64 {
+        (prompt_tokens as f64 * self.input_per_million
+            + completion_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
+/// 用量与预算配置 (config/llm_usage.yaml)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageConfig {
+    #[serde(default)]
+    pub prices: Vec<ModelPrice>,
+    /// 每个 Agent 的默认日预算 (美元), 为空表示不限
+    #[serde(default)]
+    pub default_daily_budget_usd: Option<f64>,
+    /// 按 Agent ID 覆盖日预算
+    #[serde(default)]
+    pub budgets: HashMap<String, f64>,
+}
+
+impl UsageConfig {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Self::from_yaml_str(&content)
+    }
+
+    /// 从 YAML 字符串加载并校验
+    pub fn from_yaml_str(yaml: &str) -> Result<Self> {
+        let config: UsageConfig = serde_yaml::from_str(yaml)?;
+        if let Some(price) = config
+            .prices
+            .iter()
+            .find(|p| p.input_per_million < 0.0 || p.output_per_million < 0.0)
+        {
+            bail!("Invalid price for {}", price.model);
+        }
+        let budgets = config.budgets.iter().map(|(id, b)| (id.as_str(), *b));
+        let default = config.default_daily_budget_usd.map(|b| ("default", b));
+        if let Some((id, budget)) = budgets.chain(default).find(|(_, b)| *b < 0.0) {
+            bail!("Invalid daily budget for {}: {}", id, budget);
+        }
+        Ok(config)
+    }
+
+    /// 从默认路径加载配置, 找不到文件时使用默认配置 (不计费、不限预算)
+    pub fn load_default() -> Result<Self> {
+        let possible_paths = [
+            "config/llm_usage.yaml",
+            "backend/config/llm_usage.yaml",
+            "../config/llm_usage.yaml",
+        ];
+
+        for path in possible_paths {
+            if Path::new(path).exists() {
+                return Self::from_file(path);
+            }
+        }
+
+        tracing::warn!("No LLM usage config file found, using defaults");
+        Ok(Self::default())
+    }
+
+    /// 查找模型单价: provider 匹配优先, 其次最长模型名前缀
+    pub fn price_for(&self, provider: &str, model: &str) -> Option<&ModelPrice> {
+        let model = model.to_ascii_lowercase();
+        self.prices
+            .iter()
+            .filter(|p| model.starts_with(&p.model.to_ascii_lowercase()))
+            .filter(|p| {
+                p.provider
+                    .as_deref()
+                    .is_none_or(|name| name.eq_ignore_ascii_case(provider))
+            })
+            .max_by_key(|p| (p.provider.is_some(), p.model.len()))
+    }
+
+    /// 按价格表计算费用, 未配置价格的模型记为 0
+    pub fn cost(
+        &self,
+        provider: &str,
+        model: &str,
+        prompt_tokens: u32,
+        completion_tokens: u32,
+    ) -> f64 {
+        self.price_for(provider, model)
+            .map(|p| p.cost(prompt_tokens, completion_tokens))
+            .unwrap_or(0.0)
+    }
+
+    /// Agent 的日预算
+    pub fn daily_budget(&self, agent_id: &str) -> Option<f64> {
+        self.budgets
+            .get(agent_id)
+            .copied()
+            .or(self.default_daily_budget_usd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_config_prices() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/config/llm_usage.yaml");
+        let config = UsageConfig::from_file(path).unwrap();
+
+        // 最长前缀: gpt-5-mini 不按 gpt-5 计价
+        let full = config.price_for("openai", "gpt-5").unwrap();
+        let mini = config.price_for("openai", "gpt-5-mini-2025-08-07").unwrap();
+        assert_eq!(full.model, "gpt-5");
+        assert_eq!(mini.model, "gpt-5-mini");
+        assert!((config.cost("openai", "gpt-5", 1_000_000, 100_000) - 2.25).abs() < 1e-9);
+        assert_eq!(config.cost("ollama", "llama3", 1000, 1000), 0.0);
+
+        assert!(UsageConfig::from_yaml_str("budgets:\n  a: -1\n").is_err());
+    }
+}
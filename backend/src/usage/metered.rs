@@ -0,0 +1,129 @@
+use super::store::LlmCallRecord;
+use super::tracker::UsageTracker;
+use crate::llm::{ChatRequest, ChatResponse, ChatStream, LlmProvider, StreamDelta, TokenUsage};
+use anyhow::Result;
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::warn;
+
+/// 记录每次调用用量与费用的 Provider 包装, 归属到 Agent 与决策周期
+#[derive(Clone)]
+pub struct MeteredProvider {
+    inner: Arc<dyn LlmProvider>,
+    tracker: Arc<UsageTracker>,
+    agent_id: String,
+    cycle_id: Option<String>,
+}
+
+impl MeteredProvider {
+    pub fn new(
+        inner: Arc<dyn LlmProvider>,
+        tracker: Arc<UsageTracker>,
+        agent_id: impl Into<String>,
+    ) -> Self {
+        Self {
+            inner,
+            tracker,
+            agent_id: agent_id.into(),
+            cycle_id: None,
+        }
+    }
+
+    pub fn with_cycle_id(mut self, cycle_id: impl Into<String>) -> Self {
+        self.cycle_id = Some(cycle_id.into());
+        self
+    }
+
+    fn call_record(
+        &self,
+        usage: &TokenUsage,
+        started: Instant,
+        error: Option<String>,
+    ) -> LlmCallRecord {
+        let (provider, model) = (self.inner.name(), self.inner.model());
+        LlmCallRecord {
+            model_id: self.agent_id.clone(),
+            cycle_id: self.cycle_id.clone(),
+            provider: provider.to_string(),
+            model: model.to_string(),
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            latency_ms: started.elapsed().as_millis() as u64,
+            cost_usd: self.tracker.cost(provider, model, usage),
+            error,
+            ts: self.tracker.now(),
+        }
+    }
+
+    fn result_record(&self, result: &Result<ChatResponse>, started: Instant) -> LlmCallRecord {
+        match result {
+            Ok(response) => self.call_record(&response.usage, started, None),
+            Err(e) => self.call_record(&TokenUsage::default(), started, Some(format!("{:#}", e))),
+        }
+    }
+
+    /// 记录失败只告警, 不影响调用结果
+    async fn save(&self, record: LlmCallRecord) {
+        if let Err(e) = self.tracker.record(record).await {
+            warn!("Failed to record LLM usage for {}: {:#}", self.agent_id, e);
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MeteredProvider {
+    async fn chat(&self, req: ChatRequest) -> Result<ChatResponse> {
+        let started = Instant::now();
+        let result = self.inner.chat(req).await;
+        self.save(self.result_record(&result, started)).await;
+        result
+    }
+
+    async fn chat_with_tools(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatResponse> {
+        let started = Instant::now();
+        let result = self.inner.chat_with_tools(req, tools).await;
+        self.save(self.result_record(&result, started)).await;
+        result
+    }
+
+    /// 流式调用在收到用量增量时记录, 延迟为开始到流结束用量上报的时间
+    async fn chat_stream(
+        &self,
+        req: ChatRequest,
+        tools: Vec<serde_json::Value>,
+    ) -> Result<ChatStream> {
+        let started = Instant::now();
+        let stream = match self.inner.chat_stream(req, tools).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                let error = Some(format!("{:#}", e));
+                self.save(self.call_record(&TokenUsage::default(), started, error))
+                    .await;
+                return Err(e);
+            }
+        };
+
+        let metered = self.clone();
+        Ok(Box::pin(stream.inspect(move |delta| {
+            if let Ok(StreamDelta::Usage(usage)) = delta {
+                let record = metered.call_record(usage, started, None);
+                let metered = metered.clone();
+                tokio::spawn(async move { metered.save(record).await });
+            }
+        })))
+    }
+
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn model(&self) -> &str {
+        self.inner.model()
+    }
+}
@@ -0,0 +1,11 @@
+// LLM usage accounting: per-call records, price table, daily budgets and cost-vs-PnL
+
+pub mod config;
+pub mod metered;
+pub mod store;
+pub mod tracker;
+
+pub use config::{ModelPrice, UsageConfig};
+pub use metered::MeteredProvider;
+pub use store::*;
+pub use tracker::{AgentUsage, UsageTracker};
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+/// 单次 LLM 调用记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LlmCallRecord {
+    /// Agent ID
+    pub model_id: String,
+    /// 所属决策周期
+    pub cycle_id: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub latency_ms: u64,
+    /// 按价格表计算的费用 (美元)
+    pub cost_usd: f64,
+    /// 调用失败时的错误信息
+    pub error: Option<String>,
+    /// 调用时间 (秒)
+    pub ts: i64,
+}
+
+/// LLM 调用记录持久化接口
+#[async_trait]
+pub trait UsageStore: Send + Sync {
+    async fn record(&self, call: &LlmCallRecord) -> Result<()>;
+
+    /// `[from, to]` 时间范围内的调用记录, 按时间升序; `model_id` 为空时返回全部 Agent
+    async fn load(&self, model_id: Option<&str>, from: i64, to: i64) -> Result<Vec<LlmCallRecord>>;
+}
+
+/// 内存调用记录存储
+#[derive(Default)]
+pub struct InMemoryUsageStore {
+    calls: RwLock<Vec<LlmCallRecord>>,
+}
+
+impl InMemoryUsageStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UsageStore for InMemoryUsageStore {
+    async fn record(&self, call: &LlmCallRecord) -> Result<()> {
+        self.calls.write().await.push(call.clone());
+        Ok(())
+    }
+
+    async fn load(&self, model_id: Option<&str>, from: i64, to: i64) -> Result<Vec<LlmCallRecord>> {
+        let mut calls: Vec<LlmCallRecord> = self
+            .calls
+            .read()
+            .await
+            .iter()
+            .filter(|c| model_id.is_none_or(|id| c.model_id == id))
+            .filter(|c| c.ts >= from && c.ts <= to)
+            .cloned()
+            .collect();
+        calls.sort_by_key(|c| c.ts);
+        Ok(calls)
+    }
+}
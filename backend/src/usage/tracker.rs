@@ -0,0 +1,235 @@
+use super::config::UsageConfig;
+use super::store::{LlmCallRecord, UsageStore};
+use crate::brokers::ModelAnalytics;
+use crate::engine::Clock;
+use crate::llm::TokenUsage;
+use anyhow::Result;
+use chrono::{DateTime, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+/// Agent 在一段时间内的 LLM 用量, 附带交易表现时给出单笔成本与扣除推理成本后的盈亏
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AgentUsage {
+    pub model_id: String,
+    pub calls: u64,
+    pub failed_calls: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost_usd: f64,
+    pub avg_latency_ms: f64,
+    /// 当天 (UTC) 已花费
+    pub spent_today_usd: f64,
+    pub daily_budget_usd: Option<f64>,
+    /// 超出日预算, 当天不再调度
+    pub paused: bool,
+    pub trades: Option<usize>,
+    pub cost_per_trade_usd: Option<f64>,
+    /// 含手续费的已实现盈亏
+    pub pnl: Option<f64>,
+    pub net_pnl_after_inference: Option<f64>,
+}
+
+impl AgentUsage {
+    /// 合并交易表现 (成本与盈亏按同一计价货币比较)
+    pub fn with_performance(mut self, analytics: &ModelAnalytics) -> Self {
+        let trades = analytics.overall_trades_overview_table.total_trades;
+        let pnl = analytics
+            .fee_pnl_moves_breakdown_table
+            .overall_pnl_with_fees;
+        self.trades = Some(trades);
+        self.cost_per_trade_usd = (trades > 0).then(|| self.cost_usd / trades as f64);
+        self.pnl = Some(pnl);
+        self.net_pnl_after_inference = Some(pnl - self.cost_usd);
+        self
+    }
+}
+
+/// LLM 用量统计与日预算控制
+pub struct UsageTracker {
+    store: Arc<dyn UsageStore>,
+    config: UsageConfig,
+    clock: Clock,
+    /// 各 Agent 当天花费缓存, 首次查询时从存储加载
+    spent: RwLock<HashMap<String, (NaiveDate, f64)>>,
+}
+
+impl UsageTracker {
+    pub fn new(store: Arc<dyn UsageStore>, config: UsageConfig) -> Self {
+        Self {
+            store,
+            config,
+            clock: Clock::system(),
+            spent: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// 设置时钟, 回测时使用模拟时钟
+    pub fn with_clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn config(&self) -> &UsageConfig {
+        &self.config
+    }
+
+    pub fn now(&self) -> i64 {
+        self.clock.now()
+    }
+
+    /// 按价格表计算一次调用的费用
+    pub fn cost(&self, provider: &str, model: &str, usage: &TokenUsage) -> f64 {
+        self.config.cost(
+            provider,
+            model,
+            usage.prompt_tokens,
+            usage.completion_tokens,
+        )
+    }
+
+    /// 记录一次调用
+    pub async fn record(&self, call: LlmCallRecord) -> Result<()> {
+        self.store.record(&call).await?;
+        // 未缓存时下次查询从存储加载, 已包含本次调用
+        if let Some((date, spent)) = self.spent.write().await.get_mut(&call.model_id) {
+            if *date == utc_date(call.ts) {
+                *spent += call.cost_usd;
+            }
+        }
+        Ok(())
+    }
+
+    /// Agent 当天 (UTC) 已花费的金额
+    pub async fn spent_today(&self, agent_id: &str) -> Result<f64> {
+        let now = self.clock.now();
+        let today = utc_date(now);
+        if let Some((date, spent)) = self.spent.read().await.get(agent_id) {
+            if *date == today {
+                return Ok(*spent);
+            }
+        }
+
+        let calls = self
+            .store
+            .load(Some(agent_id), day_start(today), now)
+            .await?;
+        let spent = calls.iter().map(|c| c.cost_usd).sum();
+        self.spent
+            .write()
+            .await
+            .insert(agent_id.to_string(), (today, spent));
+        Ok(spent)
+    }
+
+    /// Agent 是否已超出日预算; 读取失败时不暂停
+    pub async fn is_over_budget(&self, agent_id: &str) -> bool {
+        let Some(budget) = self.config.daily_budget(agent_id) else {
+            return false;
+        };
+        match self.spent_today(agent_id).await {
+            Ok(spent) => spent >= budget,
+            Err(e) => {
+                warn!("Failed to load LLM spend for {}: {:#}", agent_id, e);
+                false
+            }
+        }
+    }
+
+    /// `[from, to]` 内各 Agent 的用量汇总, 按 Agent ID 排序
+    pub async fn summary(
+        &self,
+        model_id: Option<&str>,
+        from: i64,
+        to: i64,
+    ) -> Result<Vec<AgentUsage>> {
+        let mut usage: BTreeMap<String, (AgentUsage, u64)> = BTreeMap::new();
+        for call in self.store.load(model_id, from, to).await? {
+            let (entry, latency) = usage.entry(call.model_id.clone()).or_default();
+            entry.calls += 1;
+            if call.error.is_some() {
+                entry.failed_calls += 1;
+            }
+            entry.prompt_tokens += call.prompt_tokens as u64;
+            entry.completion_tokens += call.completion_tokens as u64;
+            entry.cost_usd += call.cost_usd;
+            *latency += call.latency_ms;
+        }
+
+        let mut summaries = Vec::with_capacity(usage.len());
+        for (model_id, (mut entry, latency)) in usage {
+            entry.avg_latency_ms = latency as f64 / entry.calls as f64;
+            entry.spent_today_usd = self.spent_today(&model_id).await?;
+            entry.daily_budget_usd = self.config.daily_budget(&model_id);
+            entry.paused = entry
+                .daily_budget_usd
+                .is_some_and(|budget| entry.spent_today_usd >= budget);
+            entry.model_id = model_id;
+            summaries.push(entry);
+        }
+        Ok(summaries)
+    }
+}
+
+fn utc_date(ts: i64) -> NaiveDate {
+    DateTime::from_timestamp(ts, 0)
+        .map(|t| t.date_naive())
+        .unwrap_or_default()
+}
+
+fn day_start(date: NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .map(|t| t.and_utc().timestamp())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::usage::InMemoryUsageStore;
+
+    fn call(model_id: &str, cost_usd: f64, ts: i64) -> LlmCallRecord {
+        LlmCallRecord {
+            model_id: model_id.to_string(),
+            cycle_id: Some(format!("{}-1", model_id)),
+            provider: "openai".to_string(),
+            model: "gpt-5".to_string(),
+            prompt_tokens: 1000,
+            completion_tokens: 100,
+            latency_ms: 200,
+            cost_usd,
+            error: None,
+            ts,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_daily_budget_resets_next_day() {
+        let config =
+            UsageConfig::from_yaml_str("default_daily_budget_usd: 1.0\nbudgets:\n  b: 5.0\n")
+                .unwrap();
+        let clock = Clock::simulated(1_760_000_000);
+        let tracker = UsageTracker::new(Arc::new(InMemoryUsageStore::new()), config)
+            .with_clock(clock.clone());
+
+        tracker.record(call("a", 0.6, clock.now())).await.unwrap();
+        assert!(!tracker.is_over_budget("a").await);
+        tracker.record(call("a", 0.6, clock.now())).await.unwrap();
+        tracker.record(call("b", 1.2, clock.now())).await.unwrap();
+        assert!(tracker.is_over_budget("a").await);
+        assert!(!tracker.is_over_budget("b").await);
+
+        let summary = tracker.summary(None, 0, clock.now()).await.unwrap();
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].calls, 2);
+        assert!(summary[0].paused);
+        assert_eq!(summary[0].prompt_tokens, 2000);
+
+        clock.set(clock.now() + 86_400);
+        assert!(!tracker.is_over_budget("a").await);
+        assert_eq!(tracker.spent_today("a").await.unwrap(), 0.0);
+    }
+}